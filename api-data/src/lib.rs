@@ -63,9 +63,18 @@ mod tests {
     use super::*;
     use anchor_spl::associated_token::get_associated_token_address;
     use bpl_token_metadata::{
-        state::{Promo, PromoGroup},
-        utils::find_group_address,
+        state::{
+            Loyalty, MintExtensions, Promo, PromoAcceptance, PromoGroup, PromoMint, Referral,
+            ReferralReward, RewardLevel, Settlement,
+        },
+        token_2022,
+        utils::{
+            find_group_address, find_loyalty_address, find_promo_acceptance_address,
+            find_promo_address, find_promo_mint_address, find_referral_address,
+            find_referral_reward_address,
+        },
     };
+    use borsh::BorshSerialize;
     use deadpool_postgres::{Manager, ManagerConfig, Pool, RecyclingMethod};
     use mpl_auction_house::{
         pda::{
@@ -86,6 +95,7 @@ mod tests {
         state::{Account, AccountState, Mint},
     };
     use std::time::{SystemTime, UNIX_EPOCH};
+    use tokio_postgres::types::Json;
     use tracing_subscriber;
 
     fn get_now() -> i64 {
@@ -131,10 +141,21 @@ mod tests {
         client: &Client,
         key: &[u8],
         account: &Mint,
+        token_program: &Pubkey,
+        extensions: &[&str],
         slot: u64,
         write_version: u64,
     ) {
-        queries::spl_token::mint::upsert(client, key, account, slot, write_version).await;
+        queries::spl_token::mint::upsert(
+            client,
+            key,
+            account,
+            token_program,
+            extensions,
+            slot,
+            write_version,
+        )
+        .await;
         let row = client
             .query_one(
                 "SELECT * FROM mint WHERE id = $1",
@@ -152,16 +173,37 @@ mod tests {
             account.mint_authority.map(|k| k.to_string()).into(),
             "it_upserts_mint: mint_authority failed"
         );
+        assert_eq!(
+            row.get::<&str, String>("token_program"),
+            token_program.to_string(),
+            "it_upserts_mint: token_program failed"
+        );
+        assert_eq!(
+            row.get::<&str, Json<Vec<String>>>("extensions").0,
+            extensions,
+            "it_upserts_mint: extensions failed"
+        );
     }
 
     async fn it_upserts_token_account(
         client: &Client,
         key: &[u8],
         account: &Account,
+        token_program: &Pubkey,
+        extensions: &[&str],
         slot: u64,
         write_version: u64,
     ) {
-        queries::spl_token::token_account::upsert(client, key, account, slot, write_version).await;
+        queries::spl_token::token_account::upsert(
+            client,
+            key,
+            account,
+            token_program,
+            extensions,
+            slot,
+            write_version,
+        )
+        .await;
         let row = client
             .query_one(
                 "SELECT * FROM token_account WHERE id = $1",
@@ -179,6 +221,11 @@ mod tests {
             account.close_authority.map(|k| k.to_string()).into(),
             "it_upserts_token_account: close_authority failed"
         );
+        assert_eq!(
+            row.get::<&str, String>("token_program"),
+            token_program.to_string(),
+            "it_upserts_token_account: token_program failed"
+        );
     }
 
     async fn it_upserts_metadata(
@@ -226,6 +273,90 @@ mod tests {
         );
     }
 
+    async fn it_upserts_promo_acceptance(
+        client: &Client,
+        key: &[u8],
+        account: &PromoAcceptance,
+        slot: u64,
+        write_version: u64,
+    ) {
+        queries::bpl_token_metadata::promo_acceptance::upsert(
+            client,
+            key,
+            account,
+            slot,
+            write_version,
+        )
+        .await;
+        let row = client
+            .query_one(
+                "SELECT * FROM promo_acceptance WHERE id = $1",
+                &[&bs58::encode(key).into_string()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            row.get::<&str, i64>("settlement_lamports"),
+            account.settlement_lamports as i64,
+            "it_upserts_promo_acceptance: settlement_lamports"
+        );
+
+        let rows = client
+            .query(
+                "SELECT * FROM accepted_promo_group WHERE promo_acceptance = $1",
+                &[&bs58::encode(key).into_string()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            rows.len(),
+            account.groups.len(),
+            "it_upserts_promo_acceptance: accepted_promo_group"
+        );
+    }
+
+    async fn it_upserts_loyalty(
+        client: &Client,
+        key: &[u8],
+        account: &Loyalty,
+        slot: u64,
+        write_version: u64,
+    ) {
+        queries::bpl_token_metadata::loyalty::upsert(client, key, account, slot, write_version)
+            .await;
+        let row = client
+            .query_one(
+                "SELECT * FROM loyalty WHERE id = $1",
+                &[&bs58::encode(key).into_string()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            row.get::<&str, i64>("points_earned"),
+            account.points_earned as i64,
+            "it_upserts_loyalty: points_earned"
+        );
+
+        let rows = client
+            .query(
+                "SELECT * FROM loyalty_level WHERE loyalty = $1 ORDER BY level",
+                &[&bs58::encode(key).into_string()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            rows.iter()
+                .map(|row| row.get::<&str, i64>("points"))
+                .collect::<Vec<i64>>(),
+            account
+                .levels
+                .iter()
+                .map(|l| l.points as i64)
+                .collect::<Vec<i64>>(),
+            "it_upserts_loyalty: loyalty_level"
+        );
+    }
+
     async fn it_upserts_auction_house(
         client: &Client,
         key: &[u8],
@@ -351,6 +482,123 @@ mod tests {
         );
     }
 
+    async fn it_upserts_referral_reward(
+        client: &Client,
+        key: &[u8],
+        account: &ReferralReward,
+        slot: u64,
+        write_version: u64,
+    ) {
+        queries::bpl_token_metadata::referral_reward::upsert(
+            client,
+            key,
+            account,
+            slot,
+            write_version,
+        )
+        .await;
+        let row = client
+            .query_one(
+                "SELECT * FROM referral_reward WHERE id = $1",
+                &[&bs58::encode(key).into_string()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            row.get::<&str, String>("reward_mint"),
+            account.reward_mint.to_string(),
+            "it_upserts_referral_reward: reward_mint"
+        );
+    }
+
+    async fn it_upserts_referral(
+        client: &Client,
+        key: &[u8],
+        account: &Referral,
+        slot: u64,
+        write_version: u64,
+    ) {
+        queries::bpl_token_metadata::referral::upsert(client, key, account, slot, write_version)
+            .await;
+        let row = client
+            .query_one(
+                "SELECT * FROM referral WHERE id = $1",
+                &[&bs58::encode(key).into_string()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            row.get::<&str, bool>("converted"),
+            account.converted,
+            "it_upserts_referral: converted"
+        );
+    }
+
+    async fn it_upserts_promo_mint(
+        client: &Client,
+        key: &[u8],
+        account: &PromoMint,
+        slot: u64,
+        write_version: u64,
+    ) {
+        queries::bpl_token_metadata::promo_mint::upsert(client, key, account, slot, write_version)
+            .await;
+        let row = client
+            .query_one(
+                "SELECT * FROM promo_mint WHERE id = $1",
+                &[&bs58::encode(key).into_string()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            row.get::<&str, bool>("non_transferable"),
+            account.extensions.non_transferable,
+            "it_upserts_promo_mint: non_transferable"
+        );
+        assert_eq!(
+            row.get::<&str, String>("uri"),
+            account.uri,
+            "it_upserts_promo_mint: uri"
+        );
+    }
+
+    async fn it_upserts_promo_metadata(
+        client: &Client,
+        mint: &str,
+        metadata_json: &serde_json::Value,
+        slot: u64,
+        write_version: u64,
+    ) {
+        queries::bpl_token_metadata::promo_metadata::upsert(
+            client,
+            mint,
+            "https://arweave.net/promo",
+            metadata_json,
+            slot,
+            write_version,
+        )
+        .await;
+        let row = client
+            .query_one("SELECT * FROM promo_metadata WHERE mint = $1", &[&mint])
+            .await
+            .unwrap();
+        assert_eq!(
+            row.get::<&str, String>("promo_type"),
+            "buyXCurrencyGetYPercent",
+            "it_upserts_promo_metadata: promo_type"
+        );
+        assert_eq!(
+            row.get::<&str, Option<i64>>("get_y_percent"),
+            Some(10),
+            "it_upserts_promo_metadata: get_y_percent"
+        );
+        assert_eq!(
+            row.get::<&str, Option<i64>>("max_burn"),
+            Some(500),
+            "it_upserts_promo_metadata: max_burn"
+        );
+    }
+
     // =============================
     // Transactions
     // =============================
@@ -383,6 +631,36 @@ mod tests {
                 client, signature, accounts, data, slot,
             )
             .await;
+        } else if table == "delegate_accepted_promo_token" {
+            queries::bpl_token_metadata::delegate_accepted_promo_token::upsert(
+                client, signature, accounts, data, slot,
+            )
+            .await;
+        } else if table == "burn_accepted_promo_token" {
+            queries::bpl_token_metadata::burn_accepted_promo_token::upsert(
+                client, signature, accounts, data, slot,
+            )
+            .await;
+        } else if table == "earn_points" {
+            queries::bpl_token_metadata::earn_points::upsert(
+                client, signature, accounts, data, slot,
+            )
+            .await;
+        } else if table == "redeem_points" {
+            queries::bpl_token_metadata::redeem_points::upsert(
+                client, signature, accounts, data, slot,
+            )
+            .await;
+        } else if table == "mint_referred_promo_token" {
+            queries::bpl_token_metadata::mint_referred_promo_token::upsert(
+                client, signature, accounts, data, slot,
+            )
+            .await;
+        } else if table == "burn_referred_promo_token" {
+            queries::bpl_token_metadata::burn_referred_promo_token::upsert(
+                client, signature, accounts, data, slot,
+            )
+            .await;
         } else if table == "create_promo_group" {
             queries::bpl_token_metadata::create_promo_group::upsert(
                 client, signature, accounts, data, slot,
@@ -402,6 +680,20 @@ mod tests {
             accounts[0].to_string(),
             "it_upsert_{table}: payer failed"
         );
+
+        // Mints have the loyalty account before the token account.
+        let token_account_index = match table {
+            "mint_promo_token" => Some(7),
+            "mint_referred_promo_token" => Some(9),
+            _ => None,
+        };
+        if let Some(index) = token_account_index {
+            assert_eq!(
+                row.get::<&str, String>("token_account"),
+                accounts[index].to_string(),
+                "it_upsert_{table}: token_account failed"
+            );
+        }
     }
 
     #[tokio::test]
@@ -432,7 +724,7 @@ mod tests {
 
         // insert a create_promo transaction
         let data: &[u8] = &[0; 8];
-        let accounts: Vec<Pubkey> = (0..10).map(|_| Pubkey::new_unique()).collect();
+        let accounts: Vec<Pubkey> = (0..14).map(|_| Pubkey::new_unique()).collect();
 
         for table in vec![
             "create_promo_group",
@@ -440,11 +732,51 @@ mod tests {
             "mint_promo_token",
             "delegate_promo_token",
             "burn_delegated_promo_token",
+            "delegate_accepted_promo_token",
+            "burn_accepted_promo_token",
+            "burn_referred_promo_token",
         ] {
             it_upserts_transaction(&client, &Signature::default(), &accounts, data, 42, table)
                 .await;
         }
 
+        // loyalty and referral transactions are only indexed when their args parse
+        let mut earn_points_data = vec![0; 8];
+        earn_points_data.extend(
+            bpl_token_metadata::instruction::EarnPoints {
+                order_amount: 4200,
+                memo: None,
+            }
+            .try_to_vec()
+            .unwrap(),
+        );
+        let mut redeem_points_data = vec![0; 8];
+        redeem_points_data.extend(
+            bpl_token_metadata::instruction::RedeemPoints {
+                level: 0,
+                memo: Some("redeemed".to_string()),
+            }
+            .try_to_vec()
+            .unwrap(),
+        );
+        let mut mint_referred_data = vec![0; 8];
+        mint_referred_data.extend(
+            bpl_token_metadata::instruction::MintReferredPromoToken {
+                referrer: Pubkey::new_unique(),
+                memo: None,
+            }
+            .try_to_vec()
+            .unwrap(),
+        );
+        for (table, data) in vec![
+            ("earn_points", earn_points_data),
+            ("redeem_points", redeem_points_data),
+            ("mint_referred_promo_token", mint_referred_data),
+        ] {
+            it_upserts_transaction(&client, &Signature::default(), &accounts, &data, 42, table)
+                .await;
+        }
+
         // upsert group
         let owner = Pubkey::new_unique();
         let member = Pubkey::new_unique();
@@ -463,7 +795,7 @@ mod tests {
         // update a mint, null out an optional value
         mint.supply = 2;
         mint.mint_authority = COption::None;
-        it_upserts_mint(&client, mint_pubkey.as_ref(), &mint, 42, 2).await;
+        it_upserts_mint(&client, mint_pubkey.as_ref(), &mint, &spl_token::ID, &[], 42, 2).await;
 
         let owner = Pubkey::new_unique();
         let token_pubkey = get_associated_token_address(&owner, &mint_pubkey);
@@ -478,12 +810,94 @@ mod tests {
             close_authority: COption::None,
         };
         // insert a token account
-        it_upserts_token_account(&client, token_pubkey.as_ref(), &token_account, 42, 1).await;
+        it_upserts_token_account(
+            &client,
+            token_pubkey.as_ref(),
+            &token_account,
+            &spl_token::ID,
+            &[],
+            42,
+            1,
+        )
+        .await;
 
         // update a token account, null out an optional value
         token_account.amount = 1;
         token_account.close_authority = COption::None;
-        it_upserts_token_account(&client, token_pubkey.as_ref(), &token_account, 43, 1).await;
+        it_upserts_token_account(
+            &client,
+            token_pubkey.as_ref(),
+            &token_account,
+            &spl_token::ID,
+            &[],
+            43,
+            1,
+        )
+        .await;
+
+        // insert a Token-2022 mint and token account with extensions
+        let mint_2022_pubkey = Pubkey::new_unique();
+        it_upserts_mint(
+            &client,
+            mint_2022_pubkey.as_ref(),
+            &mint,
+            &token_2022::ID,
+            &["nonTransferable", "permanentDelegate", "metadataPointer"],
+            42,
+            1,
+        )
+        .await;
+        let token_2022_pubkey = token_2022::find_associated_token_address(&owner, &mint_2022_pubkey);
+        it_upserts_token_account(
+            &client,
+            token_2022_pubkey.as_ref(),
+            &Account {
+                mint: mint_2022_pubkey,
+                ..token_account
+            },
+            &token_2022::ID,
+            &["immutableOwner", "nonTransferableAccount"],
+            42,
+            1,
+        )
+        .await;
+
+        // insert promo_mint
+        let (promo_mint_pubkey, _) = find_promo_mint_address(&mint_2022_pubkey);
+        let promo_mint = PromoMint {
+            promo: find_promo_address(&mint_2022_pubkey).0,
+            mint: mint_2022_pubkey,
+            extensions: MintExtensions {
+                non_transferable: true,
+                permanent_delegate: true,
+                metadata_pointer: true,
+            },
+            name: "Token-2022 promo".to_string(),
+            symbol: "T22".to_string(),
+            uri: "https://arweave.net/promo".to_string(),
+        };
+        it_upserts_promo_mint(&client, promo_mint_pubkey.as_ref(), &promo_mint, 42, 1).await;
+
+        // insert promo metadata
+        let metadata_json = serde_json::json!({
+            "name": "buyXCurrency",
+            "symbol": "CURR",
+            "attributes": [
+                { "trait_type": "promoType", "value": "buyXCurrencyGetYPercent" },
+                { "trait_type": "buyXCurrency", "value": 200 },
+                { "trait_type": "getYPercent", "value": 10 },
+                { "trait_type": "maxMint", "value": 1000 },
+                { "trait_type": "maxBurn", "value": 500 },
+            ],
+        });
+        it_upserts_promo_metadata(
+            &client,
+            &mint_2022_pubkey.to_string(),
+            &metadata_json,
+            42,
+            1,
+        )
+        .await;
 
         // insert a metadata account
         let creators = (0..5)
@@ -544,6 +958,104 @@ mod tests {
 
         it_upserts_promo(&client, key.as_ref(), &promo, 42, 1).await;
 
+        // insert promo_acceptance
+        let (promo_acceptance_pubkey, _) = find_promo_acceptance_address(&mint_pubkey);
+        let mut promo_acceptance = PromoAcceptance {
+            promo: key,
+            owner,
+            groups: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            settlement: Settlement::IssuerPaysRedeemer,
+            settlement_lamports: 1_000_000,
+        };
+        it_upserts_promo_acceptance(
+            &client,
+            promo_acceptance_pubkey.as_ref(),
+            &promo_acceptance,
+            42,
+            1,
+        )
+        .await;
+
+        // update promo_acceptance, removing an accepted group
+        promo_acceptance.groups.pop();
+        promo_acceptance.settlement = Settlement::RedeemerPaysIssuer;
+        it_upserts_promo_acceptance(
+            &client,
+            promo_acceptance_pubkey.as_ref(),
+            &promo_acceptance,
+            43,
+            1,
+        )
+        .await;
+
+        // insert loyalty
+        let (loyalty_pubkey, _) = find_loyalty_address(&mint_pubkey);
+        let mut loyalty = Loyalty {
+            promo: key,
+            mint: mint_pubkey,
+            earn_rate: 1,
+            levels: vec![
+                RewardLevel {
+                    points: 10_000,
+                    reward: 500,
+                },
+                RewardLevel {
+                    points: 25_000,
+                    reward: 1_500,
+                },
+            ],
+            points_earned: 0,
+            points_redeemed: 0,
+        };
+        it_upserts_loyalty(&client, loyalty_pubkey.as_ref(), &loyalty, 42, 1).await;
+
+        // update loyalty after points earned
+        loyalty.points_earned = 4_200;
+        it_upserts_loyalty(&client, loyalty_pubkey.as_ref(), &loyalty, 43, 1).await;
+
+        let row = client
+            .query_one(
+                "SELECT * FROM loyalty_balance WHERE token_account = $1",
+                &[&token_pubkey.to_string()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            row.get::<&str, i64>("points"),
+            token_account.amount as i64,
+            "it_upserts_loyalty: loyalty_balance"
+        );
+
+        // insert referral_reward
+        let (referral_reward_pubkey, _) = find_referral_reward_address(&mint_pubkey);
+        let referral_reward = ReferralReward {
+            promo: key,
+            reward_mint: Pubkey::new_unique(),
+        };
+        it_upserts_referral_reward(
+            &client,
+            referral_reward_pubkey.as_ref(),
+            &referral_reward,
+            42,
+            1,
+        )
+        .await;
+
+        // insert referral
+        let (referral_pubkey, _) = find_referral_address(&mint_pubkey, &token_account.owner);
+        let mut referral = Referral {
+            promo: key,
+            mint: mint_pubkey,
+            token_owner: token_account.owner,
+            referrer: Pubkey::new_unique(),
+            converted: false,
+        };
+        it_upserts_referral(&client, referral_pubkey.as_ref(), &referral, 42, 1).await;
+
+        // update referral on conversion
+        referral.converted = true;
+        it_upserts_referral(&client, referral_pubkey.as_ref(), &referral, 43, 1).await;
+
         // insert an auction_house
         let ah_authority = Pubkey::new_unique();
         let (ah_pubkey, _) = find_auction_house_address(&ah_authority, &mint_pubkey);
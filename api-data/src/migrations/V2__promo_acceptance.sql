@@ -0,0 +1,66 @@
+BEGIN;
+SET check_function_bodies = false;
+
+-- =============================
+-- bpl_token_metadata
+-- =============================
+
+CREATE TABLE public.promo_acceptance (
+    id text NOT NULL,
+    promo text NOT NULL,
+    owner text NOT NULL,
+    groups jsonb NOT NULL,
+    settlement text NOT NULL,
+    settlement_lamports bigint NOT NULL,
+    slot bigint NOT NULL,
+    write_version bigint NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    modified_at timestamp with time zone DEFAULT now() NOT NULL
+);
+ALTER TABLE ONLY public.promo_acceptance
+    ADD CONSTRAINT promo_acceptance_pkey PRIMARY KEY (id);
+
+CREATE TABLE public.delegate_accepted_promo_token (
+    signature text NOT NULL,
+    payer text NOT NULL,
+    delegate text NOT NULL,
+    promo_group text NOT NULL,
+    token_owner text NOT NULL,
+    mint text NOT NULL,
+    promo text NOT NULL,
+    promo_acceptance text NOT NULL,
+    token_account text NOT NULL,
+    memo jsonb,
+    slot bigint NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    modified_at timestamp with time zone DEFAULT now() NOT NULL
+);
+ALTER TABLE ONLY public.delegate_accepted_promo_token
+    ADD CONSTRAINT delegate_accepted_promo_token_pkey PRIMARY KEY (signature);
+
+CREATE TABLE public.burn_accepted_promo_token (
+    signature text NOT NULL,
+    payer text NOT NULL,
+    promo_group text NOT NULL,
+    issuing_group text NOT NULL,
+    mint text NOT NULL,
+    promo text NOT NULL,
+    promo_acceptance text NOT NULL,
+    platform text NOT NULL,
+    admin_settings text NOT NULL,
+    token_account text NOT NULL,
+    memo jsonb,
+    slot bigint NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    modified_at timestamp with time zone DEFAULT now() NOT NULL
+);
+ALTER TABLE ONLY public.burn_accepted_promo_token
+    ADD CONSTRAINT burn_accepted_promo_token_pkey PRIMARY KEY (signature);
+
+-- One row per group accepting a promo so that group membership can be joined to promos.
+CREATE VIEW public.accepted_promo_group AS
+    SELECT pa.id promo_acceptance, pa.promo, pa.owner issuing_group,
+        accepted.value promo_group, pa.settlement, pa.settlement_lamports
+    FROM promo_acceptance pa
+    CROSS JOIN LATERAL jsonb_array_elements_text(pa.groups) accepted;
+COMMIT;
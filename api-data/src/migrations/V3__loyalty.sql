@@ -0,0 +1,80 @@
+BEGIN;
+SET check_function_bodies = false;
+
+-- =============================
+-- bpl_token_metadata
+-- =============================
+
+CREATE TABLE public.loyalty (
+    id text NOT NULL,
+    promo text NOT NULL,
+    mint text NOT NULL,
+    earn_rate bigint NOT NULL,
+    levels jsonb NOT NULL,
+    points_earned bigint NOT NULL,
+    points_redeemed bigint NOT NULL,
+    slot bigint NOT NULL,
+    write_version bigint NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    modified_at timestamp with time zone DEFAULT now() NOT NULL
+);
+ALTER TABLE ONLY public.loyalty
+    ADD CONSTRAINT loyalty_pkey PRIMARY KEY (id);
+
+CREATE TABLE public.earn_points (
+    signature text NOT NULL,
+    payer text NOT NULL,
+    promo_group text NOT NULL,
+    token_owner text NOT NULL,
+    mint text NOT NULL,
+    authority text NOT NULL,
+    promo text NOT NULL,
+    loyalty text NOT NULL,
+    token_account text NOT NULL,
+    order_amount bigint NOT NULL,
+    memo jsonb,
+    slot bigint NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    modified_at timestamp with time zone DEFAULT now() NOT NULL
+);
+ALTER TABLE ONLY public.earn_points
+    ADD CONSTRAINT earn_points_pkey PRIMARY KEY (signature);
+
+CREATE TABLE public.redeem_points (
+    signature text NOT NULL,
+    payer text NOT NULL,
+    promo_group text NOT NULL,
+    token_owner text NOT NULL,
+    mint text NOT NULL,
+    promo text NOT NULL,
+    loyalty text NOT NULL,
+    token_account text NOT NULL,
+    level integer NOT NULL,
+    memo jsonb,
+    slot bigint NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    modified_at timestamp with time zone DEFAULT now() NOT NULL
+);
+ALTER TABLE ONLY public.redeem_points
+    ADD CONSTRAINT redeem_points_pkey PRIMARY KEY (signature);
+
+-- One row per reward level, numbered from zero as in the redeem_points instruction.
+CREATE VIEW public.loyalty_level AS
+    SELECT l.id loyalty, l.promo, l.mint, (level.ordinality - 1)::integer AS level,
+        (level.value->>'points')::bigint points, (level.value->>'reward')::bigint reward
+    FROM loyalty l
+    CROSS JOIN LATERAL jsonb_array_elements(l.levels) WITH ORDINALITY level;
+
+-- Point balances are token account amounts for loyalty mints.
+CREATE VIEW public.loyalty_balance AS
+    SELECT ta.id token_account, ta.owner, l.id loyalty, l.promo, l.mint, ta.amount points,
+        ta.modified_at
+    FROM token_account ta
+    JOIN loyalty l ON ta.mint = l.mint;
+
+CREATE VIEW public.reward_redemption AS
+    SELECT rp.signature, rp.promo_group, rp.token_owner, rp.mint, rp.promo, rp.loyalty,
+        rp.level, ll.points, ll.reward, rp.memo, rp.slot, rp.created_at
+    FROM redeem_points rp
+    LEFT JOIN loyalty_level ll ON rp.loyalty = ll.loyalty AND rp.level = ll.level;
+COMMIT;
@@ -0,0 +1,84 @@
+BEGIN;
+SET check_function_bodies = false;
+
+-- =============================
+-- bpl_token_metadata
+-- =============================
+
+CREATE TABLE public.referral_reward (
+    id text NOT NULL,
+    promo text NOT NULL,
+    reward_mint text NOT NULL,
+    slot bigint NOT NULL,
+    write_version bigint NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    modified_at timestamp with time zone DEFAULT now() NOT NULL
+);
+ALTER TABLE ONLY public.referral_reward
+    ADD CONSTRAINT referral_reward_pkey PRIMARY KEY (id);
+
+CREATE TABLE public.referral (
+    id text NOT NULL,
+    promo text NOT NULL,
+    mint text NOT NULL,
+    token_owner text NOT NULL,
+    referrer text NOT NULL,
+    converted boolean NOT NULL,
+    slot bigint NOT NULL,
+    write_version bigint NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    modified_at timestamp with time zone DEFAULT now() NOT NULL
+);
+ALTER TABLE ONLY public.referral
+    ADD CONSTRAINT referral_pkey PRIMARY KEY (id);
+
+CREATE TABLE public.mint_referred_promo_token (
+    signature text NOT NULL,
+    payer text NOT NULL,
+    promo_group text NOT NULL,
+    token_owner text NOT NULL,
+    mint text NOT NULL,
+    authority text NOT NULL,
+    promo text NOT NULL,
+    referral_reward text NOT NULL,
+    referral text NOT NULL,
+    token_account text NOT NULL,
+    referrer text NOT NULL,
+    memo jsonb,
+    slot bigint NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    modified_at timestamp with time zone DEFAULT now() NOT NULL
+);
+ALTER TABLE ONLY public.mint_referred_promo_token
+    ADD CONSTRAINT mint_referred_promo_token_pkey PRIMARY KEY (signature);
+
+CREATE TABLE public.burn_referred_promo_token (
+    signature text NOT NULL,
+    payer text NOT NULL,
+    promo_group text NOT NULL,
+    mint text NOT NULL,
+    authority text NOT NULL,
+    promo text NOT NULL,
+    platform text NOT NULL,
+    admin_settings text NOT NULL,
+    token_account text NOT NULL,
+    referral text NOT NULL,
+    referral_reward text NOT NULL,
+    referrer text NOT NULL,
+    reward_mint text NOT NULL,
+    reward_promo text NOT NULL,
+    referrer_token_account text NOT NULL,
+    memo jsonb,
+    slot bigint NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    modified_at timestamp with time zone DEFAULT now() NOT NULL
+);
+ALTER TABLE ONLY public.burn_referred_promo_token
+    ADD CONSTRAINT burn_referred_promo_token_pkey PRIMARY KEY (signature);
+
+CREATE VIEW public.referral_conversion AS
+    SELECT brpt.signature, brpt.promo_group, r.promo, brpt.mint, r.token_owner, r.referrer,
+        brpt.reward_mint, brpt.referrer_token_account, brpt.slot, brpt.created_at
+    FROM burn_referred_promo_token brpt
+    JOIN referral r ON brpt.referral = r.id;
+COMMIT;
@@ -0,0 +1,39 @@
+BEGIN;
+SET check_function_bodies = false;
+
+-- =============================
+-- bpl_token_metadata
+-- =============================
+
+CREATE TABLE public.promo_mint (
+    id text NOT NULL,
+    promo text NOT NULL,
+    mint text NOT NULL,
+    non_transferable boolean NOT NULL,
+    permanent_delegate boolean NOT NULL,
+    metadata_pointer boolean NOT NULL,
+    name text NOT NULL,
+    symbol text NOT NULL,
+    uri text NOT NULL,
+    slot bigint NOT NULL,
+    write_version bigint NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    modified_at timestamp with time zone DEFAULT now() NOT NULL
+);
+ALTER TABLE ONLY public.promo_mint
+    ADD CONSTRAINT promo_mint_pkey PRIMARY KEY (id);
+
+-- =============================
+-- spl_token
+-- =============================
+
+-- Mints and token accounts owned by the Token-2022 program are indexed in the same tables,
+-- with the names of any extensions they have.
+ALTER TABLE public.mint
+    ADD COLUMN token_program text DEFAULT 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA' NOT NULL,
+    ADD COLUMN extensions jsonb DEFAULT '[]'::jsonb NOT NULL;
+
+ALTER TABLE public.token_account
+    ADD COLUMN token_program text DEFAULT 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA' NOT NULL,
+    ADD COLUMN extensions jsonb DEFAULT '[]'::jsonb NOT NULL;
+COMMIT;
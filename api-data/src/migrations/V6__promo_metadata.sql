@@ -0,0 +1,31 @@
+BEGIN;
+SET check_function_bodies = false;
+
+-- =============================
+-- bpl_token_metadata
+-- =============================
+
+-- Promo fields parsed from the json metadata of promo mints, from Metaplex metadata for classic
+-- token promos and from the promo mint account for Token-2022 promos.
+CREATE TABLE public.promo_metadata (
+    mint text NOT NULL,
+    uri text NOT NULL,
+    promo_type text NOT NULL,
+    product_id text,
+    buy_x_product bigint,
+    get_y_product bigint,
+    buy_x_currency bigint,
+    get_y_percent bigint,
+    earn_rate bigint,
+    loyalty_levels jsonb,
+    max_mint bigint,
+    max_burn bigint,
+    attributes jsonb NOT NULL,
+    slot bigint NOT NULL,
+    write_version bigint NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    modified_at timestamp with time zone DEFAULT now() NOT NULL
+);
+ALTER TABLE ONLY public.promo_metadata
+    ADD CONSTRAINT promo_metadata_pkey PRIMARY KEY (mint);
+COMMIT;
@@ -0,0 +1,20 @@
+BEGIN;
+SET check_function_bodies = false;
+
+-- =============================
+-- bpl_token_metadata
+-- =============================
+
+-- Labels and icons shown by wallets for a group's transaction requests. Set off chain through
+-- the transaction server with a message signed by the group owner, stored as `signature`.
+CREATE TABLE public.group_profile (
+    promo_group text NOT NULL,
+    label text NOT NULL,
+    icon text NOT NULL,
+    signature text NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    modified_at timestamp with time zone DEFAULT now() NOT NULL
+);
+ALTER TABLE ONLY public.group_profile
+    ADD CONSTRAINT group_profile_pkey PRIMARY KEY (promo_group);
+COMMIT;
@@ -0,0 +1,39 @@
+BEGIN;
+SET check_function_bodies = false;
+
+-- =============================
+-- bpl_token_metadata
+-- =============================
+
+-- Transactions paid for by the platform signer for a group, recorded by the transaction server.
+-- `kind` is `sponsored` for transactions requested by members and `settlement` for
+-- transactions reimbursing the platform `lamports` from the group's balance. `fee` and `rent`
+-- are filled in once `status` is no longer `pending`.
+CREATE TABLE public.sponsorship_ledger (
+    signature text NOT NULL,
+    promo_group text NOT NULL,
+    kind text NOT NULL,
+    status text NOT NULL,
+    fee bigint DEFAULT 0 NOT NULL,
+    rent bigint DEFAULT 0 NOT NULL,
+    lamports bigint DEFAULT 0 NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    modified_at timestamp with time zone DEFAULT now() NOT NULL
+);
+ALTER TABLE ONLY public.sponsorship_ledger
+    ADD CONSTRAINT sponsorship_ledger_pkey PRIMARY KEY (signature);
+CREATE INDEX sponsorship_ledger_promo_group_idx ON public.sponsorship_ledger (promo_group);
+CREATE INDEX sponsorship_ledger_status_idx ON public.sponsorship_ledger (status);
+
+-- Whether groups reimburse the platform for sponsored transactions. Set off chain through the
+-- transaction server with a message signed by the group owner, stored as `signature`.
+CREATE TABLE public.group_billing (
+    promo_group text NOT NULL,
+    reimburse boolean NOT NULL,
+    signature text NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    modified_at timestamp with time zone DEFAULT now() NOT NULL
+);
+ALTER TABLE ONLY public.group_billing
+    ADD CONSTRAINT group_billing_pkey PRIMARY KEY (promo_group);
+COMMIT;
@@ -0,0 +1,58 @@
+BEGIN;
+SET check_function_bodies = false;
+
+-- =============================
+-- bpl_token_metadata
+-- =============================
+
+-- Urls groups are notified of events on their promos at, registered through the transaction
+-- server by the group owner, with the signature they signed in with stored as `signature`.
+-- `events` are the kinds of events delivered and deliveries are signed with `secret`.
+CREATE TABLE public.webhook_endpoint (
+    id text NOT NULL,
+    promo_group text NOT NULL,
+    url text NOT NULL,
+    secret text NOT NULL,
+    events text[] NOT NULL,
+    signature text NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    modified_at timestamp with time zone DEFAULT now() NOT NULL
+);
+ALTER TABLE ONLY public.webhook_endpoint
+    ADD CONSTRAINT webhook_endpoint_pkey PRIMARY KEY (id);
+CREATE INDEX webhook_endpoint_promo_group_idx ON public.webhook_endpoint (promo_group);
+
+-- Events queued for webhook endpoints and the outcome of attempts to deliver them. Each event
+-- is only queued once per endpoint.
+CREATE TABLE public.webhook_delivery (
+    id text NOT NULL,
+    endpoint_id text NOT NULL,
+    event_id text NOT NULL,
+    promo_group text NOT NULL,
+    kind text NOT NULL,
+    payload text NOT NULL,
+    status text NOT NULL,
+    attempts int DEFAULT 0 NOT NULL,
+    next_attempt_at timestamp with time zone NOT NULL,
+    response_status int,
+    error text,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    modified_at timestamp with time zone DEFAULT now() NOT NULL
+);
+ALTER TABLE ONLY public.webhook_delivery
+    ADD CONSTRAINT webhook_delivery_pkey PRIMARY KEY (id);
+ALTER TABLE ONLY public.webhook_delivery
+    ADD CONSTRAINT webhook_delivery_endpoint_event_key UNIQUE (endpoint_id, event_id);
+CREATE INDEX webhook_delivery_promo_group_idx ON public.webhook_delivery (promo_group);
+CREATE INDEX webhook_delivery_status_idx ON public.webhook_delivery (status);
+
+-- Slot up to which indexed events have been queued for delivery.
+CREATE TABLE public.webhook_cursor (
+    id text NOT NULL,
+    slot bigint NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    modified_at timestamp with time zone DEFAULT now() NOT NULL
+);
+ALTER TABLE ONLY public.webhook_cursor
+    ADD CONSTRAINT webhook_cursor_pkey PRIMARY KEY (id);
+COMMIT;
@@ -13,6 +13,24 @@ DROP TABLE IF EXISTS public.create_promo CASCADE;
 DROP TABLE IF EXISTS public.mint_promo_token CASCADE;
 DROP TABLE IF EXISTS public.delegate_promo_token CASCADE;
 DROP TABLE IF EXISTS public.burn_delegated_promo_token CASCADE;
+DROP TABLE IF EXISTS public.promo_acceptance CASCADE;
+DROP TABLE IF EXISTS public.delegate_accepted_promo_token CASCADE;
+DROP TABLE IF EXISTS public.burn_accepted_promo_token CASCADE;
+DROP TABLE IF EXISTS public.loyalty CASCADE;
+DROP TABLE IF EXISTS public.earn_points CASCADE;
+DROP TABLE IF EXISTS public.redeem_points CASCADE;
+DROP TABLE IF EXISTS public.referral_reward CASCADE;
+DROP TABLE IF EXISTS public.referral CASCADE;
+DROP TABLE IF EXISTS public.mint_referred_promo_token CASCADE;
+DROP TABLE IF EXISTS public.burn_referred_promo_token CASCADE;
+DROP TABLE IF EXISTS public.promo_mint CASCADE;
+DROP TABLE IF EXISTS public.promo_metadata CASCADE;
+DROP TABLE IF EXISTS public.group_profile CASCADE;
+DROP TABLE IF EXISTS public.sponsorship_ledger CASCADE;
+DROP TABLE IF EXISTS public.group_billing CASCADE;
+DROP TABLE IF EXISTS public.webhook_endpoint CASCADE;
+DROP TABLE IF EXISTS public.webhook_delivery CASCADE;
+DROP TABLE IF EXISTS public.webhook_cursor CASCADE;
 
 -- =============================
 -- mpl_auction_house
@@ -0,0 +1,32 @@
+INSERT INTO burn_accepted_promo_token (
+    signature,
+    payer,
+    promo_group,
+    issuing_group,
+    mint,
+    promo,
+    promo_acceptance,
+    platform,
+    admin_settings,
+    token_account,
+    memo,
+    slot
+)
+    VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+ON CONFLICT ON CONSTRAINT burn_accepted_promo_token_pkey DO UPDATE 
+    SET
+        payer = EXCLUDED.payer,
+        promo_group = EXCLUDED.promo_group,
+        issuing_group = EXCLUDED.issuing_group,
+        mint = EXCLUDED.mint,
+        promo = EXCLUDED.promo,
+        promo_acceptance = EXCLUDED.promo_acceptance,
+        platform = EXCLUDED.platform,
+        admin_settings = EXCLUDED.admin_settings,
+        token_account = EXCLUDED.token_account,
+        memo = EXCLUDED.memo,
+        slot = EXCLUDED.slot,
+        modified_at = NOW()
+    WHERE
+        EXCLUDED.slot > burn_accepted_promo_token.slot
+RETURNING created_at = modified_at
\ No newline at end of file
@@ -0,0 +1,81 @@
+use borsh::de::BorshDeserialize;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use tokio_postgres::{types::Json, Client};
+use tracing::{error, info};
+
+const UPSERT_QUERY: &str = include_str!("burn_referred_promo_token_upsert.sql");
+
+#[tracing::instrument(skip_all)]
+pub async fn upsert(
+    client: &Client,
+    signature: &Signature,
+    accounts: &Vec<Pubkey>,
+    data: &[u8],
+    slot: u64,
+) {
+    let accounts: Vec<String> = accounts.iter().map(ToString::to_string).collect();
+    let memo = if let Ok(args) =
+        bpl_token_metadata::instruction::BurnReferredPromoToken::try_from_slice(&data[8..])
+    {
+        args.memo.map(|m| {
+            if let Ok(result) = serde_json::from_str::<serde_json::Value>(&m) {
+                result
+            } else {
+                serde_json::json!({ "memo": m })
+            }
+        })
+    } else {
+        None
+    };
+
+    let signature = signature.to_string();
+    let payer = &accounts[0];
+    let promo_group = &accounts[1];
+    let mint = &accounts[2];
+    let authority = &accounts[3];
+    let promo = &accounts[4];
+    let platform = &accounts[5];
+    let admin_settings = &accounts[6];
+    let token_account = &accounts[7];
+    let referral = &accounts[8];
+    let referral_reward = &accounts[9];
+    let referrer = &accounts[10];
+    let reward_mint = &accounts[11];
+    let reward_promo = &accounts[12];
+    let referrer_token_account = &accounts[13];
+    let slot = slot as i64;
+
+    let result = client
+        .query_one(
+            UPSERT_QUERY,
+            &[
+                &signature,
+                payer,
+                promo_group,
+                mint,
+                authority,
+                promo,
+                platform,
+                admin_settings,
+                token_account,
+                referral,
+                referral_reward,
+                referrer,
+                reward_mint,
+                reward_promo,
+                referrer_token_account,
+                &Json::<Option<serde_json::Value>>(memo),
+                &slot,
+            ],
+        )
+        .await;
+    match result {
+        Ok(row) => {
+            let insert = row.get::<usize, Option<bool>>(0).unwrap();
+            info!(signature = signature.as_str(), insert);
+        }
+        Err(error) => {
+            error!(signature = signature.as_str(), ?error);
+        }
+    }
+}
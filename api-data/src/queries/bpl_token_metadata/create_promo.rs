@@ -14,10 +14,17 @@ pub async fn upsert(
     slot: u64,
 ) {
     let accounts: Vec<String> = accounts.iter().map(ToString::to_string).collect();
-    let memo = if let Ok(args) =
+    // Promos with Token-2022 mints are created with the same accounts, the promo mint account
+    // taking the place of the metadata account, but with different args.
+    let memo = if let Ok(memo) =
         bpl_token_metadata::instruction::CreatePromo::try_from_slice(&data[8..])
+            .map(|args| args.memo)
+            .or_else(|_| {
+                bpl_token_metadata::instruction::CreatePromo2022::try_from_slice(&data[8..])
+                    .map(|args| args.memo)
+            })
     {
-        args.memo.map(|m| {
+        memo.map(|m| {
             if let Ok(result) = serde_json::from_str::<serde_json::Value>(&m) {
                 result
             } else {
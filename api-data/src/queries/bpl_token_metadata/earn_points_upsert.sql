@@ -0,0 +1,32 @@
+INSERT INTO earn_points (
+    signature,
+    payer,
+    promo_group,
+    token_owner,
+    mint,
+    authority,
+    promo,
+    loyalty,
+    token_account,
+    order_amount,
+    memo,
+    slot
+)
+    VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+ON CONFLICT ON CONSTRAINT earn_points_pkey DO UPDATE 
+    SET
+        payer = EXCLUDED.payer,
+        promo_group = EXCLUDED.promo_group,
+        token_owner = EXCLUDED.token_owner,
+        mint = EXCLUDED.mint,
+        authority = EXCLUDED.authority,
+        promo = EXCLUDED.promo,
+        loyalty = EXCLUDED.loyalty,
+        token_account = EXCLUDED.token_account,
+        order_amount = EXCLUDED.order_amount,
+        memo = EXCLUDED.memo,
+        slot = EXCLUDED.slot,
+        modified_at = NOW()
+    WHERE
+        EXCLUDED.slot > earn_points.slot
+RETURNING created_at = modified_at
\ No newline at end of file
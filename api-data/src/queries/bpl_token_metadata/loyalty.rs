@@ -0,0 +1,54 @@
+use bpl_token_metadata::state::Loyalty;
+use tokio_postgres::{types::Json, Client};
+use tracing::{error, info};
+
+const UPSERT_QUERY: &str = include_str!("loyalty_upsert.sql");
+
+#[tracing::instrument(skip_all)]
+pub async fn upsert(
+    client: &Client,
+    key: &[u8],
+    account: &Loyalty,
+    slot: u64,
+    write_version: u64,
+) {
+    let id = bs58::encode(key).into_string();
+    let promo = account.promo.to_string();
+    let mint = account.mint.to_string();
+    let earn_rate = account.earn_rate as i64;
+    let levels = account
+        .levels
+        .iter()
+        .map(|l| serde_json::json!({ "points": l.points, "reward": l.reward }))
+        .collect();
+    let points_earned = account.points_earned as i64;
+    let points_redeemed = account.points_redeemed as i64;
+    let slot = slot as i64;
+    let write_version = write_version as i64;
+
+    let result = client
+        .query_one(
+            UPSERT_QUERY,
+            &[
+                &id,
+                &promo,
+                &mint,
+                &earn_rate,
+                &Json::<Vec<serde_json::Value>>(levels),
+                &points_earned,
+                &points_redeemed,
+                &slot,
+                &write_version,
+            ],
+        )
+        .await;
+    match result {
+        Ok(row) => {
+            let insert = row.get::<usize, Option<bool>>(0).unwrap();
+            info!(id = id.as_str(), insert);
+        }
+        Err(error) => {
+            error!(id = id.as_str(), ?error);
+        }
+    }
+}
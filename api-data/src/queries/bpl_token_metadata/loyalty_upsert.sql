@@ -0,0 +1,30 @@
+INSERT INTO loyalty (
+    id,
+    promo,
+    mint,
+    earn_rate,
+    levels,
+    points_earned,
+    points_redeemed,
+    slot,
+    write_version
+)
+    VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9)
+ON CONFLICT ON CONSTRAINT loyalty_pkey DO UPDATE 
+    SET
+        promo = EXCLUDED.promo,
+        mint = EXCLUDED.mint,
+        earn_rate = EXCLUDED.earn_rate,
+        levels = EXCLUDED.levels,
+        points_earned = EXCLUDED.points_earned,
+        points_redeemed = EXCLUDED.points_redeemed,
+        slot = EXCLUDED.slot,
+        write_version = EXCLUDED.write_version,
+        modified_at = NOW()
+    WHERE
+        EXCLUDED.slot > loyalty.slot
+        OR (
+            EXCLUDED.slot = loyalty.slot
+            AND EXCLUDED.write_version > loyalty.write_version
+        )
+RETURNING created_at = modified_at
\ No newline at end of file
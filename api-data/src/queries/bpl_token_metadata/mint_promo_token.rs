@@ -5,6 +5,12 @@ use tracing::{error, info};
 
 const UPSERT_QUERY: &str = include_str!("mint_promo_token_upsert.sql");
 
+/// Index of the token account in `MintPromoToken`, after the loyalty account.
+const TOKEN_ACCOUNT_INDEX: usize = 7;
+
+/// Index of the token account in `MintPromoToken2022`, which has no loyalty account.
+const TOKEN_ACCOUNT_INDEX_2022: usize = 6;
+
 #[tracing::instrument(skip_all)]
 pub async fn upsert(
     client: &Client,
@@ -12,6 +18,38 @@ pub async fn upsert(
     accounts: &Vec<Pubkey>,
     data: &[u8],
     slot: u64,
+) {
+    upsert_with_token_account(client, signature, accounts, data, slot, TOKEN_ACCOUNT_INDEX).await
+}
+
+/// Records mints of promo tokens of Token-2022 mints, which have the same args as
+/// `MintPromoToken` and the same accounts up to the token account.
+#[tracing::instrument(skip_all)]
+pub async fn upsert_2022(
+    client: &Client,
+    signature: &Signature,
+    accounts: &Vec<Pubkey>,
+    data: &[u8],
+    slot: u64,
+) {
+    upsert_with_token_account(
+        client,
+        signature,
+        accounts,
+        data,
+        slot,
+        TOKEN_ACCOUNT_INDEX_2022,
+    )
+    .await
+}
+
+async fn upsert_with_token_account(
+    client: &Client,
+    signature: &Signature,
+    accounts: &Vec<Pubkey>,
+    data: &[u8],
+    slot: u64,
+    token_account_index: usize,
 ) {
     let accounts: Vec<String> = accounts.iter().map(ToString::to_string).collect();
     let memo = if let Ok(args) =
@@ -35,7 +73,7 @@ pub async fn upsert(
     let mint = &accounts[3];
     let authority = &accounts[4];
     let promo = &accounts[5];
-    let token_account = &accounts[6];
+    let token_account = &accounts[token_account_index];
     let slot = slot as i64;
 
     let result = client
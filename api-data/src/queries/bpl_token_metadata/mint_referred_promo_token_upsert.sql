@@ -0,0 +1,34 @@
+INSERT INTO mint_referred_promo_token (
+    signature,
+    payer,
+    promo_group,
+    token_owner,
+    mint,
+    authority,
+    promo,
+    referral_reward,
+    referral,
+    token_account,
+    referrer,
+    memo,
+    slot
+)
+    VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+ON CONFLICT ON CONSTRAINT mint_referred_promo_token_pkey DO UPDATE 
+    SET
+        payer = EXCLUDED.payer,
+        promo_group = EXCLUDED.promo_group,
+        token_owner = EXCLUDED.token_owner,
+        mint = EXCLUDED.mint,
+        authority = EXCLUDED.authority,
+        promo = EXCLUDED.promo,
+        referral_reward = EXCLUDED.referral_reward,
+        referral = EXCLUDED.referral,
+        token_account = EXCLUDED.token_account,
+        referrer = EXCLUDED.referrer,
+        memo = EXCLUDED.memo,
+        slot = EXCLUDED.slot,
+        modified_at = NOW()
+    WHERE
+        EXCLUDED.slot > mint_referred_promo_token.slot
+RETURNING created_at = modified_at
\ No newline at end of file
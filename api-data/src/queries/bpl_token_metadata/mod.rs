@@ -1,7 +1,19 @@
+pub mod burn_accepted_promo_token;
 pub mod burn_delegated_promo_token;
+pub mod burn_referred_promo_token;
 pub mod create_promo;
 pub mod create_promo_group;
+pub mod delegate_accepted_promo_token;
 pub mod delegate_promo_token;
+pub mod earn_points;
+pub mod loyalty;
 pub mod mint_promo_token;
+pub mod mint_referred_promo_token;
 pub mod promo;
+pub mod promo_acceptance;
 pub mod promo_group;
+pub mod promo_metadata;
+pub mod promo_mint;
+pub mod redeem_points;
+pub mod referral;
+pub mod referral_reward;
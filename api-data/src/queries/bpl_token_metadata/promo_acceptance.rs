@@ -0,0 +1,51 @@
+use bpl_token_metadata::state::{PromoAcceptance, Settlement};
+use tokio_postgres::{types::Json, Client};
+use tracing::{error, info};
+
+const UPSERT_QUERY: &str = include_str!("promo_acceptance_upsert.sql");
+
+#[tracing::instrument(skip_all)]
+pub async fn upsert(
+    client: &Client,
+    key: &[u8],
+    account: &PromoAcceptance,
+    slot: u64,
+    write_version: u64,
+) {
+    let id = bs58::encode(key).into_string();
+    let promo = account.promo.to_string();
+    let owner = account.owner.to_string();
+    let groups = account.groups.iter().map(ToString::to_string).collect();
+    let settlement = match account.settlement {
+        Settlement::IssuerPaysRedeemer => "IssuerPaysRedeemer",
+        Settlement::RedeemerPaysIssuer => "RedeemerPaysIssuer",
+    };
+    let settlement_lamports = account.settlement_lamports as i64;
+    let slot = slot as i64;
+    let write_version = write_version as i64;
+
+    let result = client
+        .query_one(
+            UPSERT_QUERY,
+            &[
+                &id,
+                &promo,
+                &owner,
+                &Json::<Vec<String>>(groups),
+                &settlement,
+                &settlement_lamports,
+                &slot,
+                &write_version,
+            ],
+        )
+        .await;
+    match result {
+        Ok(row) => {
+            let insert = row.get::<usize, Option<bool>>(0).unwrap();
+            info!(id = id.as_str(), insert);
+        }
+        Err(error) => {
+            error!(id = id.as_str(), ?error);
+        }
+    }
+}
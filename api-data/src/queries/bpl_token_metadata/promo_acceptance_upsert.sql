@@ -0,0 +1,28 @@
+INSERT INTO promo_acceptance (
+    id,
+    promo,
+    owner,
+    groups,
+    settlement,
+    settlement_lamports,
+    slot,
+    write_version
+)
+    VALUES($1, $2, $3, $4, $5, $6, $7, $8)
+ON CONFLICT ON CONSTRAINT promo_acceptance_pkey DO UPDATE 
+    SET
+        promo = EXCLUDED.promo,
+        owner = EXCLUDED.owner,
+        groups = EXCLUDED.groups,
+        settlement = EXCLUDED.settlement,
+        settlement_lamports = EXCLUDED.settlement_lamports,
+        slot = EXCLUDED.slot,
+        write_version = EXCLUDED.write_version,
+        modified_at = NOW()
+    WHERE
+        EXCLUDED.slot > promo_acceptance.slot
+        OR (
+            EXCLUDED.slot = promo_acceptance.slot
+            AND EXCLUDED.write_version > promo_acceptance.write_version
+        )
+RETURNING created_at = modified_at
\ No newline at end of file
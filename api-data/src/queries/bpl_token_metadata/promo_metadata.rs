@@ -0,0 +1,105 @@
+use bpl_promo_metadata::{PromoMetadata, PromoType};
+use std::time::Duration;
+use tokio_postgres::{types::Json, Client};
+use tracing::{error, info};
+
+const UPSERT_QUERY: &str = include_str!("promo_metadata_upsert.sql");
+
+/// Fetches the json metadata at `uri`, returning `None` if it can't be retrieved.
+pub async fn fetch_metadata_json(uri: &str) -> Option<serde_json::Value> {
+    let reqwest_client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(3))
+        .build()
+        .unwrap();
+
+    let response = reqwest_client.get(uri).send().await.ok()?;
+    response.json::<serde_json::Value>().await.ok()
+}
+
+/// Stores the promo fields of `metadata_json` for `mint`. Metadata that isn't promo metadata,
+/// such as that of other tokens, is skipped.
+#[tracing::instrument(skip_all)]
+pub async fn upsert(
+    client: &Client,
+    mint: &str,
+    uri: &str,
+    metadata_json: &serde_json::Value,
+    slot: u64,
+    write_version: u64,
+) {
+    let metadata = match PromoMetadata::from_value(metadata_json.clone()) {
+        Ok(metadata) => metadata,
+        Err(error) => {
+            info!(mint, skipped = %error);
+            return;
+        }
+    };
+
+    let attributes = &metadata.attributes;
+    let promo_type = attributes.promo_type.name();
+    let (mut product_id, mut buy_x_product, mut get_y_product) = (None, None, None);
+    let (mut buy_x_currency, mut get_y_percent) = (None, None);
+    let (mut earn_rate, mut loyalty_levels) = (None, None);
+    match &attributes.promo_type {
+        PromoType::BuyXProductGetYFree {
+            product_id: id,
+            buy_x_product: buy,
+            get_y_product: get,
+        } => {
+            product_id = Some(id.clone());
+            buy_x_product = Some(*buy as i64);
+            get_y_product = Some(*get as i64);
+        }
+        PromoType::BuyXCurrencyGetYPercent {
+            buy_x_currency: buy,
+            get_y_percent: get,
+        } => {
+            buy_x_currency = Some(*buy as i64);
+            get_y_percent = Some(*get as i64);
+        }
+        PromoType::Loyalty {
+            earn_rate: rate,
+            levels,
+        } => {
+            earn_rate = Some(*rate as i64);
+            loyalty_levels = Some(serde_json::to_value(levels).unwrap());
+        }
+    }
+    let max_mint = attributes.max_mint.map(|v| v as i64);
+    let max_burn = attributes.max_burn.map(|v| v as i64);
+    let attributes_json = metadata_json.get("attributes").cloned().unwrap_or_default();
+    let slot = slot as i64;
+    let write_version = write_version as i64;
+
+    let result = client
+        .query_one(
+            UPSERT_QUERY,
+            &[
+                &mint,
+                &uri,
+                &promo_type,
+                &product_id,
+                &buy_x_product,
+                &get_y_product,
+                &buy_x_currency,
+                &get_y_percent,
+                &earn_rate,
+                &loyalty_levels.map(Json::<serde_json::Value>),
+                &max_mint,
+                &max_burn,
+                &Json::<serde_json::Value>(attributes_json),
+                &slot,
+                &write_version,
+            ],
+        )
+        .await;
+    match result {
+        Ok(row) => {
+            let insert = row.get::<usize, Option<bool>>(0).unwrap();
+            info!(mint, insert);
+        }
+        Err(error) => {
+            error!(mint, ?error);
+        }
+    }
+}
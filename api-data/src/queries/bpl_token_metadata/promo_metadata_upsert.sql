@@ -0,0 +1,42 @@
+INSERT INTO promo_metadata (
+    mint,
+    uri,
+    promo_type,
+    product_id,
+    buy_x_product,
+    get_y_product,
+    buy_x_currency,
+    get_y_percent,
+    earn_rate,
+    loyalty_levels,
+    max_mint,
+    max_burn,
+    attributes,
+    slot,
+    write_version
+)
+    VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+ON CONFLICT ON CONSTRAINT promo_metadata_pkey DO UPDATE 
+    SET
+        uri = EXCLUDED.uri,
+        promo_type = EXCLUDED.promo_type,
+        product_id = EXCLUDED.product_id,
+        buy_x_product = EXCLUDED.buy_x_product,
+        get_y_product = EXCLUDED.get_y_product,
+        buy_x_currency = EXCLUDED.buy_x_currency,
+        get_y_percent = EXCLUDED.get_y_percent,
+        earn_rate = EXCLUDED.earn_rate,
+        loyalty_levels = EXCLUDED.loyalty_levels,
+        max_mint = EXCLUDED.max_mint,
+        max_burn = EXCLUDED.max_burn,
+        attributes = EXCLUDED.attributes,
+        slot = EXCLUDED.slot,
+        write_version = EXCLUDED.write_version,
+        modified_at = NOW()
+    WHERE
+        EXCLUDED.slot > promo_metadata.slot
+        OR (
+            EXCLUDED.slot = promo_metadata.slot
+            AND EXCLUDED.write_version > promo_metadata.write_version
+        )
+RETURNING created_at = modified_at
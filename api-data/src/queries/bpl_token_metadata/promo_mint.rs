@@ -0,0 +1,63 @@
+use crate::queries::bpl_token_metadata::promo_metadata::{self, fetch_metadata_json};
+use bpl_token_metadata::state::PromoMint;
+use tokio_postgres::Client;
+use tracing::{error, info};
+
+const UPSERT_QUERY: &str = include_str!("promo_mint_upsert.sql");
+
+#[tracing::instrument(skip_all)]
+pub async fn upsert(
+    client: &Client,
+    key: &[u8],
+    account: &PromoMint,
+    slot: u64,
+    write_version: u64,
+) {
+    let id = bs58::encode(key).into_string();
+    let promo = account.promo.to_string();
+    let mint = account.mint.to_string();
+
+    // Token-2022 promos have no Metaplex metadata, so their json metadata is fetched here.
+    if let Some(metadata_json) = fetch_metadata_json(&account.uri).await {
+        promo_metadata::upsert(
+            client,
+            &mint,
+            &account.uri,
+            &metadata_json,
+            slot,
+            write_version,
+        )
+        .await;
+    }
+
+    let slot = slot as i64;
+    let write_version = write_version as i64;
+
+    let result = client
+        .query_one(
+            UPSERT_QUERY,
+            &[
+                &id,
+                &promo,
+                &mint,
+                &account.extensions.non_transferable,
+                &account.extensions.permanent_delegate,
+                &account.extensions.metadata_pointer,
+                &account.name,
+                &account.symbol,
+                &account.uri,
+                &slot,
+                &write_version,
+            ],
+        )
+        .await;
+    match result {
+        Ok(row) => {
+            let insert = row.get::<usize, Option<bool>>(0).unwrap();
+            info!(id = id.as_str(), insert);
+        }
+        Err(error) => {
+            error!(id = id.as_str(), ?error);
+        }
+    }
+}
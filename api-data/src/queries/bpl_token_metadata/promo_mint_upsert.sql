@@ -0,0 +1,34 @@
+INSERT INTO promo_mint (
+    id,
+    promo,
+    mint,
+    non_transferable,
+    permanent_delegate,
+    metadata_pointer,
+    name,
+    symbol,
+    uri,
+    slot,
+    write_version
+)
+    VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+ON CONFLICT ON CONSTRAINT promo_mint_pkey DO UPDATE 
+    SET
+        promo = EXCLUDED.promo,
+        mint = EXCLUDED.mint,
+        non_transferable = EXCLUDED.non_transferable,
+        permanent_delegate = EXCLUDED.permanent_delegate,
+        metadata_pointer = EXCLUDED.metadata_pointer,
+        name = EXCLUDED.name,
+        symbol = EXCLUDED.symbol,
+        uri = EXCLUDED.uri,
+        slot = EXCLUDED.slot,
+        write_version = EXCLUDED.write_version,
+        modified_at = NOW()
+    WHERE
+        EXCLUDED.slot > promo_mint.slot
+        OR (
+            EXCLUDED.slot = promo_mint.slot
+            AND EXCLUDED.write_version > promo_mint.write_version
+        )
+RETURNING created_at = modified_at
\ No newline at end of file
@@ -0,0 +1,72 @@
+use borsh::de::BorshDeserialize;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use tokio_postgres::{types::Json, Client};
+use tracing::{error, info};
+
+const UPSERT_QUERY: &str = include_str!("redeem_points_upsert.sql");
+
+#[tracing::instrument(skip_all)]
+pub async fn upsert(
+    client: &Client,
+    signature: &Signature,
+    accounts: &Vec<Pubkey>,
+    data: &[u8],
+    slot: u64,
+) {
+    let accounts: Vec<String> = accounts.iter().map(ToString::to_string).collect();
+    let signature = signature.to_string();
+
+    // Level is needed to join the reward redeemed, so skip if args don't parse.
+    let args = match bpl_token_metadata::instruction::RedeemPoints::try_from_slice(&data[8..]) {
+        Ok(args) => args,
+        Err(error) => {
+            error!(signature = signature.as_str(), ?error);
+            return;
+        }
+    };
+    let level = args.level as i32;
+    let memo = args.memo.map(|m| {
+        if let Ok(result) = serde_json::from_str::<serde_json::Value>(&m) {
+            result
+        } else {
+            serde_json::json!({ "memo": m })
+        }
+    });
+
+    let payer = &accounts[0];
+    let promo_group = &accounts[1];
+    let token_owner = &accounts[2];
+    let mint = &accounts[3];
+    let promo = &accounts[4];
+    let loyalty = &accounts[5];
+    let token_account = &accounts[6];
+    let slot = slot as i64;
+
+    let result = client
+        .query_one(
+            UPSERT_QUERY,
+            &[
+                &signature,
+                payer,
+                promo_group,
+                token_owner,
+                mint,
+                promo,
+                loyalty,
+                token_account,
+                &level,
+                &Json::<Option<serde_json::Value>>(memo),
+                &slot,
+            ],
+        )
+        .await;
+    match result {
+        Ok(row) => {
+            let insert = row.get::<usize, Option<bool>>(0).unwrap();
+            info!(signature = signature.as_str(), insert);
+        }
+        Err(error) => {
+            error!(signature = signature.as_str(), ?error);
+        }
+    }
+}
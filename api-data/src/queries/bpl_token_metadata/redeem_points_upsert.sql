@@ -0,0 +1,30 @@
+INSERT INTO redeem_points (
+    signature,
+    payer,
+    promo_group,
+    token_owner,
+    mint,
+    promo,
+    loyalty,
+    token_account,
+    level,
+    memo,
+    slot
+)
+    VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+ON CONFLICT ON CONSTRAINT redeem_points_pkey DO UPDATE 
+    SET
+        payer = EXCLUDED.payer,
+        promo_group = EXCLUDED.promo_group,
+        token_owner = EXCLUDED.token_owner,
+        mint = EXCLUDED.mint,
+        promo = EXCLUDED.promo,
+        loyalty = EXCLUDED.loyalty,
+        token_account = EXCLUDED.token_account,
+        level = EXCLUDED.level,
+        memo = EXCLUDED.memo,
+        slot = EXCLUDED.slot,
+        modified_at = NOW()
+    WHERE
+        EXCLUDED.slot > redeem_points.slot
+RETURNING created_at = modified_at
\ No newline at end of file
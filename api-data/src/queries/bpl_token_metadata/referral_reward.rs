@@ -0,0 +1,33 @@
+use bpl_token_metadata::state::ReferralReward;
+use tokio_postgres::Client;
+use tracing::{error, info};
+
+const UPSERT_QUERY: &str = include_str!("referral_reward_upsert.sql");
+
+#[tracing::instrument(skip_all)]
+pub async fn upsert(
+    client: &Client,
+    key: &[u8],
+    account: &ReferralReward,
+    slot: u64,
+    write_version: u64,
+) {
+    let id = bs58::encode(key).into_string();
+    let promo = account.promo.to_string();
+    let reward_mint = account.reward_mint.to_string();
+    let slot = slot as i64;
+    let write_version = write_version as i64;
+
+    let result = client
+        .query_one(UPSERT_QUERY, &[&id, &promo, &reward_mint, &slot, &write_version])
+        .await;
+    match result {
+        Ok(row) => {
+            let insert = row.get::<usize, Option<bool>>(0).unwrap();
+            info!(id = id.as_str(), insert);
+        }
+        Err(error) => {
+            error!(id = id.as_str(), ?error);
+        }
+    }
+}
@@ -0,0 +1,22 @@
+INSERT INTO referral_reward (
+    id,
+    promo,
+    reward_mint,
+    slot,
+    write_version
+)
+    VALUES($1, $2, $3, $4, $5)
+ON CONFLICT ON CONSTRAINT referral_reward_pkey DO UPDATE 
+    SET
+        promo = EXCLUDED.promo,
+        reward_mint = EXCLUDED.reward_mint,
+        slot = EXCLUDED.slot,
+        write_version = EXCLUDED.write_version,
+        modified_at = NOW()
+    WHERE
+        EXCLUDED.slot > referral_reward.slot
+        OR (
+            EXCLUDED.slot = referral_reward.slot
+            AND EXCLUDED.write_version > referral_reward.write_version
+        )
+RETURNING created_at = modified_at
\ No newline at end of file
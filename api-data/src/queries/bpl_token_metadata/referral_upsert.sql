@@ -0,0 +1,28 @@
+INSERT INTO referral (
+    id,
+    promo,
+    mint,
+    token_owner,
+    referrer,
+    converted,
+    slot,
+    write_version
+)
+    VALUES($1, $2, $3, $4, $5, $6, $7, $8)
+ON CONFLICT ON CONSTRAINT referral_pkey DO UPDATE 
+    SET
+        promo = EXCLUDED.promo,
+        mint = EXCLUDED.mint,
+        token_owner = EXCLUDED.token_owner,
+        referrer = EXCLUDED.referrer,
+        converted = EXCLUDED.converted,
+        slot = EXCLUDED.slot,
+        write_version = EXCLUDED.write_version,
+        modified_at = NOW()
+    WHERE
+        EXCLUDED.slot > referral.slot
+        OR (
+            EXCLUDED.slot = referral.slot
+            AND EXCLUDED.write_version > referral.write_version
+        )
+RETURNING created_at = modified_at
\ No newline at end of file
@@ -1,6 +1,7 @@
-use std::time::Duration;
-
-use crate::queries::mpl_token_metadata::creator;
+use crate::queries::{
+    bpl_token_metadata::promo_metadata::{self, fetch_metadata_json},
+    mpl_token_metadata::creator,
+};
 use futures::future::try_join;
 use mpl_token_metadata::state::{Key, Metadata, TokenStandard, UseMethod};
 use tokio_postgres::{types::Json, Client};
@@ -36,6 +37,12 @@ pub async fn upsert(
     let name = account.data.name.trim_matches(char::from(0)).to_string();
     let symbol = account.data.symbol.trim_matches(char::from(0)).to_string();
     let uri = account.data.uri.trim_matches(char::from(0)).to_string();
+
+    let metadata_json = fetch_metadata_json(&uri).await;
+    if let Some(metadata_json) = metadata_json.as_ref() {
+        promo_metadata::upsert(client, &mint, &uri, metadata_json, slot, write_version).await;
+    }
+
     let seller_fee_basis_points = account.data.seller_fee_basis_points as i32;
     let primary_sale_happened = account.primary_sale_happened;
     let is_mutable = account.is_mutable;
@@ -72,22 +79,6 @@ pub async fn upsert(
 
     let slot = slot as i64;
     let write_version = write_version as i64;
-    let reqwest_client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(3))
-        .build()
-        .unwrap();
-
-    let metadata_json: Option<serde_json::Value> =
-        if let Ok(response) = reqwest_client.get(&uri).send().await {
-            if let Ok(result) = response.json::<serde_json::Value>().await {
-                Some(result)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
     let result = try_join(
         client.query_one(
             UPSERT_QUERY,
@@ -1,12 +1,22 @@
-use spl_token::state::Mint;
-use tokio_postgres::Client;
+use spl_token::{solana_program::pubkey::Pubkey, state::Mint};
+use tokio_postgres::{types::Json, Client};
 use tracing::{error, info};
 
 const UPSERT_QUERY: &str = include_str!("mint_upsert.sql");
 
 #[tracing::instrument(skip_all)]
-pub async fn upsert(client: &Client, key: &[u8], account: &Mint, slot: u64, write_version: u64) {
+pub async fn upsert(
+    client: &Client,
+    key: &[u8],
+    account: &Mint,
+    token_program: &Pubkey,
+    extensions: &[&str],
+    slot: u64,
+    write_version: u64,
+) {
     let id = bs58::encode(key).into_string();
+    let token_program = token_program.to_string();
+    let extensions = Json::<Vec<&str>>(extensions.to_vec());
     let freeze_authority: Option<String> = account.freeze_authority.map(|p| p.to_string()).into();
     let mint_authority: Option<String> = account.mint_authority.map(|p| p.to_string()).into();
     let supply = account.supply as i64;
@@ -24,6 +34,8 @@ pub async fn upsert(client: &Client, key: &[u8], account: &Mint, slot: u64, writ
                 &account.is_initialized,
                 &supply,
                 &decimals,
+                &token_program,
+                &extensions,
                 &slot,
                 &write_version,
             ],
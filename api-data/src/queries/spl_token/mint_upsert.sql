@@ -5,10 +5,12 @@ INSERT INTO mint (
     is_initialized,
     supply,
     decimals,
+    token_program,
+    extensions,
     slot,
     write_version
 )
-    VALUES($1, $2, $3, $4, $5, $6, $7, $8)
+    VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
 ON CONFLICT ON CONSTRAINT mint_pkey DO UPDATE 
     SET
         freeze_authority = EXCLUDED.freeze_authority,
@@ -16,6 +18,8 @@ ON CONFLICT ON CONSTRAINT mint_pkey DO UPDATE
         is_initialized = EXCLUDED.is_initialized,
         supply = EXCLUDED.supply ,
         decimals = EXCLUDED.decimals,
+        token_program = EXCLUDED.token_program,
+        extensions = EXCLUDED.extensions,
         slot = EXCLUDED.slot,
         write_version = EXCLUDED.write_version,
         modified_at = NOW()
@@ -1,12 +1,25 @@
-use spl_token::state::{Account, AccountState};
-use tokio_postgres::Client;
+use spl_token::{
+    solana_program::pubkey::Pubkey,
+    state::{Account, AccountState},
+};
+use tokio_postgres::{types::Json, Client};
 use tracing::{error, info};
 
 const UPSERT_QUERY: &str = include_str!("token_account_upsert.sql");
 
 #[tracing::instrument(skip_all)]
-pub async fn upsert(client: &Client, key: &[u8], account: &Account, slot: u64, write_version: u64) {
+pub async fn upsert(
+    client: &Client,
+    key: &[u8],
+    account: &Account,
+    token_program: &Pubkey,
+    extensions: &[&str],
+    slot: u64,
+    write_version: u64,
+) {
     let id = bs58::encode(key).into_string();
+    let token_program = token_program.to_string();
+    let extensions = Json::<Vec<&str>>(extensions.to_vec());
     let mint = account.mint.to_string();
     let owner = account.owner.to_string();
     let amount = account.amount as i64;
@@ -36,6 +49,8 @@ pub async fn upsert(client: &Client, key: &[u8], account: &Account, slot: u64, w
                 &is_native,
                 &delegated_amount,
                 &close_authority,
+                &token_program,
+                &extensions,
                 &slot,
                 &write_version,
             ],
@@ -8,10 +8,12 @@ INSERT INTO token_account (
     is_native,
     delegated_amount,
     close_authority,
+    token_program,
+    extensions,
     slot,
     write_version
 )
-    VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+    VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
 ON CONFLICT ON CONSTRAINT token_account_pkey DO UPDATE 
     SET
         mint = EXCLUDED.mint, 
@@ -22,6 +24,8 @@ ON CONFLICT ON CONSTRAINT token_account_pkey DO UPDATE
         is_native = EXCLUDED.is_native,
         delegated_amount = EXCLUDED.delegated_amount,
         close_authority = EXCLUDED.close_authority, 
+        token_program = EXCLUDED.token_program,
+        extensions = EXCLUDED.extensions,
         slot = EXCLUDED.slot,
         write_version = EXCLUDED.write_version,
         modified_at = NOW()
@@ -1,11 +1,12 @@
 use anchor_lang::solana_program::pubkey::ParsePubkeyError;
+use bpl_token_metadata::error::ProgramError;
 use serde_json::json;
 use solana_sdk::signature::SignerError;
 use thiserror::Error;
 
 use axum::{
     body::BoxBody,
-    http::{Response, StatusCode},
+    http::{header, Response, StatusCode},
     response::IntoResponse,
     Json,
 };
@@ -19,14 +20,105 @@ pub enum AppError {
     BincodeError(#[from] Box<bincode::ErrorKind>),
     #[error("bundlr: {0}")]
     BundlrError(#[from] bundlr_sdk::error::BundlrError),
+    #[error("storage: {0}")]
+    StorageError(String),
+    #[error(
+        "storage balance of {balance} lamports can't pay for uploads costing {price} lamports"
+    )]
+    StorageUnderfunded { balance: u64, price: u64 },
+    #[error("invalid image: {0}")]
+    InvalidImage(String),
+    #[error("image should be at most {0} bytes")]
+    ImageTooLarge(usize),
+    #[error("qr code: {0}")]
+    QrCodeError(String),
+    #[error("promo metadata: {0}")]
+    PromoMetadata(#[from] bpl_promo_metadata::MetadataError),
     #[error("create promo request: {0}")]
     CreatePromoRequestError(String),
-    #[error("data query error")]
-    DataQueryError,
+    #[error("mint not found: {0}")]
+    MintNotFound(String),
+    #[error("token account not found: {0}")]
+    TokenAccountNotFound(String),
+    #[error("promo not found for mint: {0}")]
+    PromoNotFound(String),
+    #[error("group not found: {0}")]
+    GroupNotFound(String),
+    #[error("invalid group profile: {0}")]
+    InvalidGroupProfile(String),
+    #[error("invalid webhook: {0}")]
+    InvalidWebhook(String),
+    #[error("webhook endpoint not found: {0}")]
+    WebhookEndpointNotFound(String),
+    #[error("invalid signature")]
+    InvalidSignature,
+    #[error("signed message has expired")]
+    SignedMessageExpired,
+    #[error("sign in is not available for host: {0}")]
+    InvalidSignInDomain(String),
+    #[error("sign in required")]
+    Unauthenticated,
+    #[error("account is not a member of group: {0}")]
+    NotGroupMember(String),
+    #[error("account is not the owner of group: {0}")]
+    NotGroupOwner(String),
+    #[error("{0} requires the postgres data source, set DATA_SOURCE=postgres")]
+    ReadOnlyDataSource(String),
+    #[error("too many requests, retry after {retry_after} seconds")]
+    RateLimited { retry_after: u64 },
+    #[error("denied: {0}")]
+    Denied(String),
+    #[error("request body should be at most {0} bytes")]
+    BodyTooLarge(usize),
+    #[error("redis: {0}")]
+    Redis(#[from] redis::RedisError),
+    #[error("malformed data: {0}")]
+    MalformedData(String),
+    #[error("data api error: {0}")]
+    DataApiError(String),
+    #[error("database: {0}")]
+    Database(#[from] tokio_postgres::Error),
+    #[error("database pool: {0}")]
+    DatabasePool(#[from] deadpool_postgres::PoolError),
     #[error("pubkey error")]
     PubkeyError(#[from] ParsePubkeyError),
     #[error("payer not in members")]
     PayerNotInMembers,
+    #[error("promo acceptance groups exceed capacity")]
+    AcceptanceCapacityExceeded,
+    #[error("invalid settlement: {0}")]
+    InvalidSettlement(String),
+    #[error("loyalty promos need between one and five reward levels")]
+    InvalidLoyaltyLevels,
+    #[error("promo has no referral reward")]
+    ReferralRewardNotFound,
+    #[error("not supported for Token-2022 promos: {0}")]
+    Token2022Unsupported(String),
+    #[error("promo tokens are not transferable")]
+    PromoNotTransferable,
+    #[error("account is not the owner of the token account")]
+    NotTokenOwner,
+    #[error("account is not the buyer or seller")]
+    NotTradeParticipant,
+    #[error("invalid signed transaction: {0}")]
+    InvalidSignedTransaction(String),
+    #[error("signed transaction not found or expired")]
+    SignedTransactionNotFound,
+    #[error("checkout session not found or expired")]
+    CheckoutSessionNotFound,
+    #[error("checkout session is {0}")]
+    InvalidCheckoutStatus(String),
+    #[error("account is not the checkout session delegate")]
+    NotCheckoutDelegate,
+    #[error("durable nonce account not found: {0}")]
+    NonceNotFound(String),
+    #[error("unsupported transaction version: {0}")]
+    UnsupportedTransactionVersion(u8),
+    #[error("transaction simulation failed: {error}")]
+    SimulationError {
+        error: SimulationError,
+        logs: Vec<String>,
+    },
     #[error("reqwest: {0}")]
     Reqwest(#[from] reqwest::Error),
     #[error("serde json: {0}")]
@@ -39,26 +131,125 @@ pub enum AppError {
     SolanaHashParse(#[from] solana_sdk::hash::ParseHashError),
     #[error("error posting solana transaction: {0}")]
     SolanaPostError(String),
-    #[error("error posting to clover: {0}")]
-    CloverPostError(reqwest::Error),
+    #[error("error posting webhook: {0}")]
+    WebhookPostError(reqwest::Error),
     #[error("error parsing url: {0}")]
     UrlParseError(url::ParseError),
     #[error("error decoding url encoded string: {0}")]
     UrlDecodeError(std::string::FromUtf8Error),
-    #[error("clover status not OK: {0}")]
+    #[error("status not OK: {0}")]
     StatusNotOK(reqwest::StatusCode),
 }
 
+/// Reasons generated transactions fail simulation.
+#[derive(Error, Debug)]
+pub enum SimulationError {
+    #[error("{0}")]
+    Program(ProgramError),
+    #[error("{name}: {message}")]
+    Anchor {
+        code: u32,
+        name: String,
+        message: String,
+    },
+    #[error("{0}")]
+    Other(String),
+}
+
+/// Program errors by position, used to map custom error codes back to errors. Needs to be kept
+/// in the same order as `bpl_token_metadata::error::ProgramError`.
+pub const PROGRAM_ERRORS: [ProgramError; 14] = [
+    ProgramError::MaxMintExceeded,
+    ProgramError::MaxBurnExceeded,
+    ProgramError::ExpiryExceeded,
+    ProgramError::InsufficientFunds,
+    ProgramError::ConstraintNotMet,
+    ProgramError::RewardLevelNotFound,
+    ProgramError::InsufficientPoints,
+    ProgramError::Overflow,
+    ProgramError::ReferralAlreadyConverted,
+    ProgramError::MetadataTooLong,
+    ProgramError::PermanentDelegateRequired,
+    ProgramError::LoyaltyPointsEarnedOnly,
+    ProgramError::SettlementNotApproved,
+    ProgramError::SponsorshipAllowanceExceeded,
+];
+
+impl SimulationError {
+    fn status(&self) -> StatusCode {
+        match self {
+            SimulationError::Program(
+                ProgramError::MaxMintExceeded
+                | ProgramError::MaxBurnExceeded
+                | ProgramError::ExpiryExceeded,
+            ) => StatusCode::GONE,
+            SimulationError::Program(
+                ProgramError::InsufficientFunds | ProgramError::InsufficientPoints,
+            ) => StatusCode::PAYMENT_REQUIRED,
+            SimulationError::Program(
+                ProgramError::SettlementNotApproved | ProgramError::SponsorshipAllowanceExceeded,
+            ) => StatusCode::FORBIDDEN,
+            SimulationError::Program(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            // Anchor account constraint errors.
+            SimulationError::Anchor { code, .. } if (2000..3000).contains(code) => {
+                StatusCode::FORBIDDEN
+            }
+            // AccountNotInitialized, e.g. the customer has no token account.
+            SimulationError::Anchor { code: 3012, .. } => StatusCode::NOT_FOUND,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response<BoxBody> {
-        let status = match self {
+        let status = match &self {
             AppError::GenericError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::MintNotFound(_)
+            | AppError::TokenAccountNotFound(_)
+            | AppError::PromoNotFound(_)
+            | AppError::GroupNotFound(_)
+            | AppError::CheckoutSessionNotFound
+            | AppError::WebhookEndpointNotFound(_) => StatusCode::NOT_FOUND,
+            AppError::InvalidSignature
+            | AppError::SignedMessageExpired
+            | AppError::Unauthenticated => StatusCode::UNAUTHORIZED,
+            AppError::NotGroupMember(_) | AppError::NotGroupOwner(_) => StatusCode::FORBIDDEN,
+            AppError::ReadOnlyDataSource(_) => StatusCode::NOT_IMPLEMENTED,
+            AppError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Denied(_) => StatusCode::FORBIDDEN,
+            AppError::InvalidCheckoutStatus(_) => StatusCode::CONFLICT,
+            AppError::MalformedData(_)
+            | AppError::DataApiError(_)
+            | AppError::Database(_)
+            | AppError::DatabasePool(_)
+            | AppError::Redis(_)
+            | AppError::StorageError(_) => StatusCode::BAD_GATEWAY,
+            AppError::ImageTooLarge(_) | AppError::BodyTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            AppError::StorageUnderfunded { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::SimulationError { error, .. } => error.status(),
             _ => StatusCode::BAD_REQUEST,
         };
 
-        let body = Json(json!({
+        let mut body = json!({
             "error": self.to_string(),
-        }));
-        (status, body).into_response()
+        });
+        // Program logs are only returned in debug builds.
+        if let AppError::SimulationError { logs, .. } = &self {
+            if cfg!(debug_assertions) {
+                body["logs"] = json!(logs);
+            }
+        }
+        // Clients are told when they can retry rate limited requests.
+        if let AppError::RateLimited { retry_after } = &self {
+            body["retryAfter"] = json!(retry_after);
+            return (
+                status,
+                [(header::RETRY_AFTER, retry_after.to_string())],
+                Json(body),
+            )
+                .into_response();
+        }
+        (status, Json(body)).into_response()
     }
 }
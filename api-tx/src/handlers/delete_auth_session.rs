@@ -0,0 +1,19 @@
+use axum::{
+    extract::Extension,
+    http::{header, HeaderMap, StatusCode},
+};
+use std::sync::Arc;
+
+use crate::State;
+
+/// Signs out by ending the session of the bearer token, if there is one.
+pub async fn handler(headers: HeaderMap, Extension(state): Extension<Arc<State>>) -> StatusCode {
+    if let Some(token) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    {
+        state.sign_out(token.trim());
+    }
+    StatusCode::NO_CONTENT
+}
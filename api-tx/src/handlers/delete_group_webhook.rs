@@ -0,0 +1,29 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path},
+    http::StatusCode,
+};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{error::AppError, handlers::WebhookEndpointParams, utils::auth::AuthSession, State};
+
+/// Handles a group owner removing one of the group's webhook endpoints. Deliveries still
+/// pending to it are not attempted. Requires the owner to be signed in.
+pub async fn handler(
+    session: AuthSession,
+    Path(WebhookEndpointParams {
+        group_string,
+        endpoint_id,
+    }): Path<WebhookEndpointParams>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<StatusCode, AppError> {
+    tracing::debug!(group_string, endpoint_id);
+
+    let group = Pubkey::from_str(&group_string)?;
+    state.check_group_owner(&session, &group).await?;
+    if !state.webhooks.delete_endpoint(&group, &endpoint_id).await? {
+        return Err(AppError::WebhookEndpointNotFound(endpoint_id));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,26 +1,88 @@
-use crate::{error::AppError, handlers::Params};
-use axum::{extract::Path, Json};
+use crate::{error::AppError, utils::data::GroupProfileObject, State};
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path},
+    Json,
+};
+use bpl_token_metadata::utils::find_group_address;
 use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
 
 pub const LABEL: &str = "bokoup";
 pub const ICON: &str = "https://arweave.net/wrKmRzr2KhH92c1iyFeUqkB-AHjYlE7Md7U5rK4qA8M";
 
+/// Shared by all transaction request routes. Returns the label and icon set in the profile of
+/// the group the request is for, found from the mint, token account, group seed or checkout
+/// session in the path, or the platform's if the group hasn't set a profile.
 pub async fn handler(
-    Path(Params {
-        mint_string,
-        message,
-        memo,
-    }): Path<Params>,
+    Path(params): Path<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
 ) -> Result<Json<ResponseData>, AppError> {
-    tracing::debug!(mint_string = mint_string, message = message, memo = memo);
-    Ok(Json(ResponseData {
-        label: LABEL.to_string(),
-        icon: ICON.to_string(),
+    tracing::debug!(?params);
+
+    let profile = match get_params_group(&state, &params).await {
+        Some(group) => get_group_profile(&state, &group).await,
+        None => None,
+    };
+
+    Ok(Json(match profile {
+        Some(GroupProfileObject { label, icon }) => ResponseData { label, icon },
+        None => ResponseData::default(),
     }))
 }
 
+/// Returns the profile of `group`, or `None` if it hasn't set one. Lookup errors are logged
+/// rather than returned so that wallets can still show the platform's label and icon.
+pub async fn get_group_profile(state: &State, group: &Pubkey) -> Option<GroupProfileObject> {
+    match state.data.get_group(group).await {
+        Ok(group_object) => group_object.group_profile_object,
+        Err(error) => {
+            tracing::debug!(group = group.to_string(), error = error.to_string());
+            None
+        }
+    }
+}
+
+/// Returns the group a transaction request is for from its path parameters, or `None` if the
+/// route doesn't identify one.
+pub async fn get_params_group(state: &State, params: &HashMap<String, String>) -> Option<Pubkey> {
+    if let Some(group_seed) = params.get("group_seed") {
+        let seed = Pubkey::from_str(group_seed).ok()?;
+        return Some(find_group_address(&seed).0);
+    }
+
+    let mint_object = if let Some(mint_string) = params.get("mint_string") {
+        let mint = Pubkey::from_str(mint_string).ok()?;
+        state.data.get_mint(&mint).await.ok()?
+    } else if let Some(token_account_string) = params.get("token_account_string") {
+        let token_account = Pubkey::from_str(token_account_string).ok()?;
+        state
+            .data
+            .get_token_account(&token_account)
+            .await
+            .ok()?
+            .mint_object?
+    } else if let Some(session_id) = params.get("session_id") {
+        let session = state.checkout_sessions.get(session_id)?;
+        state.data.get_mint(&session.mint).await.ok()?
+    } else {
+        return None;
+    };
+
+    Some(mint_object.promo().ok()?.group_object.id)
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct ResponseData {
     pub label: String,
     pub icon: String,
 }
+
+impl Default for ResponseData {
+    fn default() -> Self {
+        Self {
+            label: LABEL.to_string(),
+            icon: ICON.to_string(),
+        }
+    }
+}
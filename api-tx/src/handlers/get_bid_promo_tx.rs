@@ -0,0 +1,70 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{TradeParams, TransactionQuery},
+    utils::auction_house::create_buy_instructions,
+    State,
+};
+
+use super::PayResponse;
+
+/// Handles a customer bidding `price` lamports for the promo token in a token account. The
+/// bidder's address is assumed to be in the body of the request, pays the network fees and has
+/// the lamports bid escrowed in the auction house until the sale is executed.
+pub async fn handler(
+    Json(data): Json<Data>,
+    Path(TradeParams {
+        token_account_string,
+        price,
+        message,
+        memo,
+    }): Path<TradeParams>,
+    Query(tx_query): Query<TransactionQuery>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<PayResponse>, AppError> {
+    tracing::debug!(token_account_string, price, message, memo);
+
+    let buyer = Pubkey::from_str(&data.account)?;
+    let token_account = Pubkey::from_str(&token_account_string)?;
+
+    let (mint, _owner) = state
+        .data
+        .get_token_account(&token_account)
+        .await?
+        .get_tradable_mint_owner()?;
+
+    let mut instructions = create_buy_instructions(
+        state.auction_house,
+        state.platform,
+        buyer,
+        token_account,
+        mint,
+        price,
+    );
+    if let Some(memo) = memo {
+        instructions.push(spl_memo::build_memo(memo.as_bytes(), &[&buyer]));
+    }
+
+    let tx = state
+        .create_transaction(&instructions, &buyer, None, &tx_query)
+        .await?;
+
+    let transaction = state.encode_transaction(tx, &[], &tx_query).await?;
+
+    Ok(Json(PayResponse {
+        transaction,
+        message,
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Data {
+    pub account: String,
+}
@@ -1,22 +1,25 @@
 use anchor_lang::prelude::Pubkey;
 use axum::{
-    extract::{Extension, Path},
+    extract::{Extension, Path, Query},
     Json,
 };
 use serde::{Deserialize, Serialize};
-use solana_sdk::transaction::Transaction;
+use solana_sdk::instruction::Instruction;
 use std::{str::FromStr, sync::Arc};
 
 use crate::{
     error::AppError,
     utils::{
-        data::{get_mint_owner_group_from_token_account_query, TOKEN_ACCOUNT_QUERY},
-        solana::create_burn_delegated_promo_instruction,
+        data::TokenAccountObject,
+        solana::{
+            create_burn_accepted_promo_instruction, create_burn_delegated_promo_instruction,
+            create_burn_promo_2022_instruction, create_burn_referred_promo_instruction,
+        },
     },
     State,
 };
 
-use super::{BurnDelegatedParams, PayResponse};
+use super::{BurnDelegatedParams, PayResponse, TransactionQuery};
 
 pub async fn handler(
     Json(data): Json<Data>,
@@ -25,41 +28,96 @@ pub async fn handler(
         message,
         memo,
     }): Path<BurnDelegatedParams>,
+    Query(tx_query): Query<TransactionQuery>,
     Extension(state): Extension<Arc<State>>,
 ) -> Result<Json<PayResponse>, AppError> {
     tracing::debug!(token_account_string, message, memo);
 
     let payer = Pubkey::from_str(&data.account)?;
+    let token_account = Pubkey::from_str(&token_account_string)?;
 
-    let query = serde_json::json!({ "query": TOKEN_ACCOUNT_QUERY, "variables": {"id": token_account_string}});
-    let result: serde_json::Value = state
-        .solana
-        .client
-        .post(&state.data_url.to_string())
-        .json(&query)
-        .send()
-        .await?
-        .json()
-        .await?;
+    let token_account_object = state.data.get_token_account(&token_account).await?;
+    let (instruction, group) =
+        create_burn_instruction(payer, &token_account_object, state.platform, memo)?;
 
-    let (mint, token_owner, group) =
-        get_mint_owner_group_from_token_account_query(&payer, &result)?;
+    get_pay_response(instruction, payer, group, &tx_query, message, &state).await
+}
 
-    let instruction = create_burn_delegated_promo_instruction(
-        payer,
-        group,
-        token_owner,
-        mint,
-        state.platform,
-        memo,
-    )?;
+/// Returns the instruction burning the promo token in `token_account_object`, delegated to or
+/// burnable by `payer`, along with the group `payer` redeems it for.
+pub fn create_burn_instruction(
+    payer: Pubkey,
+    token_account_object: &TokenAccountObject,
+    platform: Pubkey,
+    memo: Option<String>,
+) -> Result<(Instruction, Pubkey), AppError> {
+    let (mint, token_owner) = (token_account_object.mint, token_account_object.owner);
+    let (group, issuing_group) = token_account_object.promo()?.get_redeeming_group(&payer)?;
+
+    // Members of groups accepting the promo burn with the issuing group included so the
+    // program can settle lamports between the two groups. Referral rewards are only paid
+    // out when the group that owns the promo redeems it.
+    let referral = token_account_object.get_referral();
+
+    // Token-2022 promo tokens are burned by the program as permanent delegate.
+    if token_account_object.is_token_2022() {
+        if issuing_group.is_some() {
+            return Err(AppError::Token2022Unsupported(
+                "promo acceptance".to_string(),
+            ));
+        }
+        let instruction =
+            create_burn_promo_2022_instruction(payer, group, token_owner, mint, platform, memo)?;
+        return Ok((instruction, group));
+    }
 
-    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer));
-    let recent_blockhash = state.solana.get_latest_blockhash().await?;
-    tx.message.recent_blockhash = recent_blockhash;
+    let instruction = match issuing_group {
+        None => match referral {
+            Some((referrer, reward_mint)) => create_burn_referred_promo_instruction(
+                payer,
+                group,
+                token_owner,
+                mint,
+                platform,
+                referrer,
+                reward_mint,
+                memo,
+            )?,
+            None => create_burn_delegated_promo_instruction(
+                payer,
+                group,
+                token_owner,
+                mint,
+                platform,
+                memo,
+            )?,
+        },
+        Some(issuing_group) => create_burn_accepted_promo_instruction(
+            payer,
+            group,
+            issuing_group,
+            token_owner,
+            mint,
+            platform,
+            memo,
+        )?,
+    };
+    Ok((instruction, group))
+}
 
-    let serialized = bincode::serialize(&tx)?;
-    let transaction = base64::encode(serialized);
+async fn get_pay_response(
+    instruction: Instruction,
+    payer: Pubkey,
+    group: Pubkey,
+    tx_query: &TransactionQuery,
+    message: String,
+    state: &State,
+) -> Result<Json<PayResponse>, AppError> {
+    let tx = state
+        .create_transaction(&[instruction], &payer, Some(&group), tx_query)
+        .await?;
+
+    let transaction = state.encode_transaction(tx, &[], tx_query).await?;
 
     Ok(Json(PayResponse {
         transaction,
@@ -71,6 +129,3 @@ pub async fn handler(
 pub struct Data {
     pub account: String,
 }
-
-// https://sandbox.dev.clover.com/v3/apps/MAC8DQKWCCB1R/merchants/XKDCJNW9JXGM1/notifications
-// https://sandbox.dev.clover.com/v3/apps/MAC8DQKWCCB1R/merchants/XKDCJNW9JXGM1/notifications
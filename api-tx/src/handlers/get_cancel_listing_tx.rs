@@ -0,0 +1,72 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{TradeParams, TransactionQuery},
+    utils::auction_house::create_cancel_listing_instructions,
+    State,
+};
+
+use super::PayResponse;
+
+/// Handles a customer canceling the listing of a promo token at `price` lamports. The token
+/// owner's address is assumed to be in the body of the request and pays the network fees.
+pub async fn handler(
+    Json(data): Json<Data>,
+    Path(TradeParams {
+        token_account_string,
+        price,
+        message,
+        memo,
+    }): Path<TradeParams>,
+    Query(tx_query): Query<TransactionQuery>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<PayResponse>, AppError> {
+    tracing::debug!(token_account_string, price, message, memo);
+
+    let seller = Pubkey::from_str(&data.account)?;
+    let token_account = Pubkey::from_str(&token_account_string)?;
+
+    let (mint, owner) = state
+        .data
+        .get_token_account(&token_account)
+        .await?
+        .get_tradable_mint_owner()?;
+    if owner != seller {
+        return Err(AppError::NotTokenOwner);
+    }
+
+    let mut instructions = create_cancel_listing_instructions(
+        state.auction_house,
+        state.platform,
+        seller,
+        token_account,
+        mint,
+        price,
+    );
+    if let Some(memo) = memo {
+        instructions.push(spl_memo::build_memo(memo.as_bytes(), &[&seller]));
+    }
+
+    let tx = state
+        .create_transaction(&instructions, &seller, None, &tx_query)
+        .await?;
+
+    let transaction = state.encode_transaction(tx, &[], &tx_query).await?;
+
+    Ok(Json(PayResponse {
+        transaction,
+        message,
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Data {
+    pub account: String,
+}
@@ -0,0 +1,71 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use bpl_token_metadata::utils::find_associated_token_address;
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{
+        get_burn_delegated_promo_tx::create_burn_instruction, CheckoutParams, PayResponse,
+        TransactionQuery,
+    },
+    utils::checkout::CheckoutStatus,
+    State,
+};
+
+/// Handles the checkout session's delegate finalizing payment for the order. Returns the
+/// transaction burning the customer's delegated promo token, with the order id in the memo,
+/// for the delegate to sign and submit. Finalized sessions return the transaction again in case
+/// it expired before being submitted.
+pub async fn handler(
+    Json(data): Json<Data>,
+    Path(CheckoutParams { session_id }): Path<CheckoutParams>,
+    Query(tx_query): Query<TransactionQuery>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<PayResponse>, AppError> {
+    tracing::debug!(session_id, account = data.account);
+
+    let payer = Pubkey::from_str(&data.account)?;
+
+    let mut session = state.get_checkout_session(&session_id).await?;
+    session.check_status(&[CheckoutStatus::Delegated, CheckoutStatus::Finalized])?;
+    if payer != session.delegate {
+        return Err(AppError::NotCheckoutDelegate);
+    }
+    let token_owner = session
+        .token_owner
+        .ok_or_else(|| AppError::InvalidCheckoutStatus(session.status.to_string()))?;
+
+    let token_account = find_associated_token_address(&token_owner, &session.mint);
+    let token_account_object = state.data.get_token_account(&token_account).await?;
+    let (instruction, group) = create_burn_instruction(
+        payer,
+        &token_account_object,
+        state.platform,
+        Some(session.memo()),
+    )?;
+
+    let tx = state
+        .create_transaction(&[instruction], &payer, Some(&group), &tx_query)
+        .await?;
+
+    let transaction = state.encode_transaction(tx, &[], &tx_query).await?;
+
+    let message = format!("Redeem promo for order {}", session.order_id);
+    session.status = CheckoutStatus::Finalized;
+    state.update_checkout_session(session);
+
+    Ok(Json(PayResponse {
+        transaction,
+        message,
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Data {
+    pub account: String,
+}
@@ -0,0 +1,71 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use solana_sdk::signer::Signer;
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{
+        get_delegate_promo_tx::create_delegate_instruction, CheckoutParams, PayResponse,
+        TransactionQuery,
+    },
+    utils::checkout::{get_transaction_signature, CheckoutStatus},
+    State,
+};
+
+/// Handles a customer requesting the transaction delegating their promo token to the checkout
+/// session's delegate, with the order id in the memo. The transaction can be requested again,
+/// for example after its blockhash expired, until one is confirmed.
+pub async fn handler(
+    Json(data): Json<Data>,
+    Path(CheckoutParams { session_id }): Path<CheckoutParams>,
+    Query(tx_query): Query<TransactionQuery>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<PayResponse>, AppError> {
+    tracing::debug!(session_id, account = data.account);
+
+    let token_owner = Pubkey::from_str(&data.account)?;
+    let payer = state.platform_signer.pubkey();
+
+    let mut session = state.get_checkout_session(&session_id).await?;
+    session.check_status(&[CheckoutStatus::Open, CheckoutStatus::Pending])?;
+    if session.status == CheckoutStatus::Pending && session.token_owner != Some(token_owner) {
+        return Err(AppError::NotTokenOwner);
+    }
+
+    let mint_object = state.data.get_mint(&session.mint).await?;
+    let (instruction, group) = create_delegate_instruction(
+        payer,
+        session.delegate,
+        token_owner,
+        &mint_object,
+        Some(session.memo()),
+    )?;
+
+    let tx = state
+        .create_transaction(&[instruction], &payer, Some(&group), &tx_query)
+        .await?;
+
+    let transaction = state.encode_transaction(tx, &[], &tx_query).await?;
+
+    session.status = CheckoutStatus::Pending;
+    session.token_owner = Some(token_owner);
+    session.group = Some(group);
+    session.delegate_signature = Some(get_transaction_signature(&transaction)?);
+    let message = session.message.clone();
+    state.update_checkout_session(session);
+
+    Ok(Json(PayResponse {
+        transaction,
+        message,
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Data {
+    pub account: String,
+}
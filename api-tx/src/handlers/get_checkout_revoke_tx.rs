@@ -0,0 +1,56 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use solana_sdk::signer::Signer;
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{CheckoutParams, PayResponse, TransactionQuery},
+    utils::solana::create_revoke_promo_instruction,
+    State,
+};
+
+/// Handles a customer requesting the transaction revoking the delegation of their promo token
+/// for a checkout session that expired without being finalized. Network fees are paid by the
+/// platform signer.
+pub async fn handler(
+    Json(data): Json<Data>,
+    Path(CheckoutParams { session_id }): Path<CheckoutParams>,
+    Query(tx_query): Query<TransactionQuery>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<PayResponse>, AppError> {
+    tracing::debug!(session_id, account = data.account);
+
+    let token_owner = Pubkey::from_str(&data.account)?;
+    let payer = state.platform_signer.pubkey();
+
+    let session = state.get_checkout_session(&session_id).await?;
+    if !session.needs_revoke() {
+        return Err(AppError::InvalidCheckoutStatus(session.status.to_string()));
+    }
+    if session.token_owner != Some(token_owner) {
+        return Err(AppError::NotTokenOwner);
+    }
+
+    let instruction = create_revoke_promo_instruction(token_owner, session.mint)?;
+
+    let tx = state
+        .create_transaction(&[instruction], &payer, session.group.as_ref(), &tx_query)
+        .await?;
+
+    let transaction = state.encode_transaction(tx, &[], &tx_query).await?;
+
+    Ok(Json(PayResponse {
+        transaction,
+        message: format!("Revoke promo delegation for order {}", session.order_id),
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Data {
+    pub account: String,
+}
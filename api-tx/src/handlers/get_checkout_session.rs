@@ -0,0 +1,55 @@
+use axum::{
+    extract::{Extension, Path},
+    http::HeaderMap,
+    Json,
+};
+use std::sync::Arc;
+
+use crate::{
+    error::AppError,
+    handlers::{get_base_url, CheckoutParams, CheckoutSessionResponse},
+    utils::{checkout::CheckoutSession, qr::get_solana_pay_url},
+    State,
+};
+
+/// Returns the current state of a checkout session, for the point of sale to poll until the
+/// customer's delegate transaction is confirmed.
+pub async fn handler(
+    Path(CheckoutParams { session_id }): Path<CheckoutParams>,
+    headers: HeaderMap,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<CheckoutSessionResponse>, AppError> {
+    tracing::debug!(session_id);
+
+    let session = state.get_checkout_session(&session_id).await?;
+
+    Ok(Json(get_session_response(&session, &headers)?))
+}
+
+pub fn get_session_response(
+    session: &CheckoutSession,
+    headers: &HeaderMap,
+) -> Result<CheckoutSessionResponse, AppError> {
+    let base_url = get_base_url(headers)?;
+    let get_session_url = |action: &str| {
+        get_solana_pay_url(&format!("{}/checkout/{}/{}", base_url, session.id, action))
+    };
+
+    Ok(CheckoutSessionResponse {
+        id: session.id.clone(),
+        order_id: session.order_id.clone(),
+        mint: session.mint.to_string(),
+        delegate: session.delegate.to_string(),
+        status: session.status,
+        token_owner: session
+            .token_owner
+            .map(|token_owner| token_owner.to_string()),
+        expires_at: session.expires_at_timestamp(),
+        solana_pay_url: get_session_url("delegate"),
+        revoke_solana_pay_url: if session.needs_revoke() {
+            Some(get_session_url("revoke"))
+        } else {
+            None
+        },
+    })
+}
@@ -0,0 +1,62 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use bpl_token_metadata::utils::find_group_address;
+use serde::{Deserialize, Serialize};
+use solana_sdk::signer::Signer;
+use std::{str::FromStr, sync::Arc};
+
+use crate::{error::AppError, utils::solana::create_create_group_nonce_instructions, State};
+
+use super::{GroupNonceParams, PayResponse, TransactionQuery};
+
+/// Creates the durable nonce account the platform manages for a group, used to build
+/// transactions with the `nonce` query parameter. The payer's address is assumed to be in the
+/// body of the request and funds the nonce account. The platform signer partially signs as the
+/// base address the nonce account is derived from, so the payer has to be the owner or a member
+/// of the group.
+pub async fn handler(
+    Json(data): Json<Data>,
+    Path(GroupNonceParams {
+        group_seed,
+        message,
+        memo,
+    }): Path<GroupNonceParams>,
+    Query(tx_query): Query<TransactionQuery>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<PayResponse>, AppError> {
+    tracing::debug!(group_seed, message, memo);
+
+    let payer = Pubkey::from_str(&data.account)?;
+    let group_seed = Pubkey::from_str(&group_seed)?;
+    let (group, _group_bump) = find_group_address(&group_seed);
+
+    let group_object = state.data.get_group(&group).await?;
+    if group_object.owner != payer && !group_object.members.contains(&payer) {
+        return Err(AppError::NotGroupMember(group.to_string()));
+    }
+
+    let mut instructions =
+        create_create_group_nonce_instructions(payer, state.platform_signer.pubkey(), group);
+    if let Some(memo) = memo {
+        instructions.push(spl_memo::build_memo(memo.as_bytes(), &[&payer]));
+    }
+
+    let tx = state
+        .create_transaction(&instructions, &payer, None, &tx_query)
+        .await?;
+
+    let transaction = state.encode_transaction(tx, &[], &tx_query).await?;
+
+    Ok(Json(PayResponse {
+        transaction,
+        message,
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Data {
+    pub account: String,
+}
@@ -0,0 +1,79 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use bpl_token_metadata::state::Settlement;
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{error::AppError, utils::solana::create_create_promo_acceptance_instruction, State};
+
+use super::{PayResponse, PromoAcceptanceParams, TransactionQuery};
+
+/// Returns a transaction for the owner of the group that owns a promo to name additional
+/// groups whose members can delegate and burn its tokens. `groups` is a comma separated list
+/// of group addresses.
+pub async fn handler(
+    Json(data): Json<Data>,
+    Path(PromoAcceptanceParams {
+        mint_string,
+        groups,
+        settlement,
+        settlement_lamports,
+        memo,
+    }): Path<PromoAcceptanceParams>,
+    Query(tx_query): Query<TransactionQuery>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<PayResponse>, AppError> {
+    tracing::debug!(mint_string, groups, settlement, settlement_lamports);
+
+    let payer = Pubkey::from_str(&data.account)?;
+    let mint = Pubkey::from_str(&mint_string)?;
+    let groups: Vec<Pubkey> = groups
+        .split(',')
+        .map(|s| Pubkey::from_str(s.trim()))
+        .collect::<Result<Vec<Pubkey>, _>>()?;
+    let settlement = parse_settlement(&settlement)?;
+
+    let group = state
+        .data
+        .get_mint(&mint)
+        .await?
+        .promo()?
+        .get_group(&payer)?;
+
+    let instruction = create_create_promo_acceptance_instruction(
+        payer,
+        group,
+        mint,
+        groups,
+        settlement,
+        settlement_lamports,
+        memo,
+    )?;
+
+    let tx = state
+        .create_transaction(&[instruction], &payer, Some(&group), &tx_query)
+        .await?;
+
+    let transaction = state.encode_transaction(tx, &[], &tx_query).await?;
+
+    Ok(Json(PayResponse {
+        transaction,
+        message: "Create promo acceptance".to_string(),
+    }))
+}
+
+pub fn parse_settlement(settlement: &str) -> Result<Settlement, AppError> {
+    match settlement {
+        "issuer" | "issuerPaysRedeemer" => Ok(Settlement::IssuerPaysRedeemer),
+        "redeemer" | "redeemerPaysIssuer" => Ok(Settlement::RedeemerPaysIssuer),
+        _ => Err(AppError::InvalidSettlement(settlement.to_string())),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Data {
+    pub account: String,
+}
@@ -1,12 +1,14 @@
 use anchor_lang::prelude::Pubkey;
-use axum::{extract::Path, Json};
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
 use serde::{Deserialize, Serialize};
-use solana_sdk::transaction::Transaction;
-use std::str::FromStr;
+use std::{str::FromStr, sync::Arc};
 
-use crate::{error::AppError, utils::solana::create_create_promo_group_instruction};
+use crate::{error::AppError, utils::solana::create_create_promo_group_instruction, State};
 
-use super::{PayResponse, PromoGroupParams};
+use super::{PayResponse, PromoGroupParams, TransactionQuery};
 
 pub async fn handler(
     Json(data): Json<Data>,
@@ -16,6 +18,8 @@ pub async fn handler(
         lamports,
         memo,
     }): Path<PromoGroupParams>,
+    Query(tx_query): Query<TransactionQuery>,
+    Extension(state): Extension<Arc<State>>,
 ) -> Result<Json<PayResponse>, AppError> {
     tracing::debug!(group_seed, lamports);
 
@@ -29,9 +33,10 @@ pub async fn handler(
     let instruction =
         create_create_promo_group_instruction(payer, group_seed, members, lamports, memo)?;
 
-    let tx = Transaction::new_with_payer(&[instruction], Some(&payer));
-    let serialized = bincode::serialize(&tx)?;
-    let transaction = base64::encode(serialized);
+    let tx = state
+        .create_transaction(&[instruction], &payer, None, &tx_query)
+        .await?;
+    let transaction = state.encode_transaction(tx, &[], &tx_query).await?;
 
     Ok(Json(PayResponse {
         transaction,
@@ -1,38 +1,65 @@
 use crate::{
     error::AppError,
     utils::{
-        bundlr::{upload_image, upload_metadata_json},
-        solana::create_create_promo_instruction,
+        auth::AuthSession,
+        bundlr::METADATA_SIZE_ALLOWANCE,
+        promo_image::{process_promo_image, PromoImage, MAX_IMAGE_BYTES},
+        solana::{
+            create_create_loyalty_instruction, create_create_promo_2022_instruction,
+            create_create_promo_instruction,
+        },
+        storage::{upload_image, upload_metadata_json},
     },
     State,
 };
 use anchor_lang::prelude::Pubkey;
 use axum::{
-    extract::{Multipart, Path},
+    extract::{multipart::Field, Multipart, Path, Query},
     Extension, Json,
 };
-use serde_json::{Map, Value};
-use solana_sdk::{signature::Keypair, signer::Signer, transaction::Transaction};
+use bpl_promo_metadata::{PromoMetadata, PromoType};
+use bpl_token_metadata::{
+    state::{MintExtensions, RewardLevel},
+    utils::find_group_address,
+};
+use serde_json::Value;
+use solana_sdk::{signature::Keypair, signer::Signer};
 use std::{str::FromStr, sync::Arc};
 
-use super::{CreatePromoParams, PayResponse};
+use super::{CreatePromoParams, CreatePromoQuery, PayResponse, TransactionQuery};
 
+/// Creates a promo with a classic SPL Token mint and Metaplex metadata, or with a Token-2022
+/// mint if the `token2022` query parameter is set. Set the `nonce` query parameter to keep the
+/// mint keypair signature valid until the group's durable nonce is advanced. Requires a member
+/// of the group to be signed in, since the platform pays for the uploads.
 pub async fn handler(
+    session: AuthSession,
     mut multipart: Multipart,
     Path(CreatePromoParams {
         payer,
         group_seed,
         memo,
     }): Path<CreatePromoParams>,
+    Query(create_promo_query): Query<CreatePromoQuery>,
+    Query(tx_query): Query<TransactionQuery>,
     Extension(state): Extension<Arc<State>>,
 ) -> Result<Json<PayResponse>, AppError> {
+    let payer = Pubkey::from_str(&payer)?;
+    let group_seed = Pubkey::from_str(&group_seed)?;
+    let (group, _group_bump) = find_group_address(&group_seed);
+    state.check_group_member(&session, &group).await?;
+
     // Parse data - two parts - json data and image.
-    let mut metadata_data = if let Some(field) = multipart.next_field().await.unwrap() {
+    let metadata = if let Some(mut field) = multipart.next_field().await.unwrap() {
         if field.name().expect("name field should exist") == "metadata" {
-            let json_string = field.text().await.map_err(|_| {
+            let json_bytes =
+                read_field(&mut field, METADATA_SIZE_ALLOWANCE, AppError::BodyTooLarge).await?;
+            let json_string = String::from_utf8(json_bytes).map_err(|_| {
                 AppError::CreatePromoRequestError("metadata value not valid".to_string())
             })?;
-            Ok(serde_json::from_str::<Value>(&json_string)?)
+            Ok(PromoMetadata::from_value(serde_json::from_str::<Value>(
+                &json_string,
+            )?)?)
         } else {
             return Err(AppError::CreatePromoRequestError(
                 "invalid field name".to_string(),
@@ -44,15 +71,13 @@ pub async fn handler(
         ))
     }?;
 
-    let image_data = if let Some(field) = multipart.next_field().await.unwrap() {
+    let image_bytes = if let Some(mut field) = multipart.next_field().await.unwrap() {
         if field.name().expect("name field should exist") == "image" {
-            let content_type = field.content_type().map(ToString::to_string).ok_or(
-                AppError::CreatePromoRequestError("failed to read image content type".to_string()),
-            )?;
-            let image_bytes = field.bytes().await.map_err(|_| {
-                AppError::CreatePromoRequestError("failed to read image bytes".to_string())
-            })?;
-            Ok((image_bytes.to_vec(), content_type))
+            // The content type claimed by the client is ignored in favor of the format detected
+            // from the image bytes.
+            let image_bytes =
+                read_field(&mut field, MAX_IMAGE_BYTES, AppError::ImageTooLarge).await?;
+            Ok(image_bytes)
         } else {
             return Err(AppError::CreatePromoRequestError(
                 "invalid field name".to_string(),
@@ -64,117 +89,143 @@ pub async fn handler(
         ))
     }?;
 
-    let metadata_data_obj =
-        metadata_data
-            .as_object_mut()
-            .ok_or(AppError::CreatePromoRequestError(
-                "metadata data part should be an object".to_string(),
-            ))?;
-
-    tracing::debug!(here = "here");
-
-    // Upload image to Arweave.
-    let (image_url, content_type, state) = upload_image(image_data, state).await?;
-
-    // Upload metadata json to Arweave.
-    let (uri, state) =
-        upload_metadata_json(metadata_data_obj, image_url, content_type, state).await?;
-
     // Parse promo args.
-    let (name, symbol, max_mint, max_burn) = get_promo_args(metadata_data_obj)?;
+    let loyalty_args = get_loyalty_args(&metadata);
+    let extensions = get_mint_extensions(&create_promo_query);
+    // Points are minted and burned by the program under the classic token program.
+    if extensions.is_some() && loyalty_args.is_some() {
+        return Err(AppError::Token2022Unsupported("loyalty".to_string()));
+    }
+    let (name, symbol) = (metadata.name.clone(), metadata.symbol.clone());
+    let (max_mint, max_burn) = (metadata.attributes.max_mint, metadata.attributes.max_burn);
+
+    // Attributes are written in the order defined by the token metadata specifications.
+    let mut metadata_data = metadata.to_value()?;
+    let metadata_data_obj = metadata_data
+        .as_object_mut()
+        .expect("promo metadata should serialize to an object");
+
+    // Validate and normalize image, then upload it with its thumbnail to storage. Decoding and
+    // resizing are cpu bound, so they run on the blocking thread pool.
+    let PromoImage { image, thumbnail } =
+        tokio::task::spawn_blocking(move || process_promo_image(&image_bytes))
+            .await
+            .map_err(|e| AppError::GenericError(e.to_string()))??;
+    // Nothing is uploaded if storage can't pay for all of the uploads.
+    state
+        .check_storage_funds(&[image.0.len(), thumbnail.0.len(), METADATA_SIZE_ALLOWANCE])
+        .await?;
+    let image = upload_image(state.storage.as_ref(), image).await?;
+    let thumbnail = upload_image(state.storage.as_ref(), thumbnail).await?;
+
+    // Upload metadata json to storage.
+    let uri =
+        upload_metadata_json(state.storage.as_ref(), metadata_data_obj, image, thumbnail).await?;
+
     let mint_keypair = Keypair::new();
 
-    let payer = Pubkey::from_str(&payer)?;
-    let group_seed = Pubkey::from_str(&group_seed)?;
     // Create promo instruction.
-    let ix = create_create_promo_instruction(
-        payer,
-        group_seed,
-        mint_keypair.pubkey(),
-        state.platform,
-        name,
-        symbol,
-        uri,
-        max_mint,
-        max_burn,
-        true,
-        memo,
-    )?;
-
-    let mut tx = Transaction::new_with_payer(&[ix], Some(&payer));
-    let latest_blockhash = state.solana.get_latest_blockhash().await?;
-    tx.partial_sign(&[&mint_keypair], latest_blockhash);
-
-    let serialized = bincode::serialize(&tx)?;
-    let transaction = base64::encode(serialized);
+    let ix = match extensions {
+        Some(extensions) => create_create_promo_2022_instruction(
+            payer,
+            group_seed,
+            mint_keypair.pubkey(),
+            state.platform,
+            name,
+            symbol,
+            uri,
+            max_mint,
+            max_burn,
+            extensions,
+            memo.clone(),
+        )?,
+        None => create_create_promo_instruction(
+            payer,
+            group_seed,
+            mint_keypair.pubkey(),
+            state.platform,
+            name,
+            symbol,
+            uri,
+            max_mint,
+            max_burn,
+            true,
+            memo.clone(),
+        )?,
+    };
+    let mut instructions = vec![ix];
+
+    // Loyalty promos use the promo mint for points.
+    if let Some((earn_rate, levels)) = loyalty_args {
+        instructions.push(create_create_loyalty_instruction(
+            payer,
+            group_seed,
+            mint_keypair.pubkey(),
+            earn_rate,
+            levels,
+            memo,
+        )?);
+    }
+
+    let tx = state
+        .create_transaction(&instructions, &payer, Some(&group), &tx_query)
+        .await?;
+
+    let transaction = state
+        .encode_transaction(tx, &[&mint_keypair], &tx_query)
+        .await?;
     Ok(Json(PayResponse {
         transaction,
         message: "Create promo".to_string(),
     }))
 }
 
-pub fn get_promo_args(
-    metadata_data_obj: &mut Map<String, Value>,
-) -> Result<(String, String, Option<u32>, Option<u32>), AppError> {
-    let name = metadata_data_obj["name"]
-        .as_str()
-        .ok_or(AppError::CreatePromoRequestError(
-            "name field should exist".to_string(),
-        ))?
-        .to_string();
-
-    let symbol = metadata_data_obj["symbol"]
-        .as_str()
-        .ok_or(AppError::CreatePromoRequestError(
-            "symbol field should exist".to_string(),
-        ))?
-        .to_string();
-
-    // Return max_mint and max_burn if attributes exists in json data.
-    let (max_mint, max_burn) = if let Some(value) = metadata_data_obj.get("attributes") {
-        if let Some(attributes) = value.as_array() {
-            let max_mint: Option<u32> = attributes
-                .iter()
-                .filter_map(|a| {
-                    let attribute = a.as_object()?;
-                    if let Some(trait_type) = attribute.get("trait_type") {
-                        if trait_type == "maxMint" {
-                            attribute.get("value").map(|v| v.as_u64()).unwrap_or(None)
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<u64>>()
-                .first()
-                .map(|v| v.clone() as u32);
+/// Returns the Token-2022 mint extensions if a Token-2022 mint was requested.
+pub fn get_mint_extensions(query: &CreatePromoQuery) -> Option<MintExtensions> {
+    if !query.token_2022 {
+        return None;
+    }
+    Some(MintExtensions {
+        non_transferable: query.non_transferable,
+        permanent_delegate: true,
+        metadata_pointer: query.metadata_pointer,
+    })
+}
+
+/// Reads a multipart field in chunks, failing with `too_large` as soon as more than `max_bytes`
+/// have been read instead of buffering the whole field.
+async fn read_field(
+    field: &mut Field<'_>,
+    max_bytes: usize,
+    too_large: fn(usize) -> AppError,
+) -> Result<Vec<u8>, AppError> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = field
+        .chunk()
+        .await
+        .map_err(|_| AppError::CreatePromoRequestError("failed to read request part".to_string()))?
+    {
+        if bytes.len() + chunk.len() > max_bytes {
+            return Err(too_large(max_bytes));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
 
-            let max_burn: Option<u32> = attributes
+/// Returns the earn rate and reward levels if the promo is a loyalty promo.
+pub fn get_loyalty_args(metadata: &PromoMetadata) -> Option<(u32, Vec<RewardLevel>)> {
+    match &metadata.attributes.promo_type {
+        PromoType::Loyalty { earn_rate, levels } => Some((
+            *earn_rate,
+            levels
                 .iter()
-                .filter_map(|a| {
-                    let attribute = a.as_object()?;
-                    if let Some(trait_type) = attribute.get("trait_type") {
-                        if trait_type == "maxBurn" {
-                            attribute.get("value").map(|v| v.as_u64()).unwrap_or(None)
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
+                .map(|level| RewardLevel {
+                    points: level.points,
+                    reward: level.reward,
                 })
-                .collect::<Vec<u64>>()
-                .first()
-                .map(|v| v.clone() as u32);
-
-            (max_mint, max_burn)
-        } else {
-            (None, None)
-        }
-    } else {
-        (None, None)
-    };
-    Ok((name, symbol, max_mint, max_burn))
+                .collect(),
+        )),
+        _ => None,
+    }
 }
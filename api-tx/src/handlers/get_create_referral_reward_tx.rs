@@ -0,0 +1,65 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{ReferralRewardParams, TransactionQuery},
+    utils::solana::create_create_referral_reward_instruction,
+    State,
+};
+
+use super::PayResponse;
+
+/// Handles the owner of the group that owns the promo setting another of its promos as the
+/// reward minted to referrers. Group owner address assumed to be in the body of the request.
+pub async fn handler(
+    Json(data): Json<Data>,
+    Path(ReferralRewardParams {
+        mint_string,
+        reward_mint_string,
+        memo,
+    }): Path<ReferralRewardParams>,
+    Query(tx_query): Query<TransactionQuery>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<PayResponse>, AppError> {
+    tracing::debug!(
+        mint_string = mint_string,
+        reward_mint_string = reward_mint_string,
+        memo = memo
+    );
+
+    let payer = Pubkey::from_str(&data.account)?;
+    let mint = Pubkey::from_str(&mint_string)?;
+    let reward_mint = Pubkey::from_str(&reward_mint_string)?;
+
+    let group = state
+        .data
+        .get_mint(&mint)
+        .await?
+        .promo()?
+        .get_group(&payer)?;
+
+    let instruction =
+        create_create_referral_reward_instruction(payer, group, mint, reward_mint, memo)?;
+
+    let tx = state
+        .create_transaction(&[instruction], &payer, Some(&group), &tx_query)
+        .await?;
+
+    let transaction = state.encode_transaction(tx, &[], &tx_query).await?;
+
+    Ok(Json(PayResponse {
+        transaction,
+        message: "Set referral reward".to_string(),
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Data {
+    pub account: String,
+}
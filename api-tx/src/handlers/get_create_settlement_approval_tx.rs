@@ -0,0 +1,61 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{error::AppError, utils::solana::create_create_settlement_approval_instruction, State};
+
+use super::{PayResponse, SettlementApprovalParams, TransactionQuery};
+
+/// Returns a transaction for the owner of a group accepting a promo to approve paying the
+/// issuing group up to `max_settlement_lamports` for each token of the promo its members burn.
+/// Burns where the redeeming group pays more than it approved are rejected by the program.
+pub async fn handler(
+    Json(data): Json<Data>,
+    Path(SettlementApprovalParams {
+        mint_string,
+        group_string,
+        max_settlement_lamports,
+        memo,
+    }): Path<SettlementApprovalParams>,
+    Query(tx_query): Query<TransactionQuery>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<PayResponse>, AppError> {
+    tracing::debug!(mint_string, group_string, max_settlement_lamports);
+
+    let payer = Pubkey::from_str(&data.account)?;
+    let mint = Pubkey::from_str(&mint_string)?;
+    let group = Pubkey::from_str(&group_string)?;
+
+    let group_object = state.data.get_group(&group).await?;
+    if group_object.owner != payer {
+        return Err(AppError::NotGroupOwner(group.to_string()));
+    }
+
+    let instruction = create_create_settlement_approval_instruction(
+        payer,
+        group,
+        mint,
+        max_settlement_lamports,
+        memo,
+    )?;
+
+    let tx = state
+        .create_transaction(&[instruction], &payer, Some(&group), &tx_query)
+        .await?;
+
+    let transaction = state.encode_transaction(tx, &[], &tx_query).await?;
+
+    Ok(Json(PayResponse {
+        transaction,
+        message: "Create settlement approval".to_string(),
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Data {
+    pub account: String,
+}
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError, utils::solana::create_create_sponsorship_allowance_instruction, State,
+};
+
+use super::{PayResponse, SponsorshipAllowanceParams, TransactionQuery};
+
+/// Returns a transaction for the owner of a group to allow up to `max_lamports` in total to be
+/// settled from the group to the platform for transactions sponsored for it. Settlements past
+/// the allowance are rejected by the program.
+pub async fn handler(
+    Json(data): Json<Data>,
+    Path(SponsorshipAllowanceParams {
+        group_string,
+        max_lamports,
+        memo,
+    }): Path<SponsorshipAllowanceParams>,
+    Query(tx_query): Query<TransactionQuery>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<PayResponse>, AppError> {
+    tracing::debug!(group_string, max_lamports);
+
+    let payer = Pubkey::from_str(&data.account)?;
+    let group = Pubkey::from_str(&group_string)?;
+
+    let group_object = state.data.get_group(&group).await?;
+    if group_object.owner != payer {
+        return Err(AppError::NotGroupOwner(group.to_string()));
+    }
+
+    let instruction =
+        create_create_sponsorship_allowance_instruction(payer, group, max_lamports, memo)?;
+
+    let tx = state
+        .create_transaction(&[instruction], &payer, Some(&group), &tx_query)
+        .await?;
+
+    let transaction = state.encode_transaction(tx, &[], &tx_query).await?;
+
+    Ok(Json(PayResponse {
+        transaction,
+        message: "Create sponsorship allowance".to_string(),
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Data {
+    pub account: String,
+}
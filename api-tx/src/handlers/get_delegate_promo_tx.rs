@@ -1,18 +1,18 @@
 use anchor_lang::prelude::Pubkey;
 use axum::{
-    extract::{Extension, Path},
+    extract::{Extension, Path, Query},
     Json,
 };
 use serde::{Deserialize, Serialize};
-use solana_sdk::{signer::Signer, transaction::Transaction};
+use solana_sdk::{instruction::Instruction, signer::Signer};
 use std::{str::FromStr, sync::Arc};
 
 use crate::{
     error::AppError,
-    handlers::DelegateParams,
+    handlers::{DelegateParams, TransactionQuery},
     utils::{
-        data::{get_group_from_promo_group_query, MINT_QUERY},
-        solana::create_delegate_promo_instruction,
+        data::MintObject,
+        solana::{create_delegate_accepted_promo_instruction, create_delegate_promo_instruction},
     },
     State,
 };
@@ -28,6 +28,7 @@ pub async fn handler(
         message,
         memo,
     }): Path<DelegateParams>,
+    Query(tx_query): Query<TransactionQuery>,
     Extension(state): Extension<Arc<State>>,
 ) -> Result<Json<PayResponse>, AppError> {
     tracing::debug!(
@@ -43,28 +44,15 @@ pub async fn handler(
     let mint = Pubkey::from_str(&mint_string)?;
     let delegate = Pubkey::from_str(&delegate_string)?;
 
-    let query = serde_json::json!({ "query": MINT_QUERY, "variables": {"mint": mint_string}});
-    let result: serde_json::Value = state
-        .solana
-        .client
-        .post(&state.data_url.to_string())
-        .json(&query)
-        .send()
-        .await?
-        .json()
-        .await?;
-
-    let group = get_group_from_promo_group_query(&delegate, &result)?;
+    let mint_object = state.data.get_mint(&mint).await?;
+    let (instruction, group) =
+        create_delegate_instruction(payer, delegate, token_owner, &mint_object, memo)?;
 
-    let instruction =
-        create_delegate_promo_instruction(payer, delegate, group, token_owner, mint, memo)?;
-
-    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer));
-    let recent_blockhash = state.solana.get_latest_blockhash().await?;
-    tx.try_partial_sign(&[&state.platform_signer], recent_blockhash)?;
+    let tx = state
+        .create_transaction(&[instruction], &payer, Some(&group), &tx_query)
+        .await?;
 
-    let serialized = bincode::serialize(&tx)?;
-    let transaction = base64::encode(serialized);
+    let transaction = state.encode_transaction(tx, &[], &tx_query).await?;
 
     Ok(Json(PayResponse {
         transaction,
@@ -72,9 +60,38 @@ pub async fn handler(
     }))
 }
 
+/// Returns the instruction delegating a promo token of `token_owner` to `delegate` along with
+/// the group `delegate` redeems it for.
+pub fn create_delegate_instruction(
+    payer: Pubkey,
+    delegate: Pubkey,
+    token_owner: Pubkey,
+    mint_object: &MintObject,
+    memo: Option<String>,
+) -> Result<(Instruction, Pubkey), AppError> {
+    // Token-2022 promo tokens are burned by the program as permanent delegate.
+    if mint_object.is_token_2022() {
+        return Err(AppError::Token2022Unsupported("delegation".to_string()));
+    }
+
+    // Delegate can be a member of the group that owns the promo or of a group accepting it.
+    let mint = mint_object.id;
+    let (group, issuing_group) = mint_object.promo()?.get_redeeming_group(&delegate)?;
+    let instruction = match issuing_group {
+        None => create_delegate_promo_instruction(payer, delegate, group, token_owner, mint, memo)?,
+        Some(_issuing_group) => create_delegate_accepted_promo_instruction(
+            payer,
+            delegate,
+            group,
+            token_owner,
+            mint,
+            memo,
+        )?,
+    };
+    Ok((instruction, group))
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct Data {
     pub account: String,
 }
-
-// https://sandbox.dev.clover.com/v3/apps/MAC8DQKWCCB1R/merchants/XKDCJNW9JXGM1/notifications
@@ -0,0 +1,70 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{EarnPointsParams, TransactionQuery},
+    utils::solana::create_earn_points_instruction,
+    State,
+};
+
+use super::PayResponse;
+
+/// Handles a merchant group member attesting to an order amount so the customer specified by
+/// `token_owner` earns loyalty points. The member's address is assumed to be in the body of
+/// the request and pays the network fees, including creating the customer's token account.
+pub async fn handler(
+    Json(data): Json<Data>,
+    Path(EarnPointsParams {
+        mint_string,
+        token_owner,
+        order_amount,
+        message,
+        memo,
+    }): Path<EarnPointsParams>,
+    Query(tx_query): Query<TransactionQuery>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<PayResponse>, AppError> {
+    tracing::debug!(
+        mint_string = mint_string,
+        token_owner = token_owner,
+        order_amount = order_amount,
+        message = message,
+        memo = memo
+    );
+
+    let payer = Pubkey::from_str(&data.account)?;
+    let token_owner = Pubkey::from_str(&token_owner)?;
+    let mint = Pubkey::from_str(&mint_string)?;
+
+    let group = state
+        .data
+        .get_mint(&mint)
+        .await?
+        .promo()?
+        .get_group(&payer)?;
+
+    let instruction =
+        create_earn_points_instruction(payer, group, token_owner, mint, order_amount, memo)?;
+
+    let tx = state
+        .create_transaction(&[instruction], &payer, Some(&group), &tx_query)
+        .await?;
+
+    let transaction = state.encode_transaction(tx, &[], &tx_query).await?;
+
+    Ok(Json(PayResponse {
+        transaction,
+        message,
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Data {
+    pub account: String,
+}
@@ -0,0 +1,78 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{ExecuteSaleParams, TransactionQuery},
+    utils::auction_house::create_execute_sale_instructions,
+    State,
+};
+
+use super::PayResponse;
+
+/// Handles executing the sale of a promo token to `buyer` at `price` lamports once it has been
+/// both listed and bid on at that price. Either the seller, accepting the bid, or the buyer,
+/// buying at the listed price, can sign. Their address is assumed to be in the body of the
+/// request and pays the network fees.
+pub async fn handler(
+    Json(data): Json<Data>,
+    Path(ExecuteSaleParams {
+        token_account_string,
+        buyer,
+        price,
+        message,
+        memo,
+    }): Path<ExecuteSaleParams>,
+    Query(tx_query): Query<TransactionQuery>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<PayResponse>, AppError> {
+    tracing::debug!(token_account_string, buyer, price, message, memo);
+
+    let signer = Pubkey::from_str(&data.account)?;
+    let buyer = Pubkey::from_str(&buyer)?;
+    let token_account = Pubkey::from_str(&token_account_string)?;
+
+    let (mint, seller) = state
+        .data
+        .get_token_account(&token_account)
+        .await?
+        .get_tradable_mint_owner()?;
+    if signer != seller && signer != buyer {
+        return Err(AppError::NotTradeParticipant);
+    }
+
+    let mut instructions = create_execute_sale_instructions(
+        state.auction_house,
+        state.platform,
+        signer,
+        seller,
+        buyer,
+        token_account,
+        mint,
+        price,
+    );
+    if let Some(memo) = memo {
+        instructions.push(spl_memo::build_memo(memo.as_bytes(), &[&signer]));
+    }
+
+    let tx = state
+        .create_transaction(&instructions, &signer, None, &tx_query)
+        .await?;
+
+    let transaction = state.encode_transaction(tx, &[], &tx_query).await?;
+
+    Ok(Json(PayResponse {
+        transaction,
+        message,
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Data {
+    pub account: String,
+}
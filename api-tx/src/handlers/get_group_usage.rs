@@ -0,0 +1,63 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use std::{
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    error::AppError,
+    handlers::{GroupProfileParams, GroupUsageResponse, UsageQuery},
+    utils::{
+        auth::AuthSession,
+        sponsorship::{get_day, DEFAULT_USAGE_DAYS, MAX_USAGE_DAYS},
+    },
+    State,
+};
+
+/// Returns the fees and rent the platform signer has paid for a group's sponsored transactions,
+/// in total and for each of the last `days` days, along with what the group has reimbursed.
+/// Requires a member of the group to be signed in.
+pub async fn handler(
+    session: AuthSession,
+    Path(GroupProfileParams { group_string }): Path<GroupProfileParams>,
+    Query(query): Query<UsageQuery>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<GroupUsageResponse>, AppError> {
+    tracing::debug!(group_string, ?query);
+
+    let group = Pubkey::from_str(&group_string)?;
+    state.check_group_member(&session, &group).await?;
+    let days = query
+        .days
+        .unwrap_or(DEFAULT_USAGE_DAYS)
+        .clamp(1, MAX_USAGE_DAYS);
+
+    Ok(Json(get_usage_response(&state, &group, days).await?))
+}
+
+pub async fn get_usage_response(
+    state: &State,
+    group: &Pubkey,
+    days: u64,
+) -> Result<GroupUsageResponse, AppError> {
+    let today = get_day(SystemTime::now());
+    let since = SystemTime::UNIX_EPOCH
+        + Duration::from_secs(today.saturating_sub((days - 1) * 60 * 60 * 24));
+    let usage = state.sponsorship.get_usage(group, since).await?;
+
+    Ok(GroupUsageResponse {
+        group: group.to_string(),
+        transactions: usage.transactions,
+        fees: usage.fees,
+        rent: usage.rent,
+        settled: usage.settled,
+        outstanding: usage.outstanding(),
+        reimburse: usage.reimburse,
+        days: usage.days,
+    })
+}
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path},
+    Json,
+};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{GroupProfileParams, WebhookEndpointResponse},
+    utils::{
+        auth::AuthSession,
+        webhooks::{get_timestamp, WebhookEndpoint},
+    },
+    State,
+};
+
+/// Returns the endpoints a group's events are delivered to, without their secrets. Requires a
+/// member of the group to be signed in.
+pub async fn handler(
+    session: AuthSession,
+    Path(GroupProfileParams { group_string }): Path<GroupProfileParams>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<WebhookEndpointResponse>>, AppError> {
+    tracing::debug!(group_string);
+
+    let group = Pubkey::from_str(&group_string)?;
+    state.check_group_member(&session, &group).await?;
+    let endpoints = state.webhooks.get_endpoints(&group).await?;
+
+    Ok(Json(
+        endpoints.into_iter().map(get_endpoint_response).collect(),
+    ))
+}
+
+pub fn get_endpoint_response(endpoint: WebhookEndpoint) -> WebhookEndpointResponse {
+    WebhookEndpointResponse {
+        id: endpoint.id,
+        url: endpoint.url,
+        events: endpoint.events,
+        secret: None,
+        created_at: get_timestamp(endpoint.created_at),
+    }
+}
@@ -0,0 +1,64 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{MerchantMintParams, TransactionQuery},
+    utils::solana::{create_mint_promo_2022_instruction, create_mint_promo_instruction},
+    State,
+};
+
+use super::PayResponse;
+
+/// Handles a group member approving a mint to `token_owner` by signing the transaction first.
+/// The merchant's address is assumed to be in the body of the request and pays the network
+/// fees. The merchant signs the returned transaction and posts it back to the server so the
+/// token owner can retrieve it, sign and submit it.
+pub async fn handler(
+    Json(data): Json<Data>,
+    Path(MerchantMintParams {
+        mint_string,
+        token_owner,
+        message,
+        memo,
+    }): Path<MerchantMintParams>,
+    Query(tx_query): Query<TransactionQuery>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<PayResponse>, AppError> {
+    tracing::debug!(mint_string, token_owner, message, memo);
+
+    let payer = Pubkey::from_str(&data.account)?;
+    let token_owner = Pubkey::from_str(&token_owner)?;
+    let mint = Pubkey::from_str(&mint_string)?;
+
+    let mint_object = state.data.get_mint(&mint).await?;
+
+    let group = mint_object.promo()?.get_group(&payer)?;
+
+    let instruction = if mint_object.is_token_2022() {
+        create_mint_promo_2022_instruction(payer, group, token_owner, mint, memo)?
+    } else {
+        create_mint_promo_instruction(payer, group, token_owner, mint, memo)?
+    };
+
+    let tx = state
+        .create_transaction(&[instruction], &payer, Some(&group), &tx_query)
+        .await?;
+
+    let transaction = state.encode_transaction(tx, &[], &tx_query).await?;
+
+    Ok(Json(PayResponse {
+        transaction,
+        message,
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Data {
+    pub account: String,
+}
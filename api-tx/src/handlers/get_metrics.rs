@@ -0,0 +1,18 @@
+use axum::{extract::Extension, http::header, response::IntoResponse};
+use std::sync::Arc;
+
+use crate::State;
+
+/// Returns metrics in the Prometheus text format.
+pub async fn handler(Extension(state): Extension<Arc<State>>) -> impl IntoResponse {
+    let metrics = state
+        .bundlr
+        .as_ref()
+        .map(|bundlr| bundlr.status().to_metrics())
+        .unwrap_or_default();
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics,
+    )
+}
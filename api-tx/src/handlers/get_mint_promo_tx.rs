@@ -1,17 +1,19 @@
 use anchor_lang::prelude::Pubkey;
 use axum::{
-    extract::{Extension, Path},
+    extract::{Extension, Path, Query},
     Json,
 };
 use serde::{Deserialize, Serialize};
-use solana_sdk::{signer::Signer, transaction::Transaction};
+use solana_sdk::signer::Signer;
 use std::{str::FromStr, sync::Arc};
 
 use crate::{
     error::AppError,
-    handlers::Params,
-    utils::data::get_group_from_promo_group_query,
-    utils::{data::MINT_QUERY, solana::create_mint_promo_instruction},
+    handlers::{MintQuery, Params, TransactionQuery},
+    utils::solana::{
+        create_mint_promo_2022_instruction, create_mint_promo_instruction,
+        create_mint_referred_promo_instruction,
+    },
     State,
 };
 
@@ -19,7 +21,9 @@ use super::PayResponse;
 
 /// Handles merchant having added platform signer to group members to pay for transactions
 /// so users can mint directly without merchant approval. `token_owner` address assumed
-/// to be in the body of the request.
+/// to be in the body of the request. A `referrer` query parameter, as included in links from
+/// the referral link endpoint, is recorded so the referrer is rewarded when the token is burned
+/// if the promo has a referral reward.
 pub async fn handler(
     Json(data): Json<Data>,
     Path(Params {
@@ -27,26 +31,25 @@ pub async fn handler(
         message,
         memo,
     }): Path<Params>,
+    Query(MintQuery { referrer }): Query<MintQuery>,
+    Query(tx_query): Query<TransactionQuery>,
     Extension(state): Extension<Arc<State>>,
 ) -> Result<Json<PayResponse>, AppError> {
-    tracing::debug!(mint_string = mint_string, message = message, memo = memo);
+    tracing::debug!(
+        mint_string = mint_string,
+        message = message,
+        memo = memo,
+        referrer = referrer
+    );
 
     let token_owner = Pubkey::from_str(&data.account)?;
     let payer = state.platform_signer.pubkey();
     let mint = Pubkey::from_str(&mint_string)?;
 
-    let query = serde_json::json!({ "query": MINT_QUERY, "variables": {"mint": mint_string}});
-    let result: serde_json::Value = state
-        .solana
-        .client
-        .post(&state.data_url.to_string())
-        .json(&query)
-        .send()
-        .await?
-        .json()
-        .await?;
+    let mint_object = state.data.get_mint(&mint).await?;
+    let promo = mint_object.promo()?;
 
-    let group = match get_group_from_promo_group_query(&payer, &result) {
+    let group = match promo.get_group(&payer) {
         Ok(group) => Ok(group),
         Err(e) => {
             tracing::error!(error = e.to_string());
@@ -61,13 +64,29 @@ pub async fn handler(
         mint = mint.to_string(),
         memo = memo.clone().unwrap_or("".to_string())
     );
-    let instruction = create_mint_promo_instruction(payer, group, token_owner, mint, memo)?;
+    // Referrals are only recorded for promos with a referral reward.
+    let referrer = match promo.get_referral_reward() {
+        Some(_) => referrer,
+        None => None,
+    };
+    let instruction = match (referrer, mint_object.is_token_2022()) {
+        (Some(_), true) => return Err(AppError::Token2022Unsupported("referrals".to_string())),
+        (None, true) => create_mint_promo_2022_instruction(payer, group, token_owner, mint, memo)?,
+        (Some(referrer), false) => create_mint_referred_promo_instruction(
+            payer,
+            group,
+            token_owner,
+            mint,
+            Pubkey::from_str(&referrer)?,
+            memo,
+        )?,
+        (None, false) => create_mint_promo_instruction(payer, group, token_owner, mint, memo)?,
+    };
 
-    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer));
-    let latest_blockhash = state.solana.get_latest_blockhash().await?;
-    tx.try_partial_sign(&[&state.platform_signer], latest_blockhash)?;
-    let serialized = bincode::serialize(&tx)?;
-    let transaction = base64::encode(serialized);
+    let tx = state
+        .create_transaction(&[instruction], &payer, Some(&group), &tx_query)
+        .await?;
+    let transaction = state.encode_transaction(tx, &[], &tx_query).await?;
 
     Ok(Json(PayResponse {
         transaction,
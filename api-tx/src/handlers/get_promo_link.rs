@@ -0,0 +1,39 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path},
+    http::HeaderMap,
+    Json,
+};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{get_base_url, Params, SolanaPayLinkResponse},
+    utils::qr::{get_mint_link, get_solana_pay_url},
+    State,
+};
+
+/// Returns the link to the mint promo endpoint for `mint_string` with `message` and `memo`
+/// encoded in it, along with the same link as a Solana Pay transaction request url.
+pub async fn handler(
+    Path(Params {
+        mint_string,
+        message,
+        memo,
+    }): Path<Params>,
+    headers: HeaderMap,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<SolanaPayLinkResponse>, AppError> {
+    tracing::debug!(mint_string, message, memo);
+
+    let mint = Pubkey::from_str(&mint_string)?;
+    state.data.get_mint(&mint).await?.promo()?;
+
+    let link = get_mint_link(&get_base_url(&headers)?, &mint, &message, memo.as_deref());
+    let solana_pay_url = get_solana_pay_url(&link);
+
+    Ok(Json(SolanaPayLinkResponse {
+        link,
+        solana_pay_url,
+    }))
+}
@@ -0,0 +1,93 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path, Query},
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
+};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{
+        get_app_id::{get_group_profile, ICON},
+        get_base_url, Params, QrFormat, QrQuery,
+    },
+    utils::{
+        promo_image::MAX_IMAGE_BYTES,
+        qr::{
+            get_mint_link, get_solana_pay_url, render_png, render_svg, DEFAULT_QR_SIZE,
+            MAX_QR_SIZE, MIN_QR_SIZE,
+        },
+    },
+    State,
+};
+
+/// Returns a QR code of the Solana Pay transaction request url for minting the promo, as svg
+/// or png, optionally branded with the icon wallets show for the transaction request, the
+/// group's if it has set a profile.
+pub async fn handler(
+    Path(Params {
+        mint_string,
+        message,
+        memo,
+    }): Path<Params>,
+    Query(qr_query): Query<QrQuery>,
+    headers: HeaderMap,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Response, AppError> {
+    tracing::debug!(mint_string, message, memo, ?qr_query);
+
+    let mint = Pubkey::from_str(&mint_string)?;
+    let group = state.data.get_mint(&mint).await?.promo()?.group_object.id;
+
+    let link = get_mint_link(&get_base_url(&headers)?, &mint, &message, memo.as_deref());
+    let solana_pay_url = get_solana_pay_url(&link);
+    let size = qr_query
+        .size
+        .unwrap_or(DEFAULT_QR_SIZE)
+        .clamp(MIN_QR_SIZE, MAX_QR_SIZE);
+    let icon_url = if qr_query.branded {
+        Some(
+            get_group_profile(&state, &group)
+                .await
+                .map_or_else(|| ICON.to_string(), |profile| profile.icon),
+        )
+    } else {
+        None
+    };
+
+    match qr_query.format {
+        QrFormat::Svg => {
+            let svg = render_svg(&solana_pay_url, size, icon_url.as_deref())?;
+            Ok(([(header::CONTENT_TYPE, mime::IMAGE_SVG.as_ref())], svg).into_response())
+        }
+        QrFormat::Png => {
+            let icon = match &icon_url {
+                Some(icon_url) => Some(get_icon(&state, icon_url).await?),
+                None => None,
+            };
+            let png = render_png(&solana_pay_url, size, icon.as_deref())?;
+            Ok(([(header::CONTENT_TYPE, mime::IMAGE_PNG.as_ref())], png).into_response())
+        }
+    }
+}
+
+/// Returns the image at `icon_url`, fetching it if it isn't cached.
+async fn get_icon(state: &State, icon_url: &str) -> Result<Vec<u8>, AppError> {
+    if let Some(icon) = state.icons.get(icon_url) {
+        return Ok(icon);
+    }
+    let icon = state
+        .http
+        .get(icon_url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    if icon.len() > MAX_IMAGE_BYTES {
+        return Err(AppError::ImageTooLarge(MAX_IMAGE_BYTES));
+    }
+    state.icons.insert(icon_url.to_string(), icon.to_vec());
+    Ok(icon.to_vec())
+}
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use solana_sdk::signer::Signer;
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{RedeemPointsParams, TransactionQuery},
+    utils::solana::create_redeem_points_instruction,
+    State,
+};
+
+use super::PayResponse;
+
+/// Handles a customer redeeming loyalty points for the reward at `level`. Requires the merchant
+/// to have added the platform signer to group members to pay for the transaction. `token_owner`
+/// address assumed to be in the body of the request.
+pub async fn handler(
+    Json(data): Json<Data>,
+    Path(RedeemPointsParams {
+        mint_string,
+        level,
+        message,
+        memo,
+    }): Path<RedeemPointsParams>,
+    Query(tx_query): Query<TransactionQuery>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<PayResponse>, AppError> {
+    tracing::debug!(
+        mint_string = mint_string,
+        level = level,
+        message = message,
+        memo = memo
+    );
+
+    let token_owner = Pubkey::from_str(&data.account)?;
+    let payer = state.platform_signer.pubkey();
+    let mint = Pubkey::from_str(&mint_string)?;
+
+    let group = state
+        .data
+        .get_mint(&mint)
+        .await?
+        .promo()?
+        .get_group(&payer)?;
+
+    let instruction =
+        create_redeem_points_instruction(payer, group, token_owner, mint, level, memo)?;
+
+    let tx = state
+        .create_transaction(&[instruction], &payer, Some(&group), &tx_query)
+        .await?;
+    let transaction = state.encode_transaction(tx, &[], &tx_query).await?;
+
+    Ok(Json(PayResponse {
+        transaction,
+        message,
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Data {
+    pub account: String,
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path},
+    http::HeaderMap,
+    Json,
+};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{get_base_url, ReferralLinkParams, ReferralLinkResponse},
+    utils::qr::get_solana_pay_url,
+    State,
+};
+
+/// Returns a link to the mint promo endpoint with the `referrer` encoded in it, along with the
+/// same link as a Solana Pay transaction request url, for the referrer to share. The promo must
+/// have a referral reward.
+pub async fn handler(
+    Path(ReferralLinkParams {
+        mint_string,
+        referrer,
+        message,
+    }): Path<ReferralLinkParams>,
+    headers: HeaderMap,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<ReferralLinkResponse>, AppError> {
+    tracing::debug!(
+        mint_string = mint_string,
+        referrer = referrer,
+        message = message
+    );
+
+    let mint = Pubkey::from_str(&mint_string)?;
+    let referrer = Pubkey::from_str(&referrer)?;
+
+    state
+        .data
+        .get_mint(&mint)
+        .await?
+        .promo()?
+        .get_referral_reward()
+        .ok_or(AppError::ReferralRewardNotFound)?;
+
+    let base_url = get_base_url(&headers)?;
+    let link = format!(
+        "{}/promo/mint/{}/{}?referrer={}",
+        base_url,
+        mint,
+        urlencoding::encode(&message),
+        referrer
+    );
+    let solana_pay_url = get_solana_pay_url(&link);
+
+    Ok(Json(ReferralLinkResponse {
+        link,
+        solana_pay_url,
+    }))
+}
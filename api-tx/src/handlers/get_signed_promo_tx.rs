@@ -0,0 +1,49 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use solana_sdk::transaction::Transaction;
+use std::{str::FromStr, sync::Arc};
+
+use crate::{error::AppError, handlers::SignedParams, State};
+
+use super::PayResponse;
+
+/// Handles a customer retrieving a transaction already signed by a merchant. The token owner's
+/// address is assumed to be in the body of the request and must be one of the transaction's
+/// signers. Returns the message the merchant posted with the transaction.
+pub async fn handler(
+    Json(data): Json<Data>,
+    Path(SignedParams { signature, message }): Path<SignedParams>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<PayResponse>, AppError> {
+    tracing::debug!(signature, message);
+
+    let token_owner = Pubkey::from_str(&data.account)?;
+    let signed = state
+        .signed_transactions
+        .get(&signature)
+        .ok_or(AppError::SignedTransactionNotFound)?;
+
+    let bytes = base64::decode(&signed.transaction)
+        .map_err(|e| AppError::InvalidSignedTransaction(e.to_string()))?;
+    let tx: Transaction = bincode::deserialize(&bytes)?;
+    let num_signers = tx.message.header.num_required_signatures as usize;
+    let signers = tx
+        .message
+        .account_keys
+        .get(1..num_signers)
+        .ok_or_else(|| AppError::InvalidSignedTransaction("missing signers".to_string()))?;
+    if !signers.contains(&token_owner) {
+        return Err(AppError::NotTokenOwner);
+    }
+
+    Ok(Json(signed))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Data {
+    pub account: String,
+}
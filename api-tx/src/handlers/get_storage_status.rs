@@ -0,0 +1,12 @@
+use axum::{extract::Extension, Json};
+use std::sync::Arc;
+
+use crate::{handlers::StorageStatusResponse, State};
+
+/// Returns the last known bundlr balance of the platform signer and the state of funding the
+/// node, for checking that promos can still be created.
+pub async fn handler(Extension(state): Extension<Arc<State>>) -> Json<StorageStatusResponse> {
+    Json(StorageStatusResponse {
+        bundlr: state.bundlr.as_ref().map(|bundlr| bundlr.status()),
+    })
+}
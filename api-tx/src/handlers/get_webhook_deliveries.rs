@@ -0,0 +1,59 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{DeliveriesQuery, GroupProfileParams, WebhookDeliveryResponse},
+    utils::{
+        auth::AuthSession,
+        webhooks::{
+            get_timestamp, WebhookDelivery, WebhookDeliveryStatus, DEFAULT_DELIVERIES_LIMIT,
+            MAX_DELIVERIES_LIMIT,
+        },
+    },
+    State,
+};
+
+/// Returns the log of deliveries to a group's webhook endpoints, most recent first. Requires a
+/// member of the group to be signed in.
+pub async fn handler(
+    session: AuthSession,
+    Path(GroupProfileParams { group_string }): Path<GroupProfileParams>,
+    Query(query): Query<DeliveriesQuery>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<WebhookDeliveryResponse>>, AppError> {
+    tracing::debug!(group_string, ?query);
+
+    let group = Pubkey::from_str(&group_string)?;
+    state.check_group_member(&session, &group).await?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_DELIVERIES_LIMIT)
+        .clamp(1, MAX_DELIVERIES_LIMIT);
+    let deliveries = state.webhooks.get_deliveries(&group, limit).await?;
+
+    Ok(Json(
+        deliveries.into_iter().map(get_delivery_response).collect(),
+    ))
+}
+
+pub fn get_delivery_response(delivery: WebhookDelivery) -> WebhookDeliveryResponse {
+    WebhookDeliveryResponse {
+        next_attempt_at: (delivery.status == WebhookDeliveryStatus::Pending)
+            .then(|| get_timestamp(delivery.next_attempt_at)),
+        payload: serde_json::from_str(&delivery.payload).unwrap_or_default(),
+        id: delivery.id,
+        endpoint_id: delivery.endpoint_id,
+        event_id: delivery.event_id,
+        kind: delivery.kind,
+        status: delivery.status,
+        attempts: delivery.attempts,
+        response_status: delivery.response_status,
+        error: delivery.error,
+        created_at: get_timestamp(delivery.created_at),
+    }
+}
@@ -1,11 +1,57 @@
+use axum::http::{header, HeaderMap};
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    error::AppError,
+    utils::{
+        bundlr::BundlrStatus,
+        checkout::CheckoutStatus,
+        sponsorship::DailyUsage,
+        webhooks::{WebhookDeliveryStatus, WebhookEventKind},
+    },
+};
+
+pub mod delete_auth_session;
+pub mod delete_group_webhook;
 pub mod get_app_id;
+pub mod get_bid_promo_tx;
 pub mod get_burn_delegated_promo_tx;
+pub mod get_cancel_listing_tx;
+pub mod get_checkout_burn_tx;
+pub mod get_checkout_delegate_tx;
+pub mod get_checkout_revoke_tx;
+pub mod get_checkout_session;
+pub mod get_create_group_nonce_tx;
+pub mod get_create_promo_acceptance_tx;
 pub mod get_create_promo_group_tx;
 pub mod get_create_promo_tx;
+pub mod get_create_referral_reward_tx;
+pub mod get_create_settlement_approval_tx;
+pub mod get_create_sponsorship_allowance_tx;
 pub mod get_delegate_promo_tx;
+pub mod get_earn_points_tx;
+pub mod get_execute_sale_tx;
+pub mod get_group_usage;
+pub mod get_group_webhooks;
+pub mod get_list_promo_tx;
+pub mod get_merchant_mint_promo_tx;
+pub mod get_metrics;
 pub mod get_mint_promo_tx;
+pub mod get_promo_link;
+pub mod get_promo_qr;
+pub mod get_redeem_points_tx;
+pub mod get_referral_link;
+pub mod get_signed_promo_tx;
+pub mod get_storage_status;
+pub mod get_webhook_deliveries;
+pub mod post_auth_nonce;
+pub mod post_auth_session;
+pub mod post_checkout_session;
+pub mod post_group_billing;
+pub mod post_group_profile;
+pub mod post_group_webhook;
+pub mod post_merchant_signed_tx;
+pub mod post_promo_quote;
 
 #[derive(Deserialize, Debug)]
 pub struct Params {
@@ -14,6 +60,80 @@ pub struct Params {
     pub memo: Option<String>,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct MintQuery {
+    pub referrer: Option<String>,
+}
+
+/// Options for rendering a QR code. Codes are svg unless `png` is requested, and `branded` codes
+/// have the icon shown when the transaction request is scanned in the center.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QrQuery {
+    pub format: QrFormat,
+    pub size: Option<u32>,
+    pub branded: bool,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QrFormat {
+    #[default]
+    Svg,
+    Png,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MerchantMintParams {
+    pub mint_string: String,
+    pub token_owner: String,
+    pub message: String,
+    pub memo: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SignedParams {
+    pub signature: String,
+    pub message: String,
+}
+
+/// Options for building transactions. When `nonce` is set, the transaction uses the durable
+/// nonce of the group acting in it instead of the latest blockhash, so it can be completed after
+/// the blockhash expires. Only transactions paid for by a member of the group can use the nonce,
+/// as landing any of them invalidates the others. The compute unit limit and price override the
+/// server defaults, with the price still capped by the server. A `version` of `0` returns a v0
+/// transaction loading accounts from the platform's lookup table instead of a legacy
+/// transaction.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TransactionQuery {
+    pub nonce: bool,
+    pub compute_unit_limit: Option<u32>,
+    pub compute_unit_price: Option<u64>,
+    pub version: Option<u8>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GroupNonceParams {
+    pub group_seed: String,
+    pub message: String,
+    pub memo: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ReferralLinkParams {
+    pub mint_string: String,
+    pub referrer: String,
+    pub message: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ReferralRewardParams {
+    pub mint_string: String,
+    pub reward_mint_string: String,
+    pub memo: Option<String>,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct DelegateParams {
     pub mint_string: String,
@@ -30,6 +150,30 @@ pub struct PromoGroupParams {
     pub memo: Option<String>,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct PromoAcceptanceParams {
+    pub mint_string: String,
+    pub groups: String,
+    pub settlement: String,
+    pub settlement_lamports: u64,
+    pub memo: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SettlementApprovalParams {
+    pub mint_string: String,
+    pub group_string: String,
+    pub max_settlement_lamports: u64,
+    pub memo: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SponsorshipAllowanceParams {
+    pub group_string: String,
+    pub max_lamports: u64,
+    pub memo: Option<String>,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct BurnDelegatedParams {
     pub token_account_string: String,
@@ -37,6 +181,75 @@ pub struct BurnDelegatedParams {
     pub memo: Option<String>,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct TradeParams {
+    pub token_account_string: String,
+    pub price: u64,
+    pub message: String,
+    pub memo: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ExecuteSaleParams {
+    pub token_account_string: String,
+    pub buyer: String,
+    pub price: u64,
+    pub message: String,
+    pub memo: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct EarnPointsParams {
+    pub mint_string: String,
+    pub token_owner: String,
+    pub order_amount: u64,
+    pub message: String,
+    pub memo: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RedeemPointsParams {
+    pub mint_string: String,
+    pub level: u8,
+    pub message: String,
+    pub memo: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct QuoteParams {
+    pub delegate_string: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GroupProfileParams {
+    pub group_string: String,
+}
+
+/// Days of daily usage to return, `DEFAULT_USAGE_DAYS` if not set.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct UsageQuery {
+    pub days: Option<u64>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct WebhookEndpointParams {
+    pub group_string: String,
+    pub endpoint_id: String,
+}
+
+/// Number of deliveries to return, `DEFAULT_DELIVERIES_LIMIT` if not set.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct DeliveriesQuery {
+    pub limit: Option<u64>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CheckoutParams {
+    pub session_id: String,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct CreatePromoParams {
     pub payer: String,
@@ -44,8 +257,167 @@ pub struct CreatePromoParams {
     pub memo: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+/// Options for creating a promo with a Token-2022 mint. Token-2022 promos always have the
+/// program authority as permanent delegate so there is no delegate step before redemption.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CreatePromoQuery {
+    pub token_2022: bool,
+    pub non_transferable: bool,
+    pub metadata_pointer: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct PayResponse {
     pub transaction: String,
     pub message: String,
 }
+
+/// Discounts applicable to an order, best first.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QuoteResponse {
+    pub discounts: Vec<QuoteDiscount>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteDiscount {
+    pub mint: String,
+    pub token_account: String,
+    pub promo_type: String,
+    /// Discount in 0.01 currency.
+    pub amount: u64,
+    /// Reward level redeemed for loyalty promos.
+    pub level: Option<u8>,
+    /// Transaction for the customer to sign to apply the discount, if one is needed.
+    pub transaction: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SignedResponse {
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferralLinkResponse {
+    pub link: String,
+    pub solana_pay_url: String,
+}
+
+/// Link to a transaction request endpoint along with the same link as a Solana Pay url.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SolanaPayLinkResponse {
+    pub link: String,
+    pub solana_pay_url: String,
+}
+
+/// Checkout session state for the point of sale.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckoutSessionResponse {
+    pub id: String,
+    pub order_id: String,
+    pub mint: String,
+    pub delegate: String,
+    pub status: CheckoutStatus,
+    pub token_owner: Option<String>,
+    /// Unix timestamp after which the session expires if it hasn't been finalized.
+    pub expires_at: u64,
+    /// Solana Pay transaction request url for the customer to delegate their promo token.
+    pub solana_pay_url: String,
+    /// Solana Pay transaction request url for the customer to revoke the delegation of their
+    /// promo token if the session expired after it was delegated.
+    pub revoke_solana_pay_url: Option<String>,
+}
+
+/// Message for an account to sign to sign in, with the nonce to send back with its signature.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SignInChallengeResponse {
+    pub nonce: String,
+    pub message: String,
+    /// Unix timestamp after which the nonce can't be used.
+    pub expires_at: u64,
+}
+
+/// Session token to send as a bearer token to endpoints that act for a group.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthSessionResponse {
+    pub token: String,
+    pub account: String,
+    /// Unix timestamp after which the session expires and the account needs to sign in again.
+    pub expires_at: u64,
+}
+
+/// Lamports the platform signer has paid for a group's sponsored transactions and what the
+/// group has reimbursed.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupUsageResponse {
+    pub group: String,
+    /// Confirmed and failed sponsored transactions.
+    pub transactions: u64,
+    pub fees: u64,
+    pub rent: u64,
+    pub settled: u64,
+    pub outstanding: u64,
+    pub reimburse: bool,
+    /// Daily usage, most recent first.
+    pub days: Vec<DailyUsage>,
+}
+
+/// Endpoint a group's events are delivered to. The secret is only returned when the endpoint
+/// is registered.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookEndpointResponse {
+    pub id: String,
+    pub url: String,
+    pub events: Vec<WebhookEventKind>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub created_at: u64,
+}
+
+/// Event queued for an endpoint and the outcome of the attempts to deliver it.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDeliveryResponse {
+    pub id: String,
+    pub endpoint_id: String,
+    pub event_id: String,
+    pub kind: WebhookEventKind,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: u32,
+    /// Unix timestamp of the next attempt, while the delivery is pending.
+    pub next_attempt_at: Option<u64>,
+    /// Http status of the response to the last attempt.
+    pub response_status: Option<u16>,
+    pub error: Option<String>,
+    /// Json posted to the endpoint.
+    pub payload: serde_json::Value,
+    pub created_at: u64,
+}
+
+/// Status of the storage backend. Only the bundlr backend has a balance to report.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageStatusResponse {
+    pub bundlr: Option<BundlrStatus>,
+}
+
+/// Returns the scheme and host requests were made to, for links back to this server.
+pub fn get_base_url(headers: &HeaderMap) -> Result<String, AppError> {
+    let host = headers
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .ok_or(AppError::GenericError("request has no host".to_string()))?;
+    let scheme = headers
+        .get("x-forwarded-proto")
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("https");
+    Ok(format!("{}://{}", scheme, host))
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::Extension,
+    http::{header, HeaderMap},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{error::AppError, handlers::SignInChallengeResponse, State};
+
+/// Issues a nonce for an account to sign in with, returning the Sign-In-With-Solana message
+/// for the account's wallet to sign. Requests have to be made to the configured sign-in domain.
+pub async fn handler(
+    Json(data): Json<Data>,
+    headers: HeaderMap,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<SignInChallengeResponse>, AppError> {
+    tracing::debug!(account = data.account);
+
+    let account = Pubkey::from_str(&data.account)?;
+    let host = headers
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok());
+    let (nonce, challenge) = state.create_sign_in_challenge(account, host)?;
+
+    Ok(Json(SignInChallengeResponse {
+        nonce,
+        message: challenge.message,
+        expires_at: challenge.expires_at,
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Data {
+    pub account: String,
+}
@@ -0,0 +1,30 @@
+use axum::{extract::Extension, Json};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::{error::AppError, handlers::AuthSessionResponse, State};
+
+/// Signs in the account a nonce was issued for, with its signature of the message returned with
+/// the nonce, and returns a session token.
+pub async fn handler(
+    Json(data): Json<Data>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<AuthSessionResponse>, AppError> {
+    tracing::debug!(nonce = data.nonce);
+
+    let (token, session) = state.sign_in(&data.nonce, &data.signature)?;
+    tracing::info!(account = session.account.to_string(), "signed in");
+
+    Ok(Json(AuthSessionResponse {
+        token,
+        account: session.account.to_string(),
+        expires_at: session.expires_at,
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Data {
+    pub nonce: String,
+    /// Base58 encoded signature of the message.
+    pub signature: String,
+}
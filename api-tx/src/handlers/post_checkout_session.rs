@@ -0,0 +1,52 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{extract::Extension, http::HeaderMap, Json};
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{get_checkout_session::get_session_response, CheckoutSessionResponse},
+    utils::checkout::CheckoutSession,
+    State,
+};
+
+/// Handles a point of sale starting checkout of an order with a promo, redeemed by `delegate`,
+/// a merchant device. Returns the session with the url the customer scans to get the delegate
+/// transaction.
+pub async fn handler(
+    Json(data): Json<Data>,
+    headers: HeaderMap,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<CheckoutSessionResponse>, AppError> {
+    tracing::debug!(
+        order_id = data.order_id,
+        mint = data.mint,
+        delegate = data.delegate
+    );
+
+    let mint = Pubkey::from_str(&data.mint)?;
+    let delegate = Pubkey::from_str(&data.delegate)?;
+
+    // Fails early if the delegate can't redeem the promo.
+    state
+        .data
+        .get_mint(&mint)
+        .await?
+        .promo()?
+        .get_redeeming_group(&delegate)?;
+
+    let session = CheckoutSession::new(data.order_id, mint, delegate, data.message);
+    state.update_checkout_session(session.clone());
+
+    Ok(Json(get_session_response(&session, &headers)?))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Data {
+    pub order_id: String,
+    pub mint: String,
+    pub delegate: String,
+    /// Displayed to the customer with the delegate transaction.
+    pub message: String,
+}
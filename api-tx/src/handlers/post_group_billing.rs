@@ -0,0 +1,41 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{get_group_usage::get_usage_response, GroupProfileParams, GroupUsageResponse},
+    utils::{auth::AuthSession, sponsorship::DEFAULT_USAGE_DAYS},
+    State,
+};
+
+/// Handles a group owner opting in or out of reimbursing the platform for the group's
+/// sponsored transactions from the group's balance. Requires the owner to be signed in.
+pub async fn handler(
+    session: AuthSession,
+    Json(data): Json<Data>,
+    Path(GroupProfileParams { group_string }): Path<GroupProfileParams>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<GroupUsageResponse>, AppError> {
+    tracing::debug!(group_string, reimburse = data.reimburse);
+
+    let group = Pubkey::from_str(&group_string)?;
+    state.check_group_owner(&session, &group).await?;
+    state
+        .sponsorship
+        .set_reimburse(&group, data.reimburse, &session.signature)
+        .await?;
+
+    Ok(Json(
+        get_usage_response(&state, &group, DEFAULT_USAGE_DAYS).await?,
+    ))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Data {
+    pub reimburse: bool,
+}
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{get_app_id::ResponseData, GroupProfileParams},
+    utils::{auth::AuthSession, data::GroupProfileObject, group_profile::validate_group_profile},
+    State,
+};
+
+/// Handles a group owner setting the label and icon wallets show for the group's transaction
+/// requests. Requires the owner to be signed in.
+pub async fn handler(
+    session: AuthSession,
+    Json(data): Json<Data>,
+    Path(GroupProfileParams { group_string }): Path<GroupProfileParams>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<ResponseData>, AppError> {
+    tracing::debug!(group_string, label = data.label, icon = data.icon);
+
+    let group = Pubkey::from_str(&group_string)?;
+    let profile = GroupProfileObject {
+        label: data.label,
+        icon: data.icon,
+    };
+    validate_group_profile(&profile)?;
+
+    state.check_group_owner(&session, &group).await?;
+    state
+        .data
+        .set_group_profile(&group, &profile, &session.signature)
+        .await?;
+
+    Ok(Json(ResponseData {
+        label: profile.label,
+        icon: profile.icon,
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Data {
+    pub label: String,
+    pub icon: String,
+}
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{
+        get_group_webhooks::get_endpoint_response, GroupProfileParams, WebhookEndpointResponse,
+    },
+    utils::{
+        auth::AuthSession,
+        webhooks::{
+            validate_webhook_endpoint, WebhookEndpoint, WebhookEventKind, MAX_WEBHOOK_ENDPOINTS,
+            WEBHOOK_EVENT_KINDS,
+        },
+    },
+    State,
+};
+
+/// Handles a group owner registering an https endpoint for the group's events to be delivered
+/// to. Returns the endpoint with the secret deliveries are signed with, which is generated if
+/// not provided and isn't returned again. Requires the owner to be signed in.
+pub async fn handler(
+    session: AuthSession,
+    Json(data): Json<Data>,
+    Path(GroupProfileParams { group_string }): Path<GroupProfileParams>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<WebhookEndpointResponse>, AppError> {
+    tracing::debug!(group_string, url = data.url, events = ?data.events);
+
+    let group = Pubkey::from_str(&group_string)?;
+    let mut events = data.events.unwrap_or_else(|| WEBHOOK_EVENT_KINDS.to_vec());
+    events.sort_by_key(|kind| WEBHOOK_EVENT_KINDS.iter().position(|k| k == kind));
+    events.dedup();
+    validate_webhook_endpoint(&data.url, &events, data.secret.as_deref())?;
+
+    state.check_group_owner(&session, &group).await?;
+    if state.webhooks.get_endpoints(&group).await?.len() >= MAX_WEBHOOK_ENDPOINTS {
+        return Err(AppError::InvalidWebhook(format!(
+            "groups can have at most {} endpoints",
+            MAX_WEBHOOK_ENDPOINTS
+        )));
+    }
+
+    let endpoint = WebhookEndpoint::new(group, data.url, events, data.secret);
+    state
+        .webhooks
+        .insert_endpoint(&endpoint, &session.signature)
+        .await?;
+    tracing::info!(
+        group = group.to_string(),
+        endpoint = endpoint.id,
+        "webhook endpoint registered"
+    );
+
+    let secret = endpoint.secret.clone();
+    Ok(Json(WebhookEndpointResponse {
+        secret: Some(secret),
+        ..get_endpoint_response(endpoint)
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Data {
+    pub url: String,
+    /// All events if not set.
+    pub events: Option<Vec<WebhookEventKind>>,
+    pub secret: Option<String>,
+}
@@ -0,0 +1,75 @@
+use axum::{extract::Extension, Json};
+use solana_sdk::{
+    sanitize::Sanitize, system_instruction::SystemInstruction, system_program,
+    transaction::Transaction,
+};
+use std::sync::Arc;
+
+use crate::{
+    error::AppError,
+    utils::{
+        auth::AuthSession,
+        solana::check_merchant_mint_transaction,
+        store::{NONCE_SIGNED_TRANSACTION_TTL, SIGNED_TRANSACTION_TTL},
+    },
+    State,
+};
+
+use super::{PayResponse, SignedResponse};
+
+/// Handles a merchant posting back a mint transaction they have signed, along with the message
+/// to display to the customer. The transaction is stored with the merchant's signature as the
+/// key until its blockhash expires, or for a day if it advances a durable nonce. The signature
+/// is returned so the merchant can present the customer with a link to the signed transaction
+/// endpoint. Requires the merchant to be signed in as a member of the group minting the promo
+/// and paying for the transaction, which can only mint the promo as the merchant mint endpoint
+/// does, since the customer signs it as served.
+pub async fn handler(
+    session: AuthSession,
+    Json(data): Json<PayResponse>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<SignedResponse>, AppError> {
+    let bytes = base64::decode(&data.transaction)
+        .map_err(|e| AppError::InvalidSignedTransaction(e.to_string()))?;
+    let tx: Transaction = bincode::deserialize(&bytes)?;
+    tx.sanitize()
+        .map_err(|e| AppError::InvalidSignedTransaction(e.to_string()))?;
+
+    let group = check_merchant_mint_transaction(&tx)?;
+    state.check_group_member(&session, &group).await?;
+    if tx.message.account_keys.first() != Some(&session.account) {
+        return Err(AppError::InvalidSignedTransaction(
+            "merchant isn't the fee payer".to_string(),
+        ));
+    }
+
+    // The merchant is the fee payer, so their signature is always the first one.
+    let verified = tx.verify_with_results();
+    if !verified.first().copied().unwrap_or(false) {
+        return Err(AppError::InvalidSignedTransaction(
+            "missing merchant signature".to_string(),
+        ));
+    }
+
+    let signature = tx.signatures[0].to_string();
+    tracing::debug!(signature, message = data.message);
+    let ttl = if advances_nonce(&tx) {
+        NONCE_SIGNED_TRANSACTION_TTL
+    } else {
+        SIGNED_TRANSACTION_TTL
+    };
+    state
+        .signed_transactions
+        .insert_with_ttl(signature.clone(), data, ttl);
+
+    Ok(Json(SignedResponse { signature }))
+}
+
+/// Durable nonce transactions advance the nonce in their first instruction.
+fn advances_nonce(tx: &Transaction) -> bool {
+    tx.message.instructions.first().map_or(false, |ix| {
+        tx.message.account_keys[ix.program_id_index as usize] == system_program::ID
+            && bincode::deserialize::<SystemInstruction>(&ix.data).ok()
+                == Some(SystemInstruction::AdvanceNonceAccount)
+    })
+}
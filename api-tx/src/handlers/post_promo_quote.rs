@@ -0,0 +1,153 @@
+use anchor_lang::prelude::Pubkey;
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use solana_sdk::signer::Signer;
+use std::{str::FromStr, sync::Arc};
+
+use crate::{
+    error::AppError,
+    handlers::{
+        get_delegate_promo_tx::create_delegate_instruction, QuoteDiscount, QuoteParams,
+        QuoteResponse, TransactionQuery,
+    },
+    utils::{
+        data::TokenAccountObject,
+        quote::{get_discount, Discount, Order},
+        solana::create_redeem_points_instruction,
+    },
+    State,
+};
+
+/// Returns the discounts the customer whose address is in the body of the request can get on
+/// `order` with the promos they hold that `delegate`, a merchant device, can redeem, best
+/// first. Each discount includes the transaction for the customer to sign to apply it: the
+/// delegate transaction for promos redeemed by delegation or the redeem points transaction for
+/// loyalty promos. Token-2022 promos are burned without delegation so have no transaction.
+pub async fn handler(
+    Json(data): Json<Data>,
+    Path(QuoteParams { delegate_string }): Path<QuoteParams>,
+    Query(tx_query): Query<TransactionQuery>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<QuoteResponse>, AppError> {
+    tracing::debug!(delegate_string, order = ?data.order);
+
+    let token_owner = Pubkey::from_str(&data.account)?;
+    let delegate = Pubkey::from_str(&delegate_string)?;
+
+    let mut discounts = Vec::new();
+    for token_account in state.data.get_owner_token_accounts(&token_owner).await? {
+        let discount = match get_token_account_discount(&token_account, &delegate, &data.order) {
+            Some(discount) => discount,
+            None => continue,
+        };
+        let transaction = match get_discount_transaction(
+            &token_account,
+            &discount,
+            delegate,
+            &data.order,
+            &tx_query,
+            &state,
+        )
+        .await
+        {
+            Ok(transaction) => transaction,
+            // Promos that can't be applied, for example after reaching their max burn.
+            Err(AppError::SimulationError { .. }) => continue,
+            Err(error) => return Err(error),
+        };
+
+        discounts.push(QuoteDiscount {
+            mint: token_account.mint.to_string(),
+            token_account: token_account.id.to_string(),
+            promo_type: token_account
+                .mint_object
+                .as_ref()
+                .and_then(|mint_object| mint_object.promo_attributes())
+                .map(|attributes| attributes.promo_type.name().to_string())
+                .unwrap_or_default(),
+            amount: discount.amount,
+            level: discount.level,
+            transaction,
+        });
+    }
+    discounts.sort_by(|a, b| b.amount.cmp(&a.amount));
+
+    Ok(Json(QuoteResponse { discounts }))
+}
+
+/// Returns the discount for a token account if its promo can be redeemed by `delegate` and
+/// applies to `order`.
+fn get_token_account_discount(
+    token_account: &TokenAccountObject,
+    delegate: &Pubkey,
+    order: &Order,
+) -> Option<Discount> {
+    let mint_object = token_account.mint_object.as_ref()?;
+    mint_object
+        .promo()
+        .ok()?
+        .get_redeeming_group(delegate)
+        .ok()?;
+    let attributes = mint_object.promo_attributes()?;
+    get_discount(&attributes.promo_type, order, token_account.amount)
+}
+
+async fn get_discount_transaction(
+    token_account: &TokenAccountObject,
+    discount: &Discount,
+    delegate: Pubkey,
+    order: &Order,
+    tx_query: &TransactionQuery,
+    state: &State,
+) -> Result<Option<String>, AppError> {
+    let mint_object = match token_account.mint_object.as_ref() {
+        Some(mint_object) => mint_object,
+        None => return Ok(None),
+    };
+    if mint_object.is_token_2022() {
+        return Ok(None);
+    }
+
+    let payer = state.platform_signer.pubkey();
+    let memo = order
+        .order_id
+        .as_ref()
+        .map(|order_id| serde_json::json!({ "orderId": order_id }).to_string());
+
+    let (instruction, group) = match discount.level {
+        // Points are redeemed with the platform signer paying as a member of the group.
+        Some(level) => {
+            let group = match mint_object.promo()?.get_group(&payer) {
+                Ok(group) => group,
+                Err(_) => return Ok(None),
+            };
+            let instruction = create_redeem_points_instruction(
+                payer,
+                group,
+                token_account.owner,
+                token_account.mint,
+                level,
+                memo,
+            )?;
+            (instruction, group)
+        }
+        None => {
+            create_delegate_instruction(payer, delegate, token_account.owner, mint_object, memo)?
+        }
+    };
+
+    let tx = state
+        .create_transaction(&[instruction], &payer, Some(&group), tx_query)
+        .await?;
+    let transaction = state.encode_transaction(tx, &[], tx_query).await?;
+    Ok(Some(transaction))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Data {
+    pub account: String,
+    pub order: Order,
+}
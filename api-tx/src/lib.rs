@@ -1,14 +1,23 @@
 use axum::{
     error_handling::HandleErrorLayer,
     http::{header, Method, StatusCode},
+    middleware,
     response::IntoResponse,
-    routing::get,
+    routing::{delete, get, post},
     Router,
 };
-use bundlr_sdk::{Bundlr, Ed25519Signer};
+use bundlr_sdk::Ed25519Signer;
 use ed25519_dalek::Keypair as DalekKeypair;
+use error::AppError;
 use handlers::*;
-use solana_sdk::{commitment_config::CommitmentLevel, pubkey::Pubkey, signer::keypair::Keypair};
+use mpl_auction_house::pda::find_auction_house_address;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signer::{keypair::Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
 use std::{borrow::Cow, sync::Arc, time::Duration};
 use tower::{BoxError, ServiceBuilder};
 use tower_http::{
@@ -17,7 +26,30 @@ use tower_http::{
     trace::TraceLayer,
 };
 use url::Url;
-use utils::solana::{Cluster, Solana};
+use utils::{
+    auth::{AuthSession, SignInChallenge, DEFAULT_SIGN_IN_URL},
+    bundlr::BundlrAccount,
+    checkout::CheckoutSession,
+    clients::{CachedDataSource, DataConfig, DataSource, GraphqlDataSource, SolanaRpc},
+    compute_budget::{
+        get_compute_unit_price, get_writable_accounts, set_compute_unit_limit,
+        set_compute_unit_price, ComputeBudgetConfig,
+    },
+    lookup_table::get_platform_lookup_table_addresses,
+    postgres::PostgresDataSource,
+    rate_limit::{rate_limit, RateLimiter},
+    solana::{find_group_nonce_address, Cluster, Solana},
+    sponsorship::{
+        record_sponsorship, MemorySponsorshipLedger, PostgresSponsorshipLedger, SponsorshipLedger,
+    },
+    storage::{BundlrStorage, IpfsStorage, LocalStorage, S3Storage, Storage, StorageConfig},
+    store::{
+        TtlStore, AUTH_SESSION_TTL, CHECKOUT_SESSION_TTL, ICON_CACHE_TTL, MINT_CACHE_TTL,
+        SIGNED_TRANSACTION_TTL, SIGN_IN_NONCE_TTL,
+    },
+    versioned::{MessageV0, VersionedTransaction},
+    webhooks::{MemoryWebhookStore, PostgresWebhookStore, WebhookStore},
+};
 
 pub mod error;
 pub mod handlers;
@@ -29,8 +61,8 @@ pub mod utils;
 // `platform_signer` is the courtesy signing key that pays minor network
 // fees on public mints.
 //
-// `platform_signer` is also the payer for bundlr transactions.
-// TODO: check bundlr balance programatically and alert of running low.
+// `platform_signer` is also the payer for bundlr transactions when promo artwork and metadata
+// are stored on Arweave, with its balance on the bundlr node monitored by `utils::bundlr`.
 
 pub fn parse_string_to_keypair(str: &str) -> Keypair {
     let bytes: Vec<u8> = serde_json::from_str(str).unwrap();
@@ -40,50 +72,263 @@ pub fn parse_string_to_keypair(str: &str) -> Keypair {
 pub struct State {
     pub platform_signer: Keypair,
     pub platform: Pubkey,
-    pub solana: Solana,
-    pub bundlr: bundlr_sdk::Bundlr<Ed25519Signer>,
-    pub data_url: Url,
+    /// Auction house promo tokens are traded on, with `platform` as authority and SOL as the
+    /// treasury mint.
+    pub auction_house: Pubkey,
+    pub solana: Arc<dyn SolanaRpc>,
+    pub compute_budget: ComputeBudgetConfig,
+    /// Platform address lookup table for v0 transactions, holding the addresses in
+    /// `get_platform_lookup_table_addresses`.
+    pub lookup_table: Option<Pubkey>,
+    /// Where promo images and metadata json are uploaded.
+    pub storage: Arc<dyn Storage>,
+    pub data: Arc<dyn DataSource>,
+    /// Mint transactions signed by merchants waiting to be retrieved by customers, keyed by
+    /// merchant signature.
+    pub signed_transactions: TtlStore<PayResponse>,
+    /// Checkout sessions keyed by id.
+    pub checkout_sessions: TtlStore<CheckoutSession>,
+    /// Url of the server sign-in messages are issued for.
+    pub sign_in_url: Url,
+    /// Sign-in messages waiting to be signed, keyed by nonce.
+    pub sign_in_challenges: TtlStore<SignInChallenge>,
+    /// Signed in merchants keyed by session token.
+    pub auth_sessions: TtlStore<AuthSession>,
+    /// Client for requests other than to the Solana RPC and data api, like fetching icons.
+    pub http: reqwest::Client,
+    /// Icons for branding QR codes, keyed by url.
+    pub icons: TtlStore<Vec<u8>>,
+    /// Limits on requests for transactions paid for by the platform signer, none by default.
+    pub rate_limiter: RateLimiter,
+    /// Transactions paid for by the platform signer per group, in the `api-data` database with
+    /// the postgres data source or else in memory.
+    pub sponsorship: Arc<dyn SponsorshipLedger>,
+    /// Balance of the platform signer on the bundlr node, with bundlr storage.
+    pub bundlr: Option<BundlrAccount>,
+    /// Webhook endpoints and deliveries, in the `api-data` database with the postgres data
+    /// source, which events are also read from, or else in memory.
+    pub webhooks: Arc<dyn WebhookStore>,
 }
 
 impl State {
-    fn new(cluster: Cluster, platform: Pubkey, platform_signer: Keypair, data_url: Url) -> Self {
-        let keypair = DalekKeypair::from_bytes(&platform_signer.to_bytes()).unwrap();
-        let signer = Ed25519Signer::new(keypair);
+    /// Creates state connected to `cluster`, the data source in `data_config` and the storage
+    /// backend in `storage_config`. The `solana` and `data` clients can be replaced, for
+    /// example with the fakes in `utils::fakes` for tests.
+    pub fn new(
+        cluster: Cluster,
+        platform: Pubkey,
+        platform_signer: Keypair,
+        data_config: DataConfig,
+        storage_config: StorageConfig,
+        compute_budget: ComputeBudgetConfig,
+        lookup_table: Option<Pubkey>,
+    ) -> Self {
+        let (auction_house, _auction_house_bump) =
+            find_auction_house_address(&platform, &spl_token::native_mint::ID);
+
+        let solana = Solana::new(cluster);
+        let (data, sponsorship, webhooks): (
+            Arc<dyn DataSource>,
+            Arc<dyn SponsorshipLedger>,
+            Arc<dyn WebhookStore>,
+        ) = match data_config {
+            DataConfig::Graphql(data_url) => (
+                Arc::new(CachedDataSource::new(
+                    GraphqlDataSource::new(solana.client.clone(), data_url),
+                    MINT_CACHE_TTL,
+                )),
+                Arc::new(MemorySponsorshipLedger::default()),
+                Arc::new(MemoryWebhookStore::default()),
+            ),
+            DataConfig::Postgres {
+                database_url,
+                pool_size,
+            } => {
+                let data_source = PostgresDataSource::new(&database_url, pool_size).unwrap();
+                let sponsorship = PostgresSponsorshipLedger::new(data_source.pool());
+                let webhooks = PostgresWebhookStore::new(data_source.pool());
+                (
+                    Arc::new(CachedDataSource::new(data_source, MINT_CACHE_TTL)),
+                    Arc::new(sponsorship),
+                    Arc::new(webhooks),
+                )
+            }
+        };
+
+        let bundlr = match &storage_config {
+            StorageConfig::Bundlr(node_url) => Some(BundlrAccount::new(
+                solana.client.clone(),
+                node_url.clone(),
+                platform_signer.pubkey(),
+            )),
+            _ => None,
+        };
+
+        let storage: Arc<dyn Storage> = match storage_config {
+            StorageConfig::Bundlr(node_url) => {
+                let keypair = DalekKeypair::from_bytes(&platform_signer.to_bytes()).unwrap();
+                Arc::new(BundlrStorage::new(node_url, Ed25519Signer::new(keypair)))
+            }
+            StorageConfig::S3 {
+                bucket,
+                region,
+                endpoint,
+                public_url,
+            } => Arc::new(S3Storage::new(&bucket, &region, endpoint, public_url).unwrap()),
+            StorageConfig::Ipfs {
+                api_url,
+                gateway_url,
+            } => Arc::new(IpfsStorage::new(
+                solana.client.clone(),
+                api_url,
+                gateway_url,
+            )),
+            StorageConfig::Local { dir, base_url } => Arc::new(LocalStorage::new(dir, base_url)),
+        };
+
+        let http = solana.client.clone();
 
         Self {
             platform_signer,
             platform,
-            solana: Solana {
-                cluster,
-                commitment: CommitmentLevel::Confirmed,
-                client: reqwest::Client::builder()
-                    .timeout(Duration::from_secs(10))
-                    .build()
-                    .unwrap(),
-            },
-            bundlr: Bundlr::new(
-                "https://node1.bundlr.network".to_string(),
-                "solana".to_string(),
-                "sol".to_string(),
-                signer,
+            auction_house,
+            solana: Arc::new(solana),
+            compute_budget,
+            lookup_table,
+            storage,
+            data,
+            signed_transactions: TtlStore::new(SIGNED_TRANSACTION_TTL),
+            checkout_sessions: TtlStore::new(CHECKOUT_SESSION_TTL),
+            sign_in_url: Url::parse(DEFAULT_SIGN_IN_URL).unwrap(),
+            sign_in_challenges: TtlStore::new(SIGN_IN_NONCE_TTL),
+            auth_sessions: TtlStore::new(AUTH_SESSION_TTL),
+            http,
+            icons: TtlStore::new(ICON_CACHE_TTL),
+            rate_limiter: RateLimiter::default(),
+            sponsorship,
+            bundlr,
+            webhooks,
+        }
+    }
+}
+
+impl State {
+    /// Creates a transaction with compute budget instructions and the latest blockhash or, if
+    /// the `nonce` query parameter is set and the transaction has a `group`, with the blockhash
+    /// stored in the group's durable nonce account so it can be signed and submitted after the
+    /// latest blockhash has expired. Nonce transactions advance the nonce first and are
+    /// partially signed by the platform signer as nonce authority.
+    pub async fn create_transaction(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        group: Option<&Pubkey>,
+        query: &TransactionQuery,
+    ) -> Result<Transaction, AppError> {
+        let compute_unit_price = match query.compute_unit_price {
+            Some(price) => price,
+            None => get_compute_unit_price(
+                self.solana
+                    .get_recent_prioritization_fees(&get_writable_accounts(instructions))
+                    .await?,
+                self.compute_budget.max_compute_unit_price,
             ),
-            data_url,
         }
+        .min(self.compute_budget.max_compute_unit_price);
+        let compute_unit_limit = query
+            .compute_unit_limit
+            .unwrap_or(self.compute_budget.compute_unit_limit)
+            .min(self.compute_budget.max_compute_unit_limit);
+
+        let mut budget_instructions = vec![
+            set_compute_unit_limit(compute_unit_limit),
+            set_compute_unit_price(compute_unit_price),
+        ];
+        budget_instructions.extend_from_slice(instructions);
+
+        let group = match group {
+            Some(group) if query.nonce => group,
+            _ => {
+                let mut tx = Transaction::new_with_payer(&budget_instructions, Some(payer));
+                tx.message.recent_blockhash = self.solana.get_latest_blockhash().await?;
+                return Ok(tx);
+            }
+        };
+
+        // Each group has a single nonce, so landing any transaction using it invalidates the
+        // others. Only members paying for their own transactions can use it, since anyone can
+        // land a transaction the platform signer pays for.
+        let platform_signer = self.platform_signer.pubkey();
+        if *payer == platform_signer || !self.data.get_group(group).await?.members.contains(payer) {
+            return Err(AppError::Denied(
+                "durable nonce transactions must be paid for by a member of the group".to_string(),
+            ));
+        }
+        let (nonce, _seed) = find_group_nonce_address(&platform_signer, group);
+        let recent_blockhash = self.solana.get_nonce_blockhash(&nonce).await?;
+        let mut nonce_instructions = vec![system_instruction::advance_nonce_account(
+            &nonce,
+            &platform_signer,
+        )];
+        nonce_instructions.extend(budget_instructions);
+
+        let mut tx = Transaction::new_with_payer(&nonce_instructions, Some(payer));
+        tx.message.recent_blockhash = recent_blockhash;
+        Ok(tx)
+    }
+
+    /// Signs a transaction with `signers` and with the platform signer if it is a required
+    /// signer, simulates it and serializes it to be returned in a transaction request response.
+    /// Returns a v0 transaction loading accounts from the platform's lookup table if requested.
+    pub async fn encode_transaction(
+        &self,
+        mut tx: Transaction,
+        signers: &[&Keypair],
+        query: &TransactionQuery,
+    ) -> Result<String, AppError> {
+        let num_signers = tx.message.header.num_required_signatures as usize;
+        let mut signers = signers.to_vec();
+        if tx.message.account_keys[..num_signers].contains(&self.platform_signer.pubkey()) {
+            signers.push(&self.platform_signer);
+        }
+
+        let serialized = match query.version {
+            None => {
+                tx.try_partial_sign(&signers, tx.message.recent_blockhash)?;
+                bincode::serialize(&tx)?
+            }
+            Some(0) => {
+                let addresses = match self.lookup_table {
+                    Some(_) => get_platform_lookup_table_addresses(),
+                    None => vec![],
+                };
+                let message = MessageV0::compile(
+                    &tx.message,
+                    &self.lookup_table.unwrap_or_default(),
+                    &addresses,
+                );
+                let mut versioned_tx = VersionedTransaction::new(message);
+                versioned_tx.try_partial_sign(&signers)?;
+                versioned_tx.serialize()
+            }
+            Some(version) => return Err(AppError::UnsupportedTransactionVersion(version)),
+        };
+
+        let transaction = base64::encode(serialized);
+        self.solana.simulate_transaction(&transaction, &tx).await?;
+        Ok(transaction)
     }
 }
 
-pub fn create_app(
-    cluster: Cluster,
-    platform: Pubkey,
-    platform_signer: Keypair,
-    data_url: Url,
-) -> Router {
+pub fn create_app(state: impl Into<Arc<State>>) -> Router {
+    let state: Arc<State> = state.into();
     let cors = CorsLayer::new()
-        .allow_methods([Method::GET, Method::POST])
-        .allow_headers([header::CONTENT_TYPE])
+        .allow_methods([Method::GET, Method::POST, Method::DELETE])
+        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
         .allow_origin(Any);
 
-    Router::new()
+    // Routes for transactions paid for by the platform signer.
+    let sponsored = Router::new()
         .route(
             "/promo/mint/:mint_string/:message",
             get(get_app_id::handler).post(get_mint_promo_tx::handler),
@@ -92,6 +337,79 @@ pub fn create_app(
             "/promo/mint/:mint_string/:message/:memo",
             get(get_app_id::handler).post(get_mint_promo_tx::handler),
         )
+        .route(
+            "/promo/delegate/:mint_string/:delegate_string/:message",
+            get(get_app_id::handler).post(get_delegate_promo_tx::handler),
+        )
+        .route(
+            "/promo/delegate/:mint_string/:delegate_string/:message/:memo",
+            get(get_app_id::handler).post(get_delegate_promo_tx::handler),
+        )
+        .route(
+            "/checkout/:session_id/delegate",
+            get(get_app_id::handler).post(get_checkout_delegate_tx::handler),
+        )
+        .route(
+            "/checkout/:session_id/revoke",
+            get(get_app_id::handler).post(get_checkout_revoke_tx::handler),
+        )
+        .route(
+            "/promo/redeem/:mint_string/:level/:message",
+            get(get_app_id::handler).post(get_redeem_points_tx::handler),
+        )
+        .route(
+            "/promo/redeem/:mint_string/:level/:message/:memo",
+            get(get_app_id::handler).post(get_redeem_points_tx::handler),
+        )
+        .route(
+            "/promo/quote/:delegate_string",
+            post(post_promo_quote::handler),
+        )
+        .route_layer(middleware::from_fn(record_sponsorship))
+        .route_layer(middleware::from_fn(rate_limit));
+
+    Router::new()
+        .merge(sponsored)
+        .route(
+            "/promo/nonce/:group_seed/:message",
+            get(get_app_id::handler).post(get_create_group_nonce_tx::handler),
+        )
+        .route(
+            "/promo/nonce/:group_seed/:message/:memo",
+            get(get_app_id::handler).post(get_create_group_nonce_tx::handler),
+        )
+        .route(
+            "/promo/link/:mint_string/:message",
+            get(get_promo_link::handler),
+        )
+        .route(
+            "/promo/link/:mint_string/:message/:memo",
+            get(get_promo_link::handler),
+        )
+        .route(
+            "/promo/qr/:mint_string/:message",
+            get(get_promo_qr::handler),
+        )
+        .route(
+            "/promo/qr/:mint_string/:message/:memo",
+            get(get_promo_qr::handler),
+        )
+        .route(
+            "/promo/merchant/mint/:mint_string/:token_owner/:message",
+            get(get_app_id::handler).post(get_merchant_mint_promo_tx::handler),
+        )
+        .route(
+            "/promo/merchant/mint/:mint_string/:token_owner/:message/:memo",
+            get(get_app_id::handler).post(get_merchant_mint_promo_tx::handler),
+        )
+        .route(
+            "/promo/merchant/signed",
+            post(post_merchant_signed_tx::handler),
+        )
+        .route(
+            "/promo/signed/:signature/:message",
+            get(get_app_id::handler).post(get_signed_promo_tx::handler),
+        )
         .route(
             "/promo/group/:group_seed/:members/:lamports",
             get(get_app_id::handler).post(get_create_promo_group_tx::handler),
@@ -101,12 +419,56 @@ pub fn create_app(
             get(get_app_id::handler).post(get_create_promo_group_tx::handler),
         )
         .route(
-            "/promo/delegate/:mint_string/:delegate_string/:message",
-            get(get_app_id::handler).post(get_delegate_promo_tx::handler),
+            "/promo/group-profile/:group_string",
+            post(post_group_profile::handler),
         )
+        .route("/auth/nonce", post(post_auth_nonce::handler))
         .route(
-            "/promo/delegate/:mint_string/:delegate_string/:message/:memo",
-            get(get_app_id::handler).post(get_delegate_promo_tx::handler),
+            "/auth/session",
+            post(post_auth_session::handler).delete(delete_auth_session::handler),
+        )
+        .route("/status/storage", get(get_storage_status::handler))
+        .route("/metrics", get(get_metrics::handler))
+        .route("/promo/usage/:group_string", get(get_group_usage::handler))
+        .route(
+            "/promo/group-billing/:group_string",
+            post(post_group_billing::handler),
+        )
+        .route(
+            "/promo/webhooks/:group_string",
+            get(get_group_webhooks::handler).post(post_group_webhook::handler),
+        )
+        .route(
+            "/promo/webhooks/:group_string/:endpoint_id",
+            delete(delete_group_webhook::handler),
+        )
+        .route(
+            "/promo/webhook-deliveries/:group_string",
+            get(get_webhook_deliveries::handler),
+        )
+        .route(
+            "/promo/acceptance/:mint_string/:groups/:settlement/:settlement_lamports",
+            get(get_app_id::handler).post(get_create_promo_acceptance_tx::handler),
+        )
+        .route(
+            "/promo/acceptance/:mint_string/:groups/:settlement/:settlement_lamports/:memo",
+            get(get_app_id::handler).post(get_create_promo_acceptance_tx::handler),
+        )
+        .route(
+            "/promo/settlement-approval/:mint_string/:group_string/:max_settlement_lamports",
+            get(get_app_id::handler).post(get_create_settlement_approval_tx::handler),
+        )
+        .route(
+            "/promo/settlement-approval/:mint_string/:group_string/:max_settlement_lamports/:memo",
+            get(get_app_id::handler).post(get_create_settlement_approval_tx::handler),
+        )
+        .route(
+            "/promo/sponsorship-allowance/:group_string/:max_lamports",
+            get(get_app_id::handler).post(get_create_sponsorship_allowance_tx::handler),
+        )
+        .route(
+            "/promo/sponsorship-allowance/:group_string/:max_lamports/:memo",
+            get(get_app_id::handler).post(get_create_sponsorship_allowance_tx::handler),
         )
         .route(
             "/promo/burn-delegated/:token_account_string/:message",
@@ -116,6 +478,64 @@ pub fn create_app(
             "/promo/burn-delegated/:token_account_string/:message/:memo",
             get(get_app_id::handler).post(get_burn_delegated_promo_tx::handler),
         )
+        .route("/checkout", post(post_checkout_session::handler))
+        .route("/checkout/:session_id", get(get_checkout_session::handler))
+        .route(
+            "/checkout/:session_id/finalize",
+            post(get_checkout_burn_tx::handler),
+        )
+        .route(
+            "/promo/earn/:mint_string/:token_owner/:order_amount/:message",
+            get(get_app_id::handler).post(get_earn_points_tx::handler),
+        )
+        .route(
+            "/promo/earn/:mint_string/:token_owner/:order_amount/:message/:memo",
+            get(get_app_id::handler).post(get_earn_points_tx::handler),
+        )
+        .route(
+            "/promo/list/:token_account_string/:price/:message",
+            get(get_app_id::handler).post(get_list_promo_tx::handler),
+        )
+        .route(
+            "/promo/list/:token_account_string/:price/:message/:memo",
+            get(get_app_id::handler).post(get_list_promo_tx::handler),
+        )
+        .route(
+            "/promo/cancel-listing/:token_account_string/:price/:message",
+            get(get_app_id::handler).post(get_cancel_listing_tx::handler),
+        )
+        .route(
+            "/promo/cancel-listing/:token_account_string/:price/:message/:memo",
+            get(get_app_id::handler).post(get_cancel_listing_tx::handler),
+        )
+        .route(
+            "/promo/bid/:token_account_string/:price/:message",
+            get(get_app_id::handler).post(get_bid_promo_tx::handler),
+        )
+        .route(
+            "/promo/bid/:token_account_string/:price/:message/:memo",
+            get(get_app_id::handler).post(get_bid_promo_tx::handler),
+        )
+        .route(
+            "/promo/execute-sale/:token_account_string/:buyer/:price/:message",
+            get(get_app_id::handler).post(get_execute_sale_tx::handler),
+        )
+        .route(
+            "/promo/execute-sale/:token_account_string/:buyer/:price/:message/:memo",
+            get(get_app_id::handler).post(get_execute_sale_tx::handler),
+        )
+        .route(
+            "/promo/referral/:mint_string/:referrer/:message",
+            get(get_referral_link::handler),
+        )
+        .route(
+            "/promo/referral-reward/:mint_string/:reward_mint_string",
+            get(get_app_id::handler).post(get_create_referral_reward_tx::handler),
+        )
+        .route(
+            "/promo/referral-reward/:mint_string/:reward_mint_string/:memo",
+            get(get_app_id::handler).post(get_create_referral_reward_tx::handler),
+        )
         .route(
             "/promo/create/:payer/:group_seed",
             get(get_app_id::handler).post(get_create_promo_tx::handler),
@@ -132,12 +552,7 @@ pub fn create_app(
                 .concurrency_limit(1024)
                 .timeout(Duration::from_secs(30))
                 .layer(TraceLayer::new_for_http())
-                .layer(AddExtensionLayer::new(Arc::new(State::new(
-                    cluster,
-                    platform,
-                    platform_signer,
-                    data_url,
-                ))))
+                .layer(AddExtensionLayer::new(state))
                 .into_inner(),
         )
 }
@@ -160,17 +575,20 @@ async fn handle_error(error: BoxError) -> impl IntoResponse {
     )
 }
 
-// TODO: Move to integration tests - makes live calls to data and transaction apis.
+// The create promo tests make live calls to a local validator and store promo metadata in a
+// temporary directory, other tests use the fakes in `utils::fakes`.
 #[cfg(test)]
 pub mod test {
     use super::*;
     use anchor_lang::prelude::Pubkey;
     use axum::{
-        body::Body,
-        http::{Method, Request, StatusCode},
+        body::{Body, Bytes},
+        http::{Method, Request, Response, StatusCode},
     };
     use bpl_token_metadata::utils::find_group_address;
     use handlers::PayResponse;
+    use serde::de::DeserializeOwned;
+    use serde_json::json;
     use solana_sdk::{signature::Signer, transaction::Transaction};
     use std::{
         net::{SocketAddr, TcpListener},
@@ -179,84 +597,122 @@ pub mod test {
     use tokio::fs;
     use tower::ServiceExt;
     use tracing_subscriber::{fmt, prelude::*, EnvFilter};
-    use utils::{data::*, solana::*};
+    use utils::{
+        compute_budget::DEFAULT_COMPUTE_UNIT_LIMIT,
+        data::*,
+        fakes::{FakeDataSource, FakeSolanaRpc},
+        solana::*,
+    };
 
     const MESSAGE: &str = "This is a really long message that tells you to do something.";
     const PLATFORM: &str = "2R7GkXvQQS4iHptUvQMhDvRSNXL8tAuuASNvCYgz3GQW";
     const DATA_URL: &str = "https://shining-sailfish-15.hasura.app/v1/graphql/";
 
-    async fn fetch_mint(url: &String) -> Pubkey {
-        let client = reqwest::Client::new();
-        let result: serde_json::Value = client
-            .post(url)
-            .json(&serde_json::json!({ "query": FIRST_MINT_QUERY }))
-            .send()
-            .await
-            .unwrap()
-            .json()
-            .await
-            .unwrap();
+    /// Returns state using `rpc` and `data` instead of live clients.
+    fn create_fake_state(
+        platform_signer: Keypair,
+        rpc: Arc<FakeSolanaRpc>,
+        data: Arc<FakeDataSource>,
+    ) -> State {
+        State {
+            solana: rpc,
+            data,
+            ..State::new(
+                Cluster::Localnet,
+                Pubkey::from_str(PLATFORM).unwrap(),
+                platform_signer,
+                DataConfig::Graphql(Url::from_str(DATA_URL).unwrap()),
+                create_local_storage_config(),
+                ComputeBudgetConfig::default(),
+                None,
+            )
+        }
+    }
 
-        tracing::debug!(result = result.to_string());
+    /// Returns storage in a temporary directory so promos can be created without uploading.
+    fn create_local_storage_config() -> StorageConfig {
+        let dir = std::env::temp_dir().join("bpl-api-tx");
+        StorageConfig::Local {
+            base_url: Url::from_directory_path(&dir).unwrap(),
+            dir,
+        }
+    }
 
-        let mint_str = result
-            .as_object()
-            .unwrap()
-            .get("data")
-            .unwrap()
-            .as_object()
-            .unwrap()
-            .get("mint")
-            .unwrap()
-            .as_array()
-            .unwrap()
-            .get(0)
-            .unwrap()
-            .as_object()
-            .unwrap()
-            .get("id")
-            .unwrap()
-            .as_str()
-            .unwrap();
+    fn create_promo_object(group: &Pubkey, members: &[Pubkey]) -> PromoObject {
+        PromoObject {
+            group_object: GroupObject {
+                id: *group,
+                seed: Pubkey::new_unique(),
+                members: members.to_vec(),
+            },
+            accepted_groups: vec![],
+            referral_reward_object: None,
+            promo_mint_object: None,
+        }
+    }
 
-        Pubkey::from_str(mint_str).unwrap()
+    fn create_mint_object(mint: &Pubkey, group: &Pubkey, members: &[Pubkey]) -> MintObject {
+        MintObject {
+            id: *mint,
+            token_program: anchor_spl::token::ID,
+            promo_object: Some(create_promo_object(group, members)),
+            promo_metadata_object: None,
+        }
     }
 
-    async fn fetch_token_account(url: &String) -> Pubkey {
-        let client = reqwest::Client::new();
-        let result: serde_json::Value = client
-            .post(url)
-            .json(&serde_json::json!({ "query": FIRST_TOKEN_ACCOUNT_QUERY }))
-            .send()
-            .await
-            .unwrap()
-            .json()
+    /// Sends a request to `app`, with `body` as json and `token` as the bearer token if set, and
+    /// returns the response with its body collected.
+    async fn send(
+        app: &Router,
+        method: Method,
+        uri: &str,
+        token: Option<&str>,
+        body: Option<serde_json::Value>,
+    ) -> Response<Bytes> {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::HOST, "tx.bokoup.dev");
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let body = match body {
+            Some(body) => {
+                request = request.header(header::CONTENT_TYPE, "application/json");
+                Body::from(serde_json::to_vec(&body).unwrap())
+            }
+            None => Body::empty(),
+        };
+        let response = app
+            .clone()
+            .oneshot(request.body(body).unwrap())
             .await
             .unwrap();
+        let (parts, body) = response.into_parts();
+        Response::from_parts(parts, hyper::body::to_bytes(body).await.unwrap())
+    }
 
-        tracing::debug!(result = result.to_string());
-
-        let mint_str = result
-            .as_object()
-            .unwrap()
-            .get("data")
-            .unwrap()
-            .as_object()
-            .unwrap()
-            .get("tokenAccount")
-            .unwrap()
-            .as_array()
-            .unwrap()
-            .get(0)
-            .unwrap()
-            .as_object()
-            .unwrap()
-            .get("id")
-            .unwrap()
-            .as_str()
-            .unwrap();
+    fn parse<T: DeserializeOwned>(response: &Response<Bytes>) -> T {
+        serde_json::from_slice(response.body()).unwrap()
+    }
 
-        Pubkey::from_str(mint_str).unwrap()
+    /// Returns the transaction the fake rpc would produce for `instruction`, with the default
+    /// compute budget and no prioritization fees.
+    fn create_expected_transaction(
+        instruction: Instruction,
+        payer: &Pubkey,
+        rpc: &FakeSolanaRpc,
+    ) -> Transaction {
+        let mut tx = Transaction::new_with_payer(
+            &[
+                set_compute_unit_limit(DEFAULT_COMPUTE_UNIT_LIMIT),
+                set_compute_unit_price(0),
+                instruction,
+            ],
+            Some(payer),
+        );
+        tx.message.recent_blockhash = rpc.blockhash;
+        tx
     }
 
     #[tokio::test]
@@ -267,37 +723,25 @@ pub mod test {
             .with(EnvFilter::from_default_env())
             .init();
 
-        dotenv::dotenv().ok();
-        let platform_signer =
-            parse_string_to_keypair(&std::env::var("PLATFORM_SIGNER_KEYPAIR").unwrap());
-
-        // ok to be devnet, only pulling blockhash - will succeed even if localnet validator not running
-        let app = create_app(
-            Cluster::Devnet,
-            Pubkey::from_str(PLATFORM.into()).unwrap(),
-            platform_signer,
-            Url::from_str(DATA_URL).unwrap(),
-        );
+        let app = create_app(create_fake_state(
+            Keypair::new(),
+            Arc::new(FakeSolanaRpc::default()),
+            Arc::new(FakeDataSource::default()),
+        ));
         let mint = Pubkey::new_unique();
         let message = urlencoding::encode(MESSAGE);
-        let response = app
-            .oneshot(
-                Request::builder()
-                    .uri(format!(
-                        "/promo/mint/{}/{}",
-                        mint.to_string(),
-                        message.into_owned(),
-                    ))
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
+        let response = send(
+            &app,
+            Method::GET,
+            &format!("/promo/mint/{}/{}", mint, message),
+            None,
+            None,
+        )
+        .await;
 
         assert_eq!(response.status(), StatusCode::OK);
 
-        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-        let parsed_response: get_app_id::ResponseData = serde_json::from_slice(&body).unwrap();
+        let parsed_response: get_app_id::ResponseData = parse(&response);
 
         assert_eq!(
             parsed_response,
@@ -312,45 +756,20 @@ pub mod test {
     // to pay for transaction fees with no further merchant approval required.
     #[tokio::test]
     async fn test_get_mint_promo_tx() {
-        dotenv::dotenv().ok();
-        let platform_signer =
-            parse_string_to_keypair(&std::env::var("PLATFORM_SIGNER_KEYPAIR").unwrap());
-
-        // ok to be devnet, only pulling blockhash - will succeed even if localnet validator not running
+        let platform_signer = Keypair::new();
         let platform_signer_pubkey = platform_signer.pubkey();
-
-        let state = State::new(
-            Cluster::Devnet,
-            Pubkey::from_str(PLATFORM.into()).unwrap(),
-            Keypair::from_bytes(&platform_signer.to_bytes()).unwrap(),
-            Url::from_str(DATA_URL).unwrap(),
-        );
-        let app = create_app(
-            Cluster::Devnet,
-            Pubkey::from_str(PLATFORM.into()).unwrap(),
-            Keypair::from_bytes(&platform_signer.to_bytes()).unwrap(),
-            Url::from_str(DATA_URL).unwrap(),
-        );
-
+        let mint = Pubkey::new_unique();
+        let group = Pubkey::new_unique();
         let token_owner = Pubkey::new_unique();
 
-        let mint = fetch_mint(&state.data_url.to_string()).await;
-
-        let query =
-            serde_json::json!({ "query": MINT_QUERY, "variables": {"mint": mint.to_string()}});
-        let result: serde_json::Value = state
-            .solana
-            .client
-            .post(&state.data_url.to_string())
-            .json(&query)
-            .send()
-            .await
-            .unwrap()
-            .json()
-            .await
-            .unwrap();
-
-        let group = get_group_from_promo_group_query(&platform_signer_pubkey, &result).unwrap();
+        let rpc = Arc::new(FakeSolanaRpc::default());
+        let data = Arc::new(FakeDataSource::default());
+        data.insert_mint(create_mint_object(&mint, &group, &[platform_signer_pubkey]));
+        let app = create_app(create_fake_state(
+            Keypair::from_bytes(&platform_signer.to_bytes()).unwrap(),
+            rpc.clone(),
+            data,
+        ));
 
         let data = get_mint_promo_tx::Data {
             account: token_owner.to_string(),
@@ -359,34 +778,21 @@ pub mod test {
         let memo = "jingus";
         let memo_encoded = urlencoding::encode(memo);
 
-        let response = app
-            .oneshot(
-                Request::builder()
-                    .method(Method::POST)
-                    .uri(format!(
-                        "/promo/mint/{}/{}/{}",
-                        mint.to_string(),
-                        message.into_owned(),
-                        memo_encoded.into_owned()
-                    ))
-                    .header(header::CONTENT_TYPE, "application/json")
-                    .body(Body::from(serde_json::to_vec(&data).unwrap()))
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
+        let response = send(
+            &app,
+            Method::POST,
+            &format!("/promo/mint/{}/{}/{}", mint, message, memo_encoded),
+            None,
+            Some(serde_json::to_value(&data).unwrap()),
+        )
+        .await;
 
         assert_eq!(response.status(), StatusCode::OK);
 
-        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-        let parsed_response: PayResponse = serde_json::from_slice(&body).unwrap();
-        let txd: Transaction = bincode::deserialize(
-            &base64::decode::<String>(parsed_response.transaction.clone()).unwrap(),
-        )
-        .unwrap();
+        let parsed_response: PayResponse = parse(&response);
 
         let instruction = create_mint_promo_instruction(
-            Pubkey::new(platform_signer_pubkey.as_ref().clone()),
+            platform_signer_pubkey,
             group,
             token_owner,
             mint,
@@ -394,12 +800,13 @@ pub mod test {
         )
         .unwrap();
 
-        let mut tx = Transaction::new_with_payer(&[instruction], Some(&platform_signer_pubkey));
-        tx.try_partial_sign(&[&platform_signer], txd.message.recent_blockhash)
+        let mut tx = create_expected_transaction(instruction, &platform_signer_pubkey, &rpc);
+        tx.try_partial_sign(&[&platform_signer], rpc.blockhash)
             .unwrap();
         let serialized = bincode::serialize(&tx).unwrap();
         let transaction = base64::encode(serialized);
 
+        assert_eq!(*rpc.simulated.lock().unwrap(), vec![transaction.clone()]);
         assert_eq!(
             parsed_response,
             PayResponse {
@@ -407,50 +814,173 @@ pub mod test {
                 message: MESSAGE.to_string(),
             }
         );
+
+        // Requested compute unit limits are capped, since the platform signer pays for them.
+        let response = send(
+            &app,
+            Method::POST,
+            &format!("/promo/mint/{}/{}?computeUnitLimit=1400000", mint, message),
+            None,
+            Some(serde_json::to_value(&data).unwrap()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let parsed_response: PayResponse = parse(&response);
+        let tx: Transaction =
+            bincode::deserialize(&base64::decode(parsed_response.transaction).unwrap()).unwrap();
+        assert_eq!(
+            tx.message.instructions[0].data,
+            set_compute_unit_limit(DEFAULT_COMPUTE_UNIT_LIMIT).data
+        );
     }
 
-    // Platform signer is the payer, group member is the delegate
     #[tokio::test]
-    async fn test_get_delegate_promo_tx() {
-        dotenv::dotenv().ok();
-        let platform_signer =
-            parse_string_to_keypair(&std::env::var("PLATFORM_SIGNER_KEYPAIR").unwrap());
+    async fn test_get_mint_promo_tx_errors() {
+        let platform_signer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let group = Pubkey::new_unique();
 
-        let platform_signer_pubkey = platform_signer.pubkey();
+        let rpc = Arc::new(FakeSolanaRpc::default());
+        let data = Arc::new(FakeDataSource::default());
+        data.insert_mint(create_mint_object(
+            &mint,
+            &group,
+            &[platform_signer.pubkey()],
+        ));
+        let app = create_app(create_fake_state(platform_signer, rpc.clone(), data));
 
-        let delegate = parse_string_to_keypair(&std::env::var("GROUP_MEMBER_1_KEYPAIR").unwrap());
+        let post = |mint: &Pubkey| {
+            let uri = format!("/promo/mint/{}/{}", mint, urlencoding::encode(MESSAGE));
+            let body = json!({ "account": Pubkey::new_unique().to_string() });
+            let app = app.clone();
+            async move { send(&app, Method::POST, &uri, None, Some(body)).await }
+        };
 
-        let state = State::new(
-            Cluster::Localnet,
-            Pubkey::from_str(PLATFORM.into()).unwrap(),
-            Keypair::from_bytes(&platform_signer.to_bytes()).unwrap(),
-            Url::from_str(DATA_URL).unwrap(),
-        );
-        let app = create_app(
-            Cluster::Devnet,
-            Pubkey::from_str(PLATFORM.into()).unwrap(),
-            platform_signer,
-            Url::from_str(DATA_URL).unwrap(),
-        );
+        // Mint not found in the data api.
+        let response = post(&Pubkey::new_unique()).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        // Max mints reached.
+        *rpc.simulation_error.lock().unwrap() = Some((
+            json!({ "InstructionError": [2, { "Custom": 6000 }] }),
+            vec![],
+        ));
+        let response = post(&mint).await;
+        assert_eq!(response.status(), StatusCode::GONE);
+    }
 
+    #[tokio::test]
+    async fn test_get_mint_promo_tx_referrer() {
+        let platform_signer = Keypair::new();
+        let platform_signer_pubkey = platform_signer.pubkey();
+        let mint = Pubkey::new_unique();
+        let reward_mint = Pubkey::new_unique();
+        let unrewarded_mint = Pubkey::new_unique();
+        let group = Pubkey::new_unique();
         let token_owner = Pubkey::new_unique();
-        let mint = fetch_mint(&state.data_url.to_string()).await;
-
-        let query =
-            serde_json::json!({ "query": MINT_QUERY, "variables": {"mint": mint.to_string()}});
-        let result: serde_json::Value = state
-            .solana
-            .client
-            .post(&state.data_url.to_string())
-            .json(&query)
-            .send()
-            .await
+        let referrer = Pubkey::new_unique();
+
+        let rpc = Arc::new(FakeSolanaRpc::default());
+        let data = Arc::new(FakeDataSource::default());
+        let mut mint_object = create_mint_object(&mint, &group, &[platform_signer_pubkey]);
+        mint_object
+            .promo_object
+            .as_mut()
             .unwrap()
-            .json()
-            .await
-            .unwrap();
+            .referral_reward_object = Some(ReferralRewardObject { reward_mint });
+        data.insert_mint(mint_object.clone());
+        data.insert_mint(create_mint_object(
+            &unrewarded_mint,
+            &group,
+            &[platform_signer_pubkey],
+        ));
+        let token_2022_mint = Pubkey::new_unique();
+        data.insert_mint(MintObject {
+            id: token_2022_mint,
+            token_program: bpl_token_metadata::token_2022::ID,
+            ..mint_object
+        });
+        let app = create_app(create_fake_state(
+            Keypair::from_bytes(&platform_signer.to_bytes()).unwrap(),
+            rpc.clone(),
+            data,
+        ));
+
+        let post = |mint: &Pubkey, referrer: &str| {
+            let uri = format!(
+                "/promo/mint/{}/{}?referrer={}",
+                mint,
+                urlencoding::encode(MESSAGE),
+                referrer
+            );
+            let body = json!({ "account": token_owner.to_string() });
+            let app = app.clone();
+            async move { send(&app, Method::POST, &uri, None, Some(body)).await }
+        };
+        let get_transaction = |instruction: Instruction| {
+            let mut tx = create_expected_transaction(instruction, &platform_signer_pubkey, &rpc);
+            tx.try_partial_sign(&[&platform_signer], rpc.blockhash)
+                .unwrap();
+            base64::encode(bincode::serialize(&tx).unwrap())
+        };
+
+        // The referrer is recorded for promos with a referral reward.
+        let response = post(&mint, &referrer.to_string()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let instruction = create_mint_referred_promo_instruction(
+            platform_signer_pubkey,
+            group,
+            token_owner,
+            mint,
+            referrer,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            parse::<PayResponse>(&response).transaction,
+            get_transaction(instruction)
+        );
+
+        // Referrers of promos without a referral reward are ignored.
+        let response = post(&unrewarded_mint, &referrer.to_string()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let instruction = create_mint_promo_instruction(
+            platform_signer_pubkey,
+            group,
+            token_owner,
+            unrewarded_mint,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            parse::<PayResponse>(&response).transaction,
+            get_transaction(instruction)
+        );
+
+        let response = post(&mint, "not-a-pubkey").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = post(&token_2022_mint, &referrer.to_string()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
 
-        let group = get_group_from_promo_group_query(&delegate.pubkey(), &result).unwrap();
+    // Platform signer is the payer, group member is the delegate
+    #[tokio::test]
+    async fn test_get_delegate_promo_tx() {
+        let platform_signer = Keypair::new();
+        let platform_signer_pubkey = platform_signer.pubkey();
+        let delegate = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let group = Pubkey::new_unique();
+        let token_owner = Pubkey::new_unique();
+
+        let rpc = Arc::new(FakeSolanaRpc::default());
+        let data = Arc::new(FakeDataSource::default());
+        data.insert_mint(create_mint_object(&mint, &group, &[delegate.pubkey()]));
+        let app = create_app(create_fake_state(
+            Keypair::from_bytes(&platform_signer.to_bytes()).unwrap(),
+            rpc.clone(),
+            data,
+        ));
 
         let data = get_mint_promo_tx::Data {
             account: token_owner.to_string(),
@@ -460,33 +990,24 @@ pub mod test {
         let memo = r#"{"jingus": "amongus"}"#;
         let memo_encoded = urlencoding::encode(memo);
 
-        let response = app
-            .oneshot(
-                Request::builder()
-                    .method(Method::POST)
-                    .uri(format!(
-                        "/promo/delegate/{}/{}/{}/{}",
-                        mint.to_string(),
-                        delegate.pubkey().to_string(),
-                        message.into_owned(),
-                        memo_encoded.into_owned()
-                    ))
-                    .header(header::CONTENT_TYPE, "application/json")
-                    .body(Body::from(serde_json::to_vec(&data).unwrap()))
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
+        let response = send(
+            &app,
+            Method::POST,
+            &format!(
+                "/promo/delegate/{}/{}/{}/{}",
+                mint,
+                delegate.pubkey(),
+                message,
+                memo_encoded
+            ),
+            None,
+            Some(serde_json::to_value(&data).unwrap()),
+        )
+        .await;
 
         assert_eq!(response.status(), StatusCode::OK);
 
-        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-        let parsed_response: PayResponse = serde_json::from_slice(&body).unwrap();
-
-        let txd: Transaction = bincode::deserialize(
-            &base64::decode::<String>(parsed_response.transaction.clone()).unwrap(),
-        )
-        .unwrap();
+        let parsed_response: PayResponse = parse(&response);
 
         let instruction = create_delegate_promo_instruction(
             platform_signer_pubkey,
@@ -498,10 +1019,8 @@ pub mod test {
         )
         .unwrap();
 
-        let mut tx = Transaction::new_with_payer(&[instruction], Some(&platform_signer_pubkey));
-        let recent_blockhash = txd.message.recent_blockhash;
-
-        tx.try_partial_sign(&[&state.platform_signer], recent_blockhash)
+        let mut tx = create_expected_transaction(instruction, &platform_signer_pubkey, &rpc);
+        tx.try_partial_sign(&[&platform_signer], rpc.blockhash)
             .unwrap();
         let serialized = bincode::serialize(&tx).unwrap();
         let transaction = base64::encode(serialized);
@@ -517,44 +1036,26 @@ pub mod test {
 
     #[tokio::test]
     async fn test_get_burn_delegated_promo_tx() {
-        dotenv::dotenv().ok();
-        let platform_signer =
-            parse_string_to_keypair(&std::env::var("PLATFORM_SIGNER_KEYPAIR").unwrap());
-
-        let group_member =
-            parse_string_to_keypair(&std::env::var("GROUP_MEMBER_1_KEYPAIR").unwrap());
-
-        let state = State::new(
-            Cluster::Localnet,
-            Pubkey::from_str(PLATFORM.into()).unwrap(),
-            Keypair::from_bytes(&platform_signer.to_bytes()).unwrap(),
-            Url::from_str(DATA_URL).unwrap(),
-        );
-        // ok to be devnet, only pulling blockhash - will succeed even if localnet validator not running
-        let app = create_app(
-            Cluster::Devnet,
-            Pubkey::from_str(PLATFORM.into()).unwrap(),
-            platform_signer,
-            Url::from_str(DATA_URL).unwrap(),
-        );
-
-        let token_account = fetch_token_account(&state.data_url.to_string()).await;
-
-        let query = serde_json::json!({ "query": TOKEN_ACCOUNT_QUERY, "variables": {"id": token_account.to_string()}});
-        let result: serde_json::Value = state
-            .solana
-            .client
-            .post(&state.data_url.to_string())
-            .json(&query)
-            .send()
-            .await
-            .unwrap()
-            .json()
-            .await
-            .unwrap();
+        let group_member = Keypair::new();
+        let token_account = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let group = Pubkey::new_unique();
+        let token_owner = Pubkey::new_unique();
 
-        let (mint, token_owner, group) =
-            get_mint_owner_group_from_token_account_query(&group_member.pubkey(), &result).unwrap();
+        let rpc = Arc::new(FakeSolanaRpc::default());
+        let data = Arc::new(FakeDataSource::default());
+        data.insert_token_account(TokenAccountObject {
+            id: token_account,
+            owner: token_owner,
+            mint,
+            amount: 1,
+            token_program: anchor_spl::token::ID,
+            referral_object: None,
+            mint_object: Some(create_mint_object(&mint, &group, &[group_member.pubkey()])),
+        });
+        let state = create_fake_state(Keypair::new(), rpc.clone(), data);
+        let platform = state.platform;
+        let app = create_app(state);
 
         let data = get_mint_promo_tx::Data {
             account: group_member.pubkey().to_string(),
@@ -562,46 +1063,30 @@ pub mod test {
 
         let message = urlencoding::encode(MESSAGE);
 
-        let response = app
-            .oneshot(
-                Request::builder()
-                    .method(Method::POST)
-                    .uri(format!(
-                        "/promo/burn-delegated/{}/{}",
-                        token_account.to_string(),
-                        message.into_owned(),
-                    ))
-                    .header(header::CONTENT_TYPE, "application/json")
-                    .body(Body::from(serde_json::to_vec(&data).unwrap()))
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
+        let response = send(
+            &app,
+            Method::POST,
+            &format!("/promo/burn-delegated/{}/{}", token_account, message),
+            None,
+            Some(serde_json::to_value(&data).unwrap()),
+        )
+        .await;
 
         assert_eq!(response.status(), StatusCode::OK);
 
-        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-        let parsed_response: PayResponse = serde_json::from_slice(&body).unwrap();
-
-        let txd: Transaction = bincode::deserialize(
-            &base64::decode::<String>(parsed_response.transaction.clone()).unwrap(),
-        )
-        .unwrap();
+        let parsed_response: PayResponse = parse(&response);
 
         let instruction = create_burn_delegated_promo_instruction(
             group_member.pubkey(),
             group,
             token_owner,
             mint,
-            state.platform,
+            platform,
             None,
         )
         .unwrap();
 
-        let mut tx = Transaction::new_with_payer(&[instruction], Some(&group_member.pubkey()));
-        let recent_blockhash = txd.message.recent_blockhash;
-        tx.message.recent_blockhash = recent_blockhash;
-
+        let tx = create_expected_transaction(instruction, &group_member.pubkey(), &rpc);
         let serialized = bincode::serialize(&tx).unwrap();
         let transaction = base64::encode(serialized);
 
@@ -614,6 +1099,340 @@ pub mod test {
         );
     }
 
+    #[tokio::test]
+    async fn test_merchant_signed_tx() {
+        use handlers::SignedResponse;
+
+        let merchant = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let group = Pubkey::new_unique();
+        let token_owner = Pubkey::new_unique();
+
+        let rpc = Arc::new(FakeSolanaRpc::default());
+        let data = Arc::new(FakeDataSource::default());
+        data.insert_group(PromoGroupObject {
+            id: group,
+            owner: Pubkey::new_unique(),
+            members: vec![merchant.pubkey()],
+            group_profile_object: None,
+        });
+        let state = Arc::new(create_fake_state(Keypair::new(), rpc.clone(), data));
+        let app = create_app(state.clone());
+        let (merchant_token, _) = state.create_auth_session(merchant.pubkey(), String::new());
+        let (other_token, _) = state.create_auth_session(Pubkey::new_unique(), String::new());
+
+        let sign = |instruction: Instruction| {
+            let mut tx = Transaction::new_with_payer(&[instruction], Some(&merchant.pubkey()));
+            tx.try_partial_sign(&[&merchant], rpc.blockhash).unwrap();
+            json!({
+                "transaction": base64::encode(bincode::serialize(&tx).unwrap()),
+                "message": MESSAGE,
+            })
+        };
+        let mint_tx = sign(
+            create_mint_promo_instruction(merchant.pubkey(), group, token_owner, mint, None)
+                .unwrap(),
+        );
+
+        // Only signed in members of the minting group can store transactions.
+        let response = send(
+            &app,
+            Method::POST,
+            "/promo/merchant/signed",
+            None,
+            Some(mint_tx.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = send(
+            &app,
+            Method::POST,
+            "/promo/merchant/signed",
+            Some(&other_token),
+            Some(mint_tx.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // Other promo transactions aren't stored.
+        let delegate_tx = sign(
+            create_delegate_promo_instruction(
+                merchant.pubkey(),
+                merchant.pubkey(),
+                group,
+                token_owner,
+                mint,
+                None,
+            )
+            .unwrap(),
+        );
+        let response = send(
+            &app,
+            Method::POST,
+            "/promo/merchant/signed",
+            Some(&merchant_token),
+            Some(delegate_tx),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // Customers sign stored transactions as served, so they can't do anything else.
+        let instruction =
+            create_mint_promo_instruction(merchant.pubkey(), group, token_owner, mint, None)
+                .unwrap();
+        let mut tx = Transaction::new_with_payer(
+            &[
+                instruction.clone(),
+                system_instruction::transfer(&token_owner, &merchant.pubkey(), 1_000_000_000),
+            ],
+            Some(&merchant.pubkey()),
+        );
+        tx.try_partial_sign(&[&merchant], rpc.blockhash).unwrap();
+        let transfer_tx = json!({
+            "transaction": base64::encode(bincode::serialize(&tx).unwrap()),
+            "message": MESSAGE,
+        });
+        let mut wrong_account = instruction;
+        wrong_account.accounts[7].pubkey = Pubkey::new_unique();
+        for tx in [transfer_tx, sign(wrong_account)] {
+            let response = send(
+                &app,
+                Method::POST,
+                "/promo/merchant/signed",
+                Some(&merchant_token),
+                Some(tx),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+
+        let response = send(
+            &app,
+            Method::POST,
+            "/promo/merchant/signed",
+            Some(&merchant_token),
+            Some(mint_tx.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let signed: SignedResponse = parse(&response);
+
+        let uri = format!(
+            "/promo/signed/{}/{}",
+            signed.signature,
+            urlencoding::encode(MESSAGE)
+        );
+        let response = send(
+            &app,
+            Method::POST,
+            &uri,
+            None,
+            Some(json!({ "account": Pubkey::new_unique().to_string() })),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = send(
+            &app,
+            Method::POST,
+            &uri,
+            None,
+            Some(json!({ "account": token_owner.to_string() })),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(parse::<serde_json::Value>(&response), mint_tx);
+    }
+
+    #[tokio::test]
+    async fn test_promo_acceptance() {
+        use bpl_token_metadata::state::Settlement;
+        use handlers::get_create_promo_acceptance_tx::parse_settlement;
+
+        assert_eq!(
+            parse_settlement("issuer").unwrap(),
+            Settlement::IssuerPaysRedeemer
+        );
+        assert_eq!(
+            parse_settlement("issuerPaysRedeemer").unwrap(),
+            Settlement::IssuerPaysRedeemer
+        );
+        assert_eq!(
+            parse_settlement("redeemer").unwrap(),
+            Settlement::RedeemerPaysIssuer
+        );
+        assert_eq!(
+            parse_settlement("redeemerPaysIssuer").unwrap(),
+            Settlement::RedeemerPaysIssuer
+        );
+        assert!(matches!(
+            parse_settlement("both"),
+            Err(error::AppError::InvalidSettlement(_))
+        ));
+
+        let owner = Pubkey::new_unique();
+        let accepting_owner = Pubkey::new_unique();
+        let accepting_member = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let group = Pubkey::new_unique();
+        let accepting_group = Pubkey::new_unique();
+        let other_group = Pubkey::new_unique();
+        let token_owner = Pubkey::new_unique();
+        let token_account = Pubkey::new_unique();
+
+        let mut mint_object = create_mint_object(&mint, &group, &[owner]);
+        mint_object
+            .promo_object
+            .as_mut()
+            .unwrap()
+            .accepted_groups
+            .push(AcceptedGroupObject {
+                group_object: GroupObject {
+                    id: accepting_group,
+                    seed: Pubkey::new_unique(),
+                    members: vec![accepting_member],
+                },
+            });
+        let rpc = Arc::new(FakeSolanaRpc::default());
+        let data = Arc::new(FakeDataSource::default());
+        data.insert_mint(mint_object.clone());
+        data.insert_group(PromoGroupObject {
+            id: accepting_group,
+            owner: accepting_owner,
+            members: vec![accepting_owner, accepting_member],
+            group_profile_object: None,
+        });
+        data.insert_token_account(TokenAccountObject {
+            id: token_account,
+            owner: token_owner,
+            mint,
+            amount: 1,
+            token_program: anchor_spl::token::ID,
+            referral_object: None,
+            mint_object: Some(mint_object),
+        });
+        let state = create_fake_state(Keypair::new(), rpc.clone(), data);
+        let platform = state.platform;
+        let app = create_app(state);
+
+        let post_acceptance = |account: &Pubkey, settlement: &str, memo: &str| {
+            let uri = format!(
+                "/promo/acceptance/{}/{},{}/{}/1000000{}",
+                mint, accepting_group, other_group, settlement, memo
+            );
+            let body = json!({ "account": account.to_string() });
+            let app = app.clone();
+            async move { send(&app, Method::POST, &uri, None, Some(body)).await }
+        };
+
+        // Only members of the group that owns the promo can name accepting groups.
+        let response = post_acceptance(&accepting_member, "issuer", "").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = post_acceptance(&owner, "both", "").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        for (settlement, expected_settlement, memo) in [
+            ("issuer", Settlement::IssuerPaysRedeemer, None),
+            ("redeemer", Settlement::RedeemerPaysIssuer, Some("jingus")),
+        ] {
+            let memo_path = memo.map_or(String::new(), |memo| format!("/{}", memo));
+            let response = post_acceptance(&owner, settlement, &memo_path).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let parsed_response: PayResponse = parse(&response);
+
+            let instruction = create_create_promo_acceptance_instruction(
+                owner,
+                group,
+                mint,
+                vec![accepting_group, other_group],
+                expected_settlement,
+                1_000_000,
+                memo.map(str::to_string),
+            )
+            .unwrap();
+            let tx = create_expected_transaction(instruction, &owner, &rpc);
+            assert_eq!(
+                parsed_response,
+                PayResponse {
+                    transaction: base64::encode(bincode::serialize(&tx).unwrap()),
+                    message: "Create promo acceptance".to_string(),
+                }
+            );
+        }
+
+        // Only the owner of an accepting group can approve what it pays the issuing group.
+        let approval_uri = format!(
+            "/promo/settlement-approval/{}/{}/2000000",
+            mint, accepting_group
+        );
+        let approval_body = |account: &Pubkey| json!({ "account": account.to_string() });
+        let response = send(
+            &app,
+            Method::POST,
+            &approval_uri,
+            None,
+            Some(approval_body(&accepting_member)),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = send(
+            &app,
+            Method::POST,
+            &approval_uri,
+            None,
+            Some(approval_body(&accepting_owner)),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let parsed_response: PayResponse = parse(&response);
+        let instruction = create_create_settlement_approval_instruction(
+            accepting_owner,
+            accepting_group,
+            mint,
+            2_000_000,
+            None,
+        )
+        .unwrap();
+        let tx = create_expected_transaction(instruction, &accepting_owner, &rpc);
+        assert_eq!(
+            parsed_response.transaction,
+            base64::encode(bincode::serialize(&tx).unwrap())
+        );
+
+        // Members of accepting groups burn with the issuing group so lamports can be settled.
+        let burn_uri = format!(
+            "/promo/burn-delegated/{}/{}",
+            token_account,
+            urlencoding::encode(MESSAGE)
+        );
+        let burn_body = json!({ "account": accepting_member.to_string() });
+        let response = send(&app, Method::POST, &burn_uri, None, Some(burn_body.clone())).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let parsed_response: PayResponse = parse(&response);
+        let instruction = create_burn_accepted_promo_instruction(
+            accepting_member,
+            accepting_group,
+            group,
+            token_owner,
+            mint,
+            platform,
+            None,
+        )
+        .unwrap();
+        let tx = create_expected_transaction(instruction, &accepting_member, &rpc);
+        assert_eq!(
+            parsed_response.transaction,
+            base64::encode(bincode::serialize(&tx).unwrap())
+        );
+
+        // The paying group can't cover the settlement.
+        *rpc.simulation_error.lock().unwrap() = Some((
+            json!({ "InstructionError": [2, { "Custom": 6003 }] }),
+            vec![],
+        ));
+        let response = send(&app, Method::POST, &burn_uri, None, Some(burn_body)).await;
+        assert_eq!(response.status(), StatusCode::PAYMENT_REQUIRED);
+    }
+
     #[tokio::test]
     async fn test_create_buyxproduct_promo() {
         dotenv::dotenv().ok();
@@ -631,21 +1450,14 @@ pub mod test {
             assert!(false, "localnet validator not started")
         }
 
-        let state = State::new(
-            Cluster::Localnet,
-            Pubkey::from_str(PLATFORM.into()).unwrap(),
-            Keypair::from_bytes(&platform_signer.to_bytes()).unwrap(),
-            Url::from_str(DATA_URL).unwrap(),
-        );
+        let solana = Solana::new(Cluster::Localnet);
 
-        state
-            .solana
+        solana
             .request_airdrop(promo_owner.pubkey().to_string(), 1_000_000_000)
             .await
             .unwrap();
 
-        state
-            .solana
+        solana
             .request_airdrop(group.to_string(), 1_000_000_000)
             .await
             .unwrap();
@@ -653,18 +1465,22 @@ pub mod test {
         let listener = TcpListener::bind("0.0.0.0:0".parse::<SocketAddr>().unwrap()).unwrap();
         let addr = listener.local_addr().unwrap();
 
+        let state = Arc::new(State::new(
+            Cluster::Localnet,
+            Pubkey::from_str(PLATFORM).unwrap(),
+            platform_signer,
+            DataConfig::Graphql(Url::from_str(DATA_URL).unwrap()),
+            create_local_storage_config(),
+            ComputeBudgetConfig::default(),
+            None,
+        ));
+        // Promos can only be created by members of the group.
+        let (token, _) = state.create_auth_session(promo_owner.pubkey(), String::new());
+
         tokio::spawn(async move {
             axum::Server::from_tcp(listener)
                 .unwrap()
-                .serve(
-                    create_app(
-                        Cluster::Localnet,
-                        Pubkey::from_str(PLATFORM.into()).unwrap(),
-                        platform_signer,
-                        Url::from_str(DATA_URL).unwrap(),
-                    )
-                    .into_make_service(),
-                )
+                .serve(create_app(state).into_make_service())
                 .await
                 .unwrap();
         });
@@ -739,6 +1555,7 @@ pub mod test {
                 group_seed.pubkey().to_string(),
                 memo,
             ))
+            .bearer_auth(&token)
             .multipart(form)
             .send()
             .await
@@ -756,7 +1573,7 @@ pub mod test {
 
         let serialized = bincode::serialize(&txd).unwrap();
         let tx_str = base64::encode(serialized);
-        let response = state.solana.post_transaction_test(&tx_str).await.unwrap();
+        let response = solana.post_transaction_test(&tx_str).await.unwrap();
 
         assert!(&response
             .as_object()
@@ -784,21 +1601,14 @@ pub mod test {
             assert!(false, "localnet validator not started")
         }
 
-        let state = State::new(
-            Cluster::Localnet,
-            Pubkey::from_str(PLATFORM.into()).unwrap(),
-            Keypair::from_bytes(&platform_signer.to_bytes()).unwrap(),
-            Url::from_str(DATA_URL).unwrap(),
-        );
+        let solana = Solana::new(Cluster::Localnet);
 
-        state
-            .solana
+        solana
             .request_airdrop(promo_owner.pubkey().to_string(), 1_000_000_000)
             .await
             .unwrap();
 
-        state
-            .solana
+        solana
             .request_airdrop(group.to_string(), 1_000_000_000)
             .await
             .unwrap();
@@ -806,18 +1616,22 @@ pub mod test {
         let listener = TcpListener::bind("0.0.0.0:0".parse::<SocketAddr>().unwrap()).unwrap();
         let addr = listener.local_addr().unwrap();
 
+        let state = Arc::new(State::new(
+            Cluster::Localnet,
+            Pubkey::from_str(PLATFORM).unwrap(),
+            platform_signer,
+            DataConfig::Graphql(Url::from_str(DATA_URL).unwrap()),
+            create_local_storage_config(),
+            ComputeBudgetConfig::default(),
+            None,
+        ));
+        // Promos can only be created by members of the group.
+        let (token, _) = state.create_auth_session(promo_owner.pubkey(), String::new());
+
         tokio::spawn(async move {
             axum::Server::from_tcp(listener)
                 .unwrap()
-                .serve(
-                    create_app(
-                        Cluster::Localnet,
-                        Pubkey::from_str(PLATFORM.into()).unwrap(),
-                        platform_signer,
-                        Url::from_str(DATA_URL).unwrap(),
-                    )
-                    .into_make_service(),
-                )
+                .serve(create_app(state).into_make_service())
                 .await
                 .unwrap();
         });
@@ -888,6 +1702,7 @@ pub mod test {
                 group_seed.pubkey().to_string(),
                 memo,
             ))
+            .bearer_auth(&token)
             .multipart(form)
             .send()
             .await
@@ -905,7 +1720,7 @@ pub mod test {
 
         let serialized = bincode::serialize(&txd).unwrap();
         let tx_str = base64::encode(serialized);
-        let response = state.solana.post_transaction_test(&tx_str).await.unwrap();
+        let response = solana.post_transaction_test(&tx_str).await.unwrap();
 
         assert!(&response
             .as_object()
@@ -915,4 +1730,2381 @@ pub mod test {
             .as_str()
             .is_some());
     }
+
+    #[test]
+    fn test_get_loyalty_args() {
+        use bpl_promo_metadata::PromoMetadata;
+        use bpl_token_metadata::state::RewardLevel;
+        use handlers::get_create_promo_tx::get_loyalty_args;
+
+        let metadata = PromoMetadata::from_value(serde_json::json!({
+            "name": "loyalty",
+            "symbol": "LOYAL",
+            "attributes": [
+                { "trait_type": "promoType", "value": "loyalty" },
+                { "trait_type": "earnRateLevel0", "value": 1 },
+                { "trait_type": "pointsLevel0", "value": 10000 },
+                { "trait_type": "rewardLevel0", "value": 500 },
+                { "trait_type": "pointsLevel1", "value": 25000 },
+                { "trait_type": "rewardLevel1", "value": 1500 },
+            ],
+        }))
+        .unwrap();
+
+        let (earn_rate, levels) = get_loyalty_args(&metadata).unwrap();
+        assert_eq!(earn_rate, 1);
+        assert_eq!(
+            levels,
+            vec![
+                RewardLevel {
+                    points: 10000,
+                    reward: 500
+                },
+                RewardLevel {
+                    points: 25000,
+                    reward: 1500
+                },
+            ]
+        );
+
+        let metadata = PromoMetadata::from_value(serde_json::json!({
+            "name": "buyXCurrency",
+            "symbol": "CURR",
+            "attributes": [
+                { "trait_type": "promoType", "value": "buyXCurrencyGetYPercent" },
+                { "trait_type": "buyXCurrency", "value": 10000 },
+                { "trait_type": "getYPercent", "value": 10 },
+            ],
+        }))
+        .unwrap();
+        assert!(get_loyalty_args(&metadata).is_none());
+    }
+
+    #[test]
+    fn test_get_discount() {
+        use bpl_promo_metadata::{LoyaltyLevel, PromoType};
+        use utils::quote::{get_discount, Discount, LineItem, Order};
+
+        let order = Order {
+            order_id: None,
+            items: vec![
+                LineItem {
+                    product_id: "0E9DCHTY6P7M2".to_string(),
+                    quantity: 3,
+                    price: 450,
+                },
+                LineItem {
+                    product_id: "JAF3ZKCCXKF9P".to_string(),
+                    quantity: 1,
+                    price: 1200,
+                },
+            ],
+            total: 2550,
+        };
+
+        let product = |buy_x_product| PromoType::BuyXProductGetYFree {
+            product_id: "0E9DCHTY6P7M2".to_string(),
+            buy_x_product,
+            get_y_product: 1,
+        };
+        assert_eq!(
+            get_discount(&product(3), &order, 1),
+            Some(Discount {
+                amount: 450,
+                level: None
+            })
+        );
+        assert_eq!(get_discount(&product(4), &order, 1), None);
+        assert_eq!(get_discount(&product(3), &order, 0), None);
+
+        let currency = |buy_x_currency| PromoType::BuyXCurrencyGetYPercent {
+            buy_x_currency,
+            get_y_percent: 10,
+        };
+        assert_eq!(
+            get_discount(&currency(2000), &order, 1),
+            Some(Discount {
+                amount: 255,
+                level: None
+            })
+        );
+        assert_eq!(get_discount(&currency(3000), &order, 1), None);
+
+        let loyalty = PromoType::Loyalty {
+            earn_rate: 1,
+            levels: vec![
+                LoyaltyLevel {
+                    points: 10000,
+                    reward: 500,
+                },
+                LoyaltyLevel {
+                    points: 25000,
+                    reward: 1500,
+                },
+            ],
+        };
+        assert_eq!(
+            get_discount(&loyalty, &order, 30000),
+            Some(Discount {
+                amount: 1500,
+                level: Some(1)
+            })
+        );
+        assert_eq!(
+            get_discount(&loyalty, &order, 12000),
+            Some(Discount {
+                amount: 500,
+                level: Some(0)
+            })
+        );
+        assert_eq!(get_discount(&loyalty, &order, 9000), None);
+    }
+
+    #[tokio::test]
+    async fn test_post_promo_quote() {
+        use bpl_promo_metadata::{PromoAttributes, PromoType};
+        use utils::quote::{LineItem, Order};
+
+        let platform_signer = Keypair::new();
+        let platform_signer_pubkey = platform_signer.pubkey();
+        let delegate = Keypair::new();
+        let group = Pubkey::new_unique();
+        let token_owner = Pubkey::new_unique();
+
+        let rpc = Arc::new(FakeSolanaRpc::default());
+        let data = Arc::new(FakeDataSource::default());
+        let insert_promo = |promo_type: PromoType, members: &[Pubkey]| {
+            let mint = Pubkey::new_unique();
+            let mut mint_object = create_mint_object(&mint, &group, members);
+            mint_object.promo_metadata_object = Some(PromoMetadataObject {
+                attributes: PromoAttributes {
+                    promo_type,
+                    max_mint: None,
+                    max_burn: None,
+                    other: vec![],
+                },
+            });
+            let token_account = Pubkey::new_unique();
+            data.insert_token_account(TokenAccountObject {
+                id: token_account,
+                owner: token_owner,
+                mint,
+                amount: 1,
+                token_program: anchor_spl::token::ID,
+                referral_object: None,
+                mint_object: Some(mint_object),
+            });
+            (mint, token_account)
+        };
+
+        let currency = |buy_x_currency| PromoType::BuyXCurrencyGetYPercent {
+            buy_x_currency,
+            get_y_percent: 10,
+        };
+        let (currency_mint, currency_token_account) =
+            insert_promo(currency(1000), &[delegate.pubkey()]);
+        let (product_mint, product_token_account) = insert_promo(
+            PromoType::BuyXProductGetYFree {
+                product_id: "0E9DCHTY6P7M2".to_string(),
+                buy_x_product: 2,
+                get_y_product: 1,
+            },
+            &[delegate.pubkey()],
+        );
+        // Order total below threshold.
+        insert_promo(currency(10000), &[delegate.pubkey()]);
+        // Delegate not a member of the group that owns the promo.
+        insert_promo(currency(1000), &[Pubkey::new_unique()]);
+
+        let app = create_app(create_fake_state(
+            Keypair::from_bytes(&platform_signer.to_bytes()).unwrap(),
+            rpc.clone(),
+            data,
+        ));
+
+        let order = Order {
+            order_id: Some("order-1".to_string()),
+            items: vec![LineItem {
+                product_id: "0E9DCHTY6P7M2".to_string(),
+                quantity: 2,
+                price: 1500,
+            }],
+            total: 5000,
+        };
+        let data = post_promo_quote::Data {
+            account: token_owner.to_string(),
+            order,
+        };
+
+        let response = send(
+            &app,
+            Method::POST,
+            &format!("/promo/quote/{}", delegate.pubkey()),
+            None,
+            Some(serde_json::to_value(&data).unwrap()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let parsed_response: QuoteResponse = parse(&response);
+
+        let get_transaction = |mint| {
+            let instruction = create_delegate_promo_instruction(
+                platform_signer_pubkey,
+                delegate.pubkey(),
+                group,
+                token_owner,
+                mint,
+                Some(json!({ "orderId": "order-1" }).to_string()),
+            )
+            .unwrap();
+            let mut tx = create_expected_transaction(instruction, &platform_signer_pubkey, &rpc);
+            tx.try_partial_sign(&[&platform_signer], rpc.blockhash)
+                .unwrap();
+            base64::encode(bincode::serialize(&tx).unwrap())
+        };
+
+        assert_eq!(
+            parsed_response,
+            QuoteResponse {
+                discounts: vec![
+                    QuoteDiscount {
+                        mint: product_mint.to_string(),
+                        token_account: product_token_account.to_string(),
+                        promo_type: "buyXProductGetYFree".to_string(),
+                        amount: 1500,
+                        level: None,
+                        transaction: Some(get_transaction(product_mint)),
+                    },
+                    QuoteDiscount {
+                        mint: currency_mint.to_string(),
+                        token_account: currency_token_account.to_string(),
+                        promo_type: "buyXCurrencyGetYPercent".to_string(),
+                        amount: 500,
+                        level: None,
+                        transaction: Some(get_transaction(currency_mint)),
+                    },
+                ]
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_checkout_session() {
+        use bpl_token_metadata::utils::find_associated_token_address;
+        use handlers::CheckoutSessionResponse;
+        use std::time::SystemTime;
+        use utils::{
+            checkout::{CheckoutSession, CheckoutStatus},
+            solana::SignatureStatus,
+        };
+
+        let platform_signer = Keypair::new();
+        let platform_signer_pubkey = platform_signer.pubkey();
+        let delegate = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let group = Pubkey::new_unique();
+        let token_owner = Pubkey::new_unique();
+
+        let rpc = Arc::new(FakeSolanaRpc::default());
+        let data = Arc::new(FakeDataSource::default());
+        let mint_object = create_mint_object(&mint, &group, &[delegate.pubkey()]);
+        data.insert_mint(mint_object.clone());
+        data.insert_token_account(TokenAccountObject {
+            id: find_associated_token_address(&token_owner, &mint),
+            owner: token_owner,
+            mint,
+            amount: 1,
+            token_program: anchor_spl::token::ID,
+            referral_object: None,
+            mint_object: Some(mint_object),
+        });
+        let state = create_fake_state(
+            Keypair::from_bytes(&platform_signer.to_bytes()).unwrap(),
+            rpc.clone(),
+            data,
+        );
+        let platform = state.platform;
+
+        // Session abandoned after the customer delegated their token.
+        let mut abandoned = CheckoutSession::new(
+            "order-0".to_string(),
+            mint,
+            delegate.pubkey(),
+            MESSAGE.to_string(),
+        );
+        abandoned.status = CheckoutStatus::Delegated;
+        abandoned.token_owner = Some(token_owner);
+        abandoned.group = Some(group);
+        abandoned.delegated = true;
+        abandoned.expires_at = SystemTime::now();
+        state.update_checkout_session(abandoned.clone());
+
+        let app = create_app(state);
+
+        let post = |uri: String, body: serde_json::Value| {
+            let app = app.clone();
+            async move { send(&app, Method::POST, &uri, None, Some(body)).await }
+        };
+        let get_session = |id: &str| {
+            let uri = format!("/checkout/{}", id);
+            let app = app.clone();
+            async move {
+                let response = send(&app, Method::GET, &uri, None, None).await;
+                assert_eq!(response.status(), StatusCode::OK);
+                parse::<CheckoutSessionResponse>(&response)
+            }
+        };
+        let memo = json!({ "orderId": "order-1" }).to_string();
+
+        let response = post(
+            "/checkout".to_string(),
+            json!({
+                "orderId": "order-1",
+                "mint": mint.to_string(),
+                "delegate": delegate.pubkey().to_string(),
+                "message": MESSAGE,
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let session: CheckoutSessionResponse = parse(&response);
+        assert_eq!(session.status, CheckoutStatus::Open);
+        assert_eq!(
+            session.solana_pay_url,
+            format!(
+                "solana:{}",
+                urlencoding::encode(&format!(
+                    "https://tx.bokoup.dev/checkout/{}/delegate",
+                    session.id
+                ))
+            )
+        );
+        assert!(session.revoke_solana_pay_url.is_none());
+
+        // Can't be finalized before the customer has delegated their token.
+        let finalize_body = json!({ "account": delegate.pubkey().to_string() });
+        let response = post(
+            format!("/checkout/{}/finalize", session.id),
+            finalize_body.clone(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let response = post(
+            format!("/checkout/{}/delegate", session.id),
+            json!({ "account": token_owner.to_string() }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let parsed_response: PayResponse = parse(&response);
+
+        let instruction = create_delegate_promo_instruction(
+            platform_signer_pubkey,
+            delegate.pubkey(),
+            group,
+            token_owner,
+            mint,
+            Some(memo.clone()),
+        )
+        .unwrap();
+        let mut tx = create_expected_transaction(instruction, &platform_signer_pubkey, &rpc);
+        tx.try_partial_sign(&[&platform_signer], rpc.blockhash)
+            .unwrap();
+        assert_eq!(
+            parsed_response,
+            PayResponse {
+                transaction: base64::encode(bincode::serialize(&tx).unwrap()),
+                message: MESSAGE.to_owned(),
+            }
+        );
+
+        let pending = get_session(&session.id).await;
+        assert_eq!(pending.status, CheckoutStatus::Pending);
+        assert_eq!(pending.token_owner, Some(token_owner.to_string()));
+
+        rpc.signature_statuses
+            .lock()
+            .unwrap()
+            .insert(tx.signatures[0].to_string(), SignatureStatus::Confirmed);
+        assert_eq!(
+            get_session(&session.id).await.status,
+            CheckoutStatus::Delegated
+        );
+
+        // Only the delegate can finalize.
+        let response = post(
+            format!("/checkout/{}/finalize", session.id),
+            json!({ "account": token_owner.to_string() }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = post(format!("/checkout/{}/finalize", session.id), finalize_body).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let parsed_response: PayResponse = parse(&response);
+
+        let instruction = create_burn_delegated_promo_instruction(
+            delegate.pubkey(),
+            group,
+            token_owner,
+            mint,
+            platform,
+            Some(memo),
+        )
+        .unwrap();
+        let tx = create_expected_transaction(instruction, &delegate.pubkey(), &rpc);
+        assert_eq!(
+            parsed_response,
+            PayResponse {
+                transaction: base64::encode(bincode::serialize(&tx).unwrap()),
+                message: "Redeem promo for order order-1".to_string(),
+            }
+        );
+        assert_eq!(
+            get_session(&session.id).await.status,
+            CheckoutStatus::Finalized
+        );
+
+        // The abandoned session has expired and the customer can revoke the delegation.
+        let expired = get_session(&abandoned.id).await;
+        assert_eq!(expired.status, CheckoutStatus::Expired);
+        assert!(expired.revoke_solana_pay_url.is_some());
+
+        let response = post(
+            format!("/checkout/{}/revoke", abandoned.id),
+            json!({ "account": token_owner.to_string() }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let parsed_response: PayResponse = parse(&response);
+
+        let instruction = create_revoke_promo_instruction(token_owner, mint).unwrap();
+        let mut tx = create_expected_transaction(instruction, &platform_signer_pubkey, &rpc);
+        tx.try_partial_sign(&[&platform_signer], rpc.blockhash)
+            .unwrap();
+        assert_eq!(
+            parsed_response,
+            PayResponse {
+                transaction: base64::encode(bincode::serialize(&tx).unwrap()),
+                message: "Revoke promo delegation for order order-0".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_token_2022_promo() {
+        use bpl_token_metadata::token_2022;
+        use handlers::{get_create_promo_tx::get_mint_extensions, CreatePromoQuery};
+
+        let query: CreatePromoQuery = serde_json::from_value(
+            serde_json::json!({ "token2022": true, "nonTransferable": true }),
+        )
+        .unwrap();
+        let extensions = get_mint_extensions(&query).unwrap();
+        assert!(extensions.non_transferable);
+        assert!(extensions.permanent_delegate);
+        assert!(!extensions.metadata_pointer);
+        assert!(get_mint_extensions(&CreatePromoQuery::default()).is_none());
+
+        let mint: MintObject = serde_json::from_value(serde_json::json!({
+            "id": Pubkey::new_unique().to_string(),
+            "tokenProgram": token_2022::ID.to_string(),
+            "promoObject": null,
+        }))
+        .unwrap();
+        assert!(mint.is_token_2022());
+        assert!(matches!(
+            mint.promo(),
+            Err(error::AppError::PromoNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_data_query_results() {
+        use error::AppError;
+
+        let mint = Pubkey::new_unique();
+        let group = Pubkey::new_unique();
+        let accepting_group = Pubkey::new_unique();
+        let member = Pubkey::new_unique();
+        let accepting_member = Pubkey::new_unique();
+        let response: GraphqlResponse<MintQueryData> = serde_json::from_value(json!({
+            "data": { "mintByPk": {
+                "id": mint.to_string(),
+                "tokenProgram": anchor_spl::token::ID.to_string(),
+                "promoObject": {
+                    "groupObject": {
+                        "id": group.to_string(),
+                        "seed": Pubkey::new_unique().to_string(),
+                        "members": [member.to_string()],
+                    },
+                    "acceptedGroups": [{ "groupObject": {
+                        "id": accepting_group.to_string(),
+                        "seed": Pubkey::new_unique().to_string(),
+                        "members": [accepting_member.to_string()],
+                    } }],
+                    "referralRewardObject": null,
+                    "promoMintObject": null,
+                },
+            } }
+        }))
+        .unwrap();
+        let mint_object = response.data.unwrap().mint_by_pk.unwrap();
+        assert!(!mint_object.is_token_2022());
+
+        let promo = mint_object.promo().unwrap();
+        assert_eq!(promo.get_group(&member).unwrap(), group);
+        assert!(matches!(
+            promo.get_group(&accepting_member),
+            Err(AppError::PayerNotInMembers)
+        ));
+        assert_eq!(
+            promo.get_redeeming_group(&accepting_member).unwrap(),
+            (accepting_group, Some(group))
+        );
+        assert!(promo.get_referral_reward().is_none());
+
+        let response: GraphqlResponse<MintQueryData> =
+            serde_json::from_value(json!({ "data": { "mintByPk": null } })).unwrap();
+        assert!(response.data.unwrap().mint_by_pk.is_none());
+
+        // Malformed data is an error rather than a panic.
+        let result = serde_json::from_value::<GraphqlResponse<MintQueryData>>(json!({
+            "data": { "mintByPk": { "id": "not a pubkey", "tokenProgram": null } }
+        }));
+        assert!(result.is_err());
+    }
+
+    /// Requires the `api-data` database at `PG_DATABASE_URL_LOCALNET`, which is reset.
+    #[tokio::test]
+    async fn test_postgres_data_source() {
+        use bpl_api_data::{queries, reset, DatabaseURL};
+        use bpl_token_metadata::{
+            state::{Promo, PromoAcceptance, PromoGroup, Referral, ReferralReward, Settlement},
+            utils::{
+                find_associated_token_address, find_promo_acceptance_address, find_promo_address,
+                find_referral_address, find_referral_reward_address,
+            },
+        };
+        use error::AppError;
+        use spl_token::{
+            solana_program::program_option::COption,
+            state::{Account, AccountState, Mint},
+        };
+        use utils::{clients::DataSource, postgres::PostgresDataSource};
+
+        dotenv::dotenv().ok();
+        let data_source = PostgresDataSource::new(&DatabaseURL::Localnet.url(), 2).unwrap();
+        let mut client = data_source.pool().get().await.unwrap();
+        reset(&mut client).await.unwrap();
+
+        let group = Pubkey::new_unique();
+        let accepting_group = Pubkey::new_unique();
+        let member = Pubkey::new_unique();
+        let accepting_member = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let reward_mint = Pubkey::new_unique();
+        let token_owner = Pubkey::new_unique();
+        let referrer = Pubkey::new_unique();
+        let promo = find_promo_address(&mint).0;
+        let token_account = find_associated_token_address(&token_owner, &mint);
+
+        for (id, group_member) in [(group, member), (accepting_group, accepting_member)] {
+            let account = PromoGroup {
+                owner: Pubkey::new_unique(),
+                seed: Pubkey::new_unique(),
+                nonce: 255,
+                members: vec![group_member],
+            };
+            queries::bpl_token_metadata::promo_group::upsert(&client, id.as_ref(), &account, 1, 1)
+                .await;
+        }
+        let account = Mint {
+            mint_authority: COption::None,
+            supply: 1,
+            decimals: 0,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        queries::spl_token::mint::upsert(
+            &client,
+            mint.as_ref(),
+            &account,
+            &anchor_spl::token::ID,
+            &[],
+            1,
+            1,
+        )
+        .await;
+        let account = Promo {
+            owner: group,
+            mint,
+            metadata: Pubkey::new_unique(),
+            mint_count: 1,
+            burn_count: 0,
+            max_mint: None,
+            max_burn: None,
+        };
+        queries::bpl_token_metadata::promo::upsert(&client, promo.as_ref(), &account, 1, 1).await;
+        let account = PromoAcceptance {
+            promo,
+            owner: group,
+            groups: vec![accepting_group],
+            settlement: Settlement::IssuerPaysRedeemer,
+            settlement_lamports: 1_000_000,
+        };
+        queries::bpl_token_metadata::promo_acceptance::upsert(
+            &client,
+            find_promo_acceptance_address(&mint).0.as_ref(),
+            &account,
+            1,
+            1,
+        )
+        .await;
+        let account = ReferralReward { promo, reward_mint };
+        queries::bpl_token_metadata::referral_reward::upsert(
+            &client,
+            find_referral_reward_address(&mint).0.as_ref(),
+            &account,
+            1,
+            1,
+        )
+        .await;
+        let account = Account {
+            mint,
+            owner: token_owner,
+            amount: 1,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        };
+        queries::spl_token::token_account::upsert(
+            &client,
+            token_account.as_ref(),
+            &account,
+            &anchor_spl::token::ID,
+            &[],
+            1,
+            1,
+        )
+        .await;
+        let account = Referral {
+            promo,
+            mint,
+            token_owner,
+            referrer,
+            converted: false,
+        };
+        queries::bpl_token_metadata::referral::upsert(
+            &client,
+            find_referral_address(&mint, &token_owner).0.as_ref(),
+            &account,
+            1,
+            1,
+        )
+        .await;
+        drop(client);
+
+        let mint_object = data_source.get_mint(&mint).await.unwrap();
+        assert_eq!(mint_object.id, mint);
+        assert!(!mint_object.is_token_2022());
+        assert!(mint_object.promo_metadata_object.is_none());
+        let promo_object = mint_object.promo().unwrap();
+        assert_eq!(promo_object.get_group(&member).unwrap(), group);
+        assert_eq!(
+            promo_object.get_redeeming_group(&accepting_member).unwrap(),
+            (accepting_group, Some(group))
+        );
+        assert_eq!(
+            promo_object
+                .referral_reward_object
+                .as_ref()
+                .unwrap()
+                .reward_mint,
+            reward_mint
+        );
+        assert!(promo_object.promo_mint_object.is_none());
+        assert!(matches!(
+            data_source.get_mint(&Pubkey::new_unique()).await,
+            Err(AppError::MintNotFound(_))
+        ));
+
+        let token_account_object = data_source.get_token_account(&token_account).await.unwrap();
+        assert_eq!(token_account_object.id, token_account);
+        assert_eq!(token_account_object.owner, token_owner);
+        assert_eq!(token_account_object.mint, mint);
+        assert_eq!(token_account_object.amount, 1);
+        assert_eq!(token_account_object.token_program, anchor_spl::token::ID);
+        assert_eq!(
+            token_account_object.referral_object,
+            Some(ReferralObject {
+                referrer,
+                converted: false
+            })
+        );
+        assert_eq!(token_account_object.mint_object, Some(mint_object));
+        assert_eq!(
+            data_source
+                .get_owner_token_accounts(&token_owner)
+                .await
+                .unwrap(),
+            vec![token_account_object]
+        );
+
+        let profile = GroupProfileObject {
+            label: "Test Group".to_string(),
+            icon: "https://tx.bokoup.dev/icon.png".to_string(),
+        };
+        data_source
+            .set_group_profile(&group, &profile, "signature")
+            .await
+            .unwrap();
+        let group_object = data_source.get_group(&group).await.unwrap();
+        assert_eq!(group_object.id, group);
+        assert_eq!(group_object.members, vec![member]);
+        assert_eq!(group_object.group_profile_object, Some(profile));
+        assert!(matches!(
+            data_source.get_group(&Pubkey::new_unique()).await,
+            Err(AppError::GroupNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cached_data_source() {
+        use utils::clients::{CachedDataSource, DataSource};
+
+        let mint = Pubkey::new_unique();
+        let group = Pubkey::new_unique();
+        let fake = Arc::new(FakeDataSource::default());
+        fake.insert_mint(create_mint_object(&mint, &group, &[]));
+
+        let data = CachedDataSource::new(fake.clone(), Duration::from_millis(50));
+        assert_eq!(data.get_mint(&mint).await.unwrap().id, mint);
+
+        fake.mints.lock().unwrap().clear();
+        assert_eq!(data.get_mint(&mint).await.unwrap().id, mint);
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(matches!(
+            data.get_mint(&mint).await,
+            Err(error::AppError::MintNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_local_storage() {
+        use utils::storage::{get_content_key, upload_metadata_json, LocalStorage};
+
+        let dir = std::env::temp_dir().join("bpl-api-tx-test-local-storage");
+        let base_url = Url::from_str("http://localhost:8081/storage/").unwrap();
+        let storage = LocalStorage::new(dir.clone(), base_url);
+
+        let image = vec![1, 2, 3];
+        let image_url = storage.upload(image.clone(), "image/png").await.unwrap();
+        let key = get_content_key(&image, "image/png");
+        assert!(key.ends_with(".png"));
+        assert_eq!(image_url, format!("http://localhost:8081/storage/{}", key));
+        assert_eq!(fs::read(dir.join(&key)).await.unwrap(), image);
+
+        let mut metadata = json!({"name": "buyXProduct", "symbol": "PROD"});
+        let uri = upload_metadata_json(
+            &storage,
+            metadata.as_object_mut().unwrap(),
+            (image_url.clone(), "image/png".to_string()),
+            ("thumbnail_url".to_string(), "image/jpeg".to_string()),
+        )
+        .await
+        .unwrap();
+        let stored: serde_json::Value = serde_json::from_slice(
+            &fs::read(dir.join(uri.rsplit('/').next().unwrap()))
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(stored["image"], json!(image_url));
+        assert_eq!(stored["properties"]["files"][0]["type"], json!("image/png"));
+        assert_eq!(
+            stored["properties"]["files"][1]["uri"],
+            json!("thumbnail_url")
+        );
+    }
+
+    #[test]
+    fn test_render_qr() {
+        use ::image::{DynamicImage, GenericImageView, ImageOutputFormat, Rgba, RgbaImage};
+        use std::io::Cursor;
+        use utils::qr::*;
+
+        let mint = Pubkey::new_unique();
+        let link = get_mint_link(
+            "https://tx.bokoup.dev",
+            &mint,
+            "Get a free coffee",
+            Some(r#"{"store": 1}"#),
+        );
+        assert_eq!(
+            link,
+            format!(
+                "https://tx.bokoup.dev/promo/mint/{}/Get%20a%20free%20coffee/%7B%22store%22%3A%201%7D",
+                mint
+            )
+        );
+        assert_eq!(
+            get_solana_pay_url(&link),
+            format!("solana:{}", urlencoding::encode(&link))
+        );
+
+        let svg = render_svg(&link, 256, None).unwrap();
+        assert!(svg.starts_with("<?xml"));
+        assert!(!svg.contains("<image"));
+        let svg = render_svg(&link, 256, Some("https://bokoup.dev/icon.png?a=1&b=2")).unwrap();
+        assert!(svg.contains(r#"href="https://bokoup.dev/icon.png?a=1&amp;b=2""#));
+        assert!(svg.ends_with("</svg>"));
+
+        let png = render_png(&link, 256, None).unwrap();
+        let img = ::image::load_from_memory(&png).unwrap();
+        assert_eq!(img.width(), img.height());
+        assert!(img.width() >= 256);
+        // Quiet zone.
+        assert_eq!(img.get_pixel(0, 0), Rgba([255, 255, 255, 255]));
+
+        // Icons are drawn in the center of branded codes.
+        let mut icon = Vec::new();
+        DynamicImage::ImageRgba8(RgbaImage::from_pixel(64, 64, Rgba([255, 0, 0, 255])))
+            .write_to(&mut Cursor::new(&mut icon), ImageOutputFormat::Png)
+            .unwrap();
+        let png = render_png(&link, 256, Some(&icon)).unwrap();
+        let img = ::image::load_from_memory(&png).unwrap();
+        let center = img.width() / 2;
+        assert_eq!(img.get_pixel(center, center), Rgba([255, 0, 0, 255]));
+
+        assert!(matches!(
+            render_png(&link, 256, Some(b"not an image")),
+            Err(error::AppError::InvalidImage(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_promo_qr() {
+        use handlers::SolanaPayLinkResponse;
+
+        let mint = Pubkey::new_unique();
+        let group = Pubkey::new_unique();
+        let data = Arc::new(FakeDataSource::default());
+        data.insert_mint(create_mint_object(&mint, &group, &[]));
+        let app = create_app(create_fake_state(
+            Keypair::new(),
+            Arc::new(FakeSolanaRpc::default()),
+            data,
+        ));
+
+        let message = urlencoding::encode(MESSAGE);
+
+        let response = send(
+            &app,
+            Method::GET,
+            &format!("/promo/link/{}/{}", mint, message),
+            None,
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let parsed_response: SolanaPayLinkResponse = parse(&response);
+        let link = format!("https://tx.bokoup.dev/promo/mint/{}/{}", mint, message);
+        assert_eq!(
+            parsed_response,
+            SolanaPayLinkResponse {
+                solana_pay_url: format!("solana:{}", urlencoding::encode(&link)),
+                link,
+            }
+        );
+
+        let response = send(
+            &app,
+            Method::GET,
+            &format!("/promo/qr/{}/{}", mint, message),
+            None,
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/svg+xml");
+
+        let response = send(
+            &app,
+            Method::GET,
+            &format!("/promo/qr/{}/{}?format=png&size=300", mint, message),
+            None,
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/png");
+        let img = ::image::load_from_memory(response.body()).unwrap();
+        assert!(img.width() >= 300);
+
+        // Links are only returned for promos.
+        let response = send(
+            &app,
+            Method::GET,
+            &format!("/promo/qr/{}/{}", Pubkey::new_unique(), message),
+            None,
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_group_profile() {
+        use utils::group_profile::*;
+
+        let owner = Keypair::new();
+        let member = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let group = Pubkey::new_unique();
+        let data = Arc::new(FakeDataSource::default());
+        data.insert_mint(create_mint_object(&mint, &group, &[]));
+        data.insert_group(PromoGroupObject {
+            id: group,
+            owner: owner.pubkey(),
+            members: vec![owner.pubkey(), member.pubkey()],
+            group_profile_object: None,
+        });
+        let state = Arc::new(create_fake_state(
+            Keypair::new(),
+            Arc::new(FakeSolanaRpc::default()),
+            data,
+        ));
+        let app = create_app(state.clone());
+
+        let profile = GroupProfileObject {
+            label: "Coffee Shop".to_string(),
+            icon: "https://coffee.shop/icon.png".to_string(),
+        };
+        let (owner_token, _) = state.create_auth_session(owner.pubkey(), String::new());
+        let (member_token, _) = state.create_auth_session(member.pubkey(), String::new());
+        let post = |token: Option<&str>, profile: &GroupProfileObject| {
+            let uri = format!("/promo/group-profile/{}", group);
+            let token = token.map(str::to_string);
+            let body = json!({ "label": profile.label, "icon": profile.icon });
+            let app = app.clone();
+            async move { send(&app, Method::POST, &uri, token.as_deref(), Some(body)).await }
+        };
+
+        // Only a signed in group owner can set the profile.
+        let response = post(None, &profile).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = post(Some("unknown"), &profile).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = post(Some(&member_token), &profile).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let invalid_profile = GroupProfileObject {
+            label: profile.label.clone(),
+            icon: "http://coffee.shop/icon.png".to_string(),
+        };
+        let response = post(Some(&owner_token), &invalid_profile).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = post(Some(&owner_token), &profile).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let expected = get_app_id::ResponseData {
+            label: profile.label.clone(),
+            icon: profile.icon.clone(),
+        };
+        let response = send(
+            &app,
+            Method::GET,
+            &format!("/promo/mint/{}/{}", mint, urlencoding::encode(MESSAGE)),
+            None,
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(parse::<get_app_id::ResponseData>(&response), expected);
+
+        // The graphql data api is read only, so the default configuration can't set profiles.
+        let error =
+            GraphqlDataSource::new(reqwest::Client::new(), Url::from_str(DATA_URL).unwrap())
+                .set_group_profile(&group, &profile, "signature")
+                .await
+                .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "setting group profiles requires the postgres data source, set DATA_SOURCE=postgres"
+        );
+        assert_eq!(error.into_response().status(), StatusCode::NOT_IMPLEMENTED);
+    }
+
+    #[test]
+    fn test_validate_group_profile() {
+        use utils::group_profile::*;
+
+        let profile = |label: &str, icon: &str| GroupProfileObject {
+            label: label.to_string(),
+            icon: icon.to_string(),
+        };
+        assert!(
+            validate_group_profile(&profile("Coffee Shop", "https://coffee.shop/icon.png")).is_ok()
+        );
+        assert!(validate_group_profile(&profile(" ", "https://coffee.shop/icon.png")).is_err());
+        assert!(validate_group_profile(&profile(
+            &"a".repeat(MAX_LABEL_LENGTH + 1),
+            "https://coffee.shop/icon.png"
+        ))
+        .is_err());
+        assert!(validate_group_profile(&profile("Coffee Shop", "coffee.shop/icon.png")).is_err());
+        assert!(validate_group_profile(&profile(
+            "Coffee Shop",
+            &format!("https://coffee.shop/{}", "a".repeat(MAX_ICON_URL_LENGTH))
+        ))
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit() {
+        use utils::rate_limit::{RateLimitConfig, RateLimiter};
+
+        let platform_signer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let group = Pubkey::new_unique();
+        let denied_mint = Pubkey::new_unique();
+        let denied_group = Pubkey::new_unique();
+        let data = Arc::new(FakeDataSource::default());
+        data.insert_mint(create_mint_object(
+            &mint,
+            &group,
+            &[platform_signer.pubkey()],
+        ));
+        data.insert_mint(create_mint_object(
+            &denied_mint,
+            &denied_group,
+            &[platform_signer.pubkey()],
+        ));
+        let denied_wallet = Pubkey::new_unique();
+        let app = create_app(State {
+            rate_limiter: RateLimiter::new(RateLimitConfig {
+                wallet_limit: Some(1),
+                group_daily_budget: Some(2),
+                denylist: [denied_wallet.to_string(), denied_group.to_string()].into(),
+                ..RateLimitConfig::default()
+            })
+            .unwrap(),
+            ..create_fake_state(platform_signer, Arc::new(FakeSolanaRpc::default()), data)
+        });
+
+        let post = |mint: &Pubkey, account: &Pubkey| {
+            let uri = format!("/promo/mint/{}/{}", mint, urlencoding::encode(MESSAGE));
+            let body = json!({ "account": account.to_string() });
+            let app = app.clone();
+            async move { send(&app, Method::POST, &uri, None, Some(body)).await }
+        };
+
+        let wallet = Pubkey::new_unique();
+        let response = post(&mint, &wallet).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Wallets are limited per minute.
+        let response = post(&mint, &wallet).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = response.headers()[header::RETRY_AFTER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((1..=60).contains(&retry_after));
+
+        // Failed requests don't use the group's budget.
+        let uri = format!("/promo/mint/{}/{}", mint, urlencoding::encode(MESSAGE));
+        let body = json!({ "account": "not a wallet" });
+        let response = send(&app, Method::POST, &uri, None, Some(body)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // Groups are limited per day.
+        let response = post(&mint, &Pubkey::new_unique()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = post(&mint, &Pubkey::new_unique()).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        let response = post(&denied_mint, &Pubkey::new_unique()).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = post(&Pubkey::new_unique(), &denied_wallet).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // Requests for the label and icon aren't limited.
+        let response = send(
+            &app,
+            Method::GET,
+            &format!("/promo/mint/{}/{}", mint, urlencoding::encode(MESSAGE)),
+            None,
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_request() {
+        use utils::rate_limit::{RateLimitConfig, RateLimiter, MAX_BODY_LENGTH};
+
+        let platform_signer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let data = Arc::new(FakeDataSource::default());
+        data.insert_mint(create_mint_object(
+            &mint,
+            &Pubkey::new_unique(),
+            &[platform_signer.pubkey()],
+        ));
+        let app = create_app(State {
+            rate_limiter: RateLimiter::new(RateLimitConfig {
+                ip_limit: Some(1),
+                trusted_proxies: 1,
+                ..RateLimitConfig::default()
+            })
+            .unwrap(),
+            ..create_fake_state(platform_signer, Arc::new(FakeSolanaRpc::default()), data)
+        });
+
+        let uri = format!("/promo/mint/{}/{}", mint, urlencoding::encode(MESSAGE));
+        let post = |forwarded_for: &str, body: Vec<u8>| {
+            let request = Request::builder()
+                .method(Method::POST)
+                .uri(&uri)
+                .header(header::HOST, "tx.bokoup.dev")
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::CONTENT_LENGTH, body.len())
+                .header("x-forwarded-for", forwarded_for)
+                .body(Body::from(body))
+                .unwrap();
+            let app = app.clone();
+            async move { app.oneshot(request).await.unwrap().status() }
+        };
+        let body =
+            || serde_json::to_vec(&json!({ "account": Pubkey::new_unique().to_string() })).unwrap();
+
+        assert_eq!(post("10.0.0.1, 203.0.113.1", body()).await, StatusCode::OK);
+        // Addresses to the left of the one added by the trusted proxy can be set by the client.
+        assert_eq!(
+            post("10.0.0.2, 203.0.113.1", body()).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(post("203.0.113.2", body()).await, StatusCode::OK);
+
+        // Bodies are rejected before they are buffered.
+        let mut large_body = vec![b' '; MAX_BODY_LENGTH];
+        large_body.extend(body());
+        assert_eq!(
+            post("203.0.113.3", large_body).await,
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sponsorship() {
+        use handlers::GroupUsageResponse;
+        use utils::{checkout::get_transaction_signature, sponsorship::LedgerEntryKind};
+
+        let platform_signer = Keypair::new();
+        let owner = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let group = Pubkey::new_unique();
+        let data = Arc::new(FakeDataSource::default());
+        data.insert_mint(create_mint_object(
+            &mint,
+            &group,
+            &[platform_signer.pubkey()],
+        ));
+        data.insert_group(PromoGroupObject {
+            id: group,
+            owner: owner.pubkey(),
+            members: vec![owner.pubkey()],
+            group_profile_object: None,
+        });
+        let rpc = Arc::new(FakeSolanaRpc::default());
+        let state = Arc::new(create_fake_state(platform_signer, rpc.clone(), data));
+        let app = create_app(state.clone());
+        let (owner_token, _) = state.create_auth_session(owner.pubkey(), String::new());
+
+        let response = send(
+            &app,
+            Method::POST,
+            &format!("/promo/mint/{}/{}", mint, urlencoding::encode(MESSAGE)),
+            None,
+            Some(json!({ "account": Pubkey::new_unique().to_string() })),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let pay_response: PayResponse = parse(&response);
+        let signature = get_transaction_signature(&pay_response.transaction).unwrap();
+
+        let pending = state.sponsorship.get_pending_entries().await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].signature, signature);
+        assert_eq!(pending[0].group, group);
+        assert_eq!(pending[0].kind, LedgerEntryKind::Sponsored);
+
+        // Costs are recorded once the transaction is confirmed.
+        state.reconcile_sponsorship().await.unwrap();
+        assert_eq!(
+            state.sponsorship.get_pending_entries().await.unwrap().len(),
+            1
+        );
+        rpc.transaction_costs.lock().unwrap().insert(
+            signature,
+            TransactionCost {
+                fee: 5_000,
+                rent: 2_039_280,
+                failed: false,
+            },
+        );
+        state.reconcile_sponsorship().await.unwrap();
+        assert!(state
+            .sponsorship
+            .get_pending_entries()
+            .await
+            .unwrap()
+            .is_empty());
+
+        let get_usage = || async {
+            let response = send(
+                &app,
+                Method::GET,
+                &format!("/promo/usage/{}?days=7", group),
+                Some(&owner_token),
+                None,
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::OK);
+            parse::<GroupUsageResponse>(&response)
+        };
+        let usage = get_usage().await;
+        assert_eq!(usage.transactions, 1);
+        assert_eq!(usage.fees, 5_000);
+        assert_eq!(usage.rent, 2_039_280);
+        assert_eq!(usage.outstanding, 2_044_280);
+        assert!(!usage.reimburse);
+        assert_eq!(usage.days.len(), 1);
+        assert_eq!(usage.days[0].transactions, 1);
+
+        // Groups are only settled once they opt in.
+        state.settle_sponsorship(1).await.unwrap();
+        assert!(rpc.sent.lock().unwrap().is_empty());
+
+        let post = |account: &Pubkey| {
+            let (token, _) = state.create_auth_session(*account, String::new());
+            let uri = format!("/promo/group-billing/{}", group);
+            let app = app.clone();
+            async move {
+                let body = json!({ "reimburse": true });
+                send(&app, Method::POST, &uri, Some(&token), Some(body)).await
+            }
+        };
+        let response = post(&Pubkey::new_unique()).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = post(&owner.pubkey()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(get_usage().await.reimburse);
+
+        // Only the owner can allow lamports to be settled on chain.
+        let allowance_uri = format!("/promo/sponsorship-allowance/{}/10000000", group);
+        let allowance_body = |account: &Pubkey| json!({ "account": account.to_string() });
+        let response = send(
+            &app,
+            Method::POST,
+            &allowance_uri,
+            None,
+            Some(allowance_body(&state.platform_signer.pubkey())),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = send(
+            &app,
+            Method::POST,
+            &allowance_uri,
+            None,
+            Some(allowance_body(&owner.pubkey())),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let parsed_response: PayResponse = parse(&response);
+        let instruction = create_create_sponsorship_allowance_instruction(
+            owner.pubkey(),
+            group,
+            10_000_000,
+            None,
+        )
+        .unwrap();
+        let tx = create_expected_transaction(instruction, &owner.pubkey(), &rpc);
+        assert_eq!(
+            parsed_response.transaction,
+            base64::encode(bincode::serialize(&tx).unwrap())
+        );
+
+        // Groups owing less than the minimum aren't settled.
+        state.settle_sponsorship(10_000_000).await.unwrap();
+        assert!(rpc.sent.lock().unwrap().is_empty());
+
+        state.settle_sponsorship(1).await.unwrap();
+        assert_eq!(rpc.sent.lock().unwrap().len(), 1);
+        let usage = get_usage().await;
+        assert_eq!(usage.settled, 2_044_280);
+        assert_eq!(usage.outstanding, 0);
+
+        state.settle_sponsorship(1).await.unwrap();
+        assert_eq!(rpc.sent.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sign_in() {
+        use handlers::{AuthSessionResponse, SignInChallengeResponse};
+        use utils::auth::format_timestamp;
+
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(1_700_000_000), "2023-11-14T22:13:20Z");
+
+        let member = Keypair::new();
+        let group = Pubkey::new_unique();
+        let other_group = Pubkey::new_unique();
+        let data = Arc::new(FakeDataSource::default());
+        data.insert_group(PromoGroupObject {
+            id: group,
+            owner: Pubkey::new_unique(),
+            members: vec![member.pubkey()],
+            group_profile_object: None,
+        });
+        data.insert_group(PromoGroupObject {
+            id: other_group,
+            owner: Pubkey::new_unique(),
+            members: vec![],
+            group_profile_object: None,
+        });
+        let app = create_app(create_fake_state(
+            Keypair::new(),
+            Arc::new(FakeSolanaRpc::default()),
+            data,
+        ));
+
+        let post = |uri: &'static str, body: serde_json::Value| {
+            let app = app.clone();
+            async move { send(&app, Method::POST, uri, None, Some(body)).await }
+        };
+        let get_challenge = || async {
+            let response = post(
+                "/auth/nonce",
+                json!({ "account": member.pubkey().to_string() }),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::OK);
+            parse::<SignInChallengeResponse>(&response)
+        };
+
+        let challenge = get_challenge().await;
+        assert!(challenge
+            .message
+            .starts_with("tx.bokoup.dev wants you to sign in with your Solana account:\n"));
+        assert!(challenge
+            .message
+            .contains("\nURI: https://tx.bokoup.dev/\n"));
+
+        // Nonces are only issued for the configured sign-in domain, whatever the host.
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/auth/nonce")
+            .header(header::HOST, "tx.bokoup.dev.example.com")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                json!({ "account": member.pubkey().to_string() }).to_string(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(challenge
+            .message
+            .contains(&format!("Nonce: {}", challenge.nonce)));
+
+        // Signatures by other accounts are rejected.
+        let response = post(
+            "/auth/session",
+            json!({
+                "nonce": challenge.nonce,
+                "signature": Keypair::new()
+                    .sign_message(challenge.message.as_bytes())
+                    .to_string(),
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let challenge = get_challenge().await;
+        let sign_in = json!({
+            "nonce": challenge.nonce,
+            "signature": member.sign_message(challenge.message.as_bytes()).to_string(),
+        });
+        let response = post("/auth/session", sign_in.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let session: AuthSessionResponse = parse(&response);
+        assert_eq!(session.account, member.pubkey().to_string());
+
+        // Nonces can only be used once.
+        let response = post("/auth/session", sign_in).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let get_usage = |group: Pubkey| {
+            let uri = format!("/promo/usage/{}", group);
+            let app = app.clone();
+            let token = session.token.clone();
+            async move { send(&app, Method::GET, &uri, Some(&token), None).await }
+        };
+        let response = get_usage(group).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Members of one group can't act for others.
+        let response = get_usage(other_group).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = send(
+            &app,
+            Method::DELETE,
+            "/auth/session",
+            Some(&session.token),
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = get_usage(group).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_bundlr_monitor() {
+        use error::AppError;
+        use std::sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex,
+        };
+        use utils::bundlr::{BundlrAccount, BundlrFundingConfig};
+
+        // Bundlr node charging 10 lamports per byte and crediting any funding it is sent.
+        let balance = Arc::new(AtomicU64::new(1_000));
+        let fundings = Arc::new(Mutex::new(Vec::<String>::new()));
+        let funding_address = Pubkey::new_unique();
+        let node = Router::new()
+            .route(
+                "/account/balance/solana",
+                get({
+                    let balance = balance.clone();
+                    move || async move {
+                        axum::Json(json!({ "balance": balance.load(Ordering::SeqCst).to_string() }))
+                    }
+                })
+                .post({
+                    let balance = balance.clone();
+                    let fundings = fundings.clone();
+                    move |axum::Json(body): axum::Json<serde_json::Value>| async move {
+                        fundings
+                            .lock()
+                            .unwrap()
+                            .push(body["tx_id"].as_str().unwrap().to_string());
+                        balance.fetch_add(5_000_000, Ordering::SeqCst);
+                        "OK"
+                    }
+                }),
+            )
+            .route(
+                "/price/solana/:bytes",
+                get(
+                    |axum::extract::Path(bytes): axum::extract::Path<u64>| async move {
+                        (bytes * 10).to_string()
+                    },
+                ),
+            )
+            .route(
+                "/info",
+                get(move || async move {
+                    axum::Json(json!({ "addresses": { "solana": funding_address.to_string() } }))
+                }),
+            );
+        let listener = TcpListener::bind("127.0.0.1:0".parse::<SocketAddr>().unwrap()).unwrap();
+        let node_url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        tokio::spawn(async move {
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(node.into_make_service())
+                .await
+                .unwrap();
+        });
+
+        let platform_signer = Keypair::new();
+        let address = platform_signer.pubkey();
+        let rpc = Arc::new(FakeSolanaRpc::default());
+        let state = Arc::new(State {
+            bundlr: Some(BundlrAccount::new(
+                reqwest::Client::new(),
+                node_url,
+                address,
+            )),
+            ..create_fake_state(
+                platform_signer,
+                rpc.clone(),
+                Arc::new(FakeDataSource::default()),
+            )
+        });
+
+        // Uploads the balance can't pay for fail before anything is uploaded.
+        let result = state.check_storage_funds(&[50, 60]).await;
+        assert!(matches!(
+            result,
+            Err(AppError::StorageUnderfunded {
+                balance: 1_000,
+                price: 1_100
+            })
+        ));
+        assert!(state.check_storage_funds(&[50, 40]).await.is_ok());
+
+        // The balance is only monitored until funding is configured.
+        let mut config = BundlrFundingConfig {
+            min_balance: 1_000_000,
+            ..BundlrFundingConfig::default()
+        };
+        state.monitor_bundlr(&config).await.unwrap();
+        assert!(rpc.sent.lock().unwrap().is_empty());
+        let status = state.bundlr.as_ref().unwrap().status();
+        assert_eq!(status.balance, Some(1_000));
+        assert!(status.low);
+
+        config.fund_lamports = Some(5_000_000);
+        state.monitor_bundlr(&config).await.unwrap();
+        let sent = rpc.sent.lock().unwrap().clone();
+        assert_eq!(sent.len(), 1);
+        let tx: Transaction = bincode::deserialize(&base64::decode(&sent[0]).unwrap()).unwrap();
+        let transfer = tx.message.instructions.last().unwrap();
+        assert_eq!(
+            tx.message.account_keys[transfer.accounts[1] as usize],
+            funding_address
+        );
+        assert_eq!(
+            *fundings.lock().unwrap(),
+            vec![utils::checkout::get_transaction_signature(&sent[0]).unwrap()]
+        );
+
+        // The node isn't funded again once the balance is above the minimum.
+        state.monitor_bundlr(&config).await.unwrap();
+        assert_eq!(rpc.sent.lock().unwrap().len(), 1);
+
+        let app = create_app(state);
+        let response = send(&app, Method::GET, "/status/storage", None, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let status: serde_json::Value = parse(&response);
+        assert_eq!(status["bundlr"]["balance"], 5_001_000);
+        assert_eq!(status["bundlr"]["low"], false);
+        assert_eq!(status["bundlr"]["fundings"], 1);
+        assert_eq!(status["bundlr"]["address"], address.to_string());
+
+        let response = send(&app, Method::GET, "/metrics", None, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let metrics = String::from_utf8(response.body().to_vec()).unwrap();
+        assert!(metrics.contains("bpl_api_tx_bundlr_balance_lamports 5001000\n"));
+        assert!(metrics.contains("bpl_api_tx_bundlr_fundings_total 1\n"));
+    }
+
+    #[tokio::test]
+    async fn test_webhooks() {
+        use std::{
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                Mutex,
+            },
+            time::{Duration, SystemTime},
+        };
+        use utils::webhooks::*;
+
+        // Endpoint failing the first post and accepting the rest, recording what it's sent.
+        let posts = Arc::new(AtomicUsize::new(0));
+        let received = Arc::new(Mutex::new(Vec::<(String, String, String)>::new()));
+        let receiver = Router::new().route(
+            "/hook",
+            post({
+                let posts = posts.clone();
+                let received = received.clone();
+                move |headers: axum::http::HeaderMap, body: String| async move {
+                    let get_header = |name: &str| {
+                        headers
+                            .get(name)
+                            .map(|value| value.to_str().unwrap().to_string())
+                            .unwrap_or_default()
+                    };
+                    received.lock().unwrap().push((
+                        get_header(WEBHOOK_SIGNATURE_HEADER),
+                        get_header(WEBHOOK_EVENT_HEADER),
+                        body,
+                    ));
+                    if posts.fetch_add(1, Ordering::SeqCst) == 0 {
+                        StatusCode::INTERNAL_SERVER_ERROR
+                    } else {
+                        StatusCode::OK
+                    }
+                }
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0".parse::<SocketAddr>().unwrap()).unwrap();
+        let receiver_url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(receiver.into_make_service())
+                .await
+                .unwrap();
+        });
+
+        let owner = Keypair::new();
+        let member = Keypair::new();
+        let group = Pubkey::new_unique();
+        let data = Arc::new(FakeDataSource::default());
+        data.insert_group(PromoGroupObject {
+            id: group,
+            owner: owner.pubkey(),
+            members: vec![owner.pubkey(), member.pubkey()],
+            group_profile_object: None,
+        });
+        let webhooks = Arc::new(MemoryWebhookStore::default());
+        let state = Arc::new(State {
+            webhooks: webhooks.clone(),
+            ..create_fake_state(Keypair::new(), Arc::new(FakeSolanaRpc::default()), data)
+        });
+        let app = create_app(state.clone());
+
+        let (owner_token, _) = state.create_auth_session(owner.pubkey(), String::new());
+        let (member_token, _) = state.create_auth_session(member.pubkey(), String::new());
+        let endpoints_uri = format!("/promo/webhooks/{}", group);
+        let endpoint_body = |url: &str| json!({ "url": url, "events": ["mint", "lowSupply"] });
+
+        // Only the group owner can register endpoints, and only https ones.
+        let response = send(
+            &app,
+            Method::POST,
+            &endpoints_uri,
+            Some(&member_token),
+            Some(endpoint_body("https://coffee.shop/hook")),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = send(
+            &app,
+            Method::POST,
+            &endpoints_uri,
+            Some(&owner_token),
+            Some(endpoint_body("http://coffee.shop/hook")),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = send(
+            &app,
+            Method::POST,
+            &endpoints_uri,
+            Some(&owner_token),
+            Some(endpoint_body("https://coffee.shop/hook")),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let registered: handlers::WebhookEndpointResponse = parse(&response);
+        assert_eq!(registered.url, "https://coffee.shop/hook");
+        assert_eq!(
+            registered.events,
+            vec![WebhookEventKind::Mint, WebhookEventKind::LowSupply]
+        );
+        assert!(registered.secret.is_some());
+
+        // Members can list endpoints, without their secrets.
+        let response = send(&app, Method::GET, &endpoints_uri, Some(&member_token), None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let endpoints: Vec<handlers::WebhookEndpointResponse> = parse(&response);
+        assert_eq!(endpoints.len(), 1);
+        assert_eq!(endpoints[0].id, registered.id);
+        assert!(endpoints[0].secret.is_none());
+
+        let delete_uri = format!("/promo/webhooks/{}/{}", group, registered.id);
+        let response = send(&app, Method::DELETE, &delete_uri, Some(&owner_token), None).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = send(&app, Method::DELETE, &delete_uri, Some(&owner_token), None).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        // The endpoint is served locally over http, so it's inserted directly.
+        let secret = "0123456789abcdef".to_string();
+        let endpoint = WebhookEndpoint::new(
+            group,
+            receiver_url,
+            WEBHOOK_EVENT_KINDS.to_vec(),
+            Some(secret.clone()),
+        );
+        webhooks.insert_endpoint(&endpoint, "").await.unwrap();
+
+        let mint = Pubkey::new_unique();
+        webhooks.insert_event(WebhookEvent::new(
+            WebhookEventKind::Mint,
+            "signature".to_string(),
+            group,
+            mint,
+            Pubkey::new_unique(),
+            10,
+            SystemTime::now(),
+        ));
+        webhooks.insert_promo_supply(PromoSupply {
+            mint,
+            group,
+            mint_count: 95,
+            max_mint: 100,
+            slot: 10,
+        });
+
+        // Events in the last slot are read again but not queued twice.
+        let config = WebhookConfig {
+            allow_private_addresses: true,
+            ..WebhookConfig::default()
+        };
+        assert_eq!(state.poll_webhook_events(&config).await.unwrap(), 2);
+        assert_eq!(state.poll_webhook_events(&config).await.unwrap(), 0);
+
+        state.deliver_webhooks(&config).await.unwrap();
+        let deliveries = webhooks.get_deliveries(&group, 10).await.unwrap();
+        let mint_delivery = deliveries
+            .iter()
+            .find(|delivery| delivery.kind == WebhookEventKind::Mint)
+            .unwrap()
+            .clone();
+        assert_eq!(mint_delivery.status, WebhookDeliveryStatus::Pending);
+        assert_eq!(mint_delivery.attempts, 1);
+        assert_eq!(mint_delivery.response_status, Some(500));
+        assert!(mint_delivery.next_attempt_at > SystemTime::now());
+        let low_supply_delivery = deliveries
+            .iter()
+            .find(|delivery| delivery.kind == WebhookEventKind::LowSupply)
+            .unwrap();
+        assert_eq!(low_supply_delivery.status, WebhookDeliveryStatus::Delivered);
+
+        let (signature, event, body) = received.lock().unwrap()[1].clone();
+        assert_eq!(event, "lowSupply");
+        let payload: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(payload["mint"], mint.to_string());
+        assert_eq!(payload["remaining"], 5);
+        let (timestamp, signature) = signature
+            .strip_prefix("t=")
+            .and_then(|signature| signature.split_once(",v1="))
+            .unwrap();
+        assert_eq!(
+            signature,
+            sign_webhook_payload(&secret, timestamp.parse().unwrap(), &body)
+        );
+
+        // Retries are made once they are due.
+        state.deliver_webhooks(&config).await.unwrap();
+        assert_eq!(posts.load(Ordering::SeqCst), 2);
+        webhooks
+            .update_delivery(&WebhookDelivery {
+                next_attempt_at: SystemTime::now(),
+                ..mint_delivery
+            })
+            .await
+            .unwrap();
+        state.deliver_webhooks(&config).await.unwrap();
+        assert_eq!(posts.load(Ordering::SeqCst), 3);
+
+        let response = send(
+            &app,
+            Method::GET,
+            &format!("/promo/webhook-deliveries/{}", group),
+            Some(&member_token),
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let deliveries: Vec<handlers::WebhookDeliveryResponse> = parse(&response);
+        assert_eq!(deliveries.len(), 2);
+        assert!(deliveries.iter().all(|delivery| {
+            delivery.status == WebhookDeliveryStatus::Delivered
+                && delivery.next_attempt_at.is_none()
+        }));
+        let mint_delivery = deliveries
+            .iter()
+            .find(|delivery| delivery.kind == WebhookEventKind::Mint)
+            .unwrap();
+        assert_eq!(mint_delivery.attempts, 2);
+        assert_eq!(mint_delivery.payload["signature"], "signature");
+
+        // Deliveries to endpoints resolving to private addresses fail without being posted.
+        webhooks.insert_event(WebhookEvent::new(
+            WebhookEventKind::Mint,
+            "private".to_string(),
+            group,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            11,
+            SystemTime::now(),
+        ));
+        assert_eq!(state.poll_webhook_events(&config).await.unwrap(), 1);
+        state
+            .deliver_webhooks(&WebhookConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(posts.load(Ordering::SeqCst), 3);
+        let deliveries = webhooks.get_deliveries(&group, 10).await.unwrap();
+        let private_delivery = deliveries
+            .iter()
+            .find(|delivery| delivery.payload.contains("private"))
+            .unwrap();
+        assert_eq!(private_delivery.status, WebhookDeliveryStatus::Pending);
+        assert_eq!(private_delivery.attempts, 1);
+        assert!(private_delivery
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("private address"));
+    }
+
+    #[test]
+    fn test_sign_webhook_payload() {
+        use std::time::Duration;
+        use utils::webhooks::*;
+
+        assert_eq!(
+            sign_webhook_payload("0123456789abcdef", 1_700_000_000, r#"{"kind":"mint"}"#),
+            "cabf3b8f97bbce406739491c8893c396e24c31f6df7ea960749b417896e4994a"
+        );
+        assert_eq!(get_webhook_backoff(1), Duration::from_secs(30));
+        assert_eq!(get_webhook_backoff(2), Duration::from_secs(60));
+        assert_eq!(get_webhook_backoff(40), Duration::from_secs(60 * 60));
+
+        assert!(
+            validate_webhook_endpoint("https://coffee.shop/hook", &WEBHOOK_EVENT_KINDS, None)
+                .is_ok()
+        );
+        assert!(validate_webhook_endpoint("https://coffee.shop/hook", &[], None).is_err());
+        assert!(validate_webhook_endpoint(
+            "https://coffee.shop/hook",
+            &WEBHOOK_EVENT_KINDS,
+            Some("short")
+        )
+        .is_err());
+        for url in [
+            "https://localhost/hook",
+            "https://127.0.0.1/hook",
+            "https://10.0.0.1/hook",
+            "https://192.168.1.1/hook",
+            "https://169.254.169.254/latest/meta-data",
+            "https://[::1]/hook",
+            "https://[fd00::1]/hook",
+            "https://[::ffff:10.0.0.1]/hook",
+        ] {
+            assert!(
+                validate_webhook_endpoint(url, &WEBHOOK_EVENT_KINDS, None).is_err(),
+                "{}",
+                url
+            );
+        }
+        assert!(!is_private_address("8.8.8.8".parse().unwrap()));
+        assert!(is_private_address("100.64.0.1".parse().unwrap()));
+        assert!(is_private_address("fe80::1".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_memory_rate_limit_store() {
+        use std::time::Duration;
+        use utils::rate_limit::{MemoryRateLimitStore, RateLimitStore};
+
+        let store = MemoryRateLimitStore::default();
+        let window = Duration::from_millis(50);
+        let counter = store.increment("key", 1, window).await.unwrap();
+        assert_eq!(counter.count, 1);
+        assert!(counter.reset <= window);
+        assert_eq!(store.increment("key", 0, window).await.unwrap().count, 1);
+        assert_eq!(store.increment("key", 2, window).await.unwrap().count, 3);
+        assert_eq!(store.increment("key", -1, window).await.unwrap().count, 2);
+        assert_eq!(store.increment("other", 1, window).await.unwrap().count, 1);
+        assert_eq!(store.increment("other", -2, window).await.unwrap().count, 0);
+
+        tokio::time::sleep(window).await;
+        assert_eq!(store.increment("key", 1, window).await.unwrap().count, 1);
+    }
+
+    #[test]
+    fn test_process_promo_image() {
+        use ::image::{DynamicImage, ImageOutputFormat, Rgb, RgbImage, Rgba, RgbaImage};
+        use error::AppError;
+        use std::io::Cursor;
+        use utils::promo_image::*;
+
+        fn encode_png(img: DynamicImage) -> Vec<u8> {
+            let mut bytes = Vec::new();
+            img.write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png)
+                .unwrap();
+            bytes
+        }
+
+        // Opaque images are scaled down to fit and re-encoded as jpeg.
+        let bytes = encode_png(DynamicImage::ImageRgb8(RgbImage::from_pixel(
+            1600,
+            800,
+            Rgb([255, 0, 0]),
+        )));
+        let PromoImage { image, thumbnail } = process_promo_image(&bytes).unwrap();
+        assert_eq!(image.1, "image/jpeg");
+        let img = ::image::load_from_memory(&image.0).unwrap();
+        assert_eq!((img.width(), img.height()), (IMAGE_SIZE, IMAGE_SIZE / 2));
+        let img = ::image::load_from_memory(&thumbnail.0).unwrap();
+        assert_eq!(
+            (img.width(), img.height()),
+            (THUMBNAIL_SIZE, THUMBNAIL_SIZE / 2)
+        );
+
+        // Images with transparency stay png and aren't scaled up.
+        let bytes = encode_png(DynamicImage::ImageRgba8(RgbaImage::from_pixel(
+            400,
+            400,
+            Rgba([0, 0, 255, 128]),
+        )));
+        let PromoImage { image, .. } = process_promo_image(&bytes).unwrap();
+        assert_eq!(image.1, "image/png");
+        let img = ::image::load_from_memory(&image.0).unwrap();
+        assert_eq!((img.width(), img.height()), (400, 400));
+
+        let bytes = encode_png(DynamicImage::ImageRgb8(RgbImage::new(100, 400)));
+        assert!(matches!(
+            process_promo_image(&bytes),
+            Err(AppError::InvalidImage(_))
+        ));
+        assert!(matches!(
+            process_promo_image(b"not an image"),
+            Err(AppError::InvalidImage(_))
+        ));
+        assert!(matches!(
+            process_promo_image(&vec![0; MAX_IMAGE_BYTES + 1]),
+            Err(AppError::ImageTooLarge(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_create_promo_image_too_large() {
+        use utils::promo_image::MAX_IMAGE_BYTES;
+
+        let member = Pubkey::new_unique();
+        let group_seed = Pubkey::new_unique();
+        let (group, _group_bump) = find_group_address(&group_seed);
+        let data = Arc::new(FakeDataSource::default());
+        data.insert_group(PromoGroupObject {
+            id: group,
+            owner: member,
+            members: vec![member],
+            group_profile_object: None,
+        });
+        let state = create_fake_state(Keypair::new(), Arc::new(FakeSolanaRpc::default()), data);
+        let (token, _) = state.create_auth_session(member, String::new());
+        let app = create_app(state);
+
+        let metadata = json!({
+            "name": "buyXProduct",
+            "symbol": "PROD",
+            "description": "bokoup test promo - product",
+            "attributes": [
+                { "trait_type": "promoType", "value": "buyXProductGetYFree" },
+                { "trait_type": "productId", "value": "0E9DCHTY6P7M2" },
+                { "trait_type": "buyXProduct", "value": 3 },
+                { "trait_type": "getYProduct", "value": 1 },
+                { "trait_type": "maxMint", "value": 1000 },
+                { "trait_type": "maxBurn", "value": 500 },
+            ],
+            "collection": { "name": "Product Promo", "family": "Test Merchant Promos" },
+        });
+        let boundary = "promo-boundary";
+        let mut body = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"metadata\"\r\n\r\n{}\r\n\
+                --{boundary}\r\nContent-Disposition: form-data; name=\"image\"; \
+                filename=\"image.png\"\r\n\r\n",
+            metadata,
+            boundary = boundary
+        )
+        .into_bytes();
+        body.extend(vec![0; MAX_IMAGE_BYTES + 1]);
+        body.extend(format!("\r\n--{}--\r\n", boundary).into_bytes());
+
+        // Images are rejected once they are over the limit, without reading the rest.
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(format!("/promo/create/{}/{}", member, group_seed))
+            .header(header::HOST, "tx.bokoup.dev")
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .header(
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={}", boundary),
+            )
+            .body(Body::from(body))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[test]
+    fn test_get_tradable_mint_owner() {
+        use error::AppError;
+
+        let mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut token_account = TokenAccountObject {
+            id: Pubkey::new_unique(),
+            owner,
+            mint,
+            amount: 1,
+            token_program: anchor_spl::token::ID,
+            referral_object: None,
+            mint_object: Some(create_mint_object(&mint, &Pubkey::new_unique(), &[])),
+        };
+        assert_eq!(
+            token_account.get_tradable_mint_owner().unwrap(),
+            (mint, owner)
+        );
+
+        token_account.token_program = bpl_token_metadata::token_2022::ID;
+        assert!(matches!(
+            token_account.get_tradable_mint_owner(),
+            Err(AppError::Token2022Unsupported(_))
+        ));
+
+        token_account
+            .mint_object
+            .as_mut()
+            .unwrap()
+            .promo_object
+            .as_mut()
+            .unwrap()
+            .promo_mint_object = Some(PromoMintObject {
+            non_transferable: true,
+        });
+        assert!(matches!(
+            token_account.get_tradable_mint_owner(),
+            Err(AppError::PromoNotTransferable)
+        ));
+    }
+
+    #[test]
+    fn test_ttl_store() {
+        use utils::store::TtlStore;
+
+        let store = TtlStore::new(Duration::from_millis(50));
+        store.insert(
+            "signature".to_string(),
+            PayResponse {
+                transaction: "transaction".to_string(),
+                message: MESSAGE.to_string(),
+            },
+        );
+        assert_eq!(store.get("signature").unwrap().message, MESSAGE);
+        assert!(store.get("other").is_none());
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(store.get("signature").is_none());
+
+        // Entries aren't inserted over capacity until others expire.
+        let value = PayResponse {
+            transaction: "transaction".to_string(),
+            message: MESSAGE.to_string(),
+        };
+        assert!(store.insert_within_capacity("first".to_string(), value.clone(), 1));
+        assert!(!store.insert_within_capacity("second".to_string(), value.clone(), 1));
+        assert!(store.get("second").is_none());
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(store.insert_within_capacity("second".to_string(), value, 1));
+    }
+
+    #[test]
+    fn test_find_group_nonce_address() {
+        let platform_signer = Pubkey::new_unique();
+        let group = Pubkey::new_unique();
+        let (nonce, seed) = find_group_nonce_address(&platform_signer, &group);
+        assert_eq!(seed.len(), 32);
+        assert_eq!(
+            nonce,
+            Pubkey::create_with_seed(&platform_signer, &seed, &anchor_lang::system_program::ID)
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_create_group_nonce_tx() {
+        let member = Pubkey::new_unique();
+        let group_seed = Pubkey::new_unique();
+        let (group, _group_bump) = find_group_address(&group_seed);
+
+        let rpc = Arc::new(FakeSolanaRpc::default());
+        let data = Arc::new(FakeDataSource::default());
+        data.insert_group(PromoGroupObject {
+            id: group,
+            owner: Pubkey::new_unique(),
+            members: vec![member],
+            group_profile_object: None,
+        });
+        let app = create_app(Arc::new(create_fake_state(Keypair::new(), rpc, data)));
+
+        let post = |account: Pubkey| {
+            let uri = format!("/promo/nonce/{}/{}", group_seed, MESSAGE);
+            let app = app.clone();
+            async move {
+                send(
+                    &app,
+                    Method::POST,
+                    &uri,
+                    None,
+                    Some(json!({ "account": account.to_string() })),
+                )
+                .await
+            }
+        };
+
+        let response = post(member).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let parsed_response: PayResponse = parse(&response);
+        let tx: Transaction =
+            bincode::deserialize(&base64::decode(parsed_response.transaction).unwrap()).unwrap();
+        assert_eq!(tx.message.account_keys[0], member);
+
+        // The platform signer only signs nonce accounts for members of the group.
+        let response = post(Pubkey::new_unique()).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_nonce_transaction() {
+        let platform_signer = Keypair::new();
+        let member = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let group = Pubkey::new_unique();
+        let token_owner = Pubkey::new_unique();
+        let members = [member, platform_signer.pubkey()];
+
+        let rpc = Arc::new(FakeSolanaRpc::default());
+        let data = Arc::new(FakeDataSource::default());
+        data.insert_mint(create_mint_object(&mint, &group, &members));
+        data.insert_group(PromoGroupObject {
+            id: group,
+            owner: member,
+            members: members.to_vec(),
+            group_profile_object: None,
+        });
+        let (nonce, _seed) = find_group_nonce_address(&platform_signer.pubkey(), &group);
+        let nonce_blockhash = solana_sdk::hash::Hash::new_unique();
+        rpc.nonce_blockhashes
+            .lock()
+            .unwrap()
+            .insert(nonce, nonce_blockhash);
+        let app = create_app(create_fake_state(platform_signer, rpc, data));
+
+        let post = |uri: String, account: Pubkey| {
+            let app = app.clone();
+            async move {
+                send(
+                    &app,
+                    Method::POST,
+                    &uri,
+                    None,
+                    Some(json!({ "account": account.to_string() })),
+                )
+                .await
+            }
+        };
+        let message = urlencoding::encode(MESSAGE);
+
+        // Merchants approving mints can wait for customers past the blockhash expiry.
+        let uri = format!(
+            "/promo/merchant/mint/{}/{}/{}?nonce=true",
+            mint, token_owner, message
+        );
+        let response = post(uri.clone(), member).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let parsed_response: PayResponse = parse(&response);
+        let tx: Transaction =
+            bincode::deserialize(&base64::decode(parsed_response.transaction).unwrap()).unwrap();
+        assert_eq!(tx.message.recent_blockhash, nonce_blockhash);
+        assert_eq!(tx.message.account_keys[0], member);
+
+        let response = post(uri, Pubkey::new_unique()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // Sponsored transactions can be landed by anyone, so they can't use the group's nonce.
+        let uri = format!("/promo/mint/{}/{}?nonce=true", mint, message);
+        let response = post(uri, token_owner).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_get_simulation_error() {
+        use bpl_token_metadata::error::ProgramError;
+        use error::SimulationError;
+
+        let payer = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let group = Pubkey::new_unique();
+        let instruction = create_mint_promo_instruction(payer, group, payer, mint, None).unwrap();
+        let tx = Transaction::new_with_payer(&[instruction], Some(&payer));
+
+        let err = serde_json::json!({ "InstructionError": [0, { "Custom": 6000 }] });
+        let error = get_simulation_error(&tx, &err, &[]);
+        assert!(matches!(
+            error,
+            SimulationError::Program(ProgramError::MaxMintExceeded)
+        ));
+        assert_eq!(error.to_string(), "Max mints exceeded");
+
+        // Loyalty promos can't be minted.
+        let err = serde_json::json!({ "InstructionError": [0, { "Custom": 6011 }] });
+        assert!(matches!(
+            get_simulation_error(&tx, &err, &[]),
+            SimulationError::Program(ProgramError::LoyaltyPointsEarnedOnly)
+        ));
+
+        // Redeeming groups only pay settlements they approved.
+        let err = serde_json::json!({ "InstructionError": [0, { "Custom": 6012 }] });
+        assert!(matches!(
+            get_simulation_error(&tx, &err, &[]),
+            SimulationError::Program(ProgramError::SettlementNotApproved)
+        ));
+
+        // Groups are only settled up to the allowance signed by their owner.
+        let err = serde_json::json!({ "InstructionError": [0, { "Custom": 6013 }] });
+        assert!(matches!(
+            get_simulation_error(&tx, &err, &[]),
+            SimulationError::Program(ProgramError::SponsorshipAllowanceExceeded)
+        ));
+
+        let err = serde_json::json!({ "InstructionError": [0, { "Custom": 2003 }] });
+        let logs = vec![
+            "Program log: Instruction: MintPromoToken".to_string(),
+            "Program log: AnchorError caused by account: group. Error Code: ConstraintRaw. \
+                Error Number: 2003. Error Message: A raw constraint was violated."
+                .to_string(),
+        ];
+        match get_simulation_error(&tx, &err, &logs) {
+            SimulationError::Anchor {
+                code,
+                name,
+                message,
+            } => {
+                assert_eq!(code, 2003);
+                assert_eq!(name, "ConstraintRaw");
+                assert_eq!(message, "A raw constraint was violated");
+            }
+            error => panic!("unexpected error: {}", error),
+        }
+
+        let err = serde_json::json!("AccountNotFound");
+        assert!(matches!(
+            get_simulation_error(&tx, &err, &[]),
+            SimulationError::Other(_)
+        ));
+    }
+
+    #[test]
+    fn test_compute_budget() {
+        use utils::compute_budget::*;
+
+        let query: TransactionQuery = serde_json::from_value(serde_json::json!({
+            "nonce": true,
+            "computeUnitPrice": 1000,
+        }))
+        .unwrap();
+        assert!(query.nonce);
+        assert_eq!(query.compute_unit_price, Some(1000));
+        assert!(query.compute_unit_limit.is_none());
+
+        let instruction = set_compute_unit_limit(300_000);
+        assert_eq!(instruction.program_id, ID);
+        assert_eq!(instruction.data, vec![2, 224, 147, 4, 0]);
+        let instruction = set_compute_unit_price(1);
+        assert_eq!(instruction.data, vec![3, 1, 0, 0, 0, 0, 0, 0, 0]);
+
+        assert_eq!(get_compute_unit_price(vec![], 100), 0);
+        assert_eq!(get_compute_unit_price(vec![40, 10, 30, 20, 50], 100), 40);
+        assert_eq!(get_compute_unit_price(vec![40, 10, 30, 20, 500], 100), 40);
+        assert_eq!(
+            get_compute_unit_price(vec![400, 100, 300, 200, 500], 100),
+            100
+        );
+    }
+
+    #[test]
+    fn test_compile_v0_message() {
+        use utils::{
+            lookup_table::get_platform_lookup_table_addresses,
+            postgres::PostgresDataSource,
+            versioned::{MessageV0, VersionedTransaction},
+        };
+
+        let payer = Keypair::new();
+        let group = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let lookup_table = Pubkey::new_unique();
+        let addresses = get_platform_lookup_table_addresses();
+
+        let instruction =
+            create_mint_promo_instruction(payer.pubkey(), group, payer.pubkey(), mint, None)
+                .unwrap();
+        let tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        let message = MessageV0::compile(&tx.message, &lookup_table, &addresses);
+
+        // The invoked program stays static while the other program ids and sysvars are loaded.
+        assert_eq!(
+            message.header.num_required_signatures,
+            tx.message.header.num_required_signatures
+        );
+        assert!(message.account_keys.contains(&bpl_token_metadata::id()));
+        assert!(!message.account_keys.contains(&anchor_spl::token::ID));
+        assert_eq!(message.address_table_lookups.len(), 1);
+        let lookup = &message.address_table_lookups[0];
+        assert_eq!(lookup.account_key, lookup_table);
+        assert_eq!(
+            message.account_keys.len()
+                + lookup.writable_indexes.len()
+                + lookup.readonly_indexes.len(),
+            tx.message.account_keys.len()
+        );
+
+        // Instructions reference the same accounts after being compiled.
+        let loaded: Vec<Pubkey> = message
+            .account_keys
+            .iter()
+            .copied()
+            .chain(
+                lookup
+                    .writable_indexes
+                    .iter()
+                    .map(|i| addresses[*i as usize]),
+            )
+            .chain(
+                lookup
+                    .readonly_indexes
+                    .iter()
+                    .map(|i| addresses[*i as usize]),
+            )
+            .collect();
+        let legacy_accounts: Vec<Pubkey> = tx.message.instructions[0]
+            .accounts
+            .iter()
+            .map(|i| tx.message.account_keys[*i as usize])
+            .collect();
+        let v0_accounts: Vec<Pubkey> = message.instructions[0]
+            .accounts
+            .iter()
+            .map(|i| loaded[*i as usize])
+            .collect();
+        assert_eq!(legacy_accounts, v0_accounts);
+
+        let mut versioned_tx = VersionedTransaction::new(message);
+        versioned_tx.try_partial_sign(&[&payer]).unwrap();
+        let serialized = versioned_tx.serialize();
+        assert_eq!(serialized[0], 1);
+        assert_eq!(serialized[1 + 64], 0x80);
+        assert!(versioned_tx.signatures[0]
+            .verify(payer.pubkey().as_ref(), &versioned_tx.message.serialize()));
+    }
 }
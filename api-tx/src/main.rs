@@ -1,7 +1,31 @@
 use anchor_lang::prelude::Pubkey;
-use bpl_api_tx::{create_app, parse_string_to_keypair, utils::solana::Cluster};
-use clap::Parser;
-use std::{net::SocketAddr, str::FromStr};
+use bpl_api_tx::{
+    create_app, parse_string_to_keypair,
+    utils::{
+        auth::DEFAULT_SIGN_IN_URL,
+        bundlr::{
+            spawn_bundlr_monitor, BundlrFundingConfig, DEFAULT_BUNDLR_MIN_BALANCE,
+            DEFAULT_BUNDLR_POLL_INTERVAL,
+        },
+        clients::DataConfig,
+        compute_budget::{
+            ComputeBudgetConfig, DEFAULT_COMPUTE_UNIT_LIMIT, DEFAULT_MAX_COMPUTE_UNIT_PRICE,
+        },
+        rate_limit::{
+            RateLimitConfig, RateLimiter, DEFAULT_IP_RATE_LIMIT, DEFAULT_WALLET_RATE_LIMIT,
+        },
+        solana::Cluster,
+        sponsorship::{spawn_sponsorship_tasks, SettlementConfig, DEFAULT_SETTLEMENT_MIN_LAMPORTS},
+        storage::StorageConfig,
+        webhooks::{
+            spawn_webhook_tasks, WebhookConfig, DEFAULT_LOW_SUPPLY_PERCENT,
+            DEFAULT_WEBHOOK_MAX_ATTEMPTS,
+        },
+    },
+    State,
+};
+use clap::{ArgEnum, Parser};
+use std::{net::SocketAddr, path::PathBuf, str::FromStr, sync::Arc, time::Duration};
 use tracing_subscriber::prelude::*;
 use url::Url;
 
@@ -18,6 +42,142 @@ struct Args {
     platform: Pubkey,
     #[clap(long, env = "PLATFORM_SIGNER_KEYPAIR")]
     platform_signer: String,
+    /// Compute unit limit for transactions that don't request one.
+    #[clap(long, env = "COMPUTE_UNIT_LIMIT", default_value_t = DEFAULT_COMPUTE_UNIT_LIMIT)]
+    compute_unit_limit: u32,
+    /// Cap on the compute unit limit, including requested limits, since the platform signer pays
+    /// the price for each unit of sponsored transactions.
+    #[clap(
+        long,
+        env = "MAX_COMPUTE_UNIT_LIMIT",
+        default_value_t = DEFAULT_COMPUTE_UNIT_LIMIT
+    )]
+    max_compute_unit_limit: u32,
+    /// Cap on the compute unit price in micro-lamports, including requested prices.
+    #[clap(
+        long,
+        env = "MAX_COMPUTE_UNIT_PRICE",
+        default_value_t = DEFAULT_MAX_COMPUTE_UNIT_PRICE
+    )]
+    max_compute_unit_price: u64,
+    /// Platform address lookup table used for v0 transactions.
+    #[clap(long, env = "LOOKUP_TABLE", value_parser)]
+    lookup_table: Option<Pubkey>,
+    /// Whether to read indexed data from the graphql data api or directly from Postgres.
+    #[clap(long, env = "DATA_SOURCE", arg_enum, default_value_t = DataSourceKind::Graphql, value_parser)]
+    data_source: DataSourceKind,
+    /// Postgres database written by the indexer, required for the postgres data source.
+    #[clap(long, env = "PG_DATABASE_URL")]
+    pg_database_url: Option<String>,
+    #[clap(long, env = "PG_POOL_SIZE", default_value_t = 10)]
+    pg_pool_size: usize,
+    /// Where promo images and metadata json are uploaded.
+    #[clap(long, env = "STORAGE", arg_enum, default_value_t = StorageKind::Bundlr, value_parser)]
+    storage: StorageKind,
+    #[clap(
+        long,
+        env = "BUNDLR_URL",
+        default_value = "https://node1.bundlr.network"
+    )]
+    bundlr_url: Url,
+    /// Seconds between reads of the platform signer's bundlr balance.
+    #[clap(
+        long,
+        env = "BUNDLR_POLL_SECONDS",
+        default_value_t = DEFAULT_BUNDLR_POLL_INTERVAL.as_secs()
+    )]
+    bundlr_poll_seconds: u64,
+    /// Bundlr balance in lamports below which an alert is logged and the node funded.
+    #[clap(long, env = "BUNDLR_MIN_BALANCE", default_value_t = DEFAULT_BUNDLR_MIN_BALANCE)]
+    bundlr_min_balance: u64,
+    /// Lamports to fund the bundlr node with from the platform signer when the balance is low,
+    /// the node isn't funded automatically if not set.
+    #[clap(long, env = "BUNDLR_FUND_LAMPORTS")]
+    bundlr_fund_lamports: Option<u64>,
+    #[clap(long, env = "S3_BUCKET")]
+    s3_bucket: Option<String>,
+    #[clap(long, env = "S3_REGION", default_value = "us-east-1")]
+    s3_region: String,
+    /// Endpoint for S3 compatible services other than AWS.
+    #[clap(long, env = "S3_ENDPOINT")]
+    s3_endpoint: Option<String>,
+    /// Base url uploaded objects are publicly readable from.
+    #[clap(long, env = "S3_PUBLIC_URL")]
+    s3_public_url: Option<Url>,
+    #[clap(long, env = "IPFS_API_URL", default_value = "http://127.0.0.1:5001")]
+    ipfs_api_url: Url,
+    #[clap(long, env = "IPFS_GATEWAY_URL", default_value = "https://ipfs.io")]
+    ipfs_gateway_url: Url,
+    #[clap(long, env = "STORAGE_DIR", default_value = "storage")]
+    storage_dir: PathBuf,
+    /// Base url files in the storage directory are served from, defaults to file urls.
+    #[clap(long, env = "STORAGE_BASE_URL")]
+    storage_base_url: Option<Url>,
+    /// Requests per minute per client ip to endpoints paid for by the platform signer, 0 for
+    /// no limit.
+    #[clap(long, env = "IP_RATE_LIMIT", default_value_t = DEFAULT_IP_RATE_LIMIT)]
+    ip_rate_limit: u64,
+    /// Requests per minute per wallet to endpoints paid for by the platform signer, 0 for no
+    /// limit.
+    #[clap(long, env = "WALLET_RATE_LIMIT", default_value_t = DEFAULT_WALLET_RATE_LIMIT)]
+    wallet_rate_limit: u64,
+    /// Transactions paid for by the platform signer per group per day.
+    #[clap(long, env = "GROUP_DAILY_BUDGET")]
+    group_daily_budget: Option<u64>,
+    /// Comma separated client ips, wallets and groups denied transactions paid for by the
+    /// platform signer.
+    #[clap(long, env = "DENYLIST", use_value_delimiter = true)]
+    denylist: Vec<String>,
+    /// Number of proxies, such as a load balancer, appending client ips to the X-Forwarded-For
+    /// header. Client ips are read from the header if set.
+    #[clap(long, env = "TRUSTED_PROXIES", default_value_t = 0)]
+    trusted_proxies: usize,
+    /// Url of the server sign-in messages are issued for, which wallets check against the site
+    /// asking them to sign. Sign-in nonces are only issued for requests to its host.
+    #[clap(long, env = "SIGN_IN_URL", default_value = DEFAULT_SIGN_IN_URL)]
+    sign_in_url: Url,
+    /// Redis for rate limit counters shared between instances, kept in memory if not set.
+    #[clap(long, env = "REDIS_URL")]
+    redis_url: Option<Url>,
+    /// Hours between settlements of groups that reimburse the platform for sponsored
+    /// transactions, no settlements if not set.
+    #[clap(long, env = "SETTLEMENT_INTERVAL_HOURS")]
+    settlement_interval_hours: Option<u64>,
+    /// Lamports a group needs to owe to be settled.
+    #[clap(
+        long,
+        env = "SETTLEMENT_MIN_LAMPORTS",
+        default_value_t = DEFAULT_SETTLEMENT_MIN_LAMPORTS
+    )]
+    settlement_min_lamports: u64,
+    /// Seconds between polls for promo events to deliver to merchant webhooks, webhooks aren't
+    /// delivered if not set.
+    #[clap(long, env = "WEBHOOK_POLL_SECONDS")]
+    webhook_poll_seconds: Option<u64>,
+    /// Attempts to deliver a webhook before it's marked failed.
+    #[clap(
+        long,
+        env = "WEBHOOK_MAX_ATTEMPTS",
+        default_value_t = DEFAULT_WEBHOOK_MAX_ATTEMPTS
+    )]
+    webhook_max_attempts: u32,
+    /// Percent of a promo's max mint remaining at which a low supply event is sent.
+    #[clap(long, env = "LOW_SUPPLY_PERCENT", default_value_t = DEFAULT_LOW_SUPPLY_PERCENT)]
+    low_supply_percent: u64,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ArgEnum)]
+enum DataSourceKind {
+    Graphql,
+    Postgres,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ArgEnum)]
+enum StorageKind {
+    Bundlr,
+    S3,
+    Ipfs,
+    Local,
 }
 
 #[tokio::main]
@@ -34,17 +194,110 @@ async fn main() {
 
     let platform_signer = parse_string_to_keypair(&args.platform_signer);
 
-    let data_url: Url = Url::from_str(match args.cluster {
-        Cluster::Devnet => "https://data.api.bokoup.dev/v1/graphql/",
-        _ => "https://shining-sailfish-15.hasura.app/v1/graphql/",
-    })
-    .unwrap();
+    let data_config = match args.data_source {
+        DataSourceKind::Graphql => DataConfig::Graphql(
+            Url::from_str(match args.cluster {
+                Cluster::Devnet => "https://data.api.bokoup.dev/v1/graphql/",
+                _ => "https://shining-sailfish-15.hasura.app/v1/graphql/",
+            })
+            .unwrap(),
+        ),
+        DataSourceKind::Postgres => DataConfig::Postgres {
+            database_url: args
+                .pg_database_url
+                .expect("pg-database-url is required for the postgres data source"),
+            pool_size: args.pg_pool_size,
+        },
+    };
+
+    let storage_config = match args.storage {
+        StorageKind::Bundlr => StorageConfig::Bundlr(args.bundlr_url),
+        StorageKind::S3 => StorageConfig::S3 {
+            bucket: args
+                .s3_bucket
+                .expect("s3-bucket is required for s3 storage"),
+            region: args.s3_region,
+            endpoint: args.s3_endpoint,
+            public_url: args
+                .s3_public_url
+                .expect("s3-public-url is required for s3 storage"),
+        },
+        StorageKind::Ipfs => StorageConfig::Ipfs {
+            api_url: args.ipfs_api_url,
+            gateway_url: args.ipfs_gateway_url,
+        },
+        StorageKind::Local => {
+            std::fs::create_dir_all(&args.storage_dir).unwrap();
+            let dir = args.storage_dir.canonicalize().unwrap();
+            StorageConfig::Local {
+                base_url: args
+                    .storage_base_url
+                    .unwrap_or_else(|| Url::from_directory_path(&dir).unwrap()),
+                dir,
+            }
+        }
+    };
+
+    let compute_budget = ComputeBudgetConfig {
+        compute_unit_limit: args.compute_unit_limit,
+        max_compute_unit_limit: args.max_compute_unit_limit,
+        max_compute_unit_price: args.max_compute_unit_price,
+    };
+
+    let rate_limit_config = RateLimitConfig {
+        ip_limit: Some(args.ip_rate_limit).filter(|limit| *limit > 0),
+        wallet_limit: Some(args.wallet_rate_limit).filter(|limit| *limit > 0),
+        group_daily_budget: args.group_daily_budget,
+        denylist: args.denylist.into_iter().collect(),
+        trusted_proxies: args.trusted_proxies,
+        redis_url: args.redis_url,
+    };
+
+    let settlement = args
+        .settlement_interval_hours
+        .map(|hours| SettlementConfig {
+            interval: Duration::from_secs(hours * 60 * 60),
+            min_lamports: args.settlement_min_lamports,
+        });
+
+    let webhooks = args.webhook_poll_seconds.map(|seconds| WebhookConfig {
+        poll_interval: Duration::from_secs(seconds),
+        max_attempts: args.webhook_max_attempts,
+        low_supply_percent: args.low_supply_percent,
+        allow_private_addresses: false,
+    });
+
+    let state = Arc::new(State {
+        rate_limiter: RateLimiter::new(rate_limit_config).unwrap(),
+        sign_in_url: args.sign_in_url,
+        ..State::new(
+            args.cluster,
+            args.platform,
+            platform_signer,
+            data_config,
+            storage_config,
+            compute_budget,
+            args.lookup_table,
+        )
+    });
+    spawn_sponsorship_tasks(state.clone(), settlement);
+    spawn_bundlr_monitor(
+        state.clone(),
+        BundlrFundingConfig {
+            poll_interval: Duration::from_secs(args.bundlr_poll_seconds),
+            min_balance: args.bundlr_min_balance,
+            fund_lamports: args.bundlr_fund_lamports,
+        },
+    );
+    if let Some(webhooks) = webhooks {
+        spawn_webhook_tasks(state.clone(), webhooks);
+    }
 
-    let app = create_app(args.cluster, args.platform, platform_signer, data_url);
+    let app = create_app(state);
     let addr = SocketAddr::from(([0, 0, 0, 0], 8080));
     tracing::debug!("listening on {}", addr);
     axum::Server::bind(&addr)
-        .serve(app.into_make_service())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
         .await
         .unwrap();
 }
@@ -0,0 +1,360 @@
+//! Instructions for trading promo tokens on the platform auction house.
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        sysvar,
+    },
+    system_program, InstructionData, ToAccountMetas,
+};
+use bpl_token_metadata::utils::{find_associated_token_address, find_metadata_address};
+use mpl_auction_house::{
+    accounts::{
+        Buy as buy_accounts, Cancel as cancel_accounts,
+        CancelListingReceipt as cancel_listing_receipt_accounts,
+        ExecuteSale as execute_sale_accounts, PrintBidReceipt as print_bid_receipt_accounts,
+        PrintListingReceipt as print_listing_receipt_accounts,
+        PrintPurchaseReceipt as print_purchase_receipt_accounts, Sell as sell_accounts,
+    },
+    instruction::{
+        Buy as buy_instruction, Cancel as cancel_instruction,
+        CancelListingReceipt as cancel_listing_receipt_instruction,
+        ExecuteSale as execute_sale_instruction, PrintBidReceipt as print_bid_receipt_instruction,
+        PrintListingReceipt as print_listing_receipt_instruction,
+        PrintPurchaseReceipt as print_purchase_receipt_instruction, Sell as sell_instruction,
+    },
+    pda::{
+        find_auction_house_fee_account_address, find_auction_house_treasury_address,
+        find_bid_receipt_address, find_escrow_payment_address, find_listing_receipt_address,
+        find_program_as_signer_address, find_purchase_receipt_address, find_trade_state_address,
+    },
+};
+use spl_token::native_mint;
+
+/// Promo tokens are traded one at a time.
+pub const TOKEN_SIZE: u64 = 1;
+
+fn get_trade_state(
+    wallet: &Pubkey,
+    auction_house: &Pubkey,
+    token_account: &Pubkey,
+    mint: &Pubkey,
+    price: u64,
+) -> (Pubkey, u8) {
+    find_trade_state_address(
+        wallet,
+        auction_house,
+        token_account,
+        &native_mint::ID,
+        mint,
+        price,
+        TOKEN_SIZE,
+    )
+}
+
+/// Marks `signer` as a signer. The auction house accepts either the wallet or the auction
+/// house authority as signer for some instructions, so their accounts don't mark the wallet.
+fn with_signer(mut accounts: Vec<AccountMeta>, signer: &Pubkey) -> Vec<AccountMeta> {
+    accounts
+        .iter_mut()
+        .filter(|account| account.pubkey == *signer)
+        .for_each(|account| account.is_signer = true);
+    accounts
+}
+
+/// Lists a promo token for sale at `price` lamports.
+pub fn create_sell_instructions(
+    auction_house: Pubkey,
+    authority: Pubkey,
+    seller: Pubkey,
+    token_account: Pubkey,
+    mint: Pubkey,
+    price: u64,
+) -> Vec<Instruction> {
+    let (metadata, _metadata_bump) = find_metadata_address(&mint);
+    let (auction_house_fee_account, _fee_bump) =
+        find_auction_house_fee_account_address(&auction_house);
+    let (seller_trade_state, trade_state_bump) =
+        get_trade_state(&seller, &auction_house, &token_account, &mint, price);
+    let (free_seller_trade_state, free_trade_state_bump) =
+        get_trade_state(&seller, &auction_house, &token_account, &mint, 0);
+    let (program_as_signer, program_as_signer_bump) = find_program_as_signer_address();
+    let (receipt, receipt_bump) = find_listing_receipt_address(&seller_trade_state);
+
+    let accounts = sell_accounts {
+        wallet: seller,
+        token_account,
+        metadata,
+        authority,
+        auction_house,
+        auction_house_fee_account,
+        seller_trade_state,
+        free_seller_trade_state,
+        token_program: spl_token::ID,
+        system_program: system_program::ID,
+        program_as_signer,
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(Some(true));
+
+    let data = sell_instruction {
+        trade_state_bump,
+        _free_trade_state_bump: free_trade_state_bump,
+        _program_as_signer_bump: program_as_signer_bump,
+        buyer_price: price,
+        token_size: TOKEN_SIZE,
+    }
+    .data();
+
+    let sell = Instruction {
+        program_id: mpl_auction_house::id(),
+        accounts: with_signer(accounts, &seller),
+        data,
+    };
+
+    let accounts = print_listing_receipt_accounts {
+        receipt,
+        bookkeeper: seller,
+        system_program: system_program::ID,
+        rent: sysvar::rent::id(),
+        instruction: sysvar::instructions::id(),
+    }
+    .to_account_metas(Some(true));
+
+    let data = print_listing_receipt_instruction { receipt_bump }.data();
+
+    let print_listing_receipt = Instruction {
+        program_id: mpl_auction_house::id(),
+        accounts,
+        data,
+    };
+
+    vec![sell, print_listing_receipt]
+}
+
+/// Cancels the listing of a promo token at `price` lamports.
+pub fn create_cancel_listing_instructions(
+    auction_house: Pubkey,
+    authority: Pubkey,
+    seller: Pubkey,
+    token_account: Pubkey,
+    mint: Pubkey,
+    price: u64,
+) -> Vec<Instruction> {
+    let (auction_house_fee_account, _fee_bump) =
+        find_auction_house_fee_account_address(&auction_house);
+    let (trade_state, _trade_state_bump) =
+        get_trade_state(&seller, &auction_house, &token_account, &mint, price);
+    let (receipt, _receipt_bump) = find_listing_receipt_address(&trade_state);
+
+    let accounts = cancel_accounts {
+        wallet: seller,
+        token_account,
+        token_mint: mint,
+        authority,
+        auction_house,
+        auction_house_fee_account,
+        trade_state,
+        token_program: spl_token::ID,
+    }
+    .to_account_metas(Some(true));
+
+    let data = cancel_instruction {
+        buyer_price: price,
+        token_size: TOKEN_SIZE,
+    }
+    .data();
+
+    let cancel = Instruction {
+        program_id: mpl_auction_house::id(),
+        accounts: with_signer(accounts, &seller),
+        data,
+    };
+
+    let accounts = cancel_listing_receipt_accounts {
+        receipt,
+        system_program: system_program::ID,
+        instruction: sysvar::instructions::id(),
+    }
+    .to_account_metas(Some(true));
+
+    let data = cancel_listing_receipt_instruction {}.data();
+
+    let cancel_listing_receipt = Instruction {
+        program_id: mpl_auction_house::id(),
+        accounts,
+        data,
+    };
+
+    vec![cancel, cancel_listing_receipt]
+}
+
+/// Bids `price` lamports for the promo token in `token_account`, escrowing the lamports in the
+/// auction house until the sale is executed.
+pub fn create_buy_instructions(
+    auction_house: Pubkey,
+    authority: Pubkey,
+    buyer: Pubkey,
+    token_account: Pubkey,
+    mint: Pubkey,
+    price: u64,
+) -> Vec<Instruction> {
+    let (metadata, _metadata_bump) = find_metadata_address(&mint);
+    let (auction_house_fee_account, _fee_bump) =
+        find_auction_house_fee_account_address(&auction_house);
+    let (escrow_payment_account, escrow_payment_bump) =
+        find_escrow_payment_address(&auction_house, &buyer);
+    let (buyer_trade_state, trade_state_bump) =
+        get_trade_state(&buyer, &auction_house, &token_account, &mint, price);
+    let (receipt, receipt_bump) = find_bid_receipt_address(&buyer_trade_state);
+
+    let accounts = buy_accounts {
+        wallet: buyer,
+        payment_account: buyer,
+        transfer_authority: buyer,
+        treasury_mint: native_mint::ID,
+        token_account,
+        metadata,
+        escrow_payment_account,
+        authority,
+        auction_house,
+        auction_house_fee_account,
+        buyer_trade_state,
+        token_program: spl_token::ID,
+        system_program: system_program::ID,
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(Some(true));
+
+    let data = buy_instruction {
+        trade_state_bump,
+        escrow_payment_bump,
+        buyer_price: price,
+        token_size: TOKEN_SIZE,
+    }
+    .data();
+
+    let buy = Instruction {
+        program_id: mpl_auction_house::id(),
+        accounts,
+        data,
+    };
+
+    let accounts = print_bid_receipt_accounts {
+        receipt,
+        bookkeeper: buyer,
+        system_program: system_program::ID,
+        rent: sysvar::rent::id(),
+        instruction: sysvar::instructions::id(),
+    }
+    .to_account_metas(Some(true));
+
+    let data = print_bid_receipt_instruction { receipt_bump }.data();
+
+    let print_bid_receipt = Instruction {
+        program_id: mpl_auction_house::id(),
+        accounts,
+        data,
+    };
+
+    vec![buy, print_bid_receipt]
+}
+
+/// Executes the sale of the promo token in `token_account` to `buyer` at `price` lamports,
+/// matching a listing and a bid at the same price. Either the buyer or the seller can sign as
+/// `signer`, paying for the buyer's token account if it needs to be created.
+pub fn create_execute_sale_instructions(
+    auction_house: Pubkey,
+    authority: Pubkey,
+    signer: Pubkey,
+    seller: Pubkey,
+    buyer: Pubkey,
+    token_account: Pubkey,
+    mint: Pubkey,
+    price: u64,
+) -> Vec<Instruction> {
+    let (metadata, _metadata_bump) = find_metadata_address(&mint);
+    let (auction_house_fee_account, _fee_bump) =
+        find_auction_house_fee_account_address(&auction_house);
+    let (auction_house_treasury, _treasury_bump) =
+        find_auction_house_treasury_address(&auction_house);
+    let (escrow_payment_account, escrow_payment_bump) =
+        find_escrow_payment_address(&auction_house, &buyer);
+    let buyer_receipt_token_account = find_associated_token_address(&buyer, &mint);
+    let (buyer_trade_state, _buyer_trade_state_bump) =
+        get_trade_state(&buyer, &auction_house, &token_account, &mint, price);
+    let (seller_trade_state, _seller_trade_state_bump) =
+        get_trade_state(&seller, &auction_house, &token_account, &mint, price);
+    let (free_trade_state, free_trade_state_bump) =
+        get_trade_state(&seller, &auction_house, &token_account, &mint, 0);
+    let (program_as_signer, program_as_signer_bump) = find_program_as_signer_address();
+    let (listing_receipt, _listing_receipt_bump) =
+        find_listing_receipt_address(&seller_trade_state);
+    let (bid_receipt, _bid_receipt_bump) = find_bid_receipt_address(&buyer_trade_state);
+    let (purchase_receipt, purchase_receipt_bump) =
+        find_purchase_receipt_address(&seller_trade_state, &buyer_trade_state);
+
+    let accounts = execute_sale_accounts {
+        buyer,
+        seller,
+        token_account,
+        token_mint: mint,
+        metadata,
+        treasury_mint: native_mint::ID,
+        escrow_payment_account,
+        seller_payment_receipt_account: seller,
+        buyer_receipt_token_account,
+        authority,
+        auction_house,
+        auction_house_fee_account,
+        auction_house_treasury,
+        buyer_trade_state,
+        seller_trade_state,
+        free_trade_state,
+        token_program: spl_token::ID,
+        system_program: system_program::ID,
+        ata_program: anchor_spl::associated_token::ID,
+        program_as_signer,
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(Some(true));
+
+    let data = execute_sale_instruction {
+        escrow_payment_bump,
+        _free_trade_state_bump: free_trade_state_bump,
+        program_as_signer_bump,
+        buyer_price: price,
+        token_size: TOKEN_SIZE,
+    }
+    .data();
+
+    let execute_sale = Instruction {
+        program_id: mpl_auction_house::id(),
+        accounts: with_signer(accounts, &signer),
+        data,
+    };
+
+    let accounts = print_purchase_receipt_accounts {
+        purchase_receipt,
+        listing_receipt,
+        bid_receipt,
+        bookkeeper: signer,
+        system_program: system_program::ID,
+        rent: sysvar::rent::id(),
+        instruction: sysvar::instructions::id(),
+    }
+    .to_account_metas(Some(true));
+
+    let data = print_purchase_receipt_instruction {
+        purchase_receipt_bump,
+    }
+    .data();
+
+    let print_purchase_receipt = Instruction {
+        program_id: mpl_auction_house::id(),
+        accounts,
+        data,
+    };
+
+    vec![execute_sale, print_purchase_receipt]
+}
@@ -0,0 +1,250 @@
+//! Sign-In-With-Solana sessions for merchant endpoints.
+
+use anchor_lang::prelude::Pubkey;
+use async_trait::async_trait;
+use axum::{
+    extract::{FromRequest, RequestParts},
+    http::header,
+};
+use solana_sdk::{signature::Signature, signer::keypair::Keypair, signer::Signer};
+use std::{
+    str::FromStr,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use url::Url;
+
+use crate::{
+    error::AppError,
+    utils::{
+        data::PromoGroupObject,
+        store::{AUTH_SESSION_TTL, SIGN_IN_NONCE_CAPACITY, SIGN_IN_NONCE_TTL},
+    },
+    State,
+};
+
+/// Message issued for an account to sign, keyed by its nonce.
+#[derive(Debug, Clone)]
+pub struct SignInChallenge {
+    pub account: Pubkey,
+    pub message: String,
+    /// Seconds since the unix epoch.
+    pub expires_at: u64,
+}
+
+/// Signed in account, extracted from the bearer token of requests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthSession {
+    pub account: Pubkey,
+    /// Signature of the sign-in message, kept as proof of who made changes with the session.
+    pub signature: String,
+    /// Seconds since the unix epoch.
+    pub expires_at: u64,
+}
+
+/// Url of the server sign-in messages are issued for unless configured otherwise.
+pub const DEFAULT_SIGN_IN_URL: &str = "https://tx.bokoup.dev";
+
+/// Returns the domain of `url` as used in sign-in messages, its host and any port.
+pub fn get_sign_in_domain(url: &Url) -> String {
+    let host = url.host_str().unwrap_or_default();
+    match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    }
+}
+
+/// Returns the message an account signs to sign in, in the Sign-In-With-Solana format.
+pub fn get_sign_in_message(
+    domain: &str,
+    uri: &str,
+    account: &Pubkey,
+    nonce: &str,
+    issued_at: u64,
+    expires_at: u64,
+) -> String {
+    format!(
+        "{} wants you to sign in with your Solana account:\n{}\n\nSign in to manage your bokoup groups and promos.\n\nURI: {}\nVersion: 1\nNonce: {}\nIssued At: {}\nExpiration Time: {}",
+        domain,
+        account,
+        uri,
+        nonce,
+        format_timestamp(issued_at),
+        format_timestamp(expires_at)
+    )
+}
+
+/// Checks that the base58 encoded `signature` is `signer`'s signature of `message`.
+pub fn verify_signature(signer: &Pubkey, message: &str, signature: &str) -> Result<(), AppError> {
+    let signature = Signature::from_str(signature).map_err(|_| AppError::InvalidSignature)?;
+    if !signature.verify(signer.as_ref(), message.as_bytes()) {
+        return Err(AppError::InvalidSignature);
+    }
+    Ok(())
+}
+
+/// Formats seconds since the unix epoch as an ISO 8601 UTC time, as used by sign-in messages.
+pub fn format_timestamp(timestamp: u64) -> String {
+    // Days to civil date from http://howardhinnant.github.io/date_algorithms.html.
+    let days = timestamp / 86_400 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    let seconds = timestamp % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3_600,
+        seconds % 3_600 / 60,
+        seconds % 60
+    )
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Returns a random base58 string that can't be guessed, for nonces and tokens.
+fn get_random_string() -> String {
+    Keypair::new().pubkey().to_string()
+}
+
+impl State {
+    /// Issues a nonce for `account` to sign in with and returns it with the message to sign.
+    /// Messages are for the domain of `sign_in_url`, which wallets can check against the site
+    /// asking them to sign, so requests for other hosts are rejected. `host` is the host the
+    /// request was made to, if known.
+    pub fn create_sign_in_challenge(
+        &self,
+        account: Pubkey,
+        host: Option<&str>,
+    ) -> Result<(String, SignInChallenge), AppError> {
+        let domain = get_sign_in_domain(&self.sign_in_url);
+        if let Some(host) = host.filter(|host| *host != domain) {
+            return Err(AppError::InvalidSignInDomain(host.to_string()));
+        }
+
+        let nonce = get_random_string();
+        let issued_at = now();
+        let expires_at = issued_at + SIGN_IN_NONCE_TTL.as_secs();
+        let challenge = SignInChallenge {
+            account,
+            message: get_sign_in_message(
+                &domain,
+                self.sign_in_url.as_str(),
+                &account,
+                &nonce,
+                issued_at,
+                expires_at,
+            ),
+            expires_at,
+        };
+        if !self.sign_in_challenges.insert_within_capacity(
+            nonce.clone(),
+            challenge.clone(),
+            SIGN_IN_NONCE_CAPACITY,
+        ) {
+            return Err(AppError::RateLimited {
+                retry_after: SIGN_IN_NONCE_TTL.as_secs(),
+            });
+        }
+        Ok((nonce, challenge))
+    }
+
+    /// Exchanges the signature of the message issued with `nonce` for a session token. Nonces
+    /// can only be used once.
+    pub fn sign_in(&self, nonce: &str, signature: &str) -> Result<(String, AuthSession), AppError> {
+        let challenge = self
+            .sign_in_challenges
+            .remove(nonce)
+            .ok_or(AppError::SignedMessageExpired)?;
+        verify_signature(&challenge.account, &challenge.message, signature)?;
+
+        Ok(self.create_auth_session(challenge.account, signature.to_string()))
+    }
+
+    /// Starts a session for `account`, returning its token.
+    pub fn create_auth_session(&self, account: Pubkey, signature: String) -> (String, AuthSession) {
+        let token = get_random_string();
+        let session = AuthSession {
+            account,
+            signature,
+            expires_at: now() + AUTH_SESSION_TTL.as_secs(),
+        };
+        self.auth_sessions.insert(token.clone(), session.clone());
+        (token, session)
+    }
+
+    pub fn sign_out(&self, token: &str) {
+        self.auth_sessions.remove(token);
+    }
+
+    /// Returns the group if the signed in account is its owner or one of its members.
+    pub async fn check_group_member(
+        &self,
+        session: &AuthSession,
+        group: &Pubkey,
+    ) -> Result<PromoGroupObject, AppError> {
+        let group_object = self.data.get_group(group).await?;
+        if group_object.owner != session.account && !group_object.members.contains(&session.account)
+        {
+            return Err(AppError::NotGroupMember(group.to_string()));
+        }
+        Ok(group_object)
+    }
+
+    /// Returns the group if the signed in account is its owner.
+    pub async fn check_group_owner(
+        &self,
+        session: &AuthSession,
+        group: &Pubkey,
+    ) -> Result<PromoGroupObject, AppError> {
+        let group_object = self.data.get_group(group).await?;
+        if group_object.owner != session.account {
+            return Err(AppError::NotGroupOwner(group.to_string()));
+        }
+        Ok(group_object)
+    }
+}
+
+/// Requires requests to have an `Authorization: Bearer {token}` header with the token of a
+/// session that hasn't expired.
+#[async_trait]
+impl<B: Send> FromRequest<B> for AuthSession {
+    type Rejection = AppError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let state = req
+            .extensions()
+            .get::<Arc<State>>()
+            .cloned()
+            .ok_or_else(|| AppError::GenericError("state extension missing".to_string()))?;
+        let token = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or(AppError::Unauthenticated)?;
+
+        state
+            .auth_sessions
+            .get(token.trim())
+            .ok_or(AppError::Unauthenticated)
+    }
+}
@@ -1,88 +1,354 @@
-use crate::{error::AppError, State};
-use bundlr_sdk::tags::Tag;
-use serde_json::{json, Map, Value};
-use std::sync::Arc;
-
-pub async fn upload_image(
-    image_data: (Vec<u8>, String),
-    state: Arc<State>,
-) -> Result<(String, String, Arc<State>), AppError> {
-    // Upload image to Arweave.
-    let tx = state.bundlr.create_transaction_with_tags(
-        image_data.0,
-        vec![
-            Tag::new("User-Agent".into(), "bokoup".into()),
-            Tag::new("Content-Type".into(), image_data.1.clone()),
-        ],
-    );
-
-    // Get id of uploaded image and add to metdata json.
-    let response = state.bundlr.send_transaction(tx).await?;
-    let image_id = response
-        .as_object()
-        .ok_or(AppError::CreatePromoRequestError(
-            "bundlr respsons should be an object".to_string(),
-        ))?;
-
-    let image_url = format!(
-        "https://arweave.net/{}",
-        image_id["id"]
-            .as_str()
-            .ok_or(AppError::CreatePromoRequestError(
-                "id field should exist in bundlr response".to_string(),
-            ))?
-    );
-
-    tracing::debug!(image_url = &image_url);
-
-    Ok((image_url, image_data.1, state))
+//! Monitoring and funding of the platform signer's bundlr balance.
+
+use anchor_lang::prelude::Pubkey;
+use futures::future::try_join_all;
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::{json, Value};
+use solana_sdk::{signer::Signer, system_instruction};
+use std::{
+    fmt::Write,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::OnceCell;
+use url::Url;
+
+use crate::{error::AppError, handlers::TransactionQuery, utils::storage::join_url, State};
+
+pub const DEFAULT_BUNDLR_POLL_INTERVAL: Duration = Duration::from_secs(60 * 5);
+
+/// Balance below which the platform is alerted and the node funded, enough for a few hundred
+/// promos.
+pub const DEFAULT_BUNDLR_MIN_BALANCE: u64 = 100_000_000;
+
+/// Allowance for the size of promo metadata json, which is only known once the images have been
+/// uploaded.
+pub const METADATA_SIZE_ALLOWANCE: usize = 4096;
+
+/// Fundings the node hasn't accepted within this time are assumed to have never landed.
+const FUNDING_EXPIRY: Duration = Duration::from_secs(60 * 10);
+
+/// Monitoring and funding of the bundlr balance.
+#[derive(Debug, Clone, Copy)]
+pub struct BundlrFundingConfig {
+    pub poll_interval: Duration,
+    pub min_balance: u64,
+    /// Lamports to fund the node with when the balance is below `min_balance`. The balance is
+    /// only monitored if not set.
+    pub fund_lamports: Option<u64>,
+}
+
+impl Default for BundlrFundingConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: DEFAULT_BUNDLR_POLL_INTERVAL,
+            min_balance: DEFAULT_BUNDLR_MIN_BALANCE,
+            fund_lamports: None,
+        }
+    }
+}
+
+/// Last known state of the bundlr balance.
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BundlrStatus {
+    pub address: String,
+    pub balance: Option<u64>,
+    /// Seconds since the unix epoch the balance was last read.
+    pub updated_at: Option<u64>,
+    /// Set once the balance is monitored.
+    pub min_balance: Option<u64>,
+    pub low: bool,
+    /// Signature of a funding transaction the node hasn't accepted yet.
+    pub pending_funding: Option<String>,
+    pub fundings: u64,
+    pub funded_lamports: u64,
+    /// Error from the last poll, if it failed.
+    pub error: Option<String>,
+}
+
+impl BundlrStatus {
+    /// Returns the status in the Prometheus text format.
+    pub fn to_metrics(&self) -> String {
+        let mut metrics = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: Option<u64>| {
+            if let Some(value) = value {
+                let _ = writeln!(
+                    metrics,
+                    "# HELP {0} {1}\n# TYPE {0} {2}\n{0} {3}",
+                    name, help, kind, value
+                );
+            }
+        };
+        metric(
+            "bpl_api_tx_bundlr_balance_lamports",
+            "gauge",
+            "Balance of the platform signer on the bundlr node.",
+            self.balance,
+        );
+        metric(
+            "bpl_api_tx_bundlr_min_balance_lamports",
+            "gauge",
+            "Balance below which the bundlr node is funded.",
+            self.min_balance,
+        );
+        metric(
+            "bpl_api_tx_bundlr_balance_updated_seconds",
+            "gauge",
+            "Time the bundlr balance was last read.",
+            self.updated_at,
+        );
+        metric(
+            "bpl_api_tx_bundlr_balance_low",
+            "gauge",
+            "Whether the bundlr balance is below the minimum.",
+            Some(self.low as u64),
+        );
+        metric(
+            "bpl_api_tx_bundlr_fundings_total",
+            "counter",
+            "Fundings of the bundlr node accepted since the server started.",
+            Some(self.fundings),
+        );
+        metric(
+            "bpl_api_tx_bundlr_funded_lamports_total",
+            "counter",
+            "Lamports funded to the bundlr node since the server started.",
+            Some(self.funded_lamports),
+        );
+        metrics
+    }
+}
+
+/// Account of `address` on the bundlr node at `node_url`, read through the node's http api.
+pub struct BundlrAccount {
+    client: Client,
+    node_url: Url,
+    address: Pubkey,
+    funding_address: OnceCell<Pubkey>,
+    status: Mutex<BundlrStatus>,
+    /// Time the pending funding was sent.
+    funded_at: Mutex<Option<SystemTime>>,
+}
+
+impl BundlrAccount {
+    pub fn new(client: Client, node_url: Url, address: Pubkey) -> Self {
+        Self {
+            client,
+            node_url,
+            address,
+            funding_address: OnceCell::new(),
+            status: Mutex::new(BundlrStatus {
+                address: address.to_string(),
+                ..BundlrStatus::default()
+            }),
+            funded_at: Mutex::new(None),
+        }
+    }
+
+    pub fn status(&self) -> BundlrStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Reads the balance from the node and records it.
+    pub async fn get_balance(&self) -> Result<u64, AppError> {
+        let response: Value = self
+            .get(&format!("account/balance/solana?address={}", self.address))
+            .await?
+            .json()
+            .await?;
+        // The node returns balances as strings since they can exceed the range of json numbers
+        // for other currencies.
+        let balance = match &response["balance"] {
+            Value::String(balance) => balance.parse().ok(),
+            balance => balance.as_u64(),
+        }
+        .ok_or_else(|| AppError::StorageError(format!("bundlr balance: {}", response)))?;
+
+        let mut status = self.status.lock().unwrap();
+        status.balance = Some(balance);
+        status.updated_at = Some(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        );
+        status.low = status.min_balance.map_or(false, |min| balance < min);
+        Ok(balance)
+    }
+
+    /// Returns the price in lamports of uploading `bytes`.
+    pub async fn get_price(&self, bytes: usize) -> Result<u64, AppError> {
+        let price = self
+            .get(&format!("price/solana/{}", bytes))
+            .await?
+            .text()
+            .await?;
+        price
+            .trim()
+            .parse()
+            .map_err(|_| AppError::StorageError(format!("bundlr price: {}", price)))
+    }
+
+    /// Returns the address the node is funded at.
+    pub async fn get_funding_address(&self) -> Result<Pubkey, AppError> {
+        self.funding_address
+            .get_or_try_init(|| async {
+                let response: Value = self.get("info").await?.json().await?;
+                response["addresses"]["solana"]
+                    .as_str()
+                    .and_then(|address| Pubkey::from_str(address).ok())
+                    .ok_or_else(|| {
+                        AppError::StorageError(format!("bundlr funding address: {}", response))
+                    })
+            })
+            .await
+            .copied()
+    }
+
+    /// Returns an error if the balance can't pay for uploads of `sizes` bytes.
+    pub async fn check_funds(&self, sizes: &[usize]) -> Result<(), AppError> {
+        let (balance, prices) = tokio::try_join!(
+            self.get_balance(),
+            try_join_all(sizes.iter().map(|bytes| self.get_price(*bytes)))
+        )?;
+        let price = prices.iter().sum();
+        if balance < price {
+            return Err(AppError::StorageUnderfunded { balance, price });
+        }
+        Ok(())
+    }
+
+    /// Submits the signature of a transfer to the funding address for the node to credit the
+    /// balance with. Returns whether the node accepted it, which it only does once the transfer
+    /// is confirmed.
+    async fn register_funding(&self, signature: &str) -> Result<bool, AppError> {
+        let response = self
+            .client
+            .post(join_url(&self.node_url, "account/balance/solana"))
+            .json(&json!({ "tx_id": signature }))
+            .send()
+            .await?;
+        Ok(response.status().is_success())
+    }
+
+    async fn get(&self, path: &str) -> Result<reqwest::Response, AppError> {
+        let response = self
+            .client
+            .get(join_url(&self.node_url, path))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(AppError::StorageError(format!(
+                "bundlr {} returned status {}",
+                path,
+                response.status()
+            )));
+        }
+        Ok(response)
+    }
+}
+
+impl State {
+    /// Returns an error if storage can't pay for uploads of `sizes` bytes, so that requests fail
+    /// before anything is uploaded. Only bundlr storage is paid for per upload.
+    pub async fn check_storage_funds(&self, sizes: &[usize]) -> Result<(), AppError> {
+        match &self.bundlr {
+            Some(bundlr) => bundlr.check_funds(sizes).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Reads the bundlr balance, alerting if it is below `config.min_balance` and funding the
+    /// node from the platform signer if `config.fund_lamports` is set.
+    pub async fn monitor_bundlr(&self, config: &BundlrFundingConfig) -> Result<(), AppError> {
+        let bundlr = match &self.bundlr {
+            Some(bundlr) => bundlr,
+            None => return Ok(()),
+        };
+        bundlr.status.lock().unwrap().min_balance = Some(config.min_balance);
+
+        let balance = bundlr.get_balance().await?;
+        if balance >= config.min_balance {
+            return Ok(());
+        }
+        tracing::warn!(
+            balance,
+            min_balance = config.min_balance,
+            "bundlr balance is low"
+        );
+
+        let lamports = match config.fund_lamports {
+            Some(lamports) => lamports,
+            None => return Ok(()),
+        };
+        let pending = bundlr.status.lock().unwrap().pending_funding.clone();
+        let signature = match pending {
+            Some(signature) => signature,
+            None => {
+                let signature = self.fund_bundlr(bundlr, lamports).await?;
+                tracing::info!(lamports, signature, "funded bundlr");
+                signature
+            }
+        };
+
+        if bundlr.register_funding(&signature).await? {
+            {
+                let mut status = bundlr.status.lock().unwrap();
+                status.pending_funding = None;
+                status.fundings += 1;
+                status.funded_lamports += lamports;
+            }
+            bundlr.get_balance().await?;
+        } else if bundlr.funded_at.lock().unwrap().map_or(true, |funded_at| {
+            funded_at.elapsed().unwrap_or_default() > FUNDING_EXPIRY
+        }) {
+            bundlr.status.lock().unwrap().pending_funding = None;
+            return Err(AppError::StorageError(format!(
+                "bundlr funding not accepted: {}",
+                signature
+            )));
+        }
+        Ok(())
+    }
+
+    /// Transfers `lamports` from the platform signer to the node's funding address.
+    async fn fund_bundlr(&self, bundlr: &BundlrAccount, lamports: u64) -> Result<String, AppError> {
+        let payer = self.platform_signer.pubkey();
+        let funding_address = bundlr.get_funding_address().await?;
+        let instruction = system_instruction::transfer(&payer, &funding_address, lamports);
+        let query = TransactionQuery::default();
+        let tx = self
+            .create_transaction(&[instruction], &payer, None, &query)
+            .await?;
+        let transaction = self.encode_transaction(tx, &[], &query).await?;
+        let signature = self.solana.send_transaction(&transaction).await?;
+
+        bundlr.status.lock().unwrap().pending_funding = Some(signature.clone());
+        *bundlr.funded_at.lock().unwrap() = Some(SystemTime::now());
+        Ok(signature)
+    }
 }
 
-pub async fn upload_metadata_json(
-    metadata_data_obj: &mut Map<String, Value>,
-    image_url: String,
-    content_type: String,
-    state: Arc<State>,
-) -> Result<(String, Arc<State>), AppError> {
-    metadata_data_obj.insert("image".to_string(), image_url.clone().into());
-
-    metadata_data_obj.insert(
-        "properties".to_string(),
-        json!({
-            "files": [{
-                "uri": image_url,
-                "type": content_type
-            }],
-            "category": "image"
-        }),
-    );
-
-    // Upload json metadata to Arweave and get id back for inclusion in creation of on chain Promo.
-    let tx = state.bundlr.create_transaction_with_tags(
-        serde_json::to_vec(metadata_data_obj)?,
-        vec![
-            Tag::new("User-Agent".into(), "bokoup".into()),
-            Tag::new("Content-Type".into(), "application/json".to_string()),
-        ],
-    );
-
-    let response = state.bundlr.send_transaction(tx).await?;
-    let metadata_id = response
-        .as_object()
-        .ok_or(AppError::CreatePromoRequestError(
-            "bundlr respsons should be an object".to_string(),
-        ))?;
-
-    let uri = format!(
-        "https://arweave.net/{}",
-        metadata_id["id"]
-            .as_str()
-            .ok_or(AppError::CreatePromoRequestError(
-                "id field should exist in bundlr response".to_string(),
-            ))?
-    );
-
-    tracing::debug!(metadata_json_uri = &uri);
-
-    Ok((uri, state))
+/// Polls the bundlr balance every `config.poll_interval`.
+pub fn spawn_bundlr_monitor(state: Arc<State>, config: BundlrFundingConfig) {
+    if state.bundlr.is_none() {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(config.poll_interval);
+        loop {
+            interval.tick().await;
+            let result = state.monitor_bundlr(&config).await;
+            if let Some(bundlr) = &state.bundlr {
+                bundlr.status.lock().unwrap().error =
+                    result.as_ref().err().map(|error| error.to_string());
+            }
+            if let Err(error) = result {
+                tracing::error!(error = error.to_string());
+            }
+        }
+    });
 }
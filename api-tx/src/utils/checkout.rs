@@ -0,0 +1,163 @@
+//! Checkout sessions redeeming a promo for a point of sale order.
+
+use anchor_lang::prelude::Pubkey;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use solana_sdk::{
+    signature::{Keypair, Signature},
+    signer::Signer,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    error::AppError,
+    utils::{
+        solana::SignatureStatus,
+        store::{CHECKOUT_SESSION_TIMEOUT, CHECKOUT_SESSION_TTL},
+    },
+    State,
+};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CheckoutStatus {
+    Open,
+    Pending,
+    Delegated,
+    Finalized,
+    Expired,
+}
+
+impl std::fmt::Display for CheckoutStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            CheckoutStatus::Open => "open",
+            CheckoutStatus::Pending => "pending",
+            CheckoutStatus::Delegated => "delegated",
+            CheckoutStatus::Finalized => "finalized",
+            CheckoutStatus::Expired => "expired",
+        };
+        write!(f, "{}", status)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckoutSession {
+    pub id: String,
+    pub order_id: String,
+    pub mint: Pubkey,
+    /// Merchant device the customer's token is delegated to and that burns it.
+    pub delegate: Pubkey,
+    /// Message displayed to the customer with the delegate transaction.
+    pub message: String,
+    pub status: CheckoutStatus,
+    pub token_owner: Option<Pubkey>,
+    /// Group the promo is redeemed for, known once the delegate transaction is created.
+    pub group: Option<Pubkey>,
+    /// Signature of the latest delegate transaction returned to the customer.
+    pub delegate_signature: Option<String>,
+    /// Whether the delegate transaction was confirmed, even if the session then expired.
+    pub delegated: bool,
+    pub expires_at: SystemTime,
+}
+
+impl CheckoutSession {
+    /// Creates an open session with a random id, which is what customers and the point of sale
+    /// use to access it.
+    pub fn new(order_id: String, mint: Pubkey, delegate: Pubkey, message: String) -> Self {
+        Self {
+            id: Keypair::new().pubkey().to_string(),
+            order_id,
+            mint,
+            delegate,
+            message,
+            status: CheckoutStatus::Open,
+            token_owner: None,
+            group: None,
+            delegate_signature: None,
+            delegated: false,
+            expires_at: SystemTime::now() + CHECKOUT_SESSION_TIMEOUT,
+        }
+    }
+
+    /// Memo included in the session's transactions, so they can be matched to the order.
+    pub fn memo(&self) -> String {
+        json!({ "orderId": self.order_id }).to_string()
+    }
+
+    /// Returns an error if the session isn't in one of `statuses`.
+    pub fn check_status(&self, statuses: &[CheckoutStatus]) -> Result<(), AppError> {
+        if statuses.contains(&self.status) {
+            return Ok(());
+        }
+        Err(AppError::InvalidCheckoutStatus(self.status.to_string()))
+    }
+
+    /// Whether the customer's token was left delegated when the session expired.
+    pub fn needs_revoke(&self) -> bool {
+        self.status == CheckoutStatus::Expired && self.delegated
+    }
+
+    pub fn expires_at_timestamp(&self) -> u64 {
+        self.expires_at
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default()
+    }
+}
+
+impl State {
+    /// Returns the checkout session with `id`, first updating its status with the confirmation
+    /// of a pending delegate transaction and expiring it if it has been abandoned. Failed
+    /// delegate transactions reopen the session so the customer can try again.
+    pub async fn get_checkout_session(&self, id: &str) -> Result<CheckoutSession, AppError> {
+        let mut session = self
+            .checkout_sessions
+            .get(id)
+            .ok_or(AppError::CheckoutSessionNotFound)?;
+        let previous = session.clone();
+
+        if let (CheckoutStatus::Pending, Some(signature)) =
+            (session.status, &session.delegate_signature)
+        {
+            match self.solana.get_signature_status(signature).await? {
+                SignatureStatus::Confirmed => {
+                    session.status = CheckoutStatus::Delegated;
+                    session.delegated = true;
+                }
+                SignatureStatus::Failed(error) => {
+                    tracing::debug!(id, signature, error);
+                    session.status = CheckoutStatus::Open;
+                    session.delegate_signature = None;
+                }
+                SignatureStatus::Pending => {}
+            }
+        }
+
+        if session.status != CheckoutStatus::Finalized && SystemTime::now() >= session.expires_at {
+            session.status = CheckoutStatus::Expired;
+        }
+
+        if session != previous {
+            self.update_checkout_session(session.clone());
+        }
+        Ok(session)
+    }
+
+    pub fn update_checkout_session(&self, session: CheckoutSession) {
+        self.checkout_sessions
+            .insert_with_ttl(session.id.clone(), session, CHECKOUT_SESSION_TTL);
+    }
+}
+
+/// Returns the signature identifying a base64 encoded legacy or v0 transaction once submitted,
+/// its first one. Both formats start with the compact array of signatures, which for the
+/// handful of signers of promo transactions has a one byte length prefix.
+pub fn get_transaction_signature(transaction: &str) -> Result<String, AppError> {
+    let bytes = base64::decode(transaction)
+        .map_err(|e| AppError::InvalidSignedTransaction(e.to_string()))?;
+    let signature = bytes
+        .get(1..1 + std::mem::size_of::<Signature>())
+        .ok_or_else(|| AppError::InvalidSignedTransaction("missing signature".to_string()))?;
+    Ok(Signature::new(signature).to_string())
+}
@@ -0,0 +1,322 @@
+//! Interfaces to the Solana RPC and bokoup data api.
+
+use anchor_lang::prelude::Pubkey;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use solana_sdk::{hash::Hash, transaction::Transaction};
+use std::{sync::Arc, time::Duration};
+use url::Url;
+
+use crate::{
+    error::AppError,
+    utils::{
+        data::{
+            GraphqlResponse, GroupProfileObject, GroupQueryData, MintObject, MintQueryData,
+            OwnerTokenAccountsQueryData, PromoGroupObject, TokenAccountObject,
+            TokenAccountQueryData, GROUP_QUERY, MINT_QUERY, OWNER_TOKEN_ACCOUNTS_QUERY,
+            TOKEN_ACCOUNT_QUERY,
+        },
+        solana::{SignatureStatus, Solana, TransactionCost},
+        store::TtlStore,
+    },
+};
+
+/// The Solana RPC methods used to build transactions.
+#[async_trait]
+pub trait SolanaRpc: Send + Sync {
+    async fn get_latest_blockhash(&self) -> Result<Hash, AppError>;
+
+    /// Returns the blockhash currently stored in a durable nonce account.
+    async fn get_nonce_blockhash(&self, nonce: &Pubkey) -> Result<Hash, AppError>;
+
+    /// Returns prioritization fees paid in recent slots by transactions writing to `accounts`.
+    async fn get_recent_prioritization_fees(
+        &self,
+        accounts: &[Pubkey],
+    ) -> Result<Vec<u64>, AppError>;
+
+    /// Simulates the base64 encoded `tx_str`, returning an error if it would fail. `tx` is its
+    /// legacy equivalent used to map errors to instructions.
+    async fn simulate_transaction(&self, tx_str: &str, tx: &Transaction) -> Result<(), AppError>;
+
+    /// Returns whether the transaction with the base58 encoded `signature` has been confirmed.
+    async fn get_signature_status(&self, signature: &str) -> Result<SignatureStatus, AppError>;
+
+    /// Returns what a confirmed transaction cost `account`, or `None` if it hasn't been
+    /// confirmed.
+    async fn get_transaction_cost(
+        &self,
+        signature: &str,
+        account: &Pubkey,
+    ) -> Result<Option<TransactionCost>, AppError>;
+
+    /// Submits the base64 encoded, fully signed `tx_str`, returning its signature.
+    async fn send_transaction(&self, tx_str: &str) -> Result<String, AppError>;
+}
+
+/// Where indexed data is queried from.
+pub enum DataConfig {
+    /// The graphql data api at the given url.
+    Graphql(Url),
+    /// The `api-data` Postgres database, without going through the data api.
+    Postgres {
+        database_url: String,
+        pool_size: usize,
+    },
+}
+
+/// Source of indexed promo mints and token accounts, and of the group profiles set through this
+/// server.
+#[async_trait]
+pub trait DataSource: Send + Sync {
+    async fn get_mint(&self, mint: &Pubkey) -> Result<MintObject, AppError>;
+
+    async fn get_token_account(
+        &self,
+        token_account: &Pubkey,
+    ) -> Result<TokenAccountObject, AppError>;
+
+    /// Returns the token accounts of `owner` holding tokens.
+    async fn get_owner_token_accounts(
+        &self,
+        owner: &Pubkey,
+    ) -> Result<Vec<TokenAccountObject>, AppError>;
+
+    async fn get_group(&self, group: &Pubkey) -> Result<PromoGroupObject, AppError>;
+
+    /// Sets the profile of `group`, storing the owner's `signature` authorizing it.
+    async fn set_group_profile(
+        &self,
+        group: &Pubkey,
+        profile: &GroupProfileObject,
+        signature: &str,
+    ) -> Result<(), AppError>;
+}
+
+#[async_trait]
+impl SolanaRpc for Solana {
+    async fn get_latest_blockhash(&self) -> Result<Hash, AppError> {
+        Solana::get_latest_blockhash(self).await
+    }
+
+    async fn get_nonce_blockhash(&self, nonce: &Pubkey) -> Result<Hash, AppError> {
+        Solana::get_nonce_blockhash(self, nonce).await
+    }
+
+    async fn get_recent_prioritization_fees(
+        &self,
+        accounts: &[Pubkey],
+    ) -> Result<Vec<u64>, AppError> {
+        Solana::get_recent_prioritization_fees(self, accounts).await
+    }
+
+    async fn simulate_transaction(&self, tx_str: &str, tx: &Transaction) -> Result<(), AppError> {
+        Solana::simulate_transaction(self, tx_str, tx).await
+    }
+
+    async fn get_signature_status(&self, signature: &str) -> Result<SignatureStatus, AppError> {
+        Solana::get_signature_status(self, signature).await
+    }
+
+    async fn get_transaction_cost(
+        &self,
+        signature: &str,
+        account: &Pubkey,
+    ) -> Result<Option<TransactionCost>, AppError> {
+        Solana::get_transaction_cost(self, signature, account).await
+    }
+
+    async fn send_transaction(&self, tx_str: &str) -> Result<String, AppError> {
+        Ok(Solana::post_transaction(self, tx_str).await?.result)
+    }
+}
+
+#[async_trait]
+impl<D: DataSource> DataSource for Arc<D> {
+    async fn get_mint(&self, mint: &Pubkey) -> Result<MintObject, AppError> {
+        self.as_ref().get_mint(mint).await
+    }
+
+    async fn get_token_account(
+        &self,
+        token_account: &Pubkey,
+    ) -> Result<TokenAccountObject, AppError> {
+        self.as_ref().get_token_account(token_account).await
+    }
+
+    async fn get_owner_token_accounts(
+        &self,
+        owner: &Pubkey,
+    ) -> Result<Vec<TokenAccountObject>, AppError> {
+        self.as_ref().get_owner_token_accounts(owner).await
+    }
+
+    async fn get_group(&self, group: &Pubkey) -> Result<PromoGroupObject, AppError> {
+        self.as_ref().get_group(group).await
+    }
+
+    async fn set_group_profile(
+        &self,
+        group: &Pubkey,
+        profile: &GroupProfileObject,
+        signature: &str,
+    ) -> Result<(), AppError> {
+        self.as_ref()
+            .set_group_profile(group, profile, signature)
+            .await
+    }
+}
+
+/// The bokoup graphql data api.
+pub struct GraphqlDataSource {
+    pub client: reqwest::Client,
+    pub url: Url,
+}
+
+impl GraphqlDataSource {
+    pub fn new(client: reqwest::Client, url: Url) -> Self {
+        Self { client, url }
+    }
+
+    async fn query<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: Value,
+    ) -> Result<T, AppError> {
+        let bytes = self
+            .client
+            .post(self.url.as_str())
+            .json(&json!({ "query": query, "variables": variables }))
+            .send()
+            .await?
+            .bytes()
+            .await?;
+        let response: GraphqlResponse<T> =
+            serde_json::from_slice(&bytes).map_err(|e| AppError::MalformedData(e.to_string()))?;
+
+        if let Some(error) = response.errors.into_iter().next() {
+            return Err(AppError::DataApiError(error.message));
+        }
+        response
+            .data
+            .ok_or_else(|| AppError::MalformedData("response has no data".to_string()))
+    }
+}
+
+#[async_trait]
+impl DataSource for GraphqlDataSource {
+    async fn get_mint(&self, mint: &Pubkey) -> Result<MintObject, AppError> {
+        let data: MintQueryData = self
+            .query(MINT_QUERY, json!({ "mint": mint.to_string() }))
+            .await?;
+        data.mint_by_pk
+            .ok_or_else(|| AppError::MintNotFound(mint.to_string()))
+    }
+
+    async fn get_token_account(
+        &self,
+        token_account: &Pubkey,
+    ) -> Result<TokenAccountObject, AppError> {
+        let data: TokenAccountQueryData = self
+            .query(
+                TOKEN_ACCOUNT_QUERY,
+                json!({ "id": token_account.to_string() }),
+            )
+            .await?;
+        data.token_account_by_pk
+            .ok_or_else(|| AppError::TokenAccountNotFound(token_account.to_string()))
+    }
+
+    async fn get_owner_token_accounts(
+        &self,
+        owner: &Pubkey,
+    ) -> Result<Vec<TokenAccountObject>, AppError> {
+        let data: OwnerTokenAccountsQueryData = self
+            .query(
+                OWNER_TOKEN_ACCOUNTS_QUERY,
+                json!({ "owner": owner.to_string() }),
+            )
+            .await?;
+        Ok(data.token_account)
+    }
+
+    async fn get_group(&self, group: &Pubkey) -> Result<PromoGroupObject, AppError> {
+        let data: GroupQueryData = self
+            .query(GROUP_QUERY, json!({ "id": group.to_string() }))
+            .await?;
+        data.promo_group_by_pk
+            .ok_or_else(|| AppError::GroupNotFound(group.to_string()))
+    }
+
+    /// The data api is read only for the public role, so profiles can only be set with the
+    /// postgres data source, and the endpoint returns `501 Not Implemented` with the default
+    /// configuration.
+    async fn set_group_profile(
+        &self,
+        _group: &Pubkey,
+        _profile: &GroupProfileObject,
+        _signature: &str,
+    ) -> Result<(), AppError> {
+        Err(AppError::ReadOnlyDataSource(
+            "setting group profiles".to_string(),
+        ))
+    }
+}
+
+/// Caches mints, which are looked up on every request for a promo to check the payer is a
+/// member of its group. Token accounts change with every transaction so aren't cached.
+pub struct CachedDataSource<D> {
+    inner: D,
+    mints: TtlStore<MintObject>,
+}
+
+impl<D: DataSource> CachedDataSource<D> {
+    pub fn new(inner: D, ttl: Duration) -> Self {
+        Self {
+            inner,
+            mints: TtlStore::new(ttl),
+        }
+    }
+}
+
+#[async_trait]
+impl<D: DataSource> DataSource for CachedDataSource<D> {
+    async fn get_mint(&self, mint: &Pubkey) -> Result<MintObject, AppError> {
+        if let Some(mint_object) = self.mints.get(&mint.to_string()) {
+            return Ok(mint_object);
+        }
+        let mint_object = self.inner.get_mint(mint).await?;
+        self.mints.insert(mint.to_string(), mint_object.clone());
+        Ok(mint_object)
+    }
+
+    async fn get_token_account(
+        &self,
+        token_account: &Pubkey,
+    ) -> Result<TokenAccountObject, AppError> {
+        self.inner.get_token_account(token_account).await
+    }
+
+    async fn get_owner_token_accounts(
+        &self,
+        owner: &Pubkey,
+    ) -> Result<Vec<TokenAccountObject>, AppError> {
+        self.inner.get_owner_token_accounts(owner).await
+    }
+
+    async fn get_group(&self, group: &Pubkey) -> Result<PromoGroupObject, AppError> {
+        self.inner.get_group(group).await
+    }
+
+    async fn set_group_profile(
+        &self,
+        group: &Pubkey,
+        profile: &GroupProfileObject,
+        signature: &str,
+    ) -> Result<(), AppError> {
+        self.inner
+            .set_group_profile(group, profile, signature)
+            .await
+    }
+}
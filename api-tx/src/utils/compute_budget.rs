@@ -0,0 +1,81 @@
+//! Compute budget instructions, built by hand for solana-sdk 1.9.20.
+
+use anchor_lang::prelude::Pubkey;
+use solana_sdk::instruction::Instruction;
+
+solana_sdk::declare_id!("ComputeBudget111111111111111111111111111111");
+
+// Compute budget instruction tags.
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+/// Compute unit limit used when none is requested, enough for the largest transactions, like
+/// creating a promo with loyalty or listing with a receipt.
+pub const DEFAULT_COMPUTE_UNIT_LIMIT: u32 = 400_000;
+
+/// Default cap on the compute unit price in micro-lamports, 0.0004 SOL at the default limit.
+pub const DEFAULT_MAX_COMPUTE_UNIT_PRICE: u64 = 1_000_000;
+
+/// Percentile of recent prioritization fees used as the compute unit price.
+pub const PRIORITIZATION_FEE_PERCENTILE: usize = 75;
+
+/// Server configuration for the compute budget added to every transaction.
+#[derive(Debug, Clone, Copy)]
+pub struct ComputeBudgetConfig {
+    pub compute_unit_limit: u32,
+    pub max_compute_unit_limit: u32,
+    pub max_compute_unit_price: u64,
+}
+
+impl Default for ComputeBudgetConfig {
+    fn default() -> Self {
+        Self {
+            compute_unit_limit: DEFAULT_COMPUTE_UNIT_LIMIT,
+            max_compute_unit_limit: DEFAULT_COMPUTE_UNIT_LIMIT,
+            max_compute_unit_price: DEFAULT_MAX_COMPUTE_UNIT_PRICE,
+        }
+    }
+}
+
+pub fn set_compute_unit_limit(units: u32) -> Instruction {
+    let mut data = vec![SET_COMPUTE_UNIT_LIMIT];
+    data.extend_from_slice(&units.to_le_bytes());
+    Instruction {
+        program_id: ID,
+        accounts: vec![],
+        data,
+    }
+}
+
+pub fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+    let mut data = vec![SET_COMPUTE_UNIT_PRICE];
+    data.extend_from_slice(&micro_lamports.to_le_bytes());
+    Instruction {
+        program_id: ID,
+        accounts: vec![],
+        data,
+    }
+}
+
+/// Returns the writable accounts of `instructions`, which prioritization fees are localized to.
+pub fn get_writable_accounts(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut accounts: Vec<Pubkey> = instructions
+        .iter()
+        .flat_map(|ix| ix.accounts.iter())
+        .filter(|meta| meta.is_writable)
+        .map(|meta| meta.pubkey)
+        .collect();
+    accounts.sort();
+    accounts.dedup();
+    accounts
+}
+
+/// Chooses the compute unit price from recent prioritization fees, capped at `max`.
+pub fn get_compute_unit_price(mut fees: Vec<u64>, max: u64) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    fees.sort_unstable();
+    let index = (fees.len() - 1) * PRIORITIZATION_FEE_PERCENTILE / 100;
+    fees[index].min(max)
+}
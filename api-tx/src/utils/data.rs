@@ -1,161 +1,402 @@
 use std::str::FromStr;
 
 use anchor_lang::prelude::Pubkey;
-use serde_json::Value;
+use bpl_promo_metadata::PromoAttributes;
+use bpl_token_metadata::token_2022;
+use serde::{de, Deserialize, Deserializer};
 
 use crate::error::AppError;
 
-pub const FIRST_MINT_QUERY: &str = r#"
-    query FirstMintQuery {
-        mint(limit: 1) {
+pub const MINT_QUERY: &str = r#"
+    query MintQuery($mint: String!) {
+        mintByPk(id: $mint) {
+        id
+        tokenProgram
+        promoObject {
+            groupObject {
             id
-            promoObject {
-                groupObject {
+            seed
+            members
+            }
+            acceptedGroups {
+            groupObject {
                 id
+                seed
                 members
-                }
+            }
+            }
+            referralRewardObject {
+            rewardMint
+            }
+            promoMintObject {
+            nonTransferable
             }
         }
-    }
-    "#;
-
-pub const FIRST_TOKEN_ACCOUNT_QUERY: &str = r#"
-    query FirstTokenAccount {
-        tokenAccount(limit: 1) {
-        id
+        promoMetadataObject {
+            attributes
+        }
         }
     }
     "#;
 
-pub const MINT_QUERY: &str = r#"
-    query MintQuery($mint: String!) {
-        mintByPk(id: $mint) {
+pub const TOKEN_ACCOUNT_QUERY: &str = r#"
+query TokenAccountQuery($id: String!) {
+    tokenAccountByPk(id: $id) {
+      id
+      owner
+      mint
+      amount
+      tokenProgram
+      referralObject {
+        referrer
+        converted
+      }
+      mintObject {
+        id
+        tokenProgram
         promoObject {
-            groupObject {
+          groupObject {
             id
             seed
             members
+          }
+          acceptedGroups {
+            groupObject {
+              id
+              seed
+              members
             }
+          }
+          referralRewardObject {
+            rewardMint
+          }
+          promoMintObject {
+            nonTransferable
+          }
         }
+        promoMetadataObject {
+          attributes
         }
+      }
     }
-    "#;
+  }
+  "#;
 
-pub const TOKEN_ACCOUNT_QUERY: &str = r#"
-query TokenAccountQuery($id: String!) {
-    tokenAccountByPk(id: $id) {
+/// Token accounts of an owner holding tokens, with their promos.
+pub const OWNER_TOKEN_ACCOUNTS_QUERY: &str = r#"
+query OwnerTokenAccountsQuery($owner: String!) {
+    tokenAccount(where: {owner: {_eq: $owner}, amount: {_gt: 0}}) {
       id
       owner
       mint
-      delegate
-      delegatedAmount
+      amount
+      tokenProgram
+      referralObject {
+        referrer
+        converted
+      }
       mintObject {
+        id
+        tokenProgram
         promoObject {
           groupObject {
             id
             seed
             members
           }
+          acceptedGroups {
+            groupObject {
+              id
+              seed
+              members
+            }
+          }
+          referralRewardObject {
+            rewardMint
+          }
+          promoMintObject {
+            nonTransferable
+          }
+        }
+        promoMetadataObject {
+          attributes
         }
       }
     }
-  }  
+  }
   "#;
 
-pub fn get_mint_object_from_promo_group_query(result: &Value) -> Option<&Value> {
-    result
-        .as_object()
-        .unwrap()
-        .get("data")
-        .unwrap()
-        .as_object()
-        .unwrap()
-        .get("mintByPk")
-}
-
-pub fn get_token_account_object_from_token_account_query(result: &Value) -> Option<&Value> {
-    result
-        .as_object()
-        .unwrap()
-        .get("data")
-        .unwrap()
-        .as_object()
-        .unwrap()
-        .get("tokenAccountByPk")
-}
-
-pub fn get_mint_owner_group_from_token_account_query(
-    payer: &Pubkey,
-    result: &Value,
-) -> Result<(Pubkey, Pubkey, Pubkey), AppError> {
-    let token_account_obj = get_token_account_object_from_token_account_query(result);
-    if let Some(token_account) = token_account_obj {
-        let mint = token_account
-            .get("mint")
-            .unwrap()
-            .as_str()
-            .map(Pubkey::from_str)
-            .unwrap()?;
-
-        let owner = token_account
-            .get("owner")
-            .unwrap()
-            .as_str()
-            .map(Pubkey::from_str)
-            .unwrap()?;
-
-        let mint_obj = token_account.get("mintObject");
-
-        let group = get_group_from_mint_object(payer, mint_obj)?;
-
-        Ok((mint, owner, group))
-    } else {
-        Err(AppError::DataQueryError)
+pub const GROUP_QUERY: &str = r#"
+  query GroupQuery($id: String!) {
+    promoGroupByPk(id: $id) {
+      id
+      owner
+      members
+      groupProfileObject {
+        label
+        icon
+      }
     }
+  }
+  "#;
+
+/// Response to a graphql query. Hasura returns `errors` instead of `data` if the query fails.
+#[derive(Deserialize, Debug)]
+pub struct GraphqlResponse<T> {
+    pub data: Option<T>,
+    #[serde(default)]
+    pub errors: Vec<GraphqlError>,
 }
 
-/// Looks up mint in data api, checks to make sure payer
-/// is included in group and returns group address. If mint it not found
-/// or payer is not in group returns and error so that customer requesting
-/// url gets error from server before trying to submit transaction to the network.
-pub fn get_group_from_promo_group_query(
-    payer: &Pubkey,
-    result: &Value,
-) -> Result<Pubkey, AppError> {
-    let mint_obj = get_mint_object_from_promo_group_query(result);
-    get_group_from_mint_object(payer, mint_obj)
-}
-
-pub fn get_group_from_mint_object(
-    payer: &Pubkey,
-    mint_obj: Option<&Value>,
-) -> Result<Pubkey, AppError> {
-    if let Some(mint) = mint_obj {
-        let group_obj = mint
-            .get("promoObject")
-            .unwrap()
-            .as_object()
-            .unwrap()
-            .get("groupObject")
-            .unwrap()
-            .as_object()
-            .unwrap();
-        let group_str = group_obj.get("id").unwrap().as_str().unwrap();
-
-        let group_members: Vec<Pubkey> = group_obj
-            .get("members")
-            .unwrap()
-            .as_array()
-            .unwrap()
-            .iter()
-            .map(|s| Pubkey::from_str(s.as_str().unwrap()).unwrap())
-            .collect();
-        if group_members.contains(payer) {
-            Ok(Pubkey::from_str(group_str).unwrap())
+#[derive(Deserialize, Debug)]
+pub struct GraphqlError {
+    pub message: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MintQueryData {
+    pub mint_by_pk: Option<MintObject>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenAccountQueryData {
+    pub token_account_by_pk: Option<TokenAccountObject>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnerTokenAccountsQueryData {
+    pub token_account: Vec<TokenAccountObject>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupQueryData {
+    pub promo_group_by_pk: Option<PromoGroupObject>,
+}
+
+/// Group with its owner, who can set the group's profile, and members.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PromoGroupObject {
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    pub id: Pubkey,
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    pub owner: Pubkey,
+    #[serde(deserialize_with = "deserialize_pubkeys")]
+    pub members: Vec<Pubkey>,
+    /// Missing if the owner hasn't set a profile.
+    pub group_profile_object: Option<GroupProfileObject>,
+}
+
+/// Label and icon shown by wallets for the group's transaction requests.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GroupProfileObject {
+    pub label: String,
+    pub icon: String,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MintObject {
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    pub id: Pubkey,
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    pub token_program: Pubkey,
+    /// Missing if the mint is not a promo mint.
+    pub promo_object: Option<PromoObject>,
+    /// Missing if the promo's json metadata doesn't conform to the token metadata
+    /// specifications.
+    pub promo_metadata_object: Option<PromoMetadataObject>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PromoObject {
+    pub group_object: GroupObject,
+    #[serde(default)]
+    pub accepted_groups: Vec<AcceptedGroupObject>,
+    pub referral_reward_object: Option<ReferralRewardObject>,
+    /// Only indexed for Token-2022 promos.
+    pub promo_mint_object: Option<PromoMintObject>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PromoMetadataObject {
+    pub attributes: PromoAttributes,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupObject {
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    pub id: Pubkey,
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    pub seed: Pubkey,
+    #[serde(deserialize_with = "deserialize_pubkeys")]
+    pub members: Vec<Pubkey>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AcceptedGroupObject {
+    pub group_object: GroupObject,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferralRewardObject {
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    pub reward_mint: Pubkey,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PromoMintObject {
+    pub non_transferable: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenAccountObject {
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    pub id: Pubkey,
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    pub owner: Pubkey,
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    pub mint: Pubkey,
+    pub amount: u64,
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    pub token_program: Pubkey,
+    pub referral_object: Option<ReferralObject>,
+    pub mint_object: Option<MintObject>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferralObject {
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    pub referrer: Pubkey,
+    pub converted: bool,
+}
+
+impl MintObject {
+    /// Returns the promo for the mint so that customers requesting transactions for mints
+    /// that aren't promos get an error from the server before submitting to the network.
+    pub fn promo(&self) -> Result<&PromoObject, AppError> {
+        self.promo_object
+            .as_ref()
+            .ok_or_else(|| AppError::PromoNotFound(self.id.to_string()))
+    }
+
+    /// Returns true if the mint was created under the Token-2022 program.
+    pub fn is_token_2022(&self) -> bool {
+        self.token_program == token_2022::ID
+    }
+
+    /// Returns the promo fields parsed from the mint's json metadata.
+    pub fn promo_attributes(&self) -> Option<&PromoAttributes> {
+        self.promo_metadata_object
+            .as_ref()
+            .map(|promo_metadata| &promo_metadata.attributes)
+    }
+}
+
+impl PromoObject {
+    /// Checks that `payer` is a member of the group that owns the promo and returns the group
+    /// address.
+    pub fn get_group(&self, payer: &Pubkey) -> Result<Pubkey, AppError> {
+        if self.group_object.members.contains(payer) {
+            Ok(self.group_object.id)
         } else {
             Err(AppError::PayerNotInMembers)
         }
-    } else {
-        Err(AppError::DataQueryError)
     }
+
+    /// Returns the group whose members include `payer`, checking the group that owns the promo
+    /// first and then the groups named in its promo acceptance. When `payer` is a member of an
+    /// accepting group, the group that owns the promo is returned as the issuing group so that
+    /// transactions can settle lamports between the two.
+    pub fn get_redeeming_group(
+        &self,
+        payer: &Pubkey,
+    ) -> Result<(Pubkey, Option<Pubkey>), AppError> {
+        if let Ok(group) = self.get_group(payer) {
+            return Ok((group, None));
+        }
+        self.accepted_groups
+            .iter()
+            .find(|accepted| accepted.group_object.members.contains(payer))
+            .map(|accepted| (accepted.group_object.id, Some(self.group_object.id)))
+            .ok_or(AppError::PayerNotInMembers)
+    }
+
+    /// Returns the reward mint if the group that owns the promo has set a referral reward.
+    pub fn get_referral_reward(&self) -> Option<Pubkey> {
+        self.referral_reward_object
+            .as_ref()
+            .map(|referral_reward| referral_reward.reward_mint)
+    }
+
+    pub fn is_non_transferable(&self) -> bool {
+        self.promo_mint_object
+            .as_ref()
+            .map(|promo_mint| promo_mint.non_transferable)
+            .unwrap_or(false)
+    }
+}
+
+impl TokenAccountObject {
+    pub fn promo(&self) -> Result<&PromoObject, AppError> {
+        self.mint_object
+            .as_ref()
+            .and_then(|mint| mint.promo_object.as_ref())
+            .ok_or_else(|| AppError::PromoNotFound(self.mint.to_string()))
+    }
+
+    /// Returns true if the token account is owned by the Token-2022 program.
+    pub fn is_token_2022(&self) -> bool {
+        self.token_program == token_2022::ID
+    }
+
+    /// Returns the referrer and reward mint if the token owner was referred, the referral has
+    /// not converted yet and the promo still has a referral reward.
+    pub fn get_referral(&self) -> Option<(Pubkey, Pubkey)> {
+        let referral = self.referral_object.as_ref()?;
+        if referral.converted {
+            return None;
+        }
+        let reward_mint = self.promo().ok()?.get_referral_reward()?;
+        Some((referral.referrer, reward_mint))
+    }
+
+    /// Returns the mint and owner of a token account holding a promo token that can be traded
+    /// on the auction house. Tokens of non-transferable promos can't be traded and the auction
+    /// house only supports mints of the classic token program.
+    pub fn get_tradable_mint_owner(&self) -> Result<(Pubkey, Pubkey), AppError> {
+        if self.promo()?.is_non_transferable() {
+            return Err(AppError::PromoNotTransferable);
+        }
+        if self.is_token_2022() {
+            return Err(AppError::Token2022Unsupported("auction house".to_string()));
+        }
+        Ok((self.mint, self.owner))
+    }
+}
+
+fn deserialize_pubkey<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+    let pubkey_str = String::deserialize(deserializer)?;
+    Pubkey::from_str(&pubkey_str).map_err(de::Error::custom)
+}
+
+fn deserialize_pubkeys<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Pubkey>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|pubkey_str| Pubkey::from_str(pubkey_str).map_err(de::Error::custom))
+        .collect()
 }
@@ -0,0 +1,196 @@
+//! In-memory RPC and data api clients for tests.
+
+use anchor_lang::prelude::Pubkey;
+use async_trait::async_trait;
+use serde_json::Value;
+use solana_sdk::{hash::Hash, transaction::Transaction};
+use std::{collections::HashMap, sync::Mutex};
+
+use crate::{
+    error::AppError,
+    utils::{
+        checkout::get_transaction_signature,
+        clients::{DataSource, SolanaRpc},
+        data::{GroupProfileObject, MintObject, PromoGroupObject, TokenAccountObject},
+        solana::{get_simulation_error, SignatureStatus, TransactionCost},
+    },
+};
+
+/// Returns a fixed blockhash and records simulated and sent transactions. Simulations succeed
+/// unless a simulation error has been set. Transactions are pending until a status or cost has
+/// been set for their signature.
+pub struct FakeSolanaRpc {
+    pub blockhash: Hash,
+    pub prioritization_fees: Vec<u64>,
+    pub nonce_blockhashes: Mutex<HashMap<Pubkey, Hash>>,
+    /// Error and logs returned by the next simulations, in the format of the RPC response.
+    pub simulation_error: Mutex<Option<(Value, Vec<String>)>>,
+    pub simulated: Mutex<Vec<String>>,
+    pub signature_statuses: Mutex<HashMap<String, SignatureStatus>>,
+    pub transaction_costs: Mutex<HashMap<String, TransactionCost>>,
+    pub sent: Mutex<Vec<String>>,
+}
+
+impl Default for FakeSolanaRpc {
+    fn default() -> Self {
+        Self {
+            blockhash: Hash::new_unique(),
+            prioritization_fees: vec![],
+            nonce_blockhashes: Mutex::new(HashMap::new()),
+            simulation_error: Mutex::new(None),
+            simulated: Mutex::new(vec![]),
+            signature_statuses: Mutex::new(HashMap::new()),
+            transaction_costs: Mutex::new(HashMap::new()),
+            sent: Mutex::new(vec![]),
+        }
+    }
+}
+
+#[async_trait]
+impl SolanaRpc for FakeSolanaRpc {
+    async fn get_latest_blockhash(&self) -> Result<Hash, AppError> {
+        Ok(self.blockhash)
+    }
+
+    async fn get_nonce_blockhash(&self, nonce: &Pubkey) -> Result<Hash, AppError> {
+        self.nonce_blockhashes
+            .lock()
+            .unwrap()
+            .get(nonce)
+            .copied()
+            .ok_or_else(|| AppError::NonceNotFound(nonce.to_string()))
+    }
+
+    async fn get_recent_prioritization_fees(
+        &self,
+        _accounts: &[Pubkey],
+    ) -> Result<Vec<u64>, AppError> {
+        Ok(self.prioritization_fees.clone())
+    }
+
+    async fn simulate_transaction(&self, tx_str: &str, tx: &Transaction) -> Result<(), AppError> {
+        self.simulated.lock().unwrap().push(tx_str.to_string());
+        match &*self.simulation_error.lock().unwrap() {
+            Some((err, logs)) => Err(AppError::SimulationError {
+                error: get_simulation_error(tx, err, logs),
+                logs: logs.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    async fn get_signature_status(&self, signature: &str) -> Result<SignatureStatus, AppError> {
+        Ok(self
+            .signature_statuses
+            .lock()
+            .unwrap()
+            .get(signature)
+            .cloned()
+            .unwrap_or(SignatureStatus::Pending))
+    }
+
+    async fn get_transaction_cost(
+        &self,
+        signature: &str,
+        _account: &Pubkey,
+    ) -> Result<Option<TransactionCost>, AppError> {
+        Ok(self
+            .transaction_costs
+            .lock()
+            .unwrap()
+            .get(signature)
+            .copied())
+    }
+
+    async fn send_transaction(&self, tx_str: &str) -> Result<String, AppError> {
+        self.sent.lock().unwrap().push(tx_str.to_string());
+        get_transaction_signature(tx_str)
+    }
+}
+
+/// Returns mints, token accounts and groups inserted by address.
+#[derive(Default)]
+pub struct FakeDataSource {
+    pub mints: Mutex<HashMap<Pubkey, MintObject>>,
+    pub token_accounts: Mutex<HashMap<Pubkey, TokenAccountObject>>,
+    pub groups: Mutex<HashMap<Pubkey, PromoGroupObject>>,
+}
+
+impl FakeDataSource {
+    pub fn insert_mint(&self, mint: MintObject) {
+        self.mints.lock().unwrap().insert(mint.id, mint);
+    }
+
+    pub fn insert_token_account(&self, token_account: TokenAccountObject) {
+        self.token_accounts
+            .lock()
+            .unwrap()
+            .insert(token_account.id, token_account);
+    }
+
+    pub fn insert_group(&self, group: PromoGroupObject) {
+        self.groups.lock().unwrap().insert(group.id, group);
+    }
+}
+
+#[async_trait]
+impl DataSource for FakeDataSource {
+    async fn get_mint(&self, mint: &Pubkey) -> Result<MintObject, AppError> {
+        self.mints
+            .lock()
+            .unwrap()
+            .get(mint)
+            .cloned()
+            .ok_or_else(|| AppError::MintNotFound(mint.to_string()))
+    }
+
+    async fn get_token_account(
+        &self,
+        token_account: &Pubkey,
+    ) -> Result<TokenAccountObject, AppError> {
+        self.token_accounts
+            .lock()
+            .unwrap()
+            .get(token_account)
+            .cloned()
+            .ok_or_else(|| AppError::TokenAccountNotFound(token_account.to_string()))
+    }
+
+    async fn get_owner_token_accounts(
+        &self,
+        owner: &Pubkey,
+    ) -> Result<Vec<TokenAccountObject>, AppError> {
+        Ok(self
+            .token_accounts
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|token_account| token_account.owner == *owner && token_account.amount > 0)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_group(&self, group: &Pubkey) -> Result<PromoGroupObject, AppError> {
+        self.groups
+            .lock()
+            .unwrap()
+            .get(group)
+            .cloned()
+            .ok_or_else(|| AppError::GroupNotFound(group.to_string()))
+    }
+
+    async fn set_group_profile(
+        &self,
+        group: &Pubkey,
+        profile: &GroupProfileObject,
+        _signature: &str,
+    ) -> Result<(), AppError> {
+        self.groups
+            .lock()
+            .unwrap()
+            .get_mut(group)
+            .ok_or_else(|| AppError::GroupNotFound(group.to_string()))?
+            .group_profile_object = Some(profile.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+//! Labels and icons merchants set for their groups.
+
+use url::Url;
+
+use crate::{error::AppError, utils::data::GroupProfileObject};
+
+pub const MAX_LABEL_LENGTH: usize = 32;
+
+pub const MAX_ICON_URL_LENGTH: usize = 256;
+
+/// Checks that the label fits in wallets and that the icon is an https url, as required by the
+/// Solana Pay spec.
+pub fn validate_group_profile(profile: &GroupProfileObject) -> Result<(), AppError> {
+    let label = profile.label.trim();
+    if label.is_empty() || label.chars().count() > MAX_LABEL_LENGTH {
+        return Err(AppError::InvalidGroupProfile(format!(
+            "label should be between 1 and {} characters",
+            MAX_LABEL_LENGTH
+        )));
+    }
+    if profile.icon.len() > MAX_ICON_URL_LENGTH {
+        return Err(AppError::InvalidGroupProfile(format!(
+            "icon url should be at most {} characters",
+            MAX_ICON_URL_LENGTH
+        )));
+    }
+    let icon = Url::parse(&profile.icon)
+        .map_err(|e| AppError::InvalidGroupProfile(format!("icon: {}", e)))?;
+    if icon.scheme() != "https" {
+        return Err(AppError::InvalidGroupProfile(
+            "icon should be an https url".to_string(),
+        ));
+    }
+    Ok(())
+}
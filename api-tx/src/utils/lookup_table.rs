@@ -0,0 +1,91 @@
+//! Address lookup table instructions, built by hand for solana-sdk 1.9.20.
+
+use anchor_lang::{prelude::Pubkey, solana_program::sysvar, system_program};
+use bpl_token_metadata::{
+    token_2022,
+    utils::{find_admin_address, find_authority_address},
+};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+use crate::utils::compute_budget;
+
+solana_sdk::declare_id!("AddressLookupTab1e1111111111111111111111111");
+
+// Address lookup table instruction tags.
+const CREATE_LOOKUP_TABLE: u32 = 0;
+const EXTEND_LOOKUP_TABLE: u32 = 2;
+
+/// Addresses in the platform's lookup table, in order. Transactions are compiled against these
+/// positions, so new addresses must only ever be appended, both here and to the table.
+pub fn get_platform_lookup_table_addresses() -> Vec<Pubkey> {
+    vec![
+        bpl_token_metadata::id(),
+        anchor_spl::token::ID,
+        token_2022::ID,
+        anchor_spl::associated_token::ID,
+        spl_memo::ID,
+        mpl_token_metadata::ID,
+        mpl_auction_house::ID,
+        system_program::ID,
+        compute_budget::ID,
+        sysvar::rent::ID,
+        sysvar::instructions::ID,
+        sysvar::recent_blockhashes::ID,
+        find_admin_address().0,
+        find_authority_address().0,
+    ]
+}
+
+pub fn find_lookup_table_address(authority: &Pubkey, recent_slot: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[authority.as_ref(), &recent_slot.to_le_bytes()], &ID)
+}
+
+/// Returns the instruction to create a lookup table owned by `authority` and its address.
+/// `recent_slot` needs to be a recent finalized slot.
+pub fn create_lookup_table(
+    authority: &Pubkey,
+    payer: &Pubkey,
+    recent_slot: u64,
+) -> (Instruction, Pubkey) {
+    let (lookup_table, bump_seed) = find_lookup_table_address(authority, recent_slot);
+
+    let mut data = CREATE_LOOKUP_TABLE.to_le_bytes().to_vec();
+    data.extend_from_slice(&recent_slot.to_le_bytes());
+    data.push(bump_seed);
+
+    let instruction = Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new(lookup_table, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    };
+    (instruction, lookup_table)
+}
+
+pub fn extend_lookup_table(
+    lookup_table: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    new_addresses: &[Pubkey],
+) -> Instruction {
+    let mut data = EXTEND_LOOKUP_TABLE.to_le_bytes().to_vec();
+    data.extend_from_slice(&(new_addresses.len() as u64).to_le_bytes());
+    for address in new_addresses {
+        data.extend_from_slice(address.as_ref());
+    }
+
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new(*lookup_table, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    }
+}
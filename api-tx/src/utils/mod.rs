@@ -1,3 +1,22 @@
+pub mod auction_house;
+pub mod auth;
 pub mod bundlr;
+pub mod checkout;
+pub mod clients;
+pub mod compute_budget;
 pub mod data;
+#[cfg(test)]
+pub mod fakes;
+pub mod group_profile;
+pub mod lookup_table;
+pub mod postgres;
+pub mod promo_image;
+pub mod qr;
+pub mod quote;
+pub mod rate_limit;
 pub mod solana;
+pub mod sponsorship;
+pub mod storage;
+pub mod store;
+pub mod versioned;
+pub mod webhooks;
@@ -0,0 +1,168 @@
+//! Data source querying the `api-data` schema directly.
+
+use anchor_lang::prelude::Pubkey;
+use async_trait::async_trait;
+use deadpool_postgres::{Manager, ManagerConfig, Pool, RecyclingMethod};
+use serde_json::Value;
+use std::str::FromStr;
+use tokio_postgres::{NoTls, Row};
+
+use crate::{
+    error::AppError,
+    utils::{
+        clients::DataSource,
+        data::{
+            GroupProfileObject, MintObject, PromoGroupObject, ReferralObject, TokenAccountObject,
+        },
+    },
+};
+
+const MINT_SELECT: &str = include_str!("queries/mint_select.sql");
+const TOKEN_ACCOUNT_SELECT: &str = include_str!("queries/token_account_select.sql");
+const OWNER_TOKEN_ACCOUNTS_SELECT: &str = include_str!("queries/owner_token_accounts_select.sql");
+const GROUP_SELECT: &str = include_str!("queries/group_select.sql");
+const GROUP_PROFILE_UPSERT: &str = include_str!("queries/group_profile_upsert.sql");
+
+pub struct PostgresDataSource {
+    pool: Pool,
+}
+
+impl PostgresDataSource {
+    /// Creates a connection pool for the database at `database_url`. Connections are opened
+    /// as they are needed.
+    pub fn new(database_url: &str, pool_size: usize) -> Result<Self, AppError> {
+        let pg_config = database_url.parse::<tokio_postgres::Config>()?;
+        let mgr_config = ManagerConfig {
+            recycling_method: RecyclingMethod::Fast,
+        };
+        let mgr = Manager::from_config(pg_config, NoTls, mgr_config);
+        let pool = Pool::builder(mgr)
+            .max_size(pool_size)
+            .build()
+            .map_err(|e| AppError::GenericError(e.to_string()))?;
+        Ok(Self { pool })
+    }
+
+    /// Connection pool, shared with the sponsorship ledger and webhook store.
+    pub fn pool(&self) -> Pool {
+        self.pool.clone()
+    }
+}
+
+#[async_trait]
+impl DataSource for PostgresDataSource {
+    async fn get_mint(&self, mint: &Pubkey) -> Result<MintObject, AppError> {
+        let id = mint.to_string();
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(MINT_SELECT, &[&id])
+            .await?
+            .ok_or(AppError::MintNotFound(id))?;
+
+        serde_json::from_value(row.get::<usize, Value>(0))
+            .map_err(|e| AppError::MalformedData(e.to_string()))
+    }
+
+    async fn get_token_account(
+        &self,
+        token_account: &Pubkey,
+    ) -> Result<TokenAccountObject, AppError> {
+        let id = token_account.to_string();
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(TOKEN_ACCOUNT_SELECT, &[&id])
+            .await?
+            .ok_or(AppError::TokenAccountNotFound(id))?;
+        // The mint is looked up with its own connection.
+        drop(client);
+
+        self.token_account_from_row(&row).await
+    }
+
+    async fn get_owner_token_accounts(
+        &self,
+        owner: &Pubkey,
+    ) -> Result<Vec<TokenAccountObject>, AppError> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(OWNER_TOKEN_ACCOUNTS_SELECT, &[&owner.to_string()])
+            .await?;
+        drop(client);
+
+        let mut token_accounts = Vec::with_capacity(rows.len());
+        for row in rows.iter() {
+            token_accounts.push(self.token_account_from_row(row).await?);
+        }
+        Ok(token_accounts)
+    }
+
+    async fn get_group(&self, group: &Pubkey) -> Result<PromoGroupObject, AppError> {
+        let id = group.to_string();
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(GROUP_SELECT, &[&id])
+            .await?
+            .ok_or(AppError::GroupNotFound(id))?;
+
+        serde_json::from_value(row.get::<usize, Value>(0))
+            .map_err(|e| AppError::MalformedData(e.to_string()))
+    }
+
+    async fn set_group_profile(
+        &self,
+        group: &Pubkey,
+        profile: &GroupProfileObject,
+        signature: &str,
+    ) -> Result<(), AppError> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                GROUP_PROFILE_UPSERT,
+                &[
+                    &group.to_string(),
+                    &profile.label,
+                    &profile.icon,
+                    &signature,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+impl PostgresDataSource {
+    /// Builds a token account from a row selected by `token_account_select.sql`, looking up its
+    /// mint.
+    async fn token_account_from_row(&self, row: &Row) -> Result<TokenAccountObject, AppError> {
+        let referrer: Option<String> = row.get("referrer");
+        let converted: Option<bool> = row.get("converted");
+        let referral_object = match (referrer, converted) {
+            (Some(referrer), Some(converted)) => Some(ReferralObject {
+                referrer: parse_pubkey(&referrer)?,
+                converted,
+            }),
+            _ => None,
+        };
+
+        let mint = parse_pubkey(row.get("mint"))?;
+        let mint_object = match self.get_mint(&mint).await {
+            Ok(mint_object) => Some(mint_object),
+            Err(AppError::MintNotFound(_)) => None,
+            Err(error) => return Err(error),
+        };
+
+        Ok(TokenAccountObject {
+            id: parse_pubkey(row.get("id"))?,
+            owner: parse_pubkey(row.get("owner"))?,
+            mint,
+            amount: row.get::<&str, i64>("amount") as u64,
+            token_program: parse_pubkey(row.get("token_program"))?,
+            referral_object,
+            mint_object,
+        })
+    }
+}
+
+pub fn parse_pubkey(pubkey_str: &str) -> Result<Pubkey, AppError> {
+    Pubkey::from_str(pubkey_str).map_err(|e| AppError::MalformedData(e.to_string()))
+}
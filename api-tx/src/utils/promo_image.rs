@@ -0,0 +1,109 @@
+//! Validation and normalization of promo images.
+
+use image::{imageops::FilterType, DynamicImage, GenericImageView, ImageFormat, ImageOutputFormat};
+use std::io::Cursor;
+
+use crate::error::AppError;
+
+/// Largest image accepted on promo create, before re-encoding.
+pub const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+
+/// Images need to be at least this wide and tall to render clearly in wallets.
+pub const MIN_IMAGE_DIMENSION: u32 = 200;
+
+/// Larger images are rejected rather than decoded, to bound memory use.
+pub const MAX_IMAGE_DIMENSION: u32 = 8192;
+
+/// Images are scaled down to fit within a square of this size.
+pub const IMAGE_SIZE: u32 = 1000;
+
+/// Thumbnails fit within a square of this size.
+pub const THUMBNAIL_SIZE: u32 = 200;
+
+/// Jpeg quality used for re-encoded images without transparency.
+const JPEG_QUALITY: u8 = 85;
+
+/// Normalized promo image and thumbnail, each as bytes with their content type.
+#[derive(Debug)]
+pub struct PromoImage {
+    pub image: (Vec<u8>, String),
+    pub thumbnail: (Vec<u8>, String),
+}
+
+/// Checks that `bytes` are a png, jpeg, gif or webp image of acceptable size, detecting the
+/// format from its contents rather than the content type claimed by the client, and returns it
+/// re-encoded at a normalized size along with a thumbnail.
+pub fn process_promo_image(bytes: &[u8]) -> Result<PromoImage, AppError> {
+    if bytes.len() > MAX_IMAGE_BYTES {
+        return Err(AppError::ImageTooLarge(MAX_IMAGE_BYTES));
+    }
+
+    let format = image::guess_format(bytes)
+        .map_err(|_| AppError::InvalidImage("unrecognized image format".to_string()))?;
+    if !matches!(
+        format,
+        ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::Gif | ImageFormat::WebP
+    ) {
+        return Err(AppError::InvalidImage(format!(
+            "unsupported image format: {:?}",
+            format
+        )));
+    }
+
+    let (width, height) = image::io::Reader::with_format(Cursor::new(bytes), format)
+        .into_dimensions()
+        .map_err(|e| AppError::InvalidImage(e.to_string()))?;
+    check_dimensions(width, height)?;
+
+    let img = image::load_from_memory_with_format(bytes, format)
+        .map_err(|e| AppError::InvalidImage(e.to_string()))?;
+
+    let (width, height) = img.dimensions();
+    let resized = if width > IMAGE_SIZE || height > IMAGE_SIZE {
+        img.resize(IMAGE_SIZE, IMAGE_SIZE, FilterType::Lanczos3)
+    } else {
+        img.clone()
+    };
+    let thumbnail = img.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+
+    Ok(PromoImage {
+        image: encode_image(&resized)?,
+        thumbnail: encode_image(&thumbnail)?,
+    })
+}
+
+fn check_dimensions(width: u32, height: u32) -> Result<(), AppError> {
+    if width < MIN_IMAGE_DIMENSION || height < MIN_IMAGE_DIMENSION {
+        return Err(AppError::InvalidImage(format!(
+            "image is {}x{}, should be at least {} pixels wide and tall",
+            width, height, MIN_IMAGE_DIMENSION
+        )));
+    }
+    if width > MAX_IMAGE_DIMENSION || height > MAX_IMAGE_DIMENSION {
+        return Err(AppError::InvalidImage(format!(
+            "image is {}x{}, should be at most {} pixels wide and tall",
+            width, height, MAX_IMAGE_DIMENSION
+        )));
+    }
+    Ok(())
+}
+
+/// Encodes images with transparency as png and others as jpeg, which is much smaller for
+/// photos.
+fn encode_image(img: &DynamicImage) -> Result<(Vec<u8>, String), AppError> {
+    let mut bytes = Vec::new();
+    let content_type = if img.color().has_alpha() {
+        img.write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png)
+            .map_err(|e| AppError::InvalidImage(e.to_string()))?;
+        mime::IMAGE_PNG
+    } else {
+        DynamicImage::ImageRgb8(img.to_rgb8())
+            .write_to(
+                &mut Cursor::new(&mut bytes),
+                ImageOutputFormat::Jpeg(JPEG_QUALITY),
+            )
+            .map_err(|e| AppError::InvalidImage(e.to_string()))?;
+        mime::IMAGE_JPEG
+    };
+    Ok((bytes, content_type.to_string()))
+}
@@ -0,0 +1,143 @@
+//! Solana Pay transaction request links and QR codes for promos.
+
+use anchor_lang::prelude::Pubkey;
+use image::{imageops, DynamicImage, ImageOutputFormat, Rgba, RgbaImage};
+use qrcode::{render::svg, Color, EcLevel, QrCode};
+use std::io::Cursor;
+
+use crate::error::AppError;
+
+/// Width of QR codes in pixels when not requested.
+pub const DEFAULT_QR_SIZE: u32 = 512;
+
+pub const MIN_QR_SIZE: u32 = 128;
+
+pub const MAX_QR_SIZE: u32 = 2048;
+
+/// Modules of light border around codes, as required by the QR code specification.
+const QUIET_ZONE: u32 = 4;
+
+/// Icons in branded codes are a fifth of the code's width, which the highest error correction
+/// level they're encoded with recovers from.
+const ICON_SCALE: u32 = 5;
+
+const DARK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+const LIGHT: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+/// Returns the link to the mint promo transaction request endpoint on the server at
+/// `base_url`, with the message and memo url encoded as path segments.
+pub fn get_mint_link(base_url: &str, mint: &Pubkey, message: &str, memo: Option<&str>) -> String {
+    let mut link = format!(
+        "{}/promo/mint/{}/{}",
+        base_url,
+        mint,
+        urlencoding::encode(message)
+    );
+    if let Some(memo) = memo {
+        link.push('/');
+        link.push_str(&urlencoding::encode(memo));
+    }
+    link
+}
+
+/// Returns `link` as a Solana Pay transaction request url. The link is url encoded in full, as
+/// the spec requires for links with encoded path segments or query parameters.
+pub fn get_solana_pay_url(link: &str) -> String {
+    format!("solana:{}", urlencoding::encode(link))
+}
+
+/// Renders `data` as an svg QR code at least `size` pixels wide, with the image at `icon_url`
+/// in the center if given.
+pub fn render_svg(data: &str, size: u32, icon_url: Option<&str>) -> Result<String, AppError> {
+    let code = create_qr_code(data, icon_url.is_some())?;
+    let module_size = get_module_size(&code, size);
+    let svg = code
+        .render::<svg::Color>()
+        .module_dimensions(module_size, module_size)
+        .build();
+
+    let icon_url = match icon_url {
+        Some(icon_url) => icon_url,
+        None => return Ok(svg),
+    };
+    let (icon_size, offset) = get_icon_position(get_width(&code, module_size));
+    Ok(format!(
+        r##"{}<rect x="{offset}" y="{offset}" width="{icon_size}" height="{icon_size}" fill="#fff"/><image href="{}" x="{offset}" y="{offset}" width="{icon_size}" height="{icon_size}"/></svg>"##,
+        svg.trim_end_matches("</svg>"),
+        escape_xml(icon_url),
+        offset = offset,
+        icon_size = icon_size,
+    ))
+}
+
+/// Renders `data` as a png QR code at least `size` pixels wide, with the `icon` image in the
+/// center if given.
+pub fn render_png(data: &str, size: u32, icon: Option<&[u8]>) -> Result<Vec<u8>, AppError> {
+    let code = create_qr_code(data, icon.is_some())?;
+    let module_size = get_module_size(&code, size);
+    let width = get_width(&code, module_size);
+    let modules = code.width() as u32;
+
+    let mut img = RgbaImage::from_pixel(width, width, LIGHT);
+    for (i, color) in code.to_colors().iter().enumerate() {
+        if *color != Color::Dark {
+            continue;
+        }
+        let x = (i as u32 % modules + QUIET_ZONE) * module_size;
+        let y = (i as u32 / modules + QUIET_ZONE) * module_size;
+        for dy in 0..module_size {
+            for dx in 0..module_size {
+                img.put_pixel(x + dx, y + dy, DARK);
+            }
+        }
+    }
+
+    if let Some(icon) = icon {
+        let (icon_size, offset) = get_icon_position(width);
+        let icon = image::load_from_memory(icon)
+            .map_err(|e| AppError::InvalidImage(e.to_string()))?
+            .thumbnail(icon_size, icon_size)
+            .to_rgba8();
+        let background = RgbaImage::from_pixel(icon_size, icon_size, LIGHT);
+        imageops::overlay(&mut img, &background, offset as i64, offset as i64);
+        let x = offset + (icon_size - icon.width()) / 2;
+        let y = offset + (icon_size - icon.height()) / 2;
+        imageops::overlay(&mut img, &icon, x as i64, y as i64);
+    }
+
+    let mut bytes = Vec::new();
+    DynamicImage::ImageRgba8(img)
+        .write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png)
+        .map_err(|e| AppError::QrCodeError(e.to_string()))?;
+    Ok(bytes)
+}
+
+fn create_qr_code(data: &str, branded: bool) -> Result<QrCode, AppError> {
+    let ec_level = if branded { EcLevel::H } else { EcLevel::M };
+    QrCode::with_error_correction_level(data, ec_level)
+        .map_err(|e| AppError::QrCodeError(e.to_string()))
+}
+
+/// Returns the width in pixels of modules so the code is at least `size` pixels wide.
+fn get_module_size(code: &QrCode, size: u32) -> u32 {
+    let modules = code.width() as u32 + 2 * QUIET_ZONE;
+    ((size + modules - 1) / modules).max(1)
+}
+
+fn get_width(code: &QrCode, module_size: u32) -> u32 {
+    (code.width() as u32 + 2 * QUIET_ZONE) * module_size
+}
+
+/// Returns the size and offset from the top left corner of icons in codes `width` wide.
+fn get_icon_position(width: u32) -> (u32, u32) {
+    let icon_size = width / ICON_SCALE;
+    (icon_size, (width - icon_size) / 2)
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,16 @@
+SELECT
+    id,
+    endpoint_id,
+    event_id,
+    promo_group,
+    kind,
+    payload,
+    status,
+    attempts,
+    next_attempt_at,
+    response_status,
+    error,
+    created_at
+FROM webhook_delivery
+WHERE status = 'pending' AND next_attempt_at <= $1
+ORDER BY next_attempt_at;
@@ -0,0 +1,6 @@
+INSERT INTO group_billing (promo_group, reimburse, signature)
+VALUES ($1, $2, $3)
+ON CONFLICT (promo_group) DO UPDATE SET
+    reimburse = EXCLUDED.reimburse,
+    signature = EXCLUDED.signature,
+    modified_at = now();
@@ -0,0 +1,7 @@
+INSERT INTO group_profile (promo_group, label, icon, signature)
+VALUES ($1, $2, $3, $4)
+ON CONFLICT (promo_group) DO UPDATE SET
+    label = EXCLUDED.label,
+    icon = EXCLUDED.icon,
+    signature = EXCLUDED.signature,
+    modified_at = now();
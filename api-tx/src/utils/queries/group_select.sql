@@ -0,0 +1,13 @@
+-- Selects a group and its profile as json in the shape returned by the data api's GroupQuery.
+SELECT json_build_object(
+    'id', g.id,
+    'owner', g.owner,
+    'members', g.members,
+    'groupProfileObject', (
+        SELECT json_build_object('label', gp.label, 'icon', gp.icon)
+        FROM group_profile gp
+        WHERE gp.promo_group = g.id
+    )
+)
+FROM promo_group g
+WHERE g.id = $1;
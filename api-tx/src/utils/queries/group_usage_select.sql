@@ -0,0 +1,33 @@
+-- Selects the usage of a group as json in the shape of `GroupUsage`, with daily usage of
+-- sponsored transactions since $2.
+WITH settled AS (
+    SELECT * FROM sponsorship_ledger
+    WHERE promo_group = $1 AND status <> 'pending'
+)
+SELECT json_build_object(
+    'transactions', (SELECT count(*) FROM settled WHERE kind = 'sponsored'),
+    'fees', (SELECT coalesce(sum(fee), 0)::bigint FROM settled),
+    'rent', (SELECT coalesce(sum(rent), 0)::bigint FROM settled),
+    'settled', (
+        SELECT coalesce(sum(lamports), 0)::bigint
+        FROM sponsorship_ledger
+        WHERE promo_group = $1 AND kind = 'settlement' AND status <> 'failed'
+    ),
+    'reimburse', coalesce(
+        (SELECT reimburse FROM group_billing WHERE promo_group = $1),
+        false
+    ),
+    'days', coalesce((
+        SELECT json_agg(d ORDER BY d.day DESC)
+        FROM (
+            SELECT
+                extract(epoch FROM date_trunc('day', created_at AT TIME ZONE 'UTC'))::bigint AS day,
+                count(*) AS transactions,
+                sum(fee)::bigint AS fees,
+                sum(rent)::bigint AS rent
+            FROM settled
+            WHERE kind = 'sponsored' AND created_at >= $2
+            GROUP BY 1
+        ) d
+    ), '[]'::json)
+);
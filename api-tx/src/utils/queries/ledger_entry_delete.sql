@@ -0,0 +1,2 @@
+DELETE FROM sponsorship_ledger
+WHERE signature = $1;
@@ -0,0 +1,8 @@
+INSERT INTO sponsorship_ledger (signature, promo_group, kind, status, fee, rent, lamports, created_at)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+ON CONFLICT (signature) DO UPDATE SET
+    status = EXCLUDED.status,
+    fee = EXCLUDED.fee,
+    rent = EXCLUDED.rent,
+    lamports = EXCLUDED.lamports,
+    modified_at = now();
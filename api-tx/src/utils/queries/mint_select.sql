@@ -0,0 +1,39 @@
+-- Selects a mint and its promo as json in the shape returned by the data api's MintQuery.
+SELECT json_build_object(
+    'id', m.id,
+    'tokenProgram', m.token_program,
+    'promoObject', (
+        SELECT json_build_object(
+            'groupObject', json_build_object('id', g.id, 'seed', g.seed, 'members', g.members),
+            'acceptedGroups', COALESCE((
+                SELECT json_agg(json_build_object(
+                    'groupObject',
+                    json_build_object('id', ag.id, 'seed', ag.seed, 'members', ag.members)
+                ))
+                FROM accepted_promo_group apg
+                JOIN promo_group ag ON ag.id = apg.promo_group
+                WHERE apg.promo = p.id
+            ), '[]'::json),
+            'referralRewardObject', (
+                SELECT json_build_object('rewardMint', rr.reward_mint)
+                FROM referral_reward rr
+                WHERE rr.promo = p.id
+            ),
+            'promoMintObject', (
+                SELECT json_build_object('nonTransferable', pm.non_transferable)
+                FROM promo_mint pm
+                WHERE pm.promo = p.id
+            )
+        )
+        FROM promo p
+        JOIN promo_group g ON g.id = p.owner
+        WHERE p.mint = m.id
+    ),
+    'promoMetadataObject', (
+        SELECT json_build_object('attributes', pmd.attributes)
+        FROM promo_metadata pmd
+        WHERE pmd.mint = m.id
+    )
+)
+FROM mint m
+WHERE m.id = $1;
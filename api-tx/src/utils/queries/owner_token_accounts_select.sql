@@ -0,0 +1,4 @@
+SELECT ta.id, ta.owner, ta.mint, ta.amount, ta.token_program, r.referrer, r.converted
+FROM token_account ta
+LEFT JOIN referral r ON r.mint = ta.mint AND r.token_owner = ta.owner
+WHERE ta.owner = $1 AND ta.amount > 0;
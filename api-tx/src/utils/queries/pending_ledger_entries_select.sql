@@ -0,0 +1,3 @@
+SELECT signature, promo_group, kind, status, fee, rent, lamports, created_at
+FROM sponsorship_ledger
+WHERE status = 'pending';
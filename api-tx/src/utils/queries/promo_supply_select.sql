@@ -0,0 +1,3 @@
+SELECT owner, mint_count, max_mint, slot
+FROM promo
+WHERE mint = $1 AND max_mint IS NOT NULL;
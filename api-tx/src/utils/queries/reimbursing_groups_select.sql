@@ -0,0 +1,3 @@
+SELECT promo_group
+FROM group_billing
+WHERE reimburse;
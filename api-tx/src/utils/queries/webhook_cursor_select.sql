@@ -0,0 +1,3 @@
+SELECT slot
+FROM webhook_cursor
+WHERE id = 'events';
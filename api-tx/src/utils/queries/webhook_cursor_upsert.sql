@@ -0,0 +1,5 @@
+INSERT INTO webhook_cursor (id, slot)
+VALUES ('events', $1)
+ON CONFLICT (id) DO UPDATE SET
+    slot = EXCLUDED.slot,
+    modified_at = now();
@@ -0,0 +1,17 @@
+SELECT
+    id,
+    endpoint_id,
+    event_id,
+    promo_group,
+    kind,
+    payload,
+    status,
+    attempts,
+    next_attempt_at,
+    response_status,
+    error,
+    created_at
+FROM webhook_delivery
+WHERE promo_group = $1
+ORDER BY created_at DESC
+LIMIT $2;
@@ -0,0 +1,13 @@
+INSERT INTO webhook_delivery (
+    id,
+    endpoint_id,
+    event_id,
+    promo_group,
+    kind,
+    payload,
+    status,
+    next_attempt_at,
+    created_at
+)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+ON CONFLICT (endpoint_id, event_id) DO NOTHING;
@@ -0,0 +1,9 @@
+UPDATE webhook_delivery
+SET
+    status = $2,
+    attempts = $3,
+    next_attempt_at = $4,
+    response_status = $5,
+    error = $6,
+    modified_at = now()
+WHERE id = $1;
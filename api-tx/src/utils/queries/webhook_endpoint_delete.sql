@@ -0,0 +1,2 @@
+DELETE FROM webhook_endpoint
+WHERE promo_group = $1 AND id = $2;
@@ -0,0 +1,2 @@
+INSERT INTO webhook_endpoint (id, promo_group, url, secret, events, signature, created_at)
+VALUES ($1, $2, $3, $4, $5, $6, $7);
@@ -0,0 +1,3 @@
+SELECT id, promo_group, url, secret, events, created_at
+FROM webhook_endpoint
+WHERE id = $1;
@@ -0,0 +1,4 @@
+SELECT id, promo_group, url, secret, events, created_at
+FROM webhook_endpoint
+WHERE promo_group = $1
+ORDER BY created_at;
@@ -0,0 +1,33 @@
+-- Selects mint, delegate and burn instructions indexed from slot $1, oldest first, for groups
+-- with webhook endpoints registered before the instructions were indexed.
+SELECT e.kind, e.signature, e.promo_group, e.mint, e.token_account, e.slot, e.created_at
+FROM (
+    SELECT 'mint' AS kind, signature, promo_group, mint, token_account, slot, created_at
+    FROM mint_promo_token
+    UNION ALL
+    SELECT 'mint', signature, promo_group, mint, token_account, slot, created_at
+    FROM mint_referred_promo_token
+    UNION ALL
+    SELECT 'delegate', signature, promo_group, mint, token_account, slot, created_at
+    FROM delegate_promo_token
+    UNION ALL
+    SELECT 'delegate', signature, promo_group, mint, token_account, slot, created_at
+    FROM delegate_accepted_promo_token
+    UNION ALL
+    SELECT 'burn', signature, promo_group, mint, token_account, slot, created_at
+    FROM burn_delegated_promo_token
+    UNION ALL
+    SELECT 'burn', signature, promo_group, mint, token_account, slot, created_at
+    FROM burn_referred_promo_token
+    UNION ALL
+    SELECT 'burn', signature, promo_group, mint, token_account, slot, created_at
+    FROM burn_accepted_promo_token
+) e
+WHERE e.slot >= $1
+    AND e.created_at >= (
+        SELECT min(we.created_at)
+        FROM webhook_endpoint we
+        WHERE we.promo_group = e.promo_group
+    )
+ORDER BY e.slot
+LIMIT $2;
@@ -0,0 +1,90 @@
+//! Applies promos to orders.
+
+use bpl_promo_metadata::PromoType;
+use serde::{Deserialize, Serialize};
+
+/// Order to apply promos to. Amounts are in 0.01 currency.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Order {
+    /// Order id in the merchant's point of sale system, included in the memo of transactions
+    /// applying discounts to the order.
+    #[serde(default)]
+    pub order_id: Option<String>,
+    pub items: Vec<LineItem>,
+    pub total: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LineItem {
+    pub product_id: String,
+    pub quantity: u32,
+    /// Unit price.
+    pub price: u64,
+}
+
+/// Discount a promo gives on an order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Discount {
+    pub amount: u64,
+    /// Reward level to redeem for loyalty promos.
+    pub level: Option<u8>,
+}
+
+/// Returns the discount a promo of `promo_type` gives on `order` for a customer holding
+/// `balance` tokens of it, or `None` if the order doesn't qualify. Discounts are capped at the
+/// order total.
+///
+/// * buyXProductGetYFree - `getYProduct` of the product free when the order has at least
+///   `buyXProduct` of it, priced at the first line item for the product.
+/// * buyXCurrencyGetYPercent - `getYPercent` off the total when it is at least `buyXCurrency`.
+/// * loyalty - the reward of the highest level the customer has enough points for, with each
+///   token held being a point.
+pub fn get_discount(promo_type: &PromoType, order: &Order, balance: u64) -> Option<Discount> {
+    if balance == 0 {
+        return None;
+    }
+
+    let (amount, level) = match promo_type {
+        PromoType::BuyXProductGetYFree {
+            product_id,
+            buy_x_product,
+            get_y_product,
+        } => {
+            let items: Vec<&LineItem> = order
+                .items
+                .iter()
+                .filter(|item| &item.product_id == product_id)
+                .collect();
+            let quantity: u64 = items.iter().map(|item| item.quantity as u64).sum();
+            if quantity < *buy_x_product as u64 {
+                return None;
+            }
+            (*get_y_product as u64 * items.first()?.price, None)
+        }
+        PromoType::BuyXCurrencyGetYPercent {
+            buy_x_currency,
+            get_y_percent,
+        } => {
+            if order.total < *buy_x_currency {
+                return None;
+            }
+            (order.total * *get_y_percent as u64 / 100, None)
+        }
+        PromoType::Loyalty { levels, .. } => {
+            let (level, reward_level) = levels
+                .iter()
+                .enumerate()
+                .filter(|(_, level)| level.points <= balance)
+                .max_by_key(|(_, level)| level.reward)?;
+            (reward_level.reward, Some(level as u8))
+        }
+    };
+
+    let amount = amount.min(order.total);
+    if amount == 0 {
+        return None;
+    }
+    Some(Discount { amount, level })
+}
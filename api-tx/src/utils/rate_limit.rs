@@ -0,0 +1,395 @@
+//! Rate limits on endpoints paid for by the platform signer.
+
+use anchor_lang::prelude::Pubkey;
+use async_trait::async_trait;
+use axum::{
+    body::{Body, HttpBody},
+    extract::{ConnectInfo, FromRequest, Path, RequestParts},
+    http::{header, Method, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use std::{
+    collections::{HashMap, HashSet},
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::OnceCell;
+use url::Url;
+
+use crate::{error::AppError, handlers::get_app_id::get_params_group, State};
+
+/// Requests per client ip per window, enough for a busy point of sale behind a shared ip.
+pub const DEFAULT_IP_RATE_LIMIT: u64 = 60;
+
+/// Requests per wallet per window.
+pub const DEFAULT_WALLET_RATE_LIMIT: u64 = 10;
+
+pub const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Sponsored routes take small json bodies, so larger ones are rejected before they are
+/// buffered to read the wallet.
+pub const MAX_BODY_LENGTH: usize = 16 * 1024;
+
+const DAY: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Increments a counter, setting it to expire if it is new, and returns the count and
+/// milliseconds until it expires. Negative increments return reserved requests.
+const INCREMENT_SCRIPT: &str = r"
+local count = redis.call('INCRBY', KEYS[1], ARGV[1])
+local ttl = redis.call('PTTL', KEYS[1])
+if ttl < 0 then
+    redis.call('PEXPIRE', KEYS[1], ARGV[2])
+    ttl = tonumber(ARGV[2])
+end
+return {count, ttl}
+";
+
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitConfig {
+    /// Requests per client ip per window.
+    pub ip_limit: Option<u64>,
+    /// Requests per wallet, the `account` in the body, per window.
+    pub wallet_limit: Option<u64>,
+    /// Sponsored transactions per group per day.
+    pub group_daily_budget: Option<u64>,
+    /// Client ips, wallets and groups that can't use sponsored endpoints.
+    pub denylist: HashSet<String>,
+    /// Number of proxies in front of the server that append to the `X-Forwarded-For` header,
+    /// such as a load balancer. The client ip is the address added by the outermost of them,
+    /// and with none the address of the connection is used.
+    pub trusted_proxies: usize,
+    /// Counters are kept in Redis if set, otherwise in memory.
+    pub redis_url: Option<Url>,
+}
+
+/// Counter for a key in the current window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Counter {
+    pub count: u64,
+    /// Time until the window resets.
+    pub reset: Duration,
+}
+
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Adds `amount` to the counter for `key` and returns it. New counters reset after
+    /// `window`.
+    async fn increment(
+        &self,
+        key: &str,
+        amount: i64,
+        window: Duration,
+    ) -> Result<Counter, AppError>;
+}
+
+/// Counters for a single server instance.
+#[derive(Default)]
+pub struct MemoryRateLimitStore {
+    counters: Mutex<HashMap<String, (Instant, u64)>>,
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn increment(
+        &self,
+        key: &str,
+        amount: i64,
+        window: Duration,
+    ) -> Result<Counter, AppError> {
+        let now = Instant::now();
+        let mut counters = self.counters.lock().unwrap();
+        counters.retain(|_, (reset_at, _)| *reset_at > now);
+        let (reset_at, count) = counters.entry(key.to_string()).or_insert((now + window, 0));
+        *count = match amount {
+            amount if amount < 0 => count.saturating_sub(amount.unsigned_abs()),
+            amount => *count + amount as u64,
+        };
+        Ok(Counter {
+            count: *count,
+            reset: *reset_at - now,
+        })
+    }
+}
+
+/// Counters shared by all server instances.
+pub struct RedisRateLimitStore {
+    client: redis::Client,
+    connection: OnceCell<redis::aio::MultiplexedConnection>,
+    script: redis::Script,
+}
+
+impl RedisRateLimitStore {
+    pub fn new(url: Url) -> Result<Self, AppError> {
+        Ok(Self {
+            client: redis::Client::open(url.as_str())?,
+            connection: OnceCell::new(),
+            script: redis::Script::new(INCREMENT_SCRIPT),
+        })
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RedisRateLimitStore {
+    async fn increment(
+        &self,
+        key: &str,
+        amount: i64,
+        window: Duration,
+    ) -> Result<Counter, AppError> {
+        let mut connection = self
+            .connection
+            .get_or_try_init(|| self.client.get_multiplexed_tokio_connection())
+            .await?
+            .clone();
+        let (count, reset): (i64, u64) = self
+            .script
+            .key(format!("bpl-api-tx:rate-limit:{}", key))
+            .arg(amount)
+            .arg(window.as_millis() as u64)
+            .invoke_async(&mut connection)
+            .await?;
+        Ok(Counter {
+            count: count.max(0) as u64,
+            reset: Duration::from_millis(reset),
+        })
+    }
+}
+
+pub struct RateLimiter {
+    pub config: RateLimitConfig,
+    store: Box<dyn RateLimitStore>,
+}
+
+impl Default for RateLimiter {
+    /// No limits.
+    fn default() -> Self {
+        Self {
+            config: RateLimitConfig::default(),
+            store: Box::new(MemoryRateLimitStore::default()),
+        }
+    }
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Result<Self, AppError> {
+        let store: Box<dyn RateLimitStore> = match &config.redis_url {
+            Some(url) => Box::new(RedisRateLimitStore::new(url.clone())?),
+            None => Box::new(MemoryRateLimitStore::default()),
+        };
+        Ok(Self { config, store })
+    }
+
+    pub fn check_denylist(&self, keys: &[&str]) -> Result<(), AppError> {
+        match keys.iter().find(|key| self.config.denylist.contains(**key)) {
+            Some(key) => Err(AppError::Denied(key.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Counts a request by the client at `ip` and by `wallet`, returning an error if either
+    /// has exceeded its limit or the store can't be reached.
+    pub async fn check_request(
+        &self,
+        ip: Option<IpAddr>,
+        wallet: Option<&str>,
+    ) -> Result<(), AppError> {
+        let keys = [
+            (ip.map(|ip| format!("ip:{}", ip)), self.config.ip_limit),
+            (
+                wallet.map(|wallet| format!("wallet:{}", wallet)),
+                self.config.wallet_limit,
+            ),
+        ];
+        for (key, limit) in keys {
+            if let (Some(key), Some(limit)) = (key, limit) {
+                let counter = self.store.increment(&key, 1, RATE_LIMIT_WINDOW).await?;
+                if counter.count > limit {
+                    return Err(rate_limited(counter));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reserves a sponsored transaction from `group`'s budget for the day, returning an error
+    /// if the budget has been used or the store can't be reached. Reserving before checking
+    /// keeps concurrent requests from spending more than the budget.
+    pub async fn reserve_group_budget(&self, group: &Pubkey) -> Result<(), AppError> {
+        let budget = match self.config.group_daily_budget {
+            Some(budget) => budget,
+            None => return Ok(()),
+        };
+        let counter = self
+            .store
+            .increment(&get_group_key(group), 1, until_midnight())
+            .await?;
+        if counter.count > budget {
+            self.refund_group_budget(group).await;
+            return Err(rate_limited(counter));
+        }
+        Ok(())
+    }
+
+    /// Returns a reserved transaction to `group`'s budget when it isn't sponsored after all.
+    pub async fn refund_group_budget(&self, group: &Pubkey) {
+        if self.config.group_daily_budget.is_none() {
+            return;
+        }
+        let key = get_group_key(group);
+        if let Err(error) = self.store.increment(&key, -1, until_midnight()).await {
+            tracing::error!(key, error = error.to_string());
+        }
+    }
+}
+
+fn rate_limited(counter: Counter) -> AppError {
+    AppError::RateLimited {
+        retry_after: counter.reset.as_secs().max(1),
+    }
+}
+
+fn get_group_key(group: &Pubkey) -> String {
+    let day = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        / DAY.as_secs();
+    format!("group:{}:{}", group, day)
+}
+
+fn until_midnight() -> Duration {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    Duration::from_secs(DAY.as_secs() - now % DAY.as_secs())
+}
+
+#[derive(Deserialize)]
+struct AccountData {
+    account: String,
+}
+
+/// Group sponsored transactions are requested for, added to requests by `rate_limit`.
+#[derive(Debug, Clone, Copy)]
+pub struct SponsoredGroup(pub Pubkey);
+
+/// Middleware for sponsored routes applying the limits in `State::rate_limiter` to requests
+/// for transactions. The group is found from the path in the same way as for the label and
+/// icon returned by `get_app_id`, and the transaction reserved from its budget is returned if
+/// the request fails. Requests are rejected while the store can't be reached, since the
+/// platform signer pays for them.
+pub async fn rate_limit(req: Request<Body>, next: Next<Body>) -> Response {
+    if req.method() != Method::POST {
+        return next.run(req).await;
+    }
+
+    let state = req
+        .extensions()
+        .get::<Arc<State>>()
+        .cloned()
+        .expect("state extension");
+    let limiter = &state.rate_limiter;
+    let ip = get_client_ip(&req, limiter.config.trusted_proxies);
+
+    let mut parts = RequestParts::new(req);
+    let params = Path::<HashMap<String, String>>::from_request(&mut parts)
+        .await
+        .map(|Path(params)| params)
+        .unwrap_or_default();
+    let (parts, body) = match parts.try_into_request() {
+        Ok(req) => req.into_parts(),
+        Err(error) => return AppError::GenericError(error.to_string()).into_response(),
+    };
+    let body = match read_body(&parts.headers, body).await {
+        Ok(body) => body,
+        Err(error) => return error.into_response(),
+    };
+    let wallet = serde_json::from_slice::<AccountData>(&body)
+        .ok()
+        .map(|data| data.account);
+    let mut req = Request::from_parts(parts, Body::from(body));
+
+    let group = get_params_group(&state, &params).await;
+    if let Some(group) = group {
+        req.extensions_mut().insert(SponsoredGroup(group));
+    }
+
+    let ip_string = ip.map(|ip| ip.to_string());
+    let group_string = group.map(|group| group.to_string());
+    let denylist_keys: Vec<&str> = [&ip_string, &wallet, &group_string]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect();
+    if let Err(error) = limiter.check_denylist(&denylist_keys) {
+        tracing::info!(error = error.to_string());
+        return error.into_response();
+    }
+
+    if let Err(error) = limiter.check_request(ip, wallet.as_deref()).await {
+        tracing::info!(?ip, ?wallet, error = error.to_string());
+        return error.into_response();
+    }
+
+    if let Some(group) = &group {
+        if let Err(error) = limiter.reserve_group_budget(group).await {
+            tracing::info!(group = group.to_string(), error = error.to_string());
+            return error.into_response();
+        }
+    }
+
+    let response = next.run(req).await;
+    if let Some(group) = &group {
+        if !response.status().is_success() {
+            limiter.refund_group_budget(group).await;
+        }
+    }
+    response
+}
+
+/// Buffers the body, returning an error without reading it if its `Content-Length` is more
+/// than `MAX_BODY_LENGTH`, or once more than that has been read if it has none.
+async fn read_body(headers: &header::HeaderMap, mut body: Body) -> Result<Vec<u8>, AppError> {
+    let content_length = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+    if content_length.unwrap_or_default() > MAX_BODY_LENGTH {
+        return Err(AppError::BodyTooLarge(MAX_BODY_LENGTH));
+    }
+
+    let mut bytes = Vec::with_capacity(content_length.unwrap_or_default());
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|error| AppError::GenericError(error.to_string()))?;
+        if bytes.len() + chunk.len() > MAX_BODY_LENGTH {
+            return Err(AppError::BodyTooLarge(MAX_BODY_LENGTH));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+/// Returns the ip of the client. Each proxy appends the address it received the request from
+/// to `X-Forwarded-For`, so with `trusted_proxies` in front of the server the client ip is that
+/// many addresses from the right, and addresses to the left of it can be set by the client.
+/// Otherwise the address of the connection is used.
+fn get_client_ip<B>(req: &Request<B>, trusted_proxies: usize) -> Option<IpAddr> {
+    if trusted_proxies > 0 {
+        if let Some(ip) = req
+            .headers()
+            .get("x-forwarded-for")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit(',').nth(trusted_proxies - 1))
+            .and_then(|ip| ip.trim().parse().ok())
+        {
+            return Some(ip);
+        }
+    }
+    req.extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+}
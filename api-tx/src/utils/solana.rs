@@ -1,4 +1,7 @@
-use crate::error::AppError;
+use crate::{
+    error::{AppError, SimulationError, PROGRAM_ERRORS},
+    utils::compute_budget,
+};
 use anchor_lang::{
     prelude::Pubkey,
     InstructionData, ToAccountMetas,
@@ -9,29 +12,69 @@ use anchor_lang::{
 };
 use bpl_token_metadata::{
     accounts::{
+        BurnAcceptedPromoToken as burn_accepted_promo_token_accounts,
         BurnDelegatedPromoToken as burn_delegated_promo_token_accounts,
-        CreatePromo as create_promo_accounts, CreatePromoGroup as create_promo_group_accounts,
-        DelegatePromoToken as delegate_promo_token_accounts,
+        BurnPromoToken2022 as burn_promo_token_2022_accounts,
+        BurnReferredPromoToken as burn_referred_promo_token_accounts,
+        CreateLoyalty as create_loyalty_accounts, CreatePromo as create_promo_accounts,
+        CreatePromo2022 as create_promo_2022_accounts,
+        CreatePromoAcceptance as create_promo_acceptance_accounts,
+        CreatePromoGroup as create_promo_group_accounts,
+        CreateReferralReward as create_referral_reward_accounts,
+        CreateSettlementApproval as create_settlement_approval_accounts,
+        CreateSponsorshipAllowance as create_sponsorship_allowance_accounts,
+        DelegateAcceptedPromoToken as delegate_accepted_promo_token_accounts,
+        DelegatePromoToken as delegate_promo_token_accounts, EarnPoints as earn_points_accounts,
         MintPromoToken as mint_promo_token_accounts,
+        MintPromoToken2022 as mint_promo_token_2022_accounts,
+        MintReferredPromoToken as mint_referred_promo_token_accounts,
+        RedeemPoints as redeem_points_accounts, SettleSponsorship as settle_sponsorship_accounts,
     },
     instruction::{
+        BurnAcceptedPromoToken as burn_accepted_promo_token_instruction,
         BurnDelegatedPromoToken as burn_delegated_promo_token_instruction,
-        CreatePromo as create_promo_instruction,
+        BurnPromoToken2022 as burn_promo_token_2022_instruction,
+        BurnReferredPromoToken as burn_referred_promo_token_instruction,
+        CreateLoyalty as create_loyalty_instruction, CreatePromo as create_promo_instruction,
+        CreatePromo2022 as create_promo_2022_instruction,
+        CreatePromoAcceptance as create_promo_acceptance_instruction,
         CreatePromoGroup as create_promo_group_instruction,
+        CreateReferralReward as create_referral_reward_instruction,
+        CreateSettlementApproval as create_settlement_approval_instruction,
+        CreateSponsorshipAllowance as create_sponsorship_allowance_instruction,
+        DelegateAcceptedPromoToken as delegate_accepted_promo_token_instruction,
         DelegatePromoToken as delegate_promo_token_instruction,
-        MintPromoToken as mint_promo_token_instruction,
+        EarnPoints as earn_points_instruction, MintPromoToken as mint_promo_token_instruction,
+        MintPromoToken2022 as mint_promo_token_2022_instruction,
+        MintReferredPromoToken as mint_referred_promo_token_instruction,
+        RedeemPoints as redeem_points_instruction,
+        SettleSponsorship as settle_sponsorship_instruction,
+    },
+    state::{
+        DataV2, Loyalty, MintExtensions, Promo, PromoAcceptance, PromoGroup, RewardLevel,
+        Settlement, SettlementApproval,
     },
-    state::{DataV2, Promo, PromoGroup},
+    token_2022,
     utils::{
         find_admin_address, find_associated_token_address, find_authority_address,
-        find_group_address, find_metadata_address, find_promo_address,
+        find_group_address, find_loyalty_address, find_metadata_address,
+        find_promo_acceptance_address, find_promo_address, find_promo_mint_address,
+        find_referral_address, find_referral_reward_address, find_settlement_approval_address,
+        find_sponsorship_allowance_address, ACCEPTANCE_CAPACITY, LOYALTY_LEVELS_CAPACITY,
     },
 };
 use serde::{Deserialize, Serialize};
 
 use serde_json::{json, Value};
-use solana_sdk::{commitment_config::CommitmentLevel, hash::Hash};
-use std::str::FromStr;
+use solana_sdk::{
+    commitment_config::CommitmentLevel,
+    hash::Hash,
+    nonce::{state::Versions, State as NonceState},
+    rent::Rent,
+    system_instruction::{self, SystemInstruction},
+    transaction::Transaction,
+};
+use std::{str::FromStr, time::Duration};
 
 pub fn create_create_promo_group_instruction(
     payer: Pubkey,
@@ -156,6 +199,7 @@ pub fn create_mint_promo_instruction(
 ) -> Result<Instruction, AppError> {
     let (authority, _auth_bump) = find_authority_address();
     let (promo, _promo_bump) = find_promo_address(&mint);
+    let (loyalty, _loyalty_bump) = find_loyalty_address(&mint);
     let token_account = find_associated_token_address(&token_owner, &mint);
 
     tracing::debug!(
@@ -174,6 +218,7 @@ pub fn create_mint_promo_instruction(
         mint,
         authority,
         promo,
+        loyalty,
         token_account,
         token_program: anchor_spl::token::ID,
         memo_program: spl_memo::ID,
@@ -265,38 +310,1037 @@ pub fn create_burn_delegated_promo_instruction(
     })
 }
 
-// Needed to do this since nonblocking client not avaiable in 1.9.20.
-pub struct Solana {
-    pub cluster: Cluster,
-    pub commitment: CommitmentLevel,
-    pub client: reqwest::Client,
+/// Revokes the delegation of a promo token, for customers to undo delegating a token that wasn't
+/// redeemed. The token program handles this, so no group or platform accounts are needed.
+pub fn create_revoke_promo_instruction(
+    token_owner: Pubkey,
+    mint: Pubkey,
+) -> Result<Instruction, AppError> {
+    let token_account = find_associated_token_address(&token_owner, &mint);
+    spl_token::instruction::revoke(&spl_token::ID, &token_account, &token_owner, &[])
+        .map_err(|e| AppError::GenericError(e.to_string()))
 }
 
-impl Solana {
-    pub async fn get_latest_blockhash(&self) -> Result<Hash, AppError> {
-        let mut config = serde_json::Map::new();
-        config.insert(
-            "commitment".to_string(),
-            Value::String(self.commitment.to_string()),
-        );
+pub fn create_create_promo_2022_instruction(
+    payer: Pubkey,
+    group_seed: Pubkey,
+    mint: Pubkey,
+    platform: Pubkey,
+    name: String,
+    symbol: String,
+    uri: String,
+    max_mint: Option<u32>,
+    max_burn: Option<u32>,
+    extensions: MintExtensions,
+    memo: Option<String>,
+) -> Result<Instruction, AppError> {
+    let (authority, _auth_bump) = find_authority_address();
+    let (promo, _promo_bump) = find_promo_address(&mint);
+    let (promo_mint, _promo_mint_bump) = find_promo_mint_address(&mint);
+    let (admin_settings, _admin_bump) = find_admin_address();
+    let (group, _group_bump) = find_group_address(&group_seed);
 
-        let post_object = PostObject {
-            method: String::from("getLatestBlockhash"),
-            ..Default::default()
-        };
+    let accounts = create_promo_2022_accounts {
+        payer,
+        group,
+        mint,
+        promo_mint,
+        authority,
+        promo,
+        platform,
+        admin_settings,
+        token_program: token_2022::ID,
+        memo_program: spl_memo::ID,
+        rent: sysvar::rent::id(),
+        system_program: system_program::ID,
+    }
+    .to_account_metas(Some(true));
 
-        let result: Value = self
-            .client
-            .post(self.cluster.url())
-            .json(&post_object)
-            .send()
-            .await?
-            .json()
-            .await?;
+    let promo_data = Promo {
+        owner: group,
+        mint,
+        metadata: promo_mint,
+        mint_count: 0,
+        burn_count: 0,
+        max_mint,
+        max_burn,
+    };
 
-        let hash_str = result["result"]["value"]["blockhash"].as_str().unwrap();
-        let hash = Hash::from_str(hash_str)?;
-        Ok(hash)
+    let metadata_data = DataV2 {
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points: 0,
+        creators: None,
+        collection: None,
+        uses: None,
+    };
+
+    let data = create_promo_2022_instruction {
+        promo_data,
+        metadata_data,
+        extensions,
+        memo,
+    }
+    .data();
+
+    Ok(Instruction {
+        program_id: bpl_token_metadata::id(),
+        accounts,
+        data,
+    })
+}
+
+pub fn create_mint_promo_2022_instruction(
+    payer: Pubkey,
+    group: Pubkey,
+    token_owner: Pubkey,
+    mint: Pubkey,
+    memo: Option<String>,
+) -> Result<Instruction, AppError> {
+    let (authority, _auth_bump) = find_authority_address();
+    let (promo, _promo_bump) = find_promo_address(&mint);
+    let token_account = token_2022::find_associated_token_address(&token_owner, &mint);
+
+    let accounts = mint_promo_token_2022_accounts {
+        payer,
+        group,
+        token_owner,
+        mint,
+        authority,
+        promo,
+        token_account,
+        token_program: token_2022::ID,
+        memo_program: spl_memo::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+        rent: sysvar::rent::id(),
+        system_program: system_program::ID,
+    }
+    .to_account_metas(Some(true));
+
+    let data = mint_promo_token_2022_instruction { memo }.data();
+
+    Ok(Instruction {
+        program_id: bpl_token_metadata::id(),
+        accounts,
+        data,
+    })
+}
+
+pub fn create_burn_promo_2022_instruction(
+    payer: Pubkey,
+    group: Pubkey,
+    token_owner: Pubkey,
+    mint: Pubkey,
+    platform: Pubkey,
+    memo: Option<String>,
+) -> Result<Instruction, AppError> {
+    let (authority, _auth_bump) = find_authority_address();
+    let (promo, _promo_bump) = find_promo_address(&mint);
+    let (admin_settings, _admin_bump) = find_admin_address();
+    let token_account = token_2022::find_associated_token_address(&token_owner, &mint);
+
+    let accounts = burn_promo_token_2022_accounts {
+        payer,
+        group,
+        mint,
+        authority,
+        promo,
+        platform,
+        admin_settings,
+        token_account,
+        token_program: token_2022::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+        memo_program: spl_memo::ID,
+        rent: sysvar::rent::id(),
+        system_program: system_program::ID,
+    }
+    .to_account_metas(Some(true));
+
+    let data = burn_promo_token_2022_instruction { memo }.data();
+
+    Ok(Instruction {
+        program_id: bpl_token_metadata::id(),
+        accounts,
+        data,
+    })
+}
+
+pub fn create_create_promo_acceptance_instruction(
+    payer: Pubkey,
+    group: Pubkey,
+    mint: Pubkey,
+    groups: Vec<Pubkey>,
+    settlement: Settlement,
+    settlement_lamports: u64,
+    memo: Option<String>,
+) -> Result<Instruction, AppError> {
+    if groups.len() > ACCEPTANCE_CAPACITY as usize {
+        return Err(AppError::AcceptanceCapacityExceeded);
+    }
+
+    let (promo, _promo_bump) = find_promo_address(&mint);
+    let (promo_acceptance, _acceptance_bump) = find_promo_acceptance_address(&mint);
+
+    let data = PromoAcceptance {
+        promo,
+        owner: group,
+        groups,
+        settlement,
+        settlement_lamports,
+    };
+
+    let accounts = create_promo_acceptance_accounts {
+        payer,
+        group,
+        mint,
+        promo,
+        promo_acceptance,
+        memo_program: spl_memo::ID,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(Some(true));
+
+    let data = create_promo_acceptance_instruction { data, memo }.data();
+
+    Ok(Instruction {
+        program_id: bpl_token_metadata::id(),
+        accounts,
+        data,
+    })
+}
+
+pub fn create_create_settlement_approval_instruction(
+    payer: Pubkey,
+    group: Pubkey,
+    mint: Pubkey,
+    max_settlement_lamports: u64,
+    memo: Option<String>,
+) -> Result<Instruction, AppError> {
+    let (promo, _promo_bump) = find_promo_address(&mint);
+    let (settlement_approval, _approval_bump) = find_settlement_approval_address(&mint, &group);
+
+    let data = SettlementApproval {
+        promo,
+        group,
+        max_settlement_lamports,
+    };
+
+    let accounts = create_settlement_approval_accounts {
+        payer,
+        group,
+        mint,
+        promo,
+        settlement_approval,
+        memo_program: spl_memo::ID,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(Some(true));
+
+    let data = create_settlement_approval_instruction { data, memo }.data();
+
+    Ok(Instruction {
+        program_id: bpl_token_metadata::id(),
+        accounts,
+        data,
+    })
+}
+
+pub fn create_delegate_accepted_promo_instruction(
+    payer: Pubkey,
+    delegate: Pubkey,
+    group: Pubkey,
+    token_owner: Pubkey,
+    mint: Pubkey,
+    memo: Option<String>,
+) -> Result<Instruction, AppError> {
+    let (promo, _promo_bump) = find_promo_address(&mint);
+    let (promo_acceptance, _acceptance_bump) = find_promo_acceptance_address(&mint);
+    let token_account = find_associated_token_address(&token_owner, &mint);
+
+    let accounts = delegate_accepted_promo_token_accounts {
+        payer,
+        delegate,
+        group,
+        token_owner,
+        mint,
+        promo,
+        promo_acceptance,
+        token_account,
+        token_program: anchor_spl::token::ID,
+        memo_program: spl_memo::ID,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(Some(true));
+
+    let data = delegate_accepted_promo_token_instruction { memo }.data();
+
+    Ok(Instruction {
+        program_id: bpl_token_metadata::id(),
+        accounts,
+        data,
+    })
+}
+
+pub fn create_burn_accepted_promo_instruction(
+    payer: Pubkey,
+    group: Pubkey,
+    issuing_group: Pubkey,
+    token_owner: Pubkey,
+    mint: Pubkey,
+    platform: Pubkey,
+    memo: Option<String>,
+) -> Result<Instruction, AppError> {
+    let (promo, _promo_bump) = find_promo_address(&mint);
+    let (promo_acceptance, _acceptance_bump) = find_promo_acceptance_address(&mint);
+    let (settlement_approval, _approval_bump) = find_settlement_approval_address(&mint, &group);
+    let (admin_settings, _admin_bump) = find_admin_address();
+    let token_account = find_associated_token_address(&token_owner, &mint);
+
+    let accounts = burn_accepted_promo_token_accounts {
+        payer,
+        group,
+        issuing_group,
+        mint,
+        promo,
+        promo_acceptance,
+        settlement_approval,
+        platform,
+        admin_settings,
+        token_account,
+        token_program: anchor_spl::token::ID,
+        memo_program: spl_memo::ID,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(Some(true));
+
+    let data = burn_accepted_promo_token_instruction { memo }.data();
+
+    Ok(Instruction {
+        program_id: bpl_token_metadata::id(),
+        accounts,
+        data,
+    })
+}
+
+pub fn create_create_loyalty_instruction(
+    payer: Pubkey,
+    group_seed: Pubkey,
+    mint: Pubkey,
+    earn_rate: u32,
+    levels: Vec<RewardLevel>,
+    memo: Option<String>,
+) -> Result<Instruction, AppError> {
+    if levels.is_empty() || levels.len() > LOYALTY_LEVELS_CAPACITY as usize {
+        return Err(AppError::InvalidLoyaltyLevels);
+    }
+
+    let (group, _group_bump) = find_group_address(&group_seed);
+    let (promo, _promo_bump) = find_promo_address(&mint);
+    let (loyalty, _loyalty_bump) = find_loyalty_address(&mint);
+
+    let data = Loyalty {
+        promo,
+        mint,
+        earn_rate,
+        levels,
+        points_earned: 0,
+        points_redeemed: 0,
+    };
+
+    let accounts = create_loyalty_accounts {
+        payer,
+        group,
+        mint,
+        promo,
+        loyalty,
+        memo_program: spl_memo::ID,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(Some(true));
+
+    let data = create_loyalty_instruction { data, memo }.data();
+
+    Ok(Instruction {
+        program_id: bpl_token_metadata::id(),
+        accounts,
+        data,
+    })
+}
+
+pub fn create_earn_points_instruction(
+    payer: Pubkey,
+    group: Pubkey,
+    token_owner: Pubkey,
+    mint: Pubkey,
+    order_amount: u64,
+    memo: Option<String>,
+) -> Result<Instruction, AppError> {
+    let (authority, _auth_bump) = find_authority_address();
+    let (promo, _promo_bump) = find_promo_address(&mint);
+    let (loyalty, _loyalty_bump) = find_loyalty_address(&mint);
+    let token_account = find_associated_token_address(&token_owner, &mint);
+
+    let accounts = earn_points_accounts {
+        payer,
+        group,
+        token_owner,
+        mint,
+        authority,
+        promo,
+        loyalty,
+        token_account,
+        token_program: anchor_spl::token::ID,
+        memo_program: spl_memo::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+        rent: sysvar::rent::id(),
+        system_program: system_program::ID,
+    }
+    .to_account_metas(Some(true));
+
+    let data = earn_points_instruction { order_amount, memo }.data();
+
+    Ok(Instruction {
+        program_id: bpl_token_metadata::id(),
+        accounts,
+        data,
+    })
+}
+
+pub fn create_redeem_points_instruction(
+    payer: Pubkey,
+    group: Pubkey,
+    token_owner: Pubkey,
+    mint: Pubkey,
+    level: u8,
+    memo: Option<String>,
+) -> Result<Instruction, AppError> {
+    let (promo, _promo_bump) = find_promo_address(&mint);
+    let (loyalty, _loyalty_bump) = find_loyalty_address(&mint);
+    let token_account = find_associated_token_address(&token_owner, &mint);
+
+    let accounts = redeem_points_accounts {
+        payer,
+        group,
+        token_owner,
+        mint,
+        promo,
+        loyalty,
+        token_account,
+        token_program: anchor_spl::token::ID,
+        memo_program: spl_memo::ID,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(Some(true));
+
+    let data = redeem_points_instruction { level, memo }.data();
+
+    Ok(Instruction {
+        program_id: bpl_token_metadata::id(),
+        accounts,
+        data,
+    })
+}
+
+/// Returns the instruction for `payer`, the owner of `group`, allowing up to `max_lamports` in
+/// total to be settled from the group to the platform.
+pub fn create_create_sponsorship_allowance_instruction(
+    payer: Pubkey,
+    group: Pubkey,
+    max_lamports: u64,
+    memo: Option<String>,
+) -> Result<Instruction, AppError> {
+    let (sponsorship_allowance, _allowance_bump) = find_sponsorship_allowance_address(&group);
+
+    let accounts = create_sponsorship_allowance_accounts {
+        payer,
+        group,
+        sponsorship_allowance,
+        memo_program: spl_memo::ID,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(Some(true));
+
+    let data = create_sponsorship_allowance_instruction { max_lamports, memo }.data();
+
+    Ok(Instruction {
+        program_id: bpl_token_metadata::id(),
+        accounts,
+        data,
+    })
+}
+
+/// Returns the instruction transferring `lamports` from `group` to `platform` to reimburse
+/// transactions `payer`, a member of the group, sponsored for it.
+pub fn create_settle_sponsorship_instruction(
+    payer: Pubkey,
+    group: Pubkey,
+    platform: Pubkey,
+    lamports: u64,
+    memo: Option<String>,
+) -> Result<Instruction, AppError> {
+    let (admin_settings, _admin_bump) = find_admin_address();
+    let (sponsorship_allowance, _allowance_bump) = find_sponsorship_allowance_address(&group);
+
+    let accounts = settle_sponsorship_accounts {
+        payer,
+        group,
+        sponsorship_allowance,
+        platform,
+        admin_settings,
+        memo_program: spl_memo::ID,
+    }
+    .to_account_metas(Some(true));
+
+    let data = settle_sponsorship_instruction { lamports, memo }.data();
+
+    Ok(Instruction {
+        program_id: bpl_token_metadata::id(),
+        accounts,
+        data,
+    })
+}
+
+pub fn create_create_referral_reward_instruction(
+    payer: Pubkey,
+    group: Pubkey,
+    mint: Pubkey,
+    reward_mint: Pubkey,
+    memo: Option<String>,
+) -> Result<Instruction, AppError> {
+    let (promo, _promo_bump) = find_promo_address(&mint);
+    let (reward_promo, _reward_promo_bump) = find_promo_address(&reward_mint);
+    let (referral_reward, _referral_reward_bump) = find_referral_reward_address(&mint);
+
+    let accounts = create_referral_reward_accounts {
+        payer,
+        group,
+        mint,
+        promo,
+        reward_mint,
+        reward_promo,
+        referral_reward,
+        memo_program: spl_memo::ID,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(Some(true));
+
+    let data = create_referral_reward_instruction { memo }.data();
+
+    Ok(Instruction {
+        program_id: bpl_token_metadata::id(),
+        accounts,
+        data,
+    })
+}
+
+pub fn create_mint_referred_promo_instruction(
+    payer: Pubkey,
+    group: Pubkey,
+    token_owner: Pubkey,
+    mint: Pubkey,
+    referrer: Pubkey,
+    memo: Option<String>,
+) -> Result<Instruction, AppError> {
+    let (authority, _auth_bump) = find_authority_address();
+    let (promo, _promo_bump) = find_promo_address(&mint);
+    let (loyalty, _loyalty_bump) = find_loyalty_address(&mint);
+    let (referral_reward, _referral_reward_bump) = find_referral_reward_address(&mint);
+    let (referral, _referral_bump) = find_referral_address(&mint, &token_owner);
+    let token_account = find_associated_token_address(&token_owner, &mint);
+
+    let accounts = mint_referred_promo_token_accounts {
+        payer,
+        group,
+        token_owner,
+        mint,
+        authority,
+        promo,
+        loyalty,
+        referral_reward,
+        referral,
+        token_account,
+        token_program: anchor_spl::token::ID,
+        memo_program: spl_memo::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+        rent: sysvar::rent::id(),
+        system_program: system_program::ID,
+    }
+    .to_account_metas(Some(true));
+
+    let data = mint_referred_promo_token_instruction { referrer, memo }.data();
+
+    Ok(Instruction {
+        program_id: bpl_token_metadata::id(),
+        accounts,
+        data,
+    })
+}
+
+pub fn create_burn_referred_promo_instruction(
+    payer: Pubkey,
+    group: Pubkey,
+    token_owner: Pubkey,
+    mint: Pubkey,
+    platform: Pubkey,
+    referrer: Pubkey,
+    reward_mint: Pubkey,
+    memo: Option<String>,
+) -> Result<Instruction, AppError> {
+    let (authority, _auth_bump) = find_authority_address();
+    let (promo, _promo_bump) = find_promo_address(&mint);
+    let (admin_settings, _admin_bump) = find_admin_address();
+    let token_account = find_associated_token_address(&token_owner, &mint);
+    let (referral, _referral_bump) = find_referral_address(&mint, &token_owner);
+    let (referral_reward, _referral_reward_bump) = find_referral_reward_address(&mint);
+    let (reward_promo, _reward_promo_bump) = find_promo_address(&reward_mint);
+    let referrer_token_account = find_associated_token_address(&referrer, &reward_mint);
+
+    let accounts = burn_referred_promo_token_accounts {
+        payer,
+        group,
+        mint,
+        authority,
+        promo,
+        platform,
+        admin_settings,
+        token_account,
+        referral,
+        referral_reward,
+        referrer,
+        reward_mint,
+        reward_promo,
+        referrer_token_account,
+        token_program: anchor_spl::token::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+        memo_program: spl_memo::ID,
+        rent: sysvar::rent::id(),
+        system_program: system_program::ID,
+    }
+    .to_account_metas(Some(true));
+
+    let data = burn_referred_promo_token_instruction { memo }.data();
+
+    Ok(Instruction {
+        program_id: bpl_token_metadata::id(),
+        accounts,
+        data,
+    })
+}
+
+/// Returns the group minting in `tx` if it only has the instructions the merchant mint
+/// endpoint creates: compute budget instructions, advancing a durable nonce first, and a single
+/// `mint_promo_token` instruction under either token program paid for by the fee payer, with
+/// the accounts the server creates it with.
+pub fn check_merchant_mint_transaction(tx: &Transaction) -> Result<Pubkey, AppError> {
+    let invalid = |reason: &str| AppError::InvalidSignedTransaction(reason.to_string());
+    let keys = &tx.message.account_keys;
+    let mut group = None;
+    for (index, ix) in tx.message.instructions.iter().enumerate() {
+        let program_id = keys
+            .get(ix.program_id_index as usize)
+            .ok_or_else(|| invalid("missing program"))?;
+        if *program_id == compute_budget::ID {
+            continue;
+        }
+        if index == 0
+            && *program_id == system_program::ID
+            && bincode::deserialize::<SystemInstruction>(&ix.data).ok()
+                == Some(SystemInstruction::AdvanceNonceAccount)
+        {
+            continue;
+        }
+        if *program_id != bpl_token_metadata::id() || group.is_some() {
+            return Err(invalid("unexpected instruction"));
+        }
+
+        let accounts = ix
+            .accounts
+            .iter()
+            .map(|index| keys.get(*index as usize).copied())
+            .collect::<Option<Vec<Pubkey>>>()
+            .ok_or_else(|| invalid("missing account"))?;
+        let (payer, mint_group, token_owner, mint) = match accounts[..] {
+            [payer, group, token_owner, mint, ..] => (payer, group, token_owner, mint),
+            _ => return Err(invalid("not a promo mint transaction")),
+        };
+        let expected = if ix
+            .data
+            .starts_with(&mint_promo_token_instruction { memo: None }.data()[..8])
+        {
+            create_mint_promo_instruction(payer, mint_group, token_owner, mint, None)?
+        } else if ix
+            .data
+            .starts_with(&mint_promo_token_2022_instruction { memo: None }.data()[..8])
+        {
+            create_mint_promo_2022_instruction(payer, mint_group, token_owner, mint, None)?
+        } else {
+            return Err(invalid("not a promo mint transaction"));
+        };
+        let expected_accounts: Vec<Pubkey> =
+            expected.accounts.iter().map(|meta| meta.pubkey).collect();
+        if accounts != expected_accounts || Some(&payer) != keys.first() {
+            return Err(invalid("unexpected promo mint accounts"));
+        }
+        group = Some(mint_group);
+    }
+    group.ok_or_else(|| invalid("not a promo mint transaction"))
+}
+
+/// Returns the address of the durable nonce account the platform manages for `group`, along
+/// with the seed it is derived with from the platform signer.
+pub fn find_group_nonce_address(platform_signer: &Pubkey, group: &Pubkey) -> (Pubkey, String) {
+    // Seeds are limited to 32 bytes.
+    let seed = group.to_string()[..32].to_string();
+    let nonce = Pubkey::create_with_seed(platform_signer, &seed, &system_program::ID).unwrap();
+    (nonce, seed)
+}
+
+/// Creates the durable nonce account for `group`, with the platform signer as base and
+/// authority. The payer funds the rent exempt balance.
+pub fn create_create_group_nonce_instructions(
+    payer: Pubkey,
+    platform_signer: Pubkey,
+    group: Pubkey,
+) -> Vec<Instruction> {
+    let (nonce, seed) = find_group_nonce_address(&platform_signer, &group);
+    let lamports = Rent::default().minimum_balance(NonceState::size());
+
+    tracing::debug!(
+        payer = payer.to_string(),
+        group = group.to_string(),
+        nonce = nonce.to_string(),
+        lamports
+    );
+
+    system_instruction::create_nonce_account_with_seed(
+        &payer,
+        &nonce,
+        &platform_signer,
+        &seed,
+        &platform_signer,
+        lamports,
+    )
+}
+
+/// Maps a simulation error to a program error if it came from one of this program's
+/// instructions, or to the Anchor error in the program logs otherwise.
+pub fn get_simulation_error(tx: &Transaction, err: &Value, logs: &[String]) -> SimulationError {
+    let instruction_error = &err["InstructionError"];
+    let program_id = instruction_error[0]
+        .as_u64()
+        .and_then(|index| tx.message.instructions.get(index as usize))
+        .map(|ix| tx.message.account_keys[ix.program_id_index as usize]);
+
+    if program_id == Some(bpl_token_metadata::id()) {
+        if let Some(code) = instruction_error[1]["Custom"].as_u64() {
+            if let Some(error) = PROGRAM_ERRORS
+                .iter()
+                .find(|e| u32::from(**e) as u64 == code)
+            {
+                return SimulationError::Program(*error);
+            }
+        }
+    }
+
+    // Anchor logs errors as "... Error Code: {name}. Error Number: {code}. Error Message: {msg}."
+    let anchor_error = logs.iter().rev().find_map(|log| {
+        let (_, error) = log.split_once("Error Code: ")?;
+        let (name, error) = error.split_once(". Error Number: ")?;
+        let (code, message) = error.split_once(". Error Message: ")?;
+        Some(SimulationError::Anchor {
+            code: code.parse().ok()?,
+            name: name.to_string(),
+            message: message.trim_end_matches('.').to_string(),
+        })
+    });
+
+    anchor_error.unwrap_or_else(|| SimulationError::Other(err.to_string()))
+}
+
+/// Status of a submitted transaction at the confirmed commitment level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureStatus {
+    /// Not yet confirmed, including transactions that haven't been submitted.
+    Pending,
+    Confirmed,
+    /// Confirmed with the error returned by the RPC.
+    Failed(String),
+}
+
+/// Lamports a confirmed transaction cost an account.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransactionCost {
+    /// Network fee, if the account was the fee payer.
+    pub fee: u64,
+    /// Other lamports the account spent, mostly rent for accounts the transaction created.
+    pub rent: u64,
+    /// Whether the transaction failed, in which case only the fee was charged.
+    pub failed: bool,
+}
+
+// Needed to do this since nonblocking client not avaiable in 1.9.20.
+pub struct Solana {
+    pub cluster: Cluster,
+    pub commitment: CommitmentLevel,
+    pub client: reqwest::Client,
+}
+
+impl Solana {
+    pub fn new(cluster: Cluster) -> Self {
+        Self {
+            cluster,
+            commitment: CommitmentLevel::Confirmed,
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap(),
+        }
+    }
+
+    pub async fn get_latest_blockhash(&self) -> Result<Hash, AppError> {
+        let mut config = serde_json::Map::new();
+        config.insert(
+            "commitment".to_string(),
+            Value::String(self.commitment.to_string()),
+        );
+
+        let post_object = PostObject {
+            method: String::from("getLatestBlockhash"),
+            ..Default::default()
+        };
+
+        let result: Value = self
+            .client
+            .post(self.cluster.url())
+            .json(&post_object)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        let hash_str = result["result"]["value"]["blockhash"].as_str().unwrap();
+        let hash = Hash::from_str(hash_str)?;
+        Ok(hash)
+    }
+
+    /// Returns the blockhash currently stored in a durable nonce account.
+    pub async fn get_nonce_blockhash(&self, nonce: &Pubkey) -> Result<Hash, AppError> {
+        let post_object = PostObject {
+            method: String::from("getAccountInfo"),
+            params: vec![
+                json!(nonce.to_string()),
+                json!({"encoding": "base64", "commitment": self.commitment.to_string()}),
+            ],
+            ..Default::default()
+        };
+
+        let result: Value = self
+            .client
+            .post(self.cluster.url())
+            .json(&post_object)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        let data_str = result["result"]["value"]["data"][0]
+            .as_str()
+            .ok_or(AppError::NonceNotFound(nonce.to_string()))?;
+        let data =
+            base64::decode(data_str).map_err(|_| AppError::NonceNotFound(nonce.to_string()))?;
+        match bincode::deserialize::<Versions>(&data)?.convert_to_current() {
+            NonceState::Initialized(data) => Ok(data.blockhash),
+            NonceState::Uninitialized => Err(AppError::NonceNotFound(nonce.to_string())),
+        }
+    }
+
+    /// Returns prioritization fees paid in recent slots by transactions writing to `accounts`.
+    pub async fn get_recent_prioritization_fees(
+        &self,
+        accounts: &[Pubkey],
+    ) -> Result<Vec<u64>, AppError> {
+        let accounts: Vec<String> = accounts.iter().map(ToString::to_string).collect();
+        let post_object = PostObject {
+            method: String::from("getRecentPrioritizationFees"),
+            params: vec![json!(accounts)],
+            ..Default::default()
+        };
+
+        let result: Value = self
+            .client
+            .post(self.cluster.url())
+            .json(&post_object)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        Ok(result["result"]
+            .as_array()
+            .map(|fees| {
+                fees.iter()
+                    .filter_map(|fee| fee["prioritizationFee"].as_u64())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Simulates a transaction before it is returned to be signed so requests for transactions
+    /// that would fail, for example because a promo's max mints have been reached, get an error
+    /// response instead. Signatures aren't verified since most transactions are unsigned.
+    /// `tx_str` is the base64 encoded transaction, legacy or versioned, and `tx` its legacy
+    /// equivalent used to map errors to instructions.
+    pub async fn simulate_transaction(
+        &self,
+        tx_str: &str,
+        tx: &Transaction,
+    ) -> Result<(), AppError> {
+        let post_object = PostObject {
+            method: String::from("simulateTransaction"),
+            params: vec![
+                json!(tx_str),
+                json!({
+                    "encoding": "base64",
+                    "sigVerify": false,
+                    "commitment": self.commitment.to_string(),
+                }),
+            ],
+            ..Default::default()
+        };
+
+        let result: Value = self
+            .client
+            .post(self.cluster.url())
+            .json(&post_object)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        if let Some(message) = result["error"]["message"].as_str() {
+            return Err(AppError::SolanaPostError(message.to_string()));
+        }
+
+        let value = &result["result"]["value"];
+        if value["err"].is_null() {
+            return Ok(());
+        }
+
+        let logs: Vec<String> = value["logs"]
+            .as_array()
+            .map(|logs| {
+                logs.iter()
+                    .filter_map(|log| log.as_str().map(ToString::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let error = get_simulation_error(tx, &value["err"], &logs);
+        tracing::debug!(error = error.to_string(), ?logs);
+
+        Err(AppError::SimulationError { error, logs })
+    }
+
+    /// Returns whether the transaction with `signature` has been confirmed, failed or not yet
+    /// landed. Only recent transactions are searched, which covers those issued for checkout
+    /// sessions.
+    pub async fn get_signature_status(&self, signature: &str) -> Result<SignatureStatus, AppError> {
+        let post_object = PostObject {
+            method: String::from("getSignatureStatuses"),
+            params: vec![json!([signature])],
+            ..Default::default()
+        };
+
+        let result: Value = self
+            .client
+            .post(self.cluster.url())
+            .json(&post_object)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        if let Some(message) = result["error"]["message"].as_str() {
+            return Err(AppError::SolanaPostError(message.to_string()));
+        }
+
+        let status = &result["result"]["value"][0];
+        let confirmed = matches!(
+            status["confirmationStatus"].as_str(),
+            Some("confirmed" | "finalized")
+        );
+        if !confirmed {
+            return Ok(SignatureStatus::Pending);
+        }
+        if status["err"].is_null() {
+            Ok(SignatureStatus::Confirmed)
+        } else {
+            Ok(SignatureStatus::Failed(status["err"].to_string()))
+        }
+    }
+
+    /// Returns what the transaction with `signature` cost `account` from the change in its
+    /// balance, or `None` if the transaction hasn't been confirmed.
+    pub async fn get_transaction_cost(
+        &self,
+        signature: &str,
+        account: &Pubkey,
+    ) -> Result<Option<TransactionCost>, AppError> {
+        let post_object = PostObject {
+            method: String::from("getTransaction"),
+            params: vec![
+                json!(signature),
+                json!({
+                    "encoding": "json",
+                    "commitment": self.commitment.to_string(),
+                    "maxSupportedTransactionVersion": 0,
+                }),
+            ],
+            ..Default::default()
+        };
+
+        let result: Value = self
+            .client
+            .post(self.cluster.url())
+            .json(&post_object)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        if let Some(message) = result["error"]["message"].as_str() {
+            return Err(AppError::SolanaPostError(message.to_string()));
+        }
+
+        let transaction = &result["result"];
+        if transaction.is_null() {
+            return Ok(None);
+        }
+
+        // Signers of v0 transactions are always in the static account keys.
+        let meta = &transaction["meta"];
+        let account = account.to_string();
+        let index = match transaction["transaction"]["message"]["accountKeys"]
+            .as_array()
+            .and_then(|keys| keys.iter().position(|key| key.as_str() == Some(&account)))
+        {
+            Some(index) => index,
+            None => return Ok(Some(TransactionCost::default())),
+        };
+        let spent = meta["preBalances"][index]
+            .as_u64()
+            .unwrap_or_default()
+            .saturating_sub(meta["postBalances"][index].as_u64().unwrap_or_default());
+        let fee = match index {
+            0 => meta["fee"].as_u64().unwrap_or_default().min(spent),
+            _ => 0,
+        };
+
+        Ok(Some(TransactionCost {
+            fee,
+            rent: spent - fee,
+            failed: !meta["err"].is_null(),
+        }))
     }
 
     pub async fn post_transaction(&self, tx_str: &str) -> Result<SendTransResultObject, AppError> {
@@ -0,0 +1,610 @@
+//! Accounting and settlement of transactions sponsored for groups.
+
+use anchor_lang::prelude::Pubkey;
+use async_trait::async_trait;
+use axum::{
+    body::{self, Body, Empty, Full},
+    http::Request,
+    middleware::Next,
+    response::Response,
+};
+use deadpool_postgres::Pool;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_sdk::{signature::Signature, signer::Signer};
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    error::AppError,
+    handlers::TransactionQuery,
+    utils::{
+        checkout::get_transaction_signature, postgres::parse_pubkey, rate_limit::SponsoredGroup,
+        solana::create_settle_sponsorship_instruction,
+    },
+    State,
+};
+
+const LEDGER_ENTRY_UPSERT: &str = include_str!("queries/ledger_entry_upsert.sql");
+const LEDGER_ENTRY_DELETE: &str = include_str!("queries/ledger_entry_delete.sql");
+const PENDING_LEDGER_ENTRIES_SELECT: &str =
+    include_str!("queries/pending_ledger_entries_select.sql");
+const GROUP_USAGE_SELECT: &str = include_str!("queries/group_usage_select.sql");
+const GROUP_BILLING_UPSERT: &str = include_str!("queries/group_billing_upsert.sql");
+const REIMBURSING_GROUPS_SELECT: &str = include_str!("queries/reimbursing_groups_select.sql");
+
+/// How often pending ledger entries are checked for confirmation.
+pub const RECONCILE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Sponsored transactions not confirmed within this time are assumed to never have been signed
+/// and are removed. Transactions using a group's durable nonce can be submitted long after they
+/// were requested, so this is much longer than blockhashes are valid.
+pub const SPONSORED_TRANSACTION_EXPIRY: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Settlements not confirmed once their blockhash has expired have failed.
+pub const SETTLEMENT_EXPIRY: Duration = Duration::from_secs(60 * 5);
+
+/// Days of daily usage returned by default.
+pub const DEFAULT_USAGE_DAYS: u64 = 30;
+
+pub const MAX_USAGE_DAYS: u64 = 366;
+
+/// Outstanding lamports below which groups aren't settled, so that settlement fees stay small
+/// relative to the amount reimbursed.
+pub const DEFAULT_SETTLEMENT_MIN_LAMPORTS: u64 = 10_000_000;
+
+const DAY: Duration = Duration::from_secs(60 * 60 * 24);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LedgerEntryKind {
+    /// Transaction paid for by the platform signer for a member of the group.
+    Sponsored,
+    /// Transaction reimbursing the platform from the group's balance.
+    Settlement,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LedgerEntryStatus {
+    Pending,
+    Confirmed,
+    /// Failed transactions still cost their fee.
+    Failed,
+}
+
+impl std::fmt::Display for LedgerEntryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            LedgerEntryKind::Sponsored => "sponsored",
+            LedgerEntryKind::Settlement => "settlement",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+impl FromStr for LedgerEntryKind {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sponsored" => Ok(LedgerEntryKind::Sponsored),
+            "settlement" => Ok(LedgerEntryKind::Settlement),
+            _ => Err(AppError::MalformedData(format!("ledger entry kind: {}", s))),
+        }
+    }
+}
+
+impl std::fmt::Display for LedgerEntryStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            LedgerEntryStatus::Pending => "pending",
+            LedgerEntryStatus::Confirmed => "confirmed",
+            LedgerEntryStatus::Failed => "failed",
+        };
+        write!(f, "{}", status)
+    }
+}
+
+impl FromStr for LedgerEntryStatus {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(LedgerEntryStatus::Pending),
+            "confirmed" => Ok(LedgerEntryStatus::Confirmed),
+            "failed" => Ok(LedgerEntryStatus::Failed),
+            _ => Err(AppError::MalformedData(format!(
+                "ledger entry status: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Transaction the platform signer paid for, keyed by signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LedgerEntry {
+    pub signature: String,
+    pub group: Pubkey,
+    pub kind: LedgerEntryKind,
+    pub status: LedgerEntryStatus,
+    /// Network fee paid by the platform signer, known once confirmed.
+    pub fee: u64,
+    /// Rent paid by the platform signer for accounts the transaction created, known once
+    /// confirmed.
+    pub rent: u64,
+    /// Lamports reimbursed by settlements.
+    pub lamports: u64,
+    pub created_at: SystemTime,
+}
+
+impl LedgerEntry {
+    pub fn new(signature: String, group: Pubkey, kind: LedgerEntryKind, lamports: u64) -> Self {
+        Self {
+            signature,
+            group,
+            kind,
+            status: LedgerEntryStatus::Pending,
+            fee: 0,
+            rent: 0,
+            lamports,
+            created_at: SystemTime::now(),
+        }
+    }
+
+    fn expiry(&self) -> Duration {
+        match self.kind {
+            LedgerEntryKind::Sponsored => SPONSORED_TRANSACTION_EXPIRY,
+            LedgerEntryKind::Settlement => SETTLEMENT_EXPIRY,
+        }
+    }
+}
+
+/// Sponsored transactions of a group for a day.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyUsage {
+    /// Start of the day in seconds since the unix epoch.
+    pub day: u64,
+    pub transactions: u64,
+    pub fees: u64,
+    pub rent: u64,
+}
+
+/// Totals of confirmed and failed transactions sponsored for a group and of its settlements.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupUsage {
+    pub transactions: u64,
+    /// Fees paid by the platform signer, including for settlements.
+    pub fees: u64,
+    pub rent: u64,
+    /// Lamports reimbursed by settlements that haven't failed.
+    pub settled: u64,
+    /// Whether the group has opted in to reimburse the platform.
+    pub reimburse: bool,
+    /// Most recent first.
+    pub days: Vec<DailyUsage>,
+}
+
+impl GroupUsage {
+    /// Lamports the group owes the platform.
+    pub fn outstanding(&self) -> u64 {
+        (self.fees + self.rent).saturating_sub(self.settled)
+    }
+}
+
+/// Where sponsored transactions and settlements are recorded.
+#[async_trait]
+pub trait SponsorshipLedger: Send + Sync {
+    /// Records `entry`, replacing any entry with the same signature.
+    async fn upsert_entry(&self, entry: &LedgerEntry) -> Result<(), AppError>;
+
+    async fn delete_entry(&self, signature: &str) -> Result<(), AppError>;
+
+    async fn get_pending_entries(&self) -> Result<Vec<LedgerEntry>, AppError>;
+
+    /// Returns the usage of `group` with daily usage for days starting from `since`.
+    async fn get_usage(&self, group: &Pubkey, since: SystemTime) -> Result<GroupUsage, AppError>;
+
+    /// Sets whether `group` reimburses the platform, with the group owner's `signature` of
+    /// the request.
+    async fn set_reimburse(
+        &self,
+        group: &Pubkey,
+        reimburse: bool,
+        signature: &str,
+    ) -> Result<(), AppError>;
+
+    async fn get_reimbursing_groups(&self) -> Result<Vec<Pubkey>, AppError>;
+}
+
+/// Ledger for running without a database. Entries are lost on restart.
+#[derive(Default)]
+pub struct MemorySponsorshipLedger {
+    entries: Mutex<HashMap<String, LedgerEntry>>,
+    reimburse: Mutex<HashMap<Pubkey, bool>>,
+}
+
+#[async_trait]
+impl SponsorshipLedger for MemorySponsorshipLedger {
+    async fn upsert_entry(&self, entry: &LedgerEntry) -> Result<(), AppError> {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(entry.signature.clone(), entry.clone());
+        Ok(())
+    }
+
+    async fn delete_entry(&self, signature: &str) -> Result<(), AppError> {
+        self.entries.lock().unwrap().remove(signature);
+        Ok(())
+    }
+
+    async fn get_pending_entries(&self) -> Result<Vec<LedgerEntry>, AppError> {
+        Ok(self
+            .entries
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|entry| entry.status == LedgerEntryStatus::Pending)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_usage(&self, group: &Pubkey, since: SystemTime) -> Result<GroupUsage, AppError> {
+        let mut usage = GroupUsage {
+            reimburse: self
+                .reimburse
+                .lock()
+                .unwrap()
+                .get(group)
+                .copied()
+                .unwrap_or_default(),
+            ..GroupUsage::default()
+        };
+        let mut days: HashMap<u64, DailyUsage> = HashMap::new();
+        for entry in self.entries.lock().unwrap().values() {
+            if &entry.group != group {
+                continue;
+            }
+            if entry.kind == LedgerEntryKind::Settlement
+                && entry.status != LedgerEntryStatus::Failed
+            {
+                usage.settled += entry.lamports;
+            }
+            if entry.status == LedgerEntryStatus::Pending {
+                continue;
+            }
+            usage.fees += entry.fee;
+            usage.rent += entry.rent;
+            if entry.kind == LedgerEntryKind::Sponsored {
+                usage.transactions += 1;
+                if entry.created_at >= since {
+                    let day = get_day(entry.created_at);
+                    let daily_usage = days.entry(day).or_insert(DailyUsage {
+                        day,
+                        ..DailyUsage::default()
+                    });
+                    daily_usage.transactions += 1;
+                    daily_usage.fees += entry.fee;
+                    daily_usage.rent += entry.rent;
+                }
+            }
+        }
+        usage.days = days.into_values().collect();
+        usage.days.sort_by(|a, b| b.day.cmp(&a.day));
+        Ok(usage)
+    }
+
+    async fn set_reimburse(
+        &self,
+        group: &Pubkey,
+        reimburse: bool,
+        _signature: &str,
+    ) -> Result<(), AppError> {
+        self.reimburse.lock().unwrap().insert(*group, reimburse);
+        Ok(())
+    }
+
+    async fn get_reimbursing_groups(&self) -> Result<Vec<Pubkey>, AppError> {
+        Ok(self
+            .reimburse
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, reimburse)| **reimburse)
+            .map(|(group, _)| *group)
+            .collect())
+    }
+}
+
+/// Ledger in the `api-data` database, shared by all server instances.
+pub struct PostgresSponsorshipLedger {
+    pool: Pool,
+}
+
+impl PostgresSponsorshipLedger {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SponsorshipLedger for PostgresSponsorshipLedger {
+    async fn upsert_entry(&self, entry: &LedgerEntry) -> Result<(), AppError> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                LEDGER_ENTRY_UPSERT,
+                &[
+                    &entry.signature,
+                    &entry.group.to_string(),
+                    &entry.kind.to_string(),
+                    &entry.status.to_string(),
+                    &(entry.fee as i64),
+                    &(entry.rent as i64),
+                    &(entry.lamports as i64),
+                    &entry.created_at,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_entry(&self, signature: &str) -> Result<(), AppError> {
+        let client = self.pool.get().await?;
+        client.execute(LEDGER_ENTRY_DELETE, &[&signature]).await?;
+        Ok(())
+    }
+
+    async fn get_pending_entries(&self) -> Result<Vec<LedgerEntry>, AppError> {
+        let client = self.pool.get().await?;
+        let mut entries = Vec::new();
+        for row in client.query(PENDING_LEDGER_ENTRIES_SELECT, &[]).await? {
+            entries.push(LedgerEntry {
+                signature: row.get(0),
+                group: parse_pubkey(row.get(1))?,
+                kind: row.get::<usize, &str>(2).parse()?,
+                status: row.get::<usize, &str>(3).parse()?,
+                fee: row.get::<usize, i64>(4) as u64,
+                rent: row.get::<usize, i64>(5) as u64,
+                lamports: row.get::<usize, i64>(6) as u64,
+                created_at: row.get(7),
+            });
+        }
+        Ok(entries)
+    }
+
+    async fn get_usage(&self, group: &Pubkey, since: SystemTime) -> Result<GroupUsage, AppError> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_one(GROUP_USAGE_SELECT, &[&group.to_string(), &since])
+            .await?;
+
+        serde_json::from_value(row.get::<usize, Value>(0))
+            .map_err(|e| AppError::MalformedData(e.to_string()))
+    }
+
+    async fn set_reimburse(
+        &self,
+        group: &Pubkey,
+        reimburse: bool,
+        signature: &str,
+    ) -> Result<(), AppError> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                GROUP_BILLING_UPSERT,
+                &[&group.to_string(), &reimburse, &signature],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_reimbursing_groups(&self) -> Result<Vec<Pubkey>, AppError> {
+        let client = self.pool.get().await?;
+        let mut groups = Vec::new();
+        for row in client.query(REIMBURSING_GROUPS_SELECT, &[]).await? {
+            groups.push(parse_pubkey(row.get(0))?);
+        }
+        Ok(groups)
+    }
+}
+
+/// Returns the start of the day of `time` in seconds since the unix epoch.
+pub fn get_day(time: SystemTime) -> u64 {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    seconds - seconds % DAY.as_secs()
+}
+
+/// Periodic settlement of the sponsorship of groups that reimburse the platform.
+#[derive(Debug, Clone, Copy)]
+pub struct SettlementConfig {
+    pub interval: Duration,
+    pub min_lamports: u64,
+}
+
+impl State {
+    /// Fills in the cost of sponsored transactions and settlements that have been confirmed,
+    /// removing sponsored transactions that were never submitted and failing settlements that
+    /// didn't land.
+    pub async fn reconcile_sponsorship(&self) -> Result<(), AppError> {
+        let payer = self.platform_signer.pubkey();
+        for mut entry in self.sponsorship.get_pending_entries().await? {
+            match self
+                .solana
+                .get_transaction_cost(&entry.signature, &payer)
+                .await?
+            {
+                Some(cost) => {
+                    entry.status = if cost.failed {
+                        LedgerEntryStatus::Failed
+                    } else {
+                        LedgerEntryStatus::Confirmed
+                    };
+                    entry.fee = cost.fee;
+                    entry.rent = cost.rent;
+                    self.sponsorship.upsert_entry(&entry).await?;
+                }
+                None if entry.created_at.elapsed().unwrap_or_default() > entry.expiry() => {
+                    match entry.kind {
+                        LedgerEntryKind::Sponsored => {
+                            self.sponsorship.delete_entry(&entry.signature).await?
+                        }
+                        LedgerEntryKind::Settlement => {
+                            entry.status = LedgerEntryStatus::Failed;
+                            self.sponsorship.upsert_entry(&entry).await?;
+                        }
+                    }
+                }
+                None => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Submits settlement transactions for groups that reimburse the platform and owe at least
+    /// `min_lamports`. Groups without enough balance fail simulation and are skipped until
+    /// they are topped up.
+    pub async fn settle_sponsorship(&self, min_lamports: u64) -> Result<(), AppError> {
+        let now = SystemTime::now();
+        for group in self.sponsorship.get_reimbursing_groups().await? {
+            let outstanding = self.sponsorship.get_usage(&group, now).await?.outstanding();
+            if outstanding < min_lamports {
+                continue;
+            }
+            match self.send_settlement(&group, outstanding).await {
+                Ok(signature) => tracing::info!(
+                    group = group.to_string(),
+                    outstanding,
+                    signature,
+                    "sponsorship settled"
+                ),
+                Err(error) => tracing::error!(group = group.to_string(), error = error.to_string()),
+            }
+        }
+        Ok(())
+    }
+
+    async fn send_settlement(&self, group: &Pubkey, lamports: u64) -> Result<String, AppError> {
+        let payer = self.platform_signer.pubkey();
+        let instruction =
+            create_settle_sponsorship_instruction(payer, *group, self.platform, lamports, None)?;
+        let query = TransactionQuery::default();
+        let tx = self
+            .create_transaction(&[instruction], &payer, None, &query)
+            .await?;
+        let transaction = self.encode_transaction(tx, &[], &query).await?;
+        let signature = self.solana.send_transaction(&transaction).await?;
+        self.sponsorship
+            .upsert_entry(&LedgerEntry::new(
+                signature.clone(),
+                *group,
+                LedgerEntryKind::Settlement,
+                lamports,
+            ))
+            .await?;
+        Ok(signature)
+    }
+}
+
+/// Reconciles the ledger every `RECONCILE_INTERVAL` and, if `settlement` is set, settles
+/// groups that reimburse the platform every `settlement.interval`.
+pub fn spawn_sponsorship_tasks(state: Arc<State>, settlement: Option<SettlementConfig>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(RECONCILE_INTERVAL);
+        let mut settled_at = SystemTime::now();
+        loop {
+            interval.tick().await;
+            if let Err(error) = state.reconcile_sponsorship().await {
+                tracing::error!(error = error.to_string());
+            }
+
+            let settlement = match settlement {
+                Some(settlement)
+                    if settled_at.elapsed().unwrap_or_default() >= settlement.interval =>
+                {
+                    settlement
+                }
+                _ => continue,
+            };
+            settled_at = SystemTime::now();
+            if let Err(error) = state.settle_sponsorship(settlement.min_lamports).await {
+                tracing::error!(error = error.to_string());
+            }
+        }
+    });
+}
+
+/// Middleware for sponsored routes recording the transactions in successful responses against
+/// the group found by `rate_limit`, which needs to be applied outside of it.
+pub async fn record_sponsorship(req: Request<Body>, next: Next<Body>) -> Response {
+    let group = req.extensions().get::<SponsoredGroup>().copied();
+    let state = req.extensions().get::<Arc<State>>().cloned();
+    let response = next.run(req).await;
+
+    let (SponsoredGroup(group), state) = match (group, state) {
+        (Some(group), Some(state)) if response.status().is_success() => (group, state),
+        _ => return response,
+    };
+
+    let (parts, response_body) = response.into_parts();
+    let bytes = match hyper::body::to_bytes(response_body).await {
+        Ok(bytes) => bytes,
+        Err(error) => {
+            tracing::error!(error = error.to_string());
+            return Response::from_parts(parts, body::boxed(Empty::new()));
+        }
+    };
+
+    let mut transactions = Vec::new();
+    if let Ok(value) = serde_json::from_slice::<Value>(&bytes) {
+        get_response_transactions(&value, &mut transactions);
+    }
+    for transaction in transactions {
+        let result = match get_transaction_signature(transaction) {
+            // Transactions with another fee payer aren't signed by the platform signer.
+            Ok(signature) if signature == Signature::default().to_string() => continue,
+            Ok(signature) => {
+                let entry = LedgerEntry::new(signature, group, LedgerEntryKind::Sponsored, 0);
+                state.sponsorship.upsert_entry(&entry).await
+            }
+            Err(error) => Err(error),
+        };
+        if let Err(error) = result {
+            tracing::error!(group = group.to_string(), error = error.to_string());
+        }
+    }
+
+    Response::from_parts(parts, body::boxed(Full::from(bytes)))
+}
+
+/// Collects the `transaction` fields of transaction request responses, including the
+/// transactions of each discount in quotes.
+fn get_response_transactions<'a>(value: &'a Value, transactions: &mut Vec<&'a str>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                match (key.as_str(), value) {
+                    ("transaction", Value::String(transaction)) => transactions.push(transaction),
+                    _ => get_response_transactions(value, transactions),
+                }
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                get_response_transactions(value, transactions);
+            }
+        }
+        _ => (),
+    }
+}
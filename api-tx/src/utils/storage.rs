@@ -0,0 +1,268 @@
+//! Off-chain storage for promo images and metadata json.
+
+use async_trait::async_trait;
+use bundlr_sdk::{tags::Tag, Bundlr, Ed25519Signer};
+use reqwest::Client;
+use s3::{creds::Credentials, Bucket, Region};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use solana_sdk::hash::hash;
+use std::path::PathBuf;
+use url::Url;
+
+use crate::error::AppError;
+
+/// Storage backends selectable at startup.
+pub enum StorageConfig {
+    /// Permanent storage on Arweave, paid for by the platform signer through the bundlr node
+    /// at the given url.
+    Bundlr(Url),
+    /// S3 compatible bucket readable at `public_url`. Credentials are read from the standard
+    /// `AWS_ACCESS_KEY_ID` and `AWS_SECRET_ACCESS_KEY` environment variables.
+    S3 {
+        bucket: String,
+        region: String,
+        /// Endpoint for S3 compatible services other than AWS.
+        endpoint: Option<String>,
+        public_url: Url,
+    },
+    /// IPFS node http api, with uploads pinned and served from `gateway_url`.
+    Ipfs { api_url: Url, gateway_url: Url },
+    /// Local directory, for testing promo creation without uploading anywhere.
+    Local { dir: PathBuf, base_url: Url },
+}
+
+/// Stores uploaded content and returns the uri it can be read from.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn upload(&self, data: Vec<u8>, content_type: &str) -> Result<String, AppError>;
+}
+
+pub struct BundlrStorage {
+    bundlr: Bundlr<Ed25519Signer>,
+}
+
+impl BundlrStorage {
+    pub fn new(node_url: Url, signer: Ed25519Signer) -> Self {
+        Self {
+            bundlr: Bundlr::new(
+                node_url.to_string().trim_end_matches('/').to_string(),
+                "solana".to_string(),
+                "sol".to_string(),
+                signer,
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for BundlrStorage {
+    async fn upload(&self, data: Vec<u8>, content_type: &str) -> Result<String, AppError> {
+        let tx = self.bundlr.create_transaction_with_tags(
+            data,
+            vec![
+                Tag::new("User-Agent".into(), "bokoup".into()),
+                Tag::new("Content-Type".into(), content_type.to_string()),
+            ],
+        );
+
+        let response = self.bundlr.send_transaction(tx).await?;
+        let id = response
+            .as_object()
+            .and_then(|response| response.get("id"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| {
+                AppError::StorageError("id field should exist in bundlr response".to_string())
+            })?;
+
+        Ok(format!("https://arweave.net/{}", id))
+    }
+}
+
+pub struct S3Storage {
+    bucket: Bucket,
+    public_url: Url,
+}
+
+impl S3Storage {
+    pub fn new(
+        bucket: &str,
+        region: &str,
+        endpoint: Option<String>,
+        public_url: Url,
+    ) -> Result<Self, AppError> {
+        let region = match endpoint {
+            Some(endpoint) => Region::Custom {
+                region: region.to_string(),
+                endpoint,
+            },
+            None => region
+                .parse()
+                .map_err(|_| AppError::StorageError(format!("invalid s3 region: {}", region)))?,
+        };
+        let credentials =
+            Credentials::default().map_err(|e| AppError::StorageError(e.to_string()))?;
+        let bucket = Bucket::new(bucket, region, credentials)
+            .map_err(|e| AppError::StorageError(e.to_string()))?
+            .with_path_style();
+
+        Ok(Self { bucket, public_url })
+    }
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    async fn upload(&self, data: Vec<u8>, content_type: &str) -> Result<String, AppError> {
+        let key = get_content_key(&data, content_type);
+        let response = self
+            .bucket
+            .put_object_with_content_type(&key, &data, content_type)
+            .await
+            .map_err(|e| AppError::StorageError(e.to_string()))?;
+        if response.status_code() != 200 {
+            return Err(AppError::StorageError(format!(
+                "s3 upload returned status {}",
+                response.status_code()
+            )));
+        }
+
+        Ok(join_url(&self.public_url, &key))
+    }
+}
+
+pub struct IpfsStorage {
+    client: Client,
+    api_url: Url,
+    gateway_url: Url,
+}
+
+/// Response from the IPFS `add` endpoint.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct IpfsAddResponse {
+    hash: String,
+}
+
+impl IpfsStorage {
+    pub fn new(client: Client, api_url: Url, gateway_url: Url) -> Self {
+        Self {
+            client,
+            api_url,
+            gateway_url,
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for IpfsStorage {
+    async fn upload(&self, data: Vec<u8>, content_type: &str) -> Result<String, AppError> {
+        let part = reqwest::multipart::Part::bytes(data)
+            .file_name("file")
+            .mime_str(content_type)?;
+        let form = reqwest::multipart::Form::new().part("file", part);
+
+        let response = self
+            .client
+            .post(join_url(&self.api_url, "api/v0/add?pin=true"))
+            .multipart(form)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(AppError::StorageError(format!(
+                "ipfs add returned status {}",
+                response.status()
+            )));
+        }
+        let IpfsAddResponse { hash } = response.json().await?;
+
+        Ok(join_url(&self.gateway_url, &format!("ipfs/{}", hash)))
+    }
+}
+
+pub struct LocalStorage {
+    dir: PathBuf,
+    base_url: Url,
+}
+
+impl LocalStorage {
+    pub fn new(dir: PathBuf, base_url: Url) -> Self {
+        Self { dir, base_url }
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn upload(&self, data: Vec<u8>, content_type: &str) -> Result<String, AppError> {
+        let key = get_content_key(&data, content_type);
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|e| AppError::StorageError(e.to_string()))?;
+        tokio::fs::write(self.dir.join(&key), data)
+            .await
+            .map_err(|e| AppError::StorageError(e.to_string()))?;
+
+        Ok(join_url(&self.base_url, &key))
+    }
+}
+
+/// Returns a key derived from the hash of `data`, so uploading the same content twice doesn't
+/// store it twice, with an extension matching `content_type`.
+pub fn get_content_key(data: &[u8], content_type: &str) -> String {
+    let key = hash(data).to_string();
+    match mime_guess::get_mime_extensions_str(content_type).and_then(|exts| exts.first()) {
+        Some(ext) => format!("{}.{}", key, ext),
+        None => key,
+    }
+}
+
+pub fn join_url(base_url: &Url, path: &str) -> String {
+    format!("{}/{}", base_url.as_str().trim_end_matches('/'), path)
+}
+
+/// Uploads the promo image and returns its uri with its content type.
+pub async fn upload_image(
+    storage: &dyn Storage,
+    image_data: (Vec<u8>, String),
+) -> Result<(String, String), AppError> {
+    let image_url = storage.upload(image_data.0, &image_data.1).await?;
+
+    tracing::debug!(image_url = &image_url);
+
+    Ok((image_url, image_data.1))
+}
+
+/// Adds the image and its thumbnail, each as a uri with its content type, to the promo
+/// metadata and uploads it, returning the uri to be stored on chain when the promo is created.
+pub async fn upload_metadata_json(
+    storage: &dyn Storage,
+    metadata_data_obj: &mut Map<String, Value>,
+    image: (String, String),
+    thumbnail: (String, String),
+) -> Result<String, AppError> {
+    metadata_data_obj.insert("image".to_string(), image.0.clone().into());
+
+    metadata_data_obj.insert(
+        "properties".to_string(),
+        json!({
+            "files": [
+                {
+                    "uri": image.0,
+                    "type": image.1
+                },
+                {
+                    "uri": thumbnail.0,
+                    "type": thumbnail.1
+                }
+            ],
+            "category": "image"
+        }),
+    );
+
+    let uri = storage
+        .upload(serde_json::to_vec(metadata_data_obj)?, "application/json")
+        .await?;
+
+    tracing::debug!(metadata_json_uri = &uri);
+
+    Ok(uri)
+}
@@ -0,0 +1,94 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Merchant signed transactions expire with the blockhash they were signed with, which is valid
+/// for 150 blocks, or approximately one minute.
+pub const SIGNED_TRANSACTION_TTL: Duration = Duration::from_secs(60);
+
+/// Merchant signed transactions built with a group's durable nonce stay valid until the nonce
+/// is advanced, so are kept for a day.
+pub const NONCE_SIGNED_TRANSACTION_TTL: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Mints are cached for group lookups. Group members can be updated, so only briefly.
+pub const MINT_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Checkout sessions not finalized within this time after being created are abandoned.
+pub const CHECKOUT_SESSION_TIMEOUT: Duration = Duration::from_secs(60 * 15);
+
+/// Checkout sessions are kept for a day so point of sale clients can see how they ended and
+/// customers can revoke delegations of abandoned sessions.
+pub const CHECKOUT_SESSION_TTL: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Icons branding QR codes are cached since the same few are used for every code.
+pub const ICON_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Sign-in nonces need to be signed and exchanged for a session soon after they are issued.
+pub const SIGN_IN_NONCE_TTL: Duration = Duration::from_secs(60 * 5);
+
+/// Sign-in nonces are issued without signing in, so only this many are kept at once.
+pub const SIGN_IN_NONCE_CAPACITY: usize = 10_000;
+
+/// Merchant sessions are short lived, so a leaked token can't be used for long.
+pub const AUTH_SESSION_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// In memory key-value store with entries that expire a fixed time after being inserted.
+pub struct TtlStore<V> {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, V)>>,
+}
+
+impl<V: Clone> TtlStore<V> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Inserts `value`, removing any expired entries first so the store doesn't grow unbounded.
+    pub fn insert(&self, key: String, value: V) {
+        self.insert_with_ttl(key, value, self.ttl)
+    }
+
+    /// Inserts `value` to expire after `ttl` instead of the store's default.
+    pub fn insert_with_ttl(&self, key: String, value: V, ttl: Duration) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (expires_at, _)| *expires_at > now);
+        entries.insert(key, (now + ttl, value));
+    }
+
+    /// Inserts `value` unless the store already has `capacity` entries that haven't expired,
+    /// returning whether it was inserted.
+    pub fn insert_within_capacity(&self, key: String, value: V, capacity: usize) -> bool {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (expires_at, _)| *expires_at > now);
+        if entries.len() >= capacity {
+            return false;
+        }
+        entries.insert(key, (now + self.ttl, value));
+        true
+    }
+
+    /// Returns the value for `key` if it has not expired.
+    pub fn get(&self, key: &str) -> Option<V> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(key)
+            .filter(|(expires_at, _)| *expires_at > Instant::now())
+            .map(|(_, value)| value.clone())
+    }
+
+    /// Removes and returns the value for `key` if it has not expired.
+    pub fn remove(&self, key: &str) -> Option<V> {
+        let mut entries = self.entries.lock().unwrap();
+        entries
+            .remove(key)
+            .filter(|(expires_at, _)| *expires_at > Instant::now())
+            .map(|(_, value)| value)
+    }
+}
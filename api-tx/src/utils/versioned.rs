@@ -0,0 +1,208 @@
+//! Version 0 transactions, serialized by hand for solana-sdk 1.9.20.
+
+use anchor_lang::prelude::Pubkey;
+use solana_sdk::{
+    hash::Hash,
+    instruction::CompiledInstruction,
+    message::{Message, MessageHeader},
+    signature::{Keypair, Signature},
+    signer::{Signer, SignerError},
+};
+
+/// Prefix marking a message as versioned, combined with the version number.
+const MESSAGE_VERSION_PREFIX: u8 = 0x80;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageAddressTableLookup {
+    pub account_key: Pubkey,
+    pub writable_indexes: Vec<u8>,
+    pub readonly_indexes: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageV0 {
+    pub header: MessageHeader,
+    pub account_keys: Vec<Pubkey>,
+    pub recent_blockhash: Hash,
+    pub instructions: Vec<CompiledInstruction>,
+    pub address_table_lookups: Vec<MessageAddressTableLookup>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionedTransaction {
+    pub signatures: Vec<Signature>,
+    pub message: MessageV0,
+}
+
+impl MessageV0 {
+    /// Compiles a legacy message to a v0 message, loading accounts in `addresses`, the contents
+    /// of `lookup_table`, from the table. Signers and invoked programs always stay in the
+    /// message's own account keys.
+    pub fn compile(message: &Message, lookup_table: &Pubkey, addresses: &[Pubkey]) -> Self {
+        let header = &message.header;
+        let num_keys = message.account_keys.len();
+        let num_signers = header.num_required_signatures as usize;
+        let is_writable = |i: usize| {
+            if i < num_signers {
+                i < num_signers - header.num_readonly_signed_accounts as usize
+            } else {
+                i < num_keys - header.num_readonly_unsigned_accounts as usize
+            }
+        };
+        let is_program = |i: usize| {
+            message
+                .instructions
+                .iter()
+                .any(|ix| ix.program_id_index as usize == i)
+        };
+
+        let mut static_keys = vec![];
+        let mut num_readonly_unsigned_accounts = 0;
+        let mut writable_lookups = vec![];
+        let mut readonly_lookups = vec![];
+        for (i, key) in message.account_keys.iter().enumerate() {
+            let table_index = addresses.iter().position(|address| address == key);
+            match table_index {
+                Some(table_index) if i >= num_signers && !is_program(i) => {
+                    if is_writable(i) {
+                        writable_lookups.push((i, table_index as u8));
+                    } else {
+                        readonly_lookups.push((i, table_index as u8));
+                    }
+                }
+                _ => {
+                    if i >= num_signers && !is_writable(i) {
+                        num_readonly_unsigned_accounts += 1;
+                    }
+                    static_keys.push(i);
+                }
+            }
+        }
+
+        // Loaded accounts are indexed after the static keys, writable ones first.
+        let mut new_indexes = vec![0u8; num_keys];
+        for (new_index, old_index) in static_keys
+            .iter()
+            .chain(writable_lookups.iter().map(|(i, _)| i))
+            .chain(readonly_lookups.iter().map(|(i, _)| i))
+            .enumerate()
+        {
+            new_indexes[*old_index] = new_index as u8;
+        }
+
+        let address_table_lookups = if writable_lookups.is_empty() && readonly_lookups.is_empty() {
+            vec![]
+        } else {
+            vec![MessageAddressTableLookup {
+                account_key: *lookup_table,
+                writable_indexes: writable_lookups.iter().map(|(_, j)| *j).collect(),
+                readonly_indexes: readonly_lookups.iter().map(|(_, j)| *j).collect(),
+            }]
+        };
+
+        Self {
+            header: MessageHeader {
+                num_required_signatures: header.num_required_signatures,
+                num_readonly_signed_accounts: header.num_readonly_signed_accounts,
+                num_readonly_unsigned_accounts,
+            },
+            account_keys: static_keys
+                .iter()
+                .map(|i| message.account_keys[*i])
+                .collect(),
+            recent_blockhash: message.recent_blockhash,
+            instructions: message
+                .instructions
+                .iter()
+                .map(|ix| CompiledInstruction {
+                    program_id_index: new_indexes[ix.program_id_index as usize],
+                    accounts: ix
+                        .accounts
+                        .iter()
+                        .map(|i| new_indexes[*i as usize])
+                        .collect(),
+                    data: ix.data.clone(),
+                })
+                .collect(),
+            address_table_lookups,
+        }
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = vec![MESSAGE_VERSION_PREFIX];
+        bytes.extend_from_slice(&[
+            self.header.num_required_signatures,
+            self.header.num_readonly_signed_accounts,
+            self.header.num_readonly_unsigned_accounts,
+        ]);
+        encode_length(&mut bytes, self.account_keys.len());
+        for key in &self.account_keys {
+            bytes.extend_from_slice(key.as_ref());
+        }
+        bytes.extend_from_slice(self.recent_blockhash.as_ref());
+        encode_length(&mut bytes, self.instructions.len());
+        for ix in &self.instructions {
+            bytes.push(ix.program_id_index);
+            encode_bytes(&mut bytes, &ix.accounts);
+            encode_bytes(&mut bytes, &ix.data);
+        }
+        encode_length(&mut bytes, self.address_table_lookups.len());
+        for lookup in &self.address_table_lookups {
+            bytes.extend_from_slice(lookup.account_key.as_ref());
+            encode_bytes(&mut bytes, &lookup.writable_indexes);
+            encode_bytes(&mut bytes, &lookup.readonly_indexes);
+        }
+        bytes
+    }
+}
+
+impl VersionedTransaction {
+    /// Creates an unsigned transaction with default signatures for each required signer.
+    pub fn new(message: MessageV0) -> Self {
+        Self {
+            signatures: vec![Signature::default(); message.header.num_required_signatures as usize],
+            message,
+        }
+    }
+
+    pub fn try_partial_sign(&mut self, keypairs: &[&Keypair]) -> Result<(), SignerError> {
+        let message_data = self.message.serialize();
+        for keypair in keypairs {
+            let index = self.message.account_keys[..self.signatures.len()]
+                .iter()
+                .position(|key| *key == keypair.pubkey())
+                .ok_or(SignerError::KeypairPubkeyMismatch)?;
+            self.signatures[index] = keypair.try_sign_message(&message_data)?;
+        }
+        Ok(())
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        encode_length(&mut bytes, self.signatures.len());
+        for signature in &self.signatures {
+            bytes.extend_from_slice(signature.as_ref());
+        }
+        bytes.extend(self.message.serialize());
+        bytes
+    }
+}
+
+/// Appends a length in the compact-u16 format used for lengths in transactions.
+fn encode_length(bytes: &mut Vec<u8>, len: usize) {
+    let mut rem = len as u16;
+    loop {
+        let elem = (rem & 0x7f) as u8;
+        rem >>= 7;
+        if rem == 0 {
+            bytes.push(elem);
+            break;
+        }
+        bytes.push(elem | 0x80);
+    }
+}
+
+fn encode_bytes(bytes: &mut Vec<u8>, data: &[u8]) {
+    encode_length(bytes, data.len());
+    bytes.extend_from_slice(data);
+}
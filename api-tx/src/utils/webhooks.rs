@@ -0,0 +1,1082 @@
+//! Notifications to merchants of events on their groups' promos.
+
+use anchor_lang::prelude::Pubkey;
+use async_trait::async_trait;
+use axum::http::header;
+use deadpool_postgres::Pool;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use solana_sdk::{signer::keypair::Keypair, signer::Signer};
+use std::{
+    collections::{HashMap, HashSet},
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio_postgres::Row;
+use url::{Host, Url};
+
+use crate::{error::AppError, utils::postgres::parse_pubkey, State};
+
+const WEBHOOK_ENDPOINT_INSERT: &str = include_str!("queries/webhook_endpoint_insert.sql");
+const WEBHOOK_ENDPOINT_DELETE: &str = include_str!("queries/webhook_endpoint_delete.sql");
+const WEBHOOK_ENDPOINT_SELECT: &str = include_str!("queries/webhook_endpoint_select.sql");
+const WEBHOOK_ENDPOINTS_SELECT: &str = include_str!("queries/webhook_endpoints_select.sql");
+const WEBHOOK_CURSOR_SELECT: &str = include_str!("queries/webhook_cursor_select.sql");
+const WEBHOOK_CURSOR_UPSERT: &str = include_str!("queries/webhook_cursor_upsert.sql");
+const WEBHOOK_EVENTS_SELECT: &str = include_str!("queries/webhook_events_select.sql");
+const PROMO_SUPPLY_SELECT: &str = include_str!("queries/promo_supply_select.sql");
+const WEBHOOK_DELIVERY_INSERT: &str = include_str!("queries/webhook_delivery_insert.sql");
+const WEBHOOK_DELIVERY_UPDATE: &str = include_str!("queries/webhook_delivery_update.sql");
+const DUE_WEBHOOK_DELIVERIES_SELECT: &str =
+    include_str!("queries/due_webhook_deliveries_select.sql");
+const WEBHOOK_DELIVERIES_SELECT: &str = include_str!("queries/webhook_deliveries_select.sql");
+
+/// Header with the timestamp and signature of deliveries, as `t={timestamp},v1={signature}`.
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "x-bokoup-signature";
+
+pub const WEBHOOK_EVENT_HEADER: &str = "x-bokoup-event";
+
+/// Header with the id of the delivery, which stays the same across retries.
+pub const WEBHOOK_DELIVERY_HEADER: &str = "x-bokoup-delivery";
+
+pub const MAX_WEBHOOK_ENDPOINTS: usize = 5;
+
+pub const MAX_WEBHOOK_URL_LENGTH: usize = 256;
+
+/// Secrets are generated if not provided, and provided secrets need to be hard to guess.
+pub const MIN_WEBHOOK_SECRET_LENGTH: usize = 16;
+
+pub const MAX_WEBHOOK_SECRET_LENGTH: usize = 128;
+
+pub const DEFAULT_WEBHOOK_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Attempts after which deliveries are given up on, about two hours after the first attempt
+/// with the default backoff.
+pub const DEFAULT_WEBHOOK_MAX_ATTEMPTS: u32 = 8;
+
+/// Percentage of a promo's maximum mints left at which groups are notified that the promo is
+/// running low.
+pub const DEFAULT_LOW_SUPPLY_PERCENT: u64 = 10;
+
+/// Deliveries returned by default.
+pub const DEFAULT_DELIVERIES_LIMIT: u64 = 50;
+
+pub const MAX_DELIVERIES_LIMIT: u64 = 500;
+
+/// Events read from the database per poll.
+const WEBHOOK_EVENTS_LIMIT: i64 = 1000;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+const WEBHOOK_RETRY_BASE: Duration = Duration::from_secs(30);
+
+const WEBHOOK_RETRY_MAX: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum WebhookEventKind {
+    /// Promo token minted to a customer.
+    Mint,
+    /// Promo token delegated to a member of the group for redemption.
+    Delegate,
+    /// Promo token burned when redeemed.
+    Burn,
+    /// Promo with at most the configured percentage of its maximum mints left.
+    LowSupply,
+}
+
+pub const WEBHOOK_EVENT_KINDS: [WebhookEventKind; 4] = [
+    WebhookEventKind::Mint,
+    WebhookEventKind::Delegate,
+    WebhookEventKind::Burn,
+    WebhookEventKind::LowSupply,
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    /// Not delivered within the maximum attempts, or the endpoint was removed.
+    Failed,
+}
+
+impl std::fmt::Display for WebhookEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            WebhookEventKind::Mint => "mint",
+            WebhookEventKind::Delegate => "delegate",
+            WebhookEventKind::Burn => "burn",
+            WebhookEventKind::LowSupply => "lowSupply",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+impl FromStr for WebhookEventKind {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mint" => Ok(WebhookEventKind::Mint),
+            "delegate" => Ok(WebhookEventKind::Delegate),
+            "burn" => Ok(WebhookEventKind::Burn),
+            "lowSupply" => Ok(WebhookEventKind::LowSupply),
+            _ => Err(AppError::MalformedData(format!(
+                "webhook event kind: {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for WebhookDeliveryStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            WebhookDeliveryStatus::Pending => "pending",
+            WebhookDeliveryStatus::Delivered => "delivered",
+            WebhookDeliveryStatus::Failed => "failed",
+        };
+        write!(f, "{}", status)
+    }
+}
+
+impl FromStr for WebhookDeliveryStatus {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(WebhookDeliveryStatus::Pending),
+            "delivered" => Ok(WebhookDeliveryStatus::Delivered),
+            "failed" => Ok(WebhookDeliveryStatus::Failed),
+            _ => Err(AppError::MalformedData(format!(
+                "webhook delivery status: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Url a group's events are posted to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookEndpoint {
+    pub id: String,
+    pub group: Pubkey,
+    pub url: String,
+    /// Key deliveries are signed with.
+    pub secret: String,
+    pub events: Vec<WebhookEventKind>,
+    /// Only events indexed after the endpoint was registered are delivered to it.
+    pub created_at: SystemTime,
+}
+
+impl WebhookEndpoint {
+    /// Creates an endpoint for `group`, generating a secret if `secret` isn't set.
+    pub fn new(
+        group: Pubkey,
+        url: String,
+        events: Vec<WebhookEventKind>,
+        secret: Option<String>,
+    ) -> Self {
+        Self {
+            id: get_random_string(),
+            group,
+            url,
+            secret: secret.unwrap_or_else(get_random_string),
+            events,
+            created_at: SystemTime::now(),
+        }
+    }
+
+    fn accepts(&self, event: &WebhookEvent) -> bool {
+        self.group == event.group
+            && self.events.contains(&event.kind)
+            && event.created_at >= self.created_at
+    }
+}
+
+/// Something that happened to a promo of a group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookEvent {
+    /// Unique per event, so that events read more than once are only delivered once.
+    pub id: String,
+    pub kind: WebhookEventKind,
+    pub group: Pubkey,
+    pub mint: Pubkey,
+    /// Transaction of mint, delegate and burn events.
+    pub signature: Option<String>,
+    pub token_account: Option<Pubkey>,
+    pub slot: u64,
+    /// Tokens left to mint, for low supply events.
+    pub remaining: Option<u64>,
+    pub created_at: SystemTime,
+}
+
+impl WebhookEvent {
+    /// Returns the event of `kind` in the transaction with `signature`.
+    pub fn new(
+        kind: WebhookEventKind,
+        signature: String,
+        group: Pubkey,
+        mint: Pubkey,
+        token_account: Pubkey,
+        slot: u64,
+        created_at: SystemTime,
+    ) -> Self {
+        Self {
+            id: format!("{}:{}", kind, signature),
+            kind,
+            group,
+            mint,
+            signature: Some(signature),
+            token_account: Some(token_account),
+            slot,
+            remaining: None,
+            created_at,
+        }
+    }
+
+    /// Json posted to endpoints.
+    pub fn to_payload(&self) -> Value {
+        let mut payload = json!({
+            "id": self.id,
+            "kind": self.kind,
+            "group": self.group.to_string(),
+            "mint": self.mint.to_string(),
+            "slot": self.slot,
+            "createdAt": get_timestamp(self.created_at),
+        });
+        if let Some(signature) = &self.signature {
+            payload["signature"] = json!(signature);
+        }
+        if let Some(token_account) = &self.token_account {
+            payload["tokenAccount"] = json!(token_account.to_string());
+        }
+        if let Some(remaining) = self.remaining {
+            payload["remaining"] = json!(remaining);
+        }
+        payload
+    }
+}
+
+/// Mints of a promo with a maximum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromoSupply {
+    pub mint: Pubkey,
+    pub group: Pubkey,
+    pub mint_count: u64,
+    pub max_mint: u64,
+    pub slot: u64,
+}
+
+impl PromoSupply {
+    pub fn remaining(&self) -> u64 {
+        self.max_mint.saturating_sub(self.mint_count)
+    }
+
+    /// Whether at most `percent` of the maximum mints are left.
+    pub fn is_low(&self, percent: u64) -> bool {
+        self.remaining() * 100 <= self.max_mint * percent
+    }
+
+    /// Returns the low supply event of the promo. Groups are only notified once per promo.
+    pub fn to_event(&self) -> WebhookEvent {
+        WebhookEvent {
+            id: format!("{}:{}", WebhookEventKind::LowSupply, self.mint),
+            kind: WebhookEventKind::LowSupply,
+            group: self.group,
+            mint: self.mint,
+            signature: None,
+            token_account: None,
+            slot: self.slot,
+            remaining: Some(self.remaining()),
+            created_at: SystemTime::now(),
+        }
+    }
+}
+
+/// Event queued for an endpoint, along with the outcome of attempts to post it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub endpoint_id: String,
+    pub event_id: String,
+    pub group: Pubkey,
+    pub kind: WebhookEventKind,
+    /// Json body posted to the endpoint.
+    pub payload: String,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: u32,
+    pub next_attempt_at: SystemTime,
+    /// Http status of the response to the last attempt, if there was one.
+    pub response_status: Option<u16>,
+    /// Why the last attempt failed.
+    pub error: Option<String>,
+    pub created_at: SystemTime,
+}
+
+impl WebhookDelivery {
+    pub fn new(endpoint: &WebhookEndpoint, event: &WebhookEvent) -> Self {
+        let now = SystemTime::now();
+        Self {
+            id: get_random_string(),
+            endpoint_id: endpoint.id.clone(),
+            event_id: event.id.clone(),
+            group: event.group,
+            kind: event.kind,
+            payload: event.to_payload().to_string(),
+            status: WebhookDeliveryStatus::Pending,
+            attempts: 0,
+            next_attempt_at: now,
+            response_status: None,
+            error: None,
+            created_at: now,
+        }
+    }
+}
+
+/// Where endpoints and deliveries are kept and events are read from.
+#[async_trait]
+pub trait WebhookStore: Send + Sync {
+    /// Registers `endpoint`, with the `signature` the group owner signed in with.
+    async fn insert_endpoint(
+        &self,
+        endpoint: &WebhookEndpoint,
+        signature: &str,
+    ) -> Result<(), AppError>;
+
+    /// Removes the endpoint of `group` with `id`, returning whether there was one.
+    async fn delete_endpoint(&self, group: &Pubkey, id: &str) -> Result<bool, AppError>;
+
+    async fn get_endpoint(&self, id: &str) -> Result<Option<WebhookEndpoint>, AppError>;
+
+    async fn get_endpoints(&self, group: &Pubkey) -> Result<Vec<WebhookEndpoint>, AppError>;
+
+    /// Returns the slot events have been queued up to.
+    async fn get_cursor(&self) -> Result<u64, AppError>;
+
+    async fn set_cursor(&self, slot: u64) -> Result<(), AppError>;
+
+    /// Returns mint, delegate and burn events from `slot` on, oldest first, for groups with
+    /// endpoints registered before the events were indexed.
+    async fn get_events(&self, slot: u64) -> Result<Vec<WebhookEvent>, AppError>;
+
+    /// Returns the supply of the promo of `mint`, if it has a maximum.
+    async fn get_promo_supply(&self, mint: &Pubkey) -> Result<Option<PromoSupply>, AppError>;
+
+    /// Records `delivery` unless its event has already been queued for its endpoint, returning
+    /// whether it was recorded.
+    async fn insert_delivery(&self, delivery: &WebhookDelivery) -> Result<bool, AppError>;
+
+    async fn update_delivery(&self, delivery: &WebhookDelivery) -> Result<(), AppError>;
+
+    /// Returns pending deliveries due to be attempted by `now`.
+    async fn get_due_deliveries(&self, now: SystemTime) -> Result<Vec<WebhookDelivery>, AppError>;
+
+    /// Returns the last `limit` deliveries to endpoints of `group`, most recent first.
+    async fn get_deliveries(
+        &self,
+        group: &Pubkey,
+        limit: u64,
+    ) -> Result<Vec<WebhookDelivery>, AppError>;
+}
+
+/// Store for running without a database. Without the indexer database there are no events
+/// unless they are inserted with `insert_event`, and everything is lost on restart.
+#[derive(Default)]
+pub struct MemoryWebhookStore {
+    endpoints: Mutex<Vec<WebhookEndpoint>>,
+    cursor: Mutex<u64>,
+    events: Mutex<Vec<WebhookEvent>>,
+    supplies: Mutex<HashMap<Pubkey, PromoSupply>>,
+    deliveries: Mutex<Vec<WebhookDelivery>>,
+}
+
+impl MemoryWebhookStore {
+    pub fn insert_event(&self, event: WebhookEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+
+    pub fn insert_promo_supply(&self, supply: PromoSupply) {
+        self.supplies.lock().unwrap().insert(supply.mint, supply);
+    }
+}
+
+#[async_trait]
+impl WebhookStore for MemoryWebhookStore {
+    async fn insert_endpoint(
+        &self,
+        endpoint: &WebhookEndpoint,
+        _signature: &str,
+    ) -> Result<(), AppError> {
+        self.endpoints.lock().unwrap().push(endpoint.clone());
+        Ok(())
+    }
+
+    async fn delete_endpoint(&self, group: &Pubkey, id: &str) -> Result<bool, AppError> {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let len = endpoints.len();
+        endpoints.retain(|endpoint| !(&endpoint.group == group && endpoint.id == id));
+        Ok(endpoints.len() < len)
+    }
+
+    async fn get_endpoint(&self, id: &str) -> Result<Option<WebhookEndpoint>, AppError> {
+        Ok(self
+            .endpoints
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|endpoint| endpoint.id == id)
+            .cloned())
+    }
+
+    async fn get_endpoints(&self, group: &Pubkey) -> Result<Vec<WebhookEndpoint>, AppError> {
+        Ok(self
+            .endpoints
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|endpoint| &endpoint.group == group)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_cursor(&self) -> Result<u64, AppError> {
+        Ok(*self.cursor.lock().unwrap())
+    }
+
+    async fn set_cursor(&self, slot: u64) -> Result<(), AppError> {
+        *self.cursor.lock().unwrap() = slot;
+        Ok(())
+    }
+
+    async fn get_events(&self, slot: u64) -> Result<Vec<WebhookEvent>, AppError> {
+        let endpoints = self.endpoints.lock().unwrap();
+        let mut events: Vec<WebhookEvent> = self
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| {
+                event.slot >= slot
+                    && endpoints.iter().any(|endpoint| {
+                        endpoint.group == event.group && event.created_at >= endpoint.created_at
+                    })
+            })
+            .cloned()
+            .collect();
+        events.sort_by_key(|event| event.slot);
+        Ok(events)
+    }
+
+    async fn get_promo_supply(&self, mint: &Pubkey) -> Result<Option<PromoSupply>, AppError> {
+        Ok(self.supplies.lock().unwrap().get(mint).cloned())
+    }
+
+    async fn insert_delivery(&self, delivery: &WebhookDelivery) -> Result<bool, AppError> {
+        let mut deliveries = self.deliveries.lock().unwrap();
+        if deliveries.iter().any(|queued| {
+            queued.endpoint_id == delivery.endpoint_id && queued.event_id == delivery.event_id
+        }) {
+            return Ok(false);
+        }
+        deliveries.push(delivery.clone());
+        Ok(true)
+    }
+
+    async fn update_delivery(&self, delivery: &WebhookDelivery) -> Result<(), AppError> {
+        if let Some(queued) = self
+            .deliveries
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .find(|queued| queued.id == delivery.id)
+        {
+            *queued = delivery.clone();
+        }
+        Ok(())
+    }
+
+    async fn get_due_deliveries(&self, now: SystemTime) -> Result<Vec<WebhookDelivery>, AppError> {
+        Ok(self
+            .deliveries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|delivery| {
+                delivery.status == WebhookDeliveryStatus::Pending && delivery.next_attempt_at <= now
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn get_deliveries(
+        &self,
+        group: &Pubkey,
+        limit: u64,
+    ) -> Result<Vec<WebhookDelivery>, AppError> {
+        Ok(self
+            .deliveries
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .filter(|delivery| &delivery.group == group)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+}
+
+/// Store in the `api-data` database, reading events from the tables written by the indexer.
+pub struct PostgresWebhookStore {
+    pool: Pool,
+}
+
+impl PostgresWebhookStore {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WebhookStore for PostgresWebhookStore {
+    async fn insert_endpoint(
+        &self,
+        endpoint: &WebhookEndpoint,
+        signature: &str,
+    ) -> Result<(), AppError> {
+        let events: Vec<String> = endpoint
+            .events
+            .iter()
+            .map(|kind| kind.to_string())
+            .collect();
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                WEBHOOK_ENDPOINT_INSERT,
+                &[
+                    &endpoint.id,
+                    &endpoint.group.to_string(),
+                    &endpoint.url,
+                    &endpoint.secret,
+                    &events,
+                    &signature,
+                    &endpoint.created_at,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_endpoint(&self, group: &Pubkey, id: &str) -> Result<bool, AppError> {
+        let client = self.pool.get().await?;
+        let deleted = client
+            .execute(WEBHOOK_ENDPOINT_DELETE, &[&group.to_string(), &id])
+            .await?;
+        Ok(deleted > 0)
+    }
+
+    async fn get_endpoint(&self, id: &str) -> Result<Option<WebhookEndpoint>, AppError> {
+        let client = self.pool.get().await?;
+        client
+            .query_opt(WEBHOOK_ENDPOINT_SELECT, &[&id])
+            .await?
+            .map(|row| parse_endpoint(&row))
+            .transpose()
+    }
+
+    async fn get_endpoints(&self, group: &Pubkey) -> Result<Vec<WebhookEndpoint>, AppError> {
+        let client = self.pool.get().await?;
+        client
+            .query(WEBHOOK_ENDPOINTS_SELECT, &[&group.to_string()])
+            .await?
+            .iter()
+            .map(parse_endpoint)
+            .collect()
+    }
+
+    async fn get_cursor(&self) -> Result<u64, AppError> {
+        let client = self.pool.get().await?;
+        Ok(client
+            .query_opt(WEBHOOK_CURSOR_SELECT, &[])
+            .await?
+            .map_or(0, |row| row.get::<usize, i64>(0) as u64))
+    }
+
+    async fn set_cursor(&self, slot: u64) -> Result<(), AppError> {
+        let client = self.pool.get().await?;
+        client
+            .execute(WEBHOOK_CURSOR_UPSERT, &[&(slot as i64)])
+            .await?;
+        Ok(())
+    }
+
+    async fn get_events(&self, slot: u64) -> Result<Vec<WebhookEvent>, AppError> {
+        let client = self.pool.get().await?;
+        let mut events = Vec::new();
+        for row in client
+            .query(
+                WEBHOOK_EVENTS_SELECT,
+                &[&(slot as i64), &WEBHOOK_EVENTS_LIMIT],
+            )
+            .await?
+        {
+            events.push(WebhookEvent::new(
+                row.get::<usize, &str>(0).parse()?,
+                row.get(1),
+                parse_pubkey(row.get(2))?,
+                parse_pubkey(row.get(3))?,
+                parse_pubkey(row.get(4))?,
+                row.get::<usize, i64>(5) as u64,
+                row.get(6),
+            ));
+        }
+        Ok(events)
+    }
+
+    async fn get_promo_supply(&self, mint: &Pubkey) -> Result<Option<PromoSupply>, AppError> {
+        let client = self.pool.get().await?;
+        client
+            .query_opt(PROMO_SUPPLY_SELECT, &[&mint.to_string()])
+            .await?
+            .map(|row| {
+                Ok(PromoSupply {
+                    mint: *mint,
+                    group: parse_pubkey(row.get(0))?,
+                    mint_count: row.get::<usize, i32>(1) as u64,
+                    max_mint: row.get::<usize, i32>(2) as u64,
+                    slot: row.get::<usize, i64>(3) as u64,
+                })
+            })
+            .transpose()
+    }
+
+    async fn insert_delivery(&self, delivery: &WebhookDelivery) -> Result<bool, AppError> {
+        let client = self.pool.get().await?;
+        let inserted = client
+            .execute(
+                WEBHOOK_DELIVERY_INSERT,
+                &[
+                    &delivery.id,
+                    &delivery.endpoint_id,
+                    &delivery.event_id,
+                    &delivery.group.to_string(),
+                    &delivery.kind.to_string(),
+                    &delivery.payload,
+                    &delivery.status.to_string(),
+                    &delivery.next_attempt_at,
+                    &delivery.created_at,
+                ],
+            )
+            .await?;
+        Ok(inserted > 0)
+    }
+
+    async fn update_delivery(&self, delivery: &WebhookDelivery) -> Result<(), AppError> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                WEBHOOK_DELIVERY_UPDATE,
+                &[
+                    &delivery.id,
+                    &delivery.status.to_string(),
+                    &(delivery.attempts as i32),
+                    &delivery.next_attempt_at,
+                    &delivery.response_status.map(i32::from),
+                    &delivery.error,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_due_deliveries(&self, now: SystemTime) -> Result<Vec<WebhookDelivery>, AppError> {
+        let client = self.pool.get().await?;
+        client
+            .query(DUE_WEBHOOK_DELIVERIES_SELECT, &[&now])
+            .await?
+            .iter()
+            .map(parse_delivery)
+            .collect()
+    }
+
+    async fn get_deliveries(
+        &self,
+        group: &Pubkey,
+        limit: u64,
+    ) -> Result<Vec<WebhookDelivery>, AppError> {
+        let client = self.pool.get().await?;
+        client
+            .query(
+                WEBHOOK_DELIVERIES_SELECT,
+                &[&group.to_string(), &(limit as i64)],
+            )
+            .await?
+            .iter()
+            .map(parse_delivery)
+            .collect()
+    }
+}
+
+fn parse_endpoint(row: &Row) -> Result<WebhookEndpoint, AppError> {
+    Ok(WebhookEndpoint {
+        id: row.get(0),
+        group: parse_pubkey(row.get(1))?,
+        url: row.get(2),
+        secret: row.get(3),
+        events: row
+            .get::<usize, Vec<&str>>(4)
+            .into_iter()
+            .map(WebhookEventKind::from_str)
+            .collect::<Result<_, _>>()?,
+        created_at: row.get(5),
+    })
+}
+
+fn parse_delivery(row: &Row) -> Result<WebhookDelivery, AppError> {
+    Ok(WebhookDelivery {
+        id: row.get(0),
+        endpoint_id: row.get(1),
+        event_id: row.get(2),
+        group: parse_pubkey(row.get(3))?,
+        kind: row.get::<usize, &str>(4).parse()?,
+        payload: row.get(5),
+        status: row.get::<usize, &str>(6).parse()?,
+        attempts: row.get::<usize, i32>(7) as u32,
+        next_attempt_at: row.get(8),
+        response_status: row.get::<usize, Option<i32>>(9).map(|status| status as u16),
+        error: row.get(10),
+        created_at: row.get(11),
+    })
+}
+
+/// Checks that `url` is an https url that isn't a private address and that `secret`, if
+/// provided, is long enough.
+pub fn validate_webhook_endpoint(
+    url: &str,
+    events: &[WebhookEventKind],
+    secret: Option<&str>,
+) -> Result<(), AppError> {
+    if url.len() > MAX_WEBHOOK_URL_LENGTH {
+        return Err(AppError::InvalidWebhook(format!(
+            "url should be at most {} characters",
+            MAX_WEBHOOK_URL_LENGTH
+        )));
+    }
+    let url = match Url::parse(url) {
+        Ok(url) if url.scheme() == "https" && url.host().is_some() => url,
+        _ => {
+            return Err(AppError::InvalidWebhook(
+                "url should be an https url".to_string(),
+            ))
+        }
+    };
+    let is_private = match url.host() {
+        Some(Host::Domain(domain)) => domain == "localhost" || domain.ends_with(".localhost"),
+        Some(Host::Ipv4(ip)) => is_private_address(ip.into()),
+        Some(Host::Ipv6(ip)) => is_private_address(ip.into()),
+        None => false,
+    };
+    if is_private {
+        return Err(AppError::InvalidWebhook(
+            "url should not be a private address".to_string(),
+        ));
+    }
+    if events.is_empty() {
+        return Err(AppError::InvalidWebhook(
+            "at least one event is required".to_string(),
+        ));
+    }
+    if let Some(secret) = secret {
+        let len = secret.chars().count();
+        if !(MIN_WEBHOOK_SECRET_LENGTH..=MAX_WEBHOOK_SECRET_LENGTH).contains(&len) {
+            return Err(AppError::InvalidWebhook(format!(
+                "secret should be between {} and {} characters",
+                MIN_WEBHOOK_SECRET_LENGTH, MAX_WEBHOOK_SECRET_LENGTH
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Whether `ip` is a loopback, private, link local or otherwise non public address that
+/// webhooks shouldn't be posted to.
+pub fn is_private_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let octets = ip.octets();
+            ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || octets[0] == 0
+                // Shared address space, 100.64.0.0/10.
+                || (octets[0] == 100 && (octets[1] & 0xc0) == 64)
+        }
+        IpAddr::V6(ip) => {
+            let segments = ip.segments();
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // Unique local, fc00::/7, and link local, fe80::/10.
+                || (segments[0] & 0xfe00) == 0xfc00
+                || (segments[0] & 0xffc0) == 0xfe80
+                || ip.to_ipv4().map_or(false, |ip| is_private_address(ip.into()))
+        }
+    }
+}
+
+/// Returns a client to post to `url` with, resolving its host and checking it isn't a private
+/// address unless `allow_private_addresses` is set. The checked address is the one connected
+/// to and redirects aren't followed, so the host can't be switched to a private address.
+async fn get_webhook_client(
+    url: &str,
+    allow_private_addresses: bool,
+) -> Result<reqwest::Client, AppError> {
+    let builder = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(WEBHOOK_TIMEOUT);
+    if allow_private_addresses {
+        return builder.build().map_err(AppError::WebhookPostError);
+    }
+
+    let url = Url::parse(url).map_err(AppError::UrlParseError)?;
+    let port = url.port_or_known_default().unwrap_or(443);
+    let (domain, addrs): (Option<&str>, Vec<SocketAddr>) = match url.host() {
+        Some(Host::Domain(domain)) => (
+            Some(domain),
+            tokio::net::lookup_host((domain, port))
+                .await
+                .map_err(|error| {
+                    AppError::InvalidWebhook(format!("could not resolve {}: {}", domain, error))
+                })?
+                .collect(),
+        ),
+        Some(Host::Ipv4(ip)) => (None, vec![SocketAddr::new(ip.into(), port)]),
+        Some(Host::Ipv6(ip)) => (None, vec![SocketAddr::new(ip.into(), port)]),
+        None => (None, vec![]),
+    };
+    let addr = match addrs.first() {
+        Some(addr) if addrs.iter().all(|addr| !is_private_address(addr.ip())) => *addr,
+        Some(_) => {
+            return Err(AppError::InvalidWebhook(
+                "url resolves to a private address".to_string(),
+            ))
+        }
+        None => {
+            return Err(AppError::InvalidWebhook(
+                "url doesn't resolve to an address".to_string(),
+            ))
+        }
+    };
+    match domain {
+        Some(domain) => builder.resolve(domain, addr),
+        None => builder,
+    }
+    .build()
+    .map_err(AppError::WebhookPostError)
+}
+
+/// Returns the hex encoded HMAC-SHA256 with `secret` of `{timestamp}.{payload}`, sent in the
+/// signature header for endpoints to check deliveries are from the platform and recent.
+pub fn sign_webhook_payload(secret: &str, timestamp: u64, payload: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac accepts keys of any length");
+    mac.update(format!("{}.{}", timestamp, payload).as_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Returns how long to wait before retrying a delivery that has failed `attempts` times.
+pub fn get_webhook_backoff(attempts: u32) -> Duration {
+    WEBHOOK_RETRY_BASE
+        .checked_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+        .map_or(WEBHOOK_RETRY_MAX, |backoff| backoff.min(WEBHOOK_RETRY_MAX))
+}
+
+/// Seconds since the unix epoch.
+pub fn get_timestamp(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn get_random_string() -> String {
+    Keypair::new().pubkey().to_string()
+}
+
+/// Polling of events and delivery of webhooks.
+#[derive(Debug, Clone, Copy)]
+pub struct WebhookConfig {
+    pub poll_interval: Duration,
+    pub max_attempts: u32,
+    pub low_supply_percent: u64,
+    /// Posts to endpoints resolving to private addresses, for testing against local endpoints.
+    pub allow_private_addresses: bool,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: DEFAULT_WEBHOOK_POLL_INTERVAL,
+            max_attempts: DEFAULT_WEBHOOK_MAX_ATTEMPTS,
+            low_supply_percent: DEFAULT_LOW_SUPPLY_PERCENT,
+            allow_private_addresses: false,
+        }
+    }
+}
+
+impl State {
+    /// Queues deliveries of events indexed since the last poll to the endpoints subscribed to
+    /// them, checking the supply of promos that were minted. Returns the number of deliveries
+    /// queued.
+    pub async fn poll_webhook_events(&self, config: &WebhookConfig) -> Result<usize, AppError> {
+        let cursor = self.webhooks.get_cursor().await?;
+        let mut events = self.webhooks.get_events(cursor).await?;
+        let slot = events
+            .iter()
+            .map(|event| event.slot)
+            .max()
+            .unwrap_or(cursor);
+
+        let minted: HashSet<Pubkey> = events
+            .iter()
+            .filter(|event| event.kind == WebhookEventKind::Mint)
+            .map(|event| event.mint)
+            .collect();
+        for mint in minted {
+            match self.webhooks.get_promo_supply(&mint).await? {
+                Some(supply) if supply.is_low(config.low_supply_percent) => {
+                    events.push(supply.to_event())
+                }
+                _ => (),
+            }
+        }
+
+        let mut endpoints: HashMap<Pubkey, Vec<WebhookEndpoint>> = HashMap::new();
+        let mut queued = 0;
+        for event in events {
+            if !endpoints.contains_key(&event.group) {
+                let group_endpoints = self.webhooks.get_endpoints(&event.group).await?;
+                endpoints.insert(event.group, group_endpoints);
+            }
+            for endpoint in &endpoints[&event.group] {
+                if endpoint.accepts(&event)
+                    && self
+                        .webhooks
+                        .insert_delivery(&WebhookDelivery::new(endpoint, &event))
+                        .await?
+                {
+                    queued += 1;
+                }
+            }
+        }
+
+        // Events in the last slot are read again next time, in case more of them are indexed,
+        // and aren't queued twice.
+        self.webhooks.set_cursor(slot).await?;
+        Ok(queued)
+    }
+
+    /// Posts deliveries that are due, retrying failed ones after `get_webhook_backoff` until
+    /// `config.max_attempts` have been made.
+    pub async fn deliver_webhooks(&self, config: &WebhookConfig) -> Result<(), AppError> {
+        for mut delivery in self.webhooks.get_due_deliveries(SystemTime::now()).await? {
+            let endpoint = match self.webhooks.get_endpoint(&delivery.endpoint_id).await? {
+                Some(endpoint) => endpoint,
+                None => {
+                    delivery.status = WebhookDeliveryStatus::Failed;
+                    delivery.error = Some("endpoint removed".to_string());
+                    self.webhooks.update_delivery(&delivery).await?;
+                    continue;
+                }
+            };
+
+            let result = send_webhook(&endpoint, &delivery, config).await;
+            delivery.attempts += 1;
+            match result {
+                Ok(status) => {
+                    delivery.status = WebhookDeliveryStatus::Delivered;
+                    delivery.response_status = Some(status);
+                    delivery.error = None;
+                }
+                Err(error) => {
+                    delivery.response_status = match &error {
+                        AppError::StatusNotOK(status) => Some(status.as_u16()),
+                        _ => None,
+                    };
+                    delivery.error = Some(error.to_string());
+                    if delivery.attempts >= config.max_attempts {
+                        delivery.status = WebhookDeliveryStatus::Failed;
+                        tracing::warn!(
+                            delivery = delivery.id,
+                            endpoint = endpoint.url,
+                            error = error.to_string(),
+                            "webhook delivery failed"
+                        );
+                    } else {
+                        delivery.next_attempt_at =
+                            SystemTime::now() + get_webhook_backoff(delivery.attempts);
+                    }
+                }
+            }
+            self.webhooks.update_delivery(&delivery).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Posts the delivery's payload to the endpoint, returning the response status if it was
+/// successful.
+async fn send_webhook(
+    endpoint: &WebhookEndpoint,
+    delivery: &WebhookDelivery,
+    config: &WebhookConfig,
+) -> Result<u16, AppError> {
+    let client = get_webhook_client(&endpoint.url, config.allow_private_addresses).await?;
+    let timestamp = get_timestamp(SystemTime::now());
+    let signature = sign_webhook_payload(&endpoint.secret, timestamp, &delivery.payload);
+    let response = client
+        .post(&endpoint.url)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(
+            WEBHOOK_SIGNATURE_HEADER,
+            format!("t={},v1={}", timestamp, signature),
+        )
+        .header(WEBHOOK_EVENT_HEADER, delivery.kind.to_string())
+        .header(WEBHOOK_DELIVERY_HEADER, &delivery.id)
+        .body(delivery.payload.clone())
+        .send()
+        .await
+        .map_err(AppError::WebhookPostError)?;
+    if !response.status().is_success() {
+        return Err(AppError::StatusNotOK(response.status()));
+    }
+    Ok(response.status().as_u16())
+}
+
+/// Polls for events and delivers webhooks every `config.poll_interval`.
+pub fn spawn_webhook_tasks(state: Arc<State>, config: WebhookConfig) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(config.poll_interval);
+        loop {
+            interval.tick().await;
+            if let Err(error) = state.poll_webhook_events(&config).await {
+                tracing::error!(error = error.to_string());
+            }
+            if let Err(error) = state.deliver_webhooks(&config).await {
+                tracing::error!(error = error.to_string());
+            }
+        }
+    });
+}
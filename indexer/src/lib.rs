@@ -53,6 +53,8 @@ pub async fn process<'a>(pg_client: deadpool_postgres::Object, message: MessageD
                 programs::mpl_token_metadata::process(pg_client, message).await
             } else if message.account.owner == programs::spl_token::ID.as_ref() {
                 programs::spl_token::process(pg_client, message).await
+            } else if message.account.owner == bpl_token_metadata::token_2022::ID.as_ref() {
+                programs::spl_token::process_2022(pg_client, message).await
             };
         }
         MessageData::Transaction(message) => {
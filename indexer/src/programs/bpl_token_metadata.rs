@@ -2,13 +2,15 @@ use crate::{AccountMessageData, TransactionMessageData};
 use anchor_lang::AccountDeserialize;
 use bpl_api_data::{
     queries::bpl_token_metadata::{
-        burn_delegated_promo_token, create_promo, create_promo_group, delegate_promo_token,
-        mint_promo_token, promo, promo_group,
+        burn_accepted_promo_token, burn_delegated_promo_token, burn_referred_promo_token,
+        create_promo, create_promo_group, delegate_accepted_promo_token, delegate_promo_token,
+        earn_points, loyalty, mint_promo_token, mint_referred_promo_token, promo,
+        promo_acceptance, promo_group, promo_mint, redeem_points, referral, referral_reward,
     },
     Client,
 };
 pub use bpl_token_metadata::{
-    state::{Promo, PromoGroup},
+    state::{Loyalty, Promo, PromoAcceptance, PromoGroup, PromoMint, Referral, ReferralReward},
     ID,
 };
 
@@ -44,6 +46,90 @@ async fn process_promo_group<'a>(
     }
 }
 
+#[tracing::instrument(skip_all)]
+async fn process_promo_acceptance<'a>(
+    pg_client: &Client,
+    key: &[u8],
+    buf: &mut &[u8],
+    slot: u64,
+    write_version: u64,
+) {
+    match PromoAcceptance::try_deserialize(buf) {
+        Ok(ref account) => {
+            promo_acceptance::upsert(pg_client, key, account, slot, write_version).await
+        }
+        Err(error) => {
+            tracing::error!(id = bs58::encode(key).into_string(), ?error)
+        }
+    }
+}
+
+#[tracing::instrument(skip_all)]
+async fn process_loyalty<'a>(
+    pg_client: &Client,
+    key: &[u8],
+    buf: &mut &[u8],
+    slot: u64,
+    write_version: u64,
+) {
+    match Loyalty::try_deserialize(buf) {
+        Ok(ref account) => loyalty::upsert(pg_client, key, account, slot, write_version).await,
+        Err(error) => {
+            tracing::error!(id = bs58::encode(key).into_string(), ?error)
+        }
+    }
+}
+
+#[tracing::instrument(skip_all)]
+async fn process_referral_reward<'a>(
+    pg_client: &Client,
+    key: &[u8],
+    buf: &mut &[u8],
+    slot: u64,
+    write_version: u64,
+) {
+    match ReferralReward::try_deserialize(buf) {
+        Ok(ref account) => {
+            referral_reward::upsert(pg_client, key, account, slot, write_version).await
+        }
+        Err(error) => {
+            tracing::error!(id = bs58::encode(key).into_string(), ?error)
+        }
+    }
+}
+
+#[tracing::instrument(skip_all)]
+async fn process_referral<'a>(
+    pg_client: &Client,
+    key: &[u8],
+    buf: &mut &[u8],
+    slot: u64,
+    write_version: u64,
+) {
+    match Referral::try_deserialize(buf) {
+        Ok(ref account) => referral::upsert(pg_client, key, account, slot, write_version).await,
+        Err(error) => {
+            tracing::error!(id = bs58::encode(key).into_string(), ?error)
+        }
+    }
+}
+
+#[tracing::instrument(skip_all)]
+async fn process_promo_mint<'a>(
+    pg_client: &Client,
+    key: &[u8],
+    buf: &mut &[u8],
+    slot: u64,
+    write_version: u64,
+) {
+    match PromoMint::try_deserialize(buf) {
+        Ok(ref account) => promo_mint::upsert(pg_client, key, account, slot, write_version).await,
+        Err(error) => {
+            tracing::error!(id = bs58::encode(key).into_string(), ?error)
+        }
+    }
+}
+
 pub async fn process<'a>(pg_client: deadpool_postgres::Object, message: AccountMessageData<'a>) {
     let key = message.account.pubkey.as_ref();
     let mut buf = message.account.data.as_ref();
@@ -55,6 +141,15 @@ pub async fn process<'a>(pg_client: deadpool_postgres::Object, message: AccountM
         PromoGroup::LEN => {
             process_promo_group(&pg_client, key, &mut buf, slot, write_version).await
         }
+        PromoAcceptance::LEN => {
+            process_promo_acceptance(&pg_client, key, &mut buf, slot, write_version).await
+        }
+        Loyalty::LEN => process_loyalty(&pg_client, key, &mut buf, slot, write_version).await,
+        ReferralReward::LEN => {
+            process_referral_reward(&pg_client, key, &mut buf, slot, write_version).await
+        }
+        Referral::LEN => process_referral(&pg_client, key, &mut buf, slot, write_version).await,
+        PromoMint::LEN => process_promo_mint(&pg_client, key, &mut buf, slot, write_version).await,
         _ => (),
     }
 }
@@ -68,6 +163,15 @@ impl Discriminator {
     pub const MINT_PROMO_TOKEN: [u8; 8] = [75, 139, 89, 205, 32, 105, 163, 161];
     pub const DELEGATE_PROMO_TOKEN: [u8; 8] = [85, 206, 226, 194, 207, 166, 164, 22];
     pub const BURN_DELEGATED_PROMO_TOKEN: [u8; 8] = [119, 36, 30, 56, 83, 96, 21, 132];
+    pub const DELEGATE_ACCEPTED_PROMO_TOKEN: [u8; 8] = [121, 86, 131, 209, 4, 229, 149, 40];
+    pub const BURN_ACCEPTED_PROMO_TOKEN: [u8; 8] = [163, 191, 229, 253, 151, 74, 123, 25];
+    pub const EARN_POINTS: [u8; 8] = [75, 160, 182, 236, 70, 39, 168, 6];
+    pub const REDEEM_POINTS: [u8; 8] = [178, 79, 85, 218, 121, 101, 34, 12];
+    pub const MINT_REFERRED_PROMO_TOKEN: [u8; 8] = [42, 220, 130, 127, 234, 114, 105, 179];
+    pub const BURN_REFERRED_PROMO_TOKEN: [u8; 8] = [128, 125, 7, 0, 136, 30, 68, 171];
+    pub const CREATE_PROMO_2022: [u8; 8] = [138, 52, 49, 127, 131, 200, 182, 102];
+    pub const MINT_PROMO_TOKEN_2022: [u8; 8] = [182, 125, 214, 24, 121, 198, 70, 91];
+    pub const BURN_PROMO_TOKEN_2022: [u8; 8] = [196, 1, 119, 9, 92, 230, 14, 180];
 }
 
 #[tracing::instrument(skip_all)]
@@ -89,7 +193,9 @@ pub async fn process_transaction<'a>(
             .await
         }
 
-        Discriminator::CREATE_PROMO => {
+        // Token-2022 variants of creating and burning have the same accounts as the classic
+        // token variants.
+        Discriminator::CREATE_PROMO | Discriminator::CREATE_PROMO_2022 => {
             create_promo::upsert(
                 &pg_client,
                 &message.signature,
@@ -109,6 +215,16 @@ pub async fn process_transaction<'a>(
             )
             .await
         }
+        Discriminator::MINT_PROMO_TOKEN_2022 => {
+            mint_promo_token::upsert_2022(
+                &pg_client,
+                &message.signature,
+                &message.accounts,
+                &message.data,
+                message.slot,
+            )
+            .await
+        }
         Discriminator::DELEGATE_PROMO_TOKEN => {
             delegate_promo_token::upsert(
                 &pg_client,
@@ -119,7 +235,7 @@ pub async fn process_transaction<'a>(
             )
             .await
         }
-        Discriminator::BURN_DELEGATED_PROMO_TOKEN => {
+        Discriminator::BURN_DELEGATED_PROMO_TOKEN | Discriminator::BURN_PROMO_TOKEN_2022 => {
             burn_delegated_promo_token::upsert(
                 &pg_client,
                 &message.signature,
@@ -129,6 +245,66 @@ pub async fn process_transaction<'a>(
             )
             .await
         }
+        Discriminator::DELEGATE_ACCEPTED_PROMO_TOKEN => {
+            delegate_accepted_promo_token::upsert(
+                &pg_client,
+                &message.signature,
+                &message.accounts,
+                &message.data,
+                message.slot,
+            )
+            .await
+        }
+        Discriminator::BURN_ACCEPTED_PROMO_TOKEN => {
+            burn_accepted_promo_token::upsert(
+                &pg_client,
+                &message.signature,
+                &message.accounts,
+                &message.data,
+                message.slot,
+            )
+            .await
+        }
+        Discriminator::EARN_POINTS => {
+            earn_points::upsert(
+                &pg_client,
+                &message.signature,
+                &message.accounts,
+                &message.data,
+                message.slot,
+            )
+            .await
+        }
+        Discriminator::REDEEM_POINTS => {
+            redeem_points::upsert(
+                &pg_client,
+                &message.signature,
+                &message.accounts,
+                &message.data,
+                message.slot,
+            )
+            .await
+        }
+        Discriminator::MINT_REFERRED_PROMO_TOKEN => {
+            mint_referred_promo_token::upsert(
+                &pg_client,
+                &message.signature,
+                &message.accounts,
+                &message.data,
+                message.slot,
+            )
+            .await
+        }
+        Discriminator::BURN_REFERRED_PROMO_TOKEN => {
+            burn_referred_promo_token::upsert(
+                &pg_client,
+                &message.signature,
+                &message.accounts,
+                &message.data,
+                message.slot,
+            )
+            .await
+        }
         _ => {
             tracing::info!(
                 discriminator = format!("{:?}", discriminator),
@@ -5,6 +5,11 @@ use bpl_api_data::{
     queries::spl_token::{mint, token_account},
     Client,
 };
+use bpl_token_metadata::token_2022;
+
+/// Account type byte following the base account in Token-2022 accounts with extensions.
+const ACCOUNT_TYPE_MINT: u8 = 1;
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
 
 #[tracing::instrument(skip_all)]
 async fn process_mint<'a>(
@@ -15,7 +20,9 @@ async fn process_mint<'a>(
     write_version: u64,
 ) {
     match Mint::try_deserialize(buf) {
-        Ok(ref account) => mint::upsert(pg_client, key, account, slot, write_version).await,
+        Ok(ref account) => {
+            mint::upsert(pg_client, key, account, &ID, &[], slot, write_version).await
+        }
         Err(error) => {
             tracing::error!(id = bs58::encode(key).into_string(), ?error)
         }
@@ -32,7 +39,63 @@ async fn process_token_account<'a>(
 ) {
     match TokenAccount::try_deserialize(buf) {
         Ok(ref account) => {
-            token_account::upsert(pg_client, key, account, slot, write_version).await
+            token_account::upsert(pg_client, key, account, &ID, &[], slot, write_version).await
+        }
+        Err(error) => {
+            tracing::error!(id = bs58::encode(key).into_string(), ?error)
+        }
+    }
+}
+
+#[tracing::instrument(skip_all)]
+async fn process_mint_2022<'a>(
+    pg_client: &Client,
+    key: &[u8],
+    buf: &[u8],
+    slot: u64,
+    write_version: u64,
+) {
+    match token_2022::unpack_mint(buf) {
+        Ok(ref account) => {
+            let extensions = token_2022::get_extension_names(buf);
+            mint::upsert(
+                pg_client,
+                key,
+                account,
+                &token_2022::ID,
+                &extensions,
+                slot,
+                write_version,
+            )
+            .await
+        }
+        Err(error) => {
+            tracing::error!(id = bs58::encode(key).into_string(), ?error)
+        }
+    }
+}
+
+#[tracing::instrument(skip_all)]
+async fn process_token_account_2022<'a>(
+    pg_client: &Client,
+    key: &[u8],
+    buf: &[u8],
+    slot: u64,
+    write_version: u64,
+) {
+    match token_2022::unpack_token_account(buf) {
+        Ok(ref account) => {
+            let extensions = token_2022::get_extension_names(buf);
+            token_account::upsert(
+                pg_client,
+                key,
+                account,
+                &token_2022::ID,
+                &extensions,
+                slot,
+                write_version,
+            )
+            .await
         }
         Err(error) => {
             tracing::error!(id = bs58::encode(key).into_string(), ?error)
@@ -53,3 +116,25 @@ pub async fn process<'a>(pg_client: deadpool_postgres::Object, message: AccountM
         _ => (),
     }
 }
+
+/// Token-2022 accounts without extensions have the same layouts as classic token accounts.
+/// Accounts with extensions are identified by the account type following the base account.
+pub async fn process_2022<'a>(
+    pg_client: deadpool_postgres::Object,
+    message: AccountMessageData<'a>,
+) {
+    let key = message.account.pubkey.as_ref();
+    let buf = message.account.data.as_ref();
+    let slot = message.slot;
+    let write_version = message.account.write_version;
+    let account_type = buf.get(token_2022::BASE_LEN - 1).copied();
+    match (buf.len(), account_type) {
+        (Mint::LEN, _) | (_, Some(ACCOUNT_TYPE_MINT)) => {
+            process_mint_2022(&pg_client, key, buf, slot, write_version).await
+        }
+        (TokenAccount::LEN, _) | (_, Some(ACCOUNT_TYPE_ACCOUNT)) => {
+            process_token_account_2022(&pg_client, key, buf, slot, write_version).await
+        }
+        _ => (),
+    }
+}
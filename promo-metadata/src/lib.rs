@@ -0,0 +1,499 @@
+//! Typed promo metadata as defined in `specifications/token_metadata.md`, shared by the
+//! transaction api, which validates create promo requests against it, and the indexer, which
+//! stores the parsed fields.
+//!
+//! Promo fields are stored in the json metadata as a Metaplex attribute array so that wallets
+//! can display them. `PromoAttributes` converts between the two, keeping any attributes added
+//! by merchants.
+
+use bpl_token_metadata::utils::LOYALTY_LEVELS_CAPACITY;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+/// Metaplex limits on metadata name and symbol lengths.
+pub const MAX_NAME_LENGTH: usize = 32;
+pub const MAX_SYMBOL_LENGTH: usize = 10;
+
+pub const PROMO_TYPE: &str = "promoType";
+pub const PRODUCT_ID: &str = "productId";
+pub const BUY_X_PRODUCT: &str = "buyXProduct";
+pub const GET_Y_PRODUCT: &str = "getYProduct";
+pub const BUY_X_CURRENCY: &str = "buyXCurrency";
+pub const GET_Y_PERCENT: &str = "getYPercent";
+pub const EARN_RATE: &str = "earnRateLevel0";
+pub const MAX_MINT: &str = "maxMint";
+pub const MAX_BURN: &str = "maxBurn";
+
+#[derive(Error, Debug)]
+pub enum MetadataError {
+    #[error("invalid metadata: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("missing attribute: {0}")]
+    MissingAttribute(String),
+    #[error("attribute {0} should be {1}")]
+    InvalidAttribute(String, &'static str),
+    #[error("unknown promo type: {0}")]
+    UnknownPromoType(String),
+    #[error("loyalty promos need between one and {0} reward levels")]
+    InvalidLoyaltyLevels(u8),
+    #[error("invalid metadata: {0}")]
+    Invalid(String),
+}
+
+/// Promo metadata json, with the promo fields parsed from `attributes`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PromoMetadata {
+    pub name: String,
+    pub symbol: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub attributes: PromoAttributes,
+    /// Other fields, such as `image`, `collection` and `properties`.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// Metaplex metadata attribute.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub trait_type: String,
+    pub value: Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(try_from = "Vec<Attribute>", into = "Vec<Attribute>")]
+pub struct PromoAttributes {
+    pub promo_type: PromoType,
+    pub max_mint: Option<u32>,
+    pub max_burn: Option<u32>,
+    /// Attributes added by the merchant, kept in order after the promo attributes.
+    pub other: Vec<Attribute>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PromoType {
+    /// Buy `buy_x_product` of the product and get `get_y_product` of it free.
+    BuyXProductGetYFree {
+        product_id: String,
+        buy_x_product: u32,
+        get_y_product: u32,
+    },
+    /// Spend `buy_x_currency`, in 0.01 currency, and get `get_y_percent` off.
+    BuyXCurrencyGetYPercent {
+        buy_x_currency: u64,
+        get_y_percent: u32,
+    },
+    /// Earn `earn_rate` points per 0.01 currency spent, redeemable at `levels`.
+    Loyalty {
+        earn_rate: u32,
+        levels: Vec<LoyaltyLevel>,
+    },
+}
+
+/// Read from consecutive `pointsLevelN` / `rewardLevelN` attribute pairs starting at zero.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct LoyaltyLevel {
+    /// Number of points required to be eligible for the reward.
+    pub points: u64,
+    /// Discount in 0.01 currency available when the points are redeemed.
+    pub reward: u64,
+}
+
+impl PromoType {
+    /// Value of the `promoType` attribute.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PromoType::BuyXProductGetYFree { .. } => "buyXProductGetYFree",
+            PromoType::BuyXCurrencyGetYPercent { .. } => "buyXCurrencyGetYPercent",
+            PromoType::Loyalty { .. } => "loyalty",
+        }
+    }
+}
+
+impl PromoMetadata {
+    /// Parses and validates metadata json submitted to create a promo.
+    pub fn from_value(value: Value) -> Result<Self, MetadataError> {
+        let metadata: PromoMetadata = serde_json::from_value(value)?;
+        metadata.validate()?;
+        Ok(metadata)
+    }
+
+    pub fn to_value(&self) -> Result<Value, MetadataError> {
+        Ok(serde_json::to_value(self)?)
+    }
+
+    /// Checks that the name and symbol fit on chain and that the promo fields can be applied
+    /// to orders.
+    pub fn validate(&self) -> Result<(), MetadataError> {
+        if self.name.is_empty() || self.name.len() > MAX_NAME_LENGTH {
+            return Err(MetadataError::Invalid(format!(
+                "name should be between 1 and {} bytes",
+                MAX_NAME_LENGTH
+            )));
+        }
+        if self.symbol.len() > MAX_SYMBOL_LENGTH {
+            return Err(MetadataError::Invalid(format!(
+                "symbol should be at most {} bytes",
+                MAX_SYMBOL_LENGTH
+            )));
+        }
+        self.attributes.validate()
+    }
+}
+
+impl PromoAttributes {
+    fn validate(&self) -> Result<(), MetadataError> {
+        if let (Some(max_mint), Some(max_burn)) = (self.max_mint, self.max_burn) {
+            if max_burn > max_mint {
+                return Err(MetadataError::Invalid(
+                    "maxBurn should not be greater than maxMint".to_string(),
+                ));
+            }
+        }
+
+        match &self.promo_type {
+            PromoType::BuyXProductGetYFree {
+                product_id,
+                buy_x_product,
+                get_y_product,
+            } => {
+                if product_id.is_empty() {
+                    return Err(MetadataError::InvalidAttribute(
+                        PRODUCT_ID.to_string(),
+                        "a product id",
+                    ));
+                }
+                check_positive(BUY_X_PRODUCT, *buy_x_product as u64)?;
+                check_positive(GET_Y_PRODUCT, *get_y_product as u64)?;
+            }
+            PromoType::BuyXCurrencyGetYPercent {
+                buy_x_currency,
+                get_y_percent,
+            } => {
+                check_positive(BUY_X_CURRENCY, *buy_x_currency)?;
+                if !(1..=100).contains(get_y_percent) {
+                    return Err(MetadataError::InvalidAttribute(
+                        GET_Y_PERCENT.to_string(),
+                        "between 1 and 100",
+                    ));
+                }
+            }
+            PromoType::Loyalty { earn_rate, levels } => {
+                check_positive(EARN_RATE, *earn_rate as u64)?;
+                if levels.is_empty() || levels.len() > LOYALTY_LEVELS_CAPACITY as usize {
+                    return Err(MetadataError::InvalidLoyaltyLevels(LOYALTY_LEVELS_CAPACITY));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<Vec<Attribute>> for PromoAttributes {
+    type Error = MetadataError;
+
+    fn try_from(attributes: Vec<Attribute>) -> Result<Self, Self::Error> {
+        let mut parser = AttributeParser {
+            attributes,
+            parsed: Vec::new(),
+        };
+
+        let promo_type = match parser.get_str(PROMO_TYPE)?.as_str() {
+            "buyXProductGetYFree" => PromoType::BuyXProductGetYFree {
+                product_id: parser.get_str(PRODUCT_ID)?,
+                buy_x_product: parser.get_u32(BUY_X_PRODUCT)?,
+                get_y_product: parser.get_u32(GET_Y_PRODUCT)?,
+            },
+            "buyXCurrencyGetYPercent" => PromoType::BuyXCurrencyGetYPercent {
+                buy_x_currency: parser.get_u64(BUY_X_CURRENCY)?,
+                get_y_percent: parser.get_u32(GET_Y_PERCENT)?,
+            },
+            "loyalty" => {
+                let earn_rate = parser.get_u32(EARN_RATE)?;
+                let mut levels = Vec::new();
+                loop {
+                    let points = format!("pointsLevel{}", levels.len());
+                    let reward = format!("rewardLevel{}", levels.len());
+                    if !parser.contains(&points) || !parser.contains(&reward) {
+                        break;
+                    }
+                    levels.push(LoyaltyLevel {
+                        points: parser.get_u64(&points)?,
+                        reward: parser.get_u64(&reward)?,
+                    });
+                }
+                PromoType::Loyalty { earn_rate, levels }
+            }
+            promo_type => return Err(MetadataError::UnknownPromoType(promo_type.to_string())),
+        };
+
+        let max_mint = parser.get_optional_u32(MAX_MINT)?;
+        let max_burn = parser.get_optional_u32(MAX_BURN)?;
+
+        Ok(PromoAttributes {
+            promo_type,
+            max_mint,
+            max_burn,
+            other: parser.remaining(),
+        })
+    }
+}
+
+impl From<PromoAttributes> for Vec<Attribute> {
+    fn from(promo_attributes: PromoAttributes) -> Self {
+        let attribute = |trait_type: &str, value: Value| Attribute {
+            trait_type: trait_type.to_string(),
+            value,
+        };
+
+        let mut attributes = vec![attribute(
+            PROMO_TYPE,
+            promo_attributes.promo_type.name().into(),
+        )];
+        match promo_attributes.promo_type {
+            PromoType::BuyXProductGetYFree {
+                product_id,
+                buy_x_product,
+                get_y_product,
+            } => {
+                attributes.push(attribute(PRODUCT_ID, product_id.into()));
+                attributes.push(attribute(BUY_X_PRODUCT, buy_x_product.into()));
+                attributes.push(attribute(GET_Y_PRODUCT, get_y_product.into()));
+            }
+            PromoType::BuyXCurrencyGetYPercent {
+                buy_x_currency,
+                get_y_percent,
+            } => {
+                attributes.push(attribute(BUY_X_CURRENCY, buy_x_currency.into()));
+                attributes.push(attribute(GET_Y_PERCENT, get_y_percent.into()));
+            }
+            PromoType::Loyalty { earn_rate, levels } => {
+                attributes.push(attribute(EARN_RATE, earn_rate.into()));
+                for (i, level) in levels.iter().enumerate() {
+                    attributes.push(attribute(&format!("pointsLevel{}", i), level.points.into()));
+                    attributes.push(attribute(&format!("rewardLevel{}", i), level.reward.into()));
+                }
+            }
+        }
+        if let Some(max_mint) = promo_attributes.max_mint {
+            attributes.push(attribute(MAX_MINT, max_mint.into()));
+        }
+        if let Some(max_burn) = promo_attributes.max_burn {
+            attributes.push(attribute(MAX_BURN, max_burn.into()));
+        }
+        attributes.extend(promo_attributes.other);
+        attributes
+    }
+}
+
+/// Looks up attributes by trait type, keeping track of the ones parsed so the rest can be kept
+/// as merchant attributes.
+struct AttributeParser {
+    attributes: Vec<Attribute>,
+    parsed: Vec<String>,
+}
+
+impl AttributeParser {
+    fn contains(&self, trait_type: &str) -> bool {
+        self.attributes.iter().any(|a| a.trait_type == trait_type)
+    }
+
+    fn get_optional(&mut self, trait_type: &str) -> Option<Value> {
+        let value = self
+            .attributes
+            .iter()
+            .find(|a| a.trait_type == trait_type)?
+            .value
+            .clone();
+        self.parsed.push(trait_type.to_string());
+        Some(value)
+    }
+
+    fn get(&mut self, trait_type: &str) -> Result<Value, MetadataError> {
+        self.get_optional(trait_type)
+            .ok_or_else(|| MetadataError::MissingAttribute(trait_type.to_string()))
+    }
+
+    fn get_str(&mut self, trait_type: &str) -> Result<String, MetadataError> {
+        self.get(trait_type)?
+            .as_str()
+            .map(ToString::to_string)
+            .ok_or_else(|| MetadataError::InvalidAttribute(trait_type.to_string(), "a string"))
+    }
+
+    fn get_u64(&mut self, trait_type: &str) -> Result<u64, MetadataError> {
+        self.get(trait_type)?
+            .as_u64()
+            .ok_or_else(|| MetadataError::InvalidAttribute(trait_type.to_string(), "an integer"))
+    }
+
+    fn get_u32(&mut self, trait_type: &str) -> Result<u32, MetadataError> {
+        to_u32(trait_type, self.get_u64(trait_type)?)
+    }
+
+    fn get_optional_u32(&mut self, trait_type: &str) -> Result<Option<u32>, MetadataError> {
+        match self.get_optional(trait_type) {
+            None => Ok(None),
+            Some(value) => {
+                let value = value.as_u64().ok_or_else(|| {
+                    MetadataError::InvalidAttribute(trait_type.to_string(), "an integer")
+                })?;
+                to_u32(trait_type, value).map(Some)
+            }
+        }
+    }
+
+    fn remaining(self) -> Vec<Attribute> {
+        let parsed = self.parsed;
+        self.attributes
+            .into_iter()
+            .filter(|a| !parsed.contains(&a.trait_type))
+            .collect()
+    }
+}
+
+fn to_u32(trait_type: &str, value: u64) -> Result<u32, MetadataError> {
+    u32::try_from(value)
+        .map_err(|_| MetadataError::InvalidAttribute(trait_type.to_string(), "a 32 bit integer"))
+}
+
+fn check_positive(trait_type: &str, value: u64) -> Result<(), MetadataError> {
+    if value == 0 {
+        return Err(MetadataError::InvalidAttribute(
+            trait_type.to_string(),
+            "greater than zero",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_parses_buy_x_product_get_y_free() {
+        let value = json!({
+            "name": "buyXProduct",
+            "symbol": "PROD",
+            "description": "bokoup test promo - product",
+            "attributes": [
+                { "trait_type": "promoType", "value": "buyXProductGetYFree" },
+                { "trait_type": "productId", "value": "0E9DCHTY6P7M2" },
+                { "trait_type": "buyXProduct", "value": 3 },
+                { "trait_type": "getYProduct", "value": 1 },
+                { "trait_type": "maxMint", "value": 1000 },
+                { "trait_type": "maxBurn", "value": 500 },
+                { "trait_type": "season", "value": "summer" },
+            ],
+            "collection": { "name": "Product Promo", "family": "Test Merchant Promos" }
+        });
+
+        let metadata = PromoMetadata::from_value(value.clone()).unwrap();
+        assert_eq!(
+            metadata.attributes.promo_type,
+            PromoType::BuyXProductGetYFree {
+                product_id: "0E9DCHTY6P7M2".to_string(),
+                buy_x_product: 3,
+                get_y_product: 1,
+            }
+        );
+        assert_eq!(metadata.attributes.max_mint, Some(1000));
+        assert_eq!(metadata.attributes.max_burn, Some(500));
+        assert_eq!(metadata.attributes.other[0].trait_type, "season");
+        assert!(metadata.other.contains_key("collection"));
+
+        // Round trips to the same Metaplex json.
+        assert_eq!(metadata.to_value().unwrap(), value);
+    }
+
+    #[test]
+    fn it_parses_loyalty() {
+        let metadata = PromoMetadata::from_value(json!({
+            "name": "loyalty",
+            "symbol": "LOYAL",
+            "attributes": [
+                { "trait_type": "promoType", "value": "loyalty" },
+                { "trait_type": "earnRateLevel0", "value": 1 },
+                { "trait_type": "pointsLevel0", "value": 10000 },
+                { "trait_type": "rewardLevel0", "value": 500 },
+                { "trait_type": "pointsLevel1", "value": 25000 },
+                { "trait_type": "rewardLevel1", "value": 1500 },
+            ],
+        }))
+        .unwrap();
+        assert_eq!(
+            metadata.attributes.promo_type,
+            PromoType::Loyalty {
+                earn_rate: 1,
+                levels: vec![
+                    LoyaltyLevel {
+                        points: 10000,
+                        reward: 500
+                    },
+                    LoyaltyLevel {
+                        points: 25000,
+                        reward: 1500
+                    },
+                ],
+            }
+        );
+        assert!(metadata.attributes.other.is_empty());
+    }
+
+    #[test]
+    fn it_rejects_invalid_metadata() {
+        let metadata = |attributes: Value| {
+            PromoMetadata::from_value(json!({
+                "name": "promo",
+                "symbol": "PROMO",
+                "attributes": attributes,
+            }))
+        };
+
+        assert!(matches!(
+            metadata(json!([{ "trait_type": "promoType", "value": "buyXCurrencyGetYPercent" }])),
+            Err(MetadataError::Json(_))
+        ));
+        assert!(matches!(
+            metadata(json!([
+                { "trait_type": "promoType", "value": "buyXCurrencyGetYPercent" },
+                { "trait_type": "buyXCurrency", "value": 10000 },
+                { "trait_type": "getYPercent", "value": 110 },
+            ])),
+            Err(MetadataError::InvalidAttribute(_, _))
+        ));
+        assert!(matches!(
+            metadata(json!([
+                { "trait_type": "promoType", "value": "loyalty" },
+                { "trait_type": "earnRateLevel0", "value": 1 },
+            ])),
+            Err(MetadataError::InvalidLoyaltyLevels(_))
+        ));
+        assert!(matches!(
+            metadata(json!([
+                { "trait_type": "promoType", "value": "buyXProductGetYFree" },
+                { "trait_type": "productId", "value": "0E9DCHTY6P7M2" },
+                { "trait_type": "buyXProduct", "value": 3 },
+                { "trait_type": "getYProduct", "value": 1 },
+                { "trait_type": "maxMint", "value": 10 },
+                { "trait_type": "maxBurn", "value": 20 },
+            ])),
+            Err(MetadataError::Invalid(_))
+        ));
+        assert!(matches!(
+            PromoMetadata::from_value(json!({
+                "name": "a name that is much too long to fit on chain",
+                "symbol": "PROMO",
+                "attributes": [
+                    { "trait_type": "promoType", "value": "buyXCurrencyGetYPercent" },
+                    { "trait_type": "buyXCurrency", "value": 10000 },
+                    { "trait_type": "getYPercent", "value": 10 },
+                ],
+            })),
+            Err(MetadataError::Invalid(_))
+        ));
+    }
+}
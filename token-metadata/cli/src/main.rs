@@ -1,16 +1,22 @@
 use anchor_client::{
     solana_sdk::{
         commitment_config::CommitmentConfig,
+        pubkey::Pubkey,
         signature::{read_keypair_file, Keypair},
         signer::Signer,
-        system_program
+        system_program, sysvar,
     },
     Client, Cluster,
 };
+use bpl_api_tx::utils::lookup_table;
 use bpl_token_metadata::{instruction, accounts, state::{AdminSettings, PromoGroup}, utils::{self, find_group_address}};
 use bundlr_sdk::{tags::Tag, Bundlr, Ed25519Signer};
 use clap::{Parser, Subcommand};
 use ed25519_dalek::Keypair as DalekKeypair;
+use mpl_auction_house::pda::{
+    find_auction_house_address, find_auction_house_fee_account_address,
+    find_auction_house_treasury_address,
+};
 use tokio::time::sleep;
 use std::{path::PathBuf, rc::Rc, time::Duration};
 use tracing_subscriber::prelude::*;
@@ -58,6 +64,20 @@ enum Commands {
         #[clap(long, default_value_t = 500_000_000, value_parser)]
         lamports: u64,
     },
+    #[clap(about = "Create platform auction house promo tokens are traded on, with SOL as treasury mint")]
+    CreateAuctionHouse {
+        #[clap(long, default_value_t = 0, value_parser)]
+        seller_fee_basis_points: u16,
+    },
+    #[clap(about = "Create platform address lookup table used for v0 transactions")]
+    CreateLookupTable,
+    #[clap(about = "Extend lookup table, with the platform addresses if none are given")]
+    ExtendLookupTable {
+        #[clap(long, value_parser)]
+        lookup_table: Pubkey,
+        #[clap(value_parser)]
+        addresses: Vec<Pubkey>,
+    },
     #[clap(about = "Tesing requesting data from graphql api")]
     TestGql,
 }
@@ -214,6 +234,112 @@ async fn main() -> anyhow::Result<()> {
             Ok(())
 
         }
+        Commands::CreateAuctionHouse {
+            seller_fee_basis_points,
+        } => {
+            let authority = platform_keypair.pubkey();
+            let rc_payer_keypair = Rc::new(platform_keypair);
+            let client = Client::new_with_options(
+                cli.cluster,
+                rc_payer_keypair,
+                CommitmentConfig::confirmed(),
+            );
+
+            let program = client.program(mpl_auction_house::id());
+            let treasury_mint = anchor_spl::token::spl_token::native_mint::ID;
+            let (auction_house, bump) = find_auction_house_address(&authority, &treasury_mint);
+            let (auction_house_fee_account, fee_payer_bump) =
+                find_auction_house_fee_account_address(&auction_house);
+            let (auction_house_treasury, treasury_bump) =
+                find_auction_house_treasury_address(&auction_house);
+
+            // Sales are signed by buyers and sellers without the platform signing off.
+            let tx = program
+                .request()
+                .accounts(mpl_auction_house::accounts::CreateAuctionHouse {
+                    treasury_mint,
+                    payer: authority,
+                    authority,
+                    fee_withdrawal_destination: authority,
+                    treasury_withdrawal_destination: authority,
+                    treasury_withdrawal_destination_owner: authority,
+                    auction_house,
+                    auction_house_fee_account,
+                    auction_house_treasury,
+                    token_program: anchor_spl::token::ID,
+                    system_program: system_program::ID,
+                    ata_program: anchor_spl::associated_token::ID,
+                    rent: sysvar::rent::ID,
+                })
+                .args(mpl_auction_house::instruction::CreateAuctionHouse {
+                    _bump: bump,
+                    fee_payer_bump,
+                    treasury_bump,
+                    seller_fee_basis_points: *seller_fee_basis_points,
+                    requires_sign_off: false,
+                    can_change_sale_price: false,
+                })
+                .send()?;
+            tracing::info!(
+                signature = tx.to_string(),
+                auction_house = auction_house.to_string()
+            );
+            Ok(())
+        }
+        Commands::CreateLookupTable => {
+            let authority = platform_signer_keypair.pubkey();
+            let rc_payer_keypair = Rc::new(platform_signer_keypair);
+            let client = Client::new_with_options(
+                cli.cluster,
+                rc_payer_keypair,
+                CommitmentConfig::confirmed(),
+            );
+
+            let program = client.program(bpl_token_metadata::id());
+
+            // Lookup table addresses are derived from a recent finalized slot.
+            let recent_slot = program
+                .rpc()
+                .get_slot_with_commitment(CommitmentConfig::finalized())?;
+            let (ix, lookup_table) =
+                lookup_table::create_lookup_table(&authority, &authority, recent_slot);
+
+            let tx = program.request().instruction(ix).send()?;
+            tracing::info!(
+                signature = tx.to_string(),
+                lookup_table = lookup_table.to_string()
+            );
+            Ok(())
+        }
+        Commands::ExtendLookupTable {
+            lookup_table,
+            addresses,
+        } => {
+            let authority = platform_signer_keypair.pubkey();
+            let rc_payer_keypair = Rc::new(platform_signer_keypair);
+            let client = Client::new_with_options(
+                cli.cluster,
+                rc_payer_keypair,
+                CommitmentConfig::confirmed(),
+            );
+
+            let program = client.program(bpl_token_metadata::id());
+
+            let addresses = if addresses.is_empty() {
+                lookup_table::get_platform_lookup_table_addresses()
+            } else {
+                addresses.clone()
+            };
+            let ix = lookup_table::extend_lookup_table(lookup_table, &authority, &authority, &addresses);
+
+            let tx = program.request().instruction(ix).send()?;
+            tracing::info!(
+                signature = tx.to_string(),
+                lookup_table = lookup_table.to_string(),
+                addresses = addresses.len()
+            );
+            Ok(())
+        }
         Commands::UploadString => {
             let data = tokio::fs::read(&cli.program_authority_path).await.unwrap();
             let bytes: Vec<u8> = serde_json::from_slice(&data).unwrap();
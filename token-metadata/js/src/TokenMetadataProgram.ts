@@ -12,7 +12,18 @@ import {
   Mint,
 } from '@solana/spl-token';
 import idl from '../../../target/idl/bpl_token_metadata.json';
-import { Promo, PromoExtended, DataV2, MetadataJson, AdminSettings, PromoExtendeds, PromoGroup } from '.';
+import {
+  Promo,
+  PromoExtended,
+  DataV2,
+  MetadataJson,
+  AdminSettings,
+  PromoExtendeds,
+  PromoGroup,
+  PromoAcceptance,
+  Settlement,
+  SettlementApproval,
+} from '.';
 const camelcaseKeysDeep = require('camelcase-keys-deep');
 
 export class TokenMetadataProgram {
@@ -27,6 +38,11 @@ export class TokenMetadataProgram {
   readonly AUTHORITY_PREFIX: string;
   readonly METADATA_PREFIX: string;
   readonly PROMO_PREFIX: string;
+  readonly ACCEPTANCE_PREFIX: string;
+  readonly REFERRAL_PREFIX: string;
+  readonly REFERRAL_REWARD_PREFIX: string;
+  readonly LOYALTY_PREFIX: string;
+  readonly SETTLEMENT_APPROVAL_PREFIX: string;
 
   program: Program;
   payer: Wallet;
@@ -44,6 +60,11 @@ export class TokenMetadataProgram {
     this.AUTHORITY_PREFIX = 'authority';
     this.METADATA_PREFIX = 'metadata';
     this.PROMO_PREFIX = 'promo';
+    this.ACCEPTANCE_PREFIX = 'acceptance';
+    this.REFERRAL_PREFIX = 'referral';
+    this.REFERRAL_REWARD_PREFIX = 'referral_reward';
+    this.LOYALTY_PREFIX = 'loyalty';
+    this.SETTLEMENT_APPROVAL_PREFIX = 'settlement_approval';
 
     this.program = new Program(idl as Idl, this.PUBKEY, provider);
     const anchorProvider = this.program.provider as AnchorProvider;
@@ -195,6 +216,7 @@ export class TokenMetadataProgram {
   async mintPromoToken(mint: PublicKey, groupMember: Keypair, groupSeed: PublicKey, memo: string | null): Promise<PublicKey> {
     const [tokenAccount] = await this.findAssociatedTokenAccountAddress(mint, this.payer.publicKey);
     const [group] = await this.findPromoGroupAddress(groupSeed);
+    const [loyalty] = await this.findLoyaltyAddress(mint);
 
     await this.program.methods
       .mintPromoToken(memo)
@@ -203,6 +225,7 @@ export class TokenMetadataProgram {
         group,
         tokenOwner: this.payer.publicKey,
         mint,
+        loyalty,
         tokenAccount,
         memoProgram: this.MEMO_PROGRAM_ID,
       })
@@ -268,6 +291,286 @@ export class TokenMetadataProgram {
     return tokenAccount;
   }
 
+  /**
+   * Create or update the groups accepting a promo and how redemptions are settled
+   *
+   * @param mint               Promo mint
+   * @param groupSeed          Seed of the group issuing the promo
+   * @param groups             Groups whose members can redeem the promo
+   * @param settlement         Which group pays the other for each token burned
+   * @param settlementLamports Lamports transferred for each token burned
+   *
+   * @return Address of promo acceptance account
+   */
+  async createPromoAcceptance(
+    mint: PublicKey,
+    groupSeed: PublicKey,
+    groups: Array<PublicKey>,
+    settlement: Settlement,
+    settlementLamports: number,
+    memo: string | null
+  ): Promise<PublicKey> {
+    const [group] = await this.findPromoGroupAddress(groupSeed);
+    const [promo] = await this.findPromoAddress(mint);
+    const [promoAcceptance] = await this.findPromoAcceptanceAddress(mint);
+
+    const data: PromoAcceptance = {
+      promo,
+      owner: group,
+      groups,
+      settlement,
+      settlementLamports: new BN(settlementLamports),
+    };
+
+    await this.program.methods
+      .createPromoAcceptance(data, memo)
+      .accounts({
+        group,
+        mint,
+        promo,
+        promoAcceptance,
+        memoProgram: this.MEMO_PROGRAM_ID,
+      })
+      .rpc();
+
+    return promoAcceptance;
+  }
+
+  /**
+   * Create or update the most an accepting group pays the issuing group for each token of a
+   * promo its members burn
+   *
+   * @param mint                  Promo mint
+   * @param groupSeed             Seed of the accepting group
+   * @param maxSettlementLamports Most lamports paid for each token burned, 0 to withdraw
+   *
+   * @return Address of settlement approval account
+   */
+  async createSettlementApproval(
+    mint: PublicKey,
+    groupSeed: PublicKey,
+    maxSettlementLamports: number,
+    memo: string | null
+  ): Promise<PublicKey> {
+    const [group] = await this.findPromoGroupAddress(groupSeed);
+    const [promo] = await this.findPromoAddress(mint);
+    const [settlementApproval] = await this.findSettlementApprovalAddress(mint, group);
+
+    const data: SettlementApproval = {
+      promo,
+      group,
+      maxSettlementLamports: new BN(maxSettlementLamports),
+    };
+
+    await this.program.methods
+      .createSettlementApproval(data, memo)
+      .accounts({
+        group,
+        mint,
+        promo,
+        settlementApproval,
+        memoProgram: this.MEMO_PROGRAM_ID,
+      })
+      .rpc();
+
+    return settlementApproval;
+  }
+
+  /**
+   * Delegate promo token to a member of a group accepting the promo
+   *
+   * @param mint      Mint address
+   * @param delegate  Member of the accepting group
+   * @param groupSeed Seed of the accepting group
+   *
+   * @return Token account address
+   */
+  async delegateAcceptedPromoToken(
+    mint: PublicKey,
+    delegate: PublicKey,
+    groupSeed: PublicKey,
+    memo: string | null
+  ): Promise<PublicKey> {
+    const [tokenAccount] = await this.findAssociatedTokenAccountAddress(mint, this.payer.publicKey);
+    const [group] = await this.findPromoGroupAddress(groupSeed);
+    const [promoAcceptance] = await this.findPromoAcceptanceAddress(mint);
+
+    await this.program.methods.delegateAcceptedPromoToken(memo).accounts({
+      delegate,
+      group,
+      tokenOwner: this.payer.publicKey,
+      mint,
+      promoAcceptance,
+      tokenAccount,
+      memoProgram: this.MEMO_PROGRAM_ID,
+    }).rpc();
+
+    return tokenAccount;
+  }
+
+  /**
+   * Burn promo token delegated to a member of a group accepting the promo, settling
+   * between the issuing and accepting groups.
+   *
+   * @param mint              Mint address
+   * @param tokenOwner        Owner of the token
+   * @param platform          Platform address
+   * @param groupSeed         Seed of the accepting group
+   * @param issuingGroupSeed  Seed of the group issuing the promo
+   *
+   * @return Token account address
+   */
+  async burnAcceptedPromoToken(
+    mint: PublicKey,
+    tokenOwner: PublicKey,
+    platform: PublicKey,
+    groupSeed: PublicKey,
+    issuingGroupSeed: PublicKey,
+    memo: string | null
+  ): Promise<PublicKey> {
+    const [tokenAccount] = await this.findAssociatedTokenAccountAddress(mint, tokenOwner);
+    const [group] = await this.findPromoGroupAddress(groupSeed);
+    const [issuingGroup] = await this.findPromoGroupAddress(issuingGroupSeed);
+    const [promoAcceptance] = await this.findPromoAcceptanceAddress(mint);
+    const [settlementApproval] = await this.findSettlementApprovalAddress(mint, group);
+
+    await this.program.methods
+      .burnAcceptedPromoToken(memo)
+      .accounts({
+        group,
+        issuingGroup,
+        mint,
+        promoAcceptance,
+        settlementApproval,
+        platform,
+        tokenAccount,
+        memoProgram: this.MEMO_PROGRAM_ID,
+      })
+      .rpc();
+
+    return tokenAccount;
+  }
+
+  /**
+   * Create or update the promo minted to referrers when tokens they referred are burned
+   *
+   * @param mint        Promo mint
+   * @param rewardMint  Mint of the reward promo, owned by the same group
+   * @param groupSeed   Seed of the group owning both promos
+   *
+   * @return Address of referral reward account
+   */
+  async createReferralReward(
+    mint: PublicKey,
+    rewardMint: PublicKey,
+    groupSeed: PublicKey,
+    memo: string | null
+  ): Promise<PublicKey> {
+    const [group] = await this.findPromoGroupAddress(groupSeed);
+    const [referralReward] = await this.findReferralRewardAddress(mint);
+
+    await this.program.methods
+      .createReferralReward(memo)
+      .accounts({
+        group,
+        mint,
+        rewardMint,
+        referralReward,
+        memoProgram: this.MEMO_PROGRAM_ID,
+      })
+      .rpc();
+
+    return referralReward;
+  }
+
+  /**
+   * Mint promo token recording the referrer of the token owner
+   *
+   * @param mint        Promo mint
+   * @param referrer    Address rewarded when the token is burned
+   * @param groupMember Keypair of group member paying for the transaction
+   * @param groupSeed   Seed of the group owning the promo
+   *
+   * @return Token account address
+   */
+  async mintReferredPromoToken(
+    mint: PublicKey,
+    referrer: PublicKey,
+    groupMember: Keypair,
+    groupSeed: PublicKey,
+    memo: string | null
+  ): Promise<PublicKey> {
+    const [tokenAccount] = await this.findAssociatedTokenAccountAddress(mint, this.payer.publicKey);
+    const [group] = await this.findPromoGroupAddress(groupSeed);
+    const [referralReward] = await this.findReferralRewardAddress(mint);
+    const [referral] = await this.findReferralAddress(mint, this.payer.publicKey);
+    const [loyalty] = await this.findLoyaltyAddress(mint);
+
+    await this.program.methods
+      .mintReferredPromoToken(referrer, memo)
+      .accounts({
+        payer: groupMember.publicKey,
+        group,
+        tokenOwner: this.payer.publicKey,
+        mint,
+        loyalty,
+        referralReward,
+        referral,
+        tokenAccount,
+        memoProgram: this.MEMO_PROGRAM_ID,
+      })
+      .signers([groupMember])
+      .rpc();
+
+    return tokenAccount;
+  }
+
+  /**
+   * Burn delegated promo token minting a reward promo token to the referrer
+   *
+   * @param mint        Mint address
+   * @param tokenOwner  Owner of the token
+   * @param referrer    Referrer recorded when the token was minted
+   * @param rewardMint  Mint of the reward promo
+   * @param platform    Platform address
+   * @param groupSeed   Seed of the group owning the promo
+   *
+   * @return Referrer's reward token account address
+   */
+  async burnReferredPromoToken(
+    mint: PublicKey,
+    tokenOwner: PublicKey,
+    referrer: PublicKey,
+    rewardMint: PublicKey,
+    platform: PublicKey,
+    groupSeed: PublicKey,
+    memo: string | null
+  ): Promise<PublicKey> {
+    const [tokenAccount] = await this.findAssociatedTokenAccountAddress(mint, tokenOwner);
+    const [referrerTokenAccount] = await this.findAssociatedTokenAccountAddress(rewardMint, referrer);
+    const [group] = await this.findPromoGroupAddress(groupSeed);
+    const [referral] = await this.findReferralAddress(mint, tokenOwner);
+    const [referralReward] = await this.findReferralRewardAddress(mint);
+
+    await this.program.methods
+      .burnReferredPromoToken(memo)
+      .accounts({
+        group,
+        mint,
+        platform,
+        tokenAccount,
+        referral,
+        referralReward,
+        referrer,
+        rewardMint,
+        referrerTokenAccount,
+        memoProgram: this.MEMO_PROGRAM_ID,
+      })
+      .rpc();
+
+    return referrerTokenAccount;
+  }
+
   async getTokenAccount(address: PublicKey): Promise<TokenAccount> {
     return await getTokenAccount(this.program.provider.connection, address);
   }
@@ -372,6 +675,41 @@ export class TokenMetadataProgram {
     );
   }
 
+  async findPromoAcceptanceAddress(mint: PublicKey): Promise<[PublicKey, number]> {
+    return await PublicKey.findProgramAddress(
+      [Buffer.from(this.ACCEPTANCE_PREFIX), mint.toBuffer()],
+      this.PUBKEY,
+    );
+  }
+
+  async findReferralAddress(mint: PublicKey, tokenOwner: PublicKey): Promise<[PublicKey, number]> {
+    return await PublicKey.findProgramAddress(
+      [Buffer.from(this.REFERRAL_PREFIX), mint.toBuffer(), tokenOwner.toBuffer()],
+      this.PUBKEY,
+    );
+  }
+
+  async findReferralRewardAddress(mint: PublicKey): Promise<[PublicKey, number]> {
+    return await PublicKey.findProgramAddress(
+      [Buffer.from(this.REFERRAL_REWARD_PREFIX), mint.toBuffer()],
+      this.PUBKEY,
+    );
+  }
+
+  async findSettlementApprovalAddress(mint: PublicKey, group: PublicKey): Promise<[PublicKey, number]> {
+    return await PublicKey.findProgramAddress(
+      [Buffer.from(this.SETTLEMENT_APPROVAL_PREFIX), mint.toBuffer(), group.toBuffer()],
+      this.PUBKEY,
+    );
+  }
+
+  async findLoyaltyAddress(mint: PublicKey): Promise<[PublicKey, number]> {
+    return await PublicKey.findProgramAddress(
+      [Buffer.from(this.LOYALTY_PREFIX), mint.toBuffer()],
+      this.PUBKEY,
+    );
+  }
+
   async findPromoGroupAddress(groupSeed: PublicKey): Promise<[PublicKey, number]> {
     return await PublicKey.findProgramAddress(
       [groupSeed.toBuffer()],
@@ -28,6 +28,22 @@ export type PromoGroup = {
   members: Array<PublicKey>;
 };
 
+export type Settlement = { issuerPaysRedeemer: {} } | { redeemerPaysIssuer: {} };
+
+export type PromoAcceptance = {
+  promo: PublicKey;
+  owner: PublicKey;
+  groups: Array<PublicKey>;
+  settlement: Settlement;
+  settlementLamports: BN;
+};
+
+export type SettlementApproval = {
+  promo: PublicKey;
+  group: PublicKey;
+  maxSettlementLamports: BN;
+};
+
 export type UI<T> = T & {
   publicKey: PublicKey;
 };
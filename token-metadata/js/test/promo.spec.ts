@@ -1,5 +1,5 @@
 import * as anchor from '@project-serum/anchor';
-import { TokenMetadataProgram, AdminSettings, DataV2, PromoExtended, PromoGroup } from '../src';
+import { TokenMetadataProgram, AdminSettings, DataV2, PromoExtended, PromoGroup, PromoAcceptance } from '../src';
 import { PublicKey, Keypair, Transaction, Connection } from '@solana/web3.js';
 import chai = require('chai');
 import chaiAsPromised = require('chai-as-promised');
@@ -292,4 +292,180 @@ describe('promo', () => {
     }
   });
 
+
+  // A second group whose members can redeem the promo of the first group.
+  const acceptingGroupSeed = Keypair.generate().publicKey;
+  let acceptingGroup: PublicKey;
+
+  it('Creates a promo acceptance', async () => {
+    [acceptingGroup] = await tokenMetadataProgramPromoOwner.createPromoGroup(
+      acceptingGroupSeed,
+      [promoOwner.publicKey, groupMember1.publicKey],
+      100_000_000,
+      null,
+    );
+
+    const promoAcceptance = await tokenMetadataProgramPromoOwner.createPromoAcceptance(
+      mint,
+      groupSeed,
+      [acceptingGroup],
+      { issuerPaysRedeemer: {} },
+      1_000_000,
+      null,
+    );
+
+    const promoAcceptanceAccount = (await tokenMetadataProgram.program.account.promoAcceptance.fetch(
+      promoAcceptance,
+    )) as PromoAcceptance;
+    expect(promoAcceptanceAccount.owner.toString()).to.equal(group.toString(), 'Owner incorrect.');
+    expect(promoAcceptanceAccount.groups.map((g) => g.toString())).to.deep.equal(
+      [acceptingGroup.toString()],
+      'Groups incorrect.',
+    );
+  });
+
+  const burnAccepted = async (): Promise<[number, number]> => {
+    await tokenMetadataProgram.mintPromoToken(mint, groupMember1, groupSeed, null);
+    await tokenMetadataProgram.delegateAcceptedPromoToken(mint, groupMember1.publicKey, acceptingGroupSeed, null);
+
+    const connection = tokenMetadataProgram.program.provider.connection;
+    const [issuingStart, acceptingStart] = await Promise.all([
+      connection.getBalance(group),
+      connection.getBalance(acceptingGroup),
+    ]);
+    await tokenMetadataProgramGroupMember1.burnAcceptedPromoToken(
+      mint,
+      tokenOwner,
+      platform.publicKey,
+      acceptingGroupSeed,
+      groupSeed,
+      null,
+    );
+    const [issuingEnd, acceptingEnd] = await Promise.all([
+      connection.getBalance(group),
+      connection.getBalance(acceptingGroup),
+    ]);
+    return [issuingEnd - issuingStart, acceptingEnd - acceptingStart];
+  };
+
+  it('Burns an accepted promo token with the issuer paying', async () => {
+    const [issuingChange, acceptingChange] = await burnAccepted();
+    expect(issuingChange).to.equal(-1_000_000, 'Issuing group lamports incorrect.');
+    expect(acceptingChange).to.equal(1_000_000, 'Accepting group lamports incorrect.');
+  });
+
+  it('Burns an accepted promo token with the redeemer paying', async () => {
+    await tokenMetadataProgramPromoOwner.createPromoAcceptance(
+      mint,
+      groupSeed,
+      [acceptingGroup],
+      { redeemerPaysIssuer: {} },
+      2_000_000,
+      null,
+    );
+    // The accepting group only pays up to what its owner approved.
+    await tokenMetadataProgramPromoOwner.createSettlementApproval(mint, acceptingGroupSeed, 2_000_000, null);
+    const [issuingChange, acceptingChange] = await burnAccepted();
+    expect(issuingChange).to.equal(2_000_000, 'Issuing group lamports incorrect.');
+    expect(acceptingChange).to.equal(-2_000_000, 'Accepting group lamports incorrect.');
+  });
+
+  it('Rejects burning an accepted promo token the redeemer cannot pay for', async () => {
+    // The accepting group has to stay rent exempt, so it can't pay out its whole balance.
+    const acceptingLamports = await tokenMetadataProgram.program.provider.connection.getBalance(acceptingGroup);
+    await tokenMetadataProgramPromoOwner.createPromoAcceptance(
+      mint,
+      groupSeed,
+      [acceptingGroup],
+      { redeemerPaysIssuer: {} },
+      acceptingLamports,
+      null,
+    );
+    // Its approval of 2_000_000 lamports doesn't cover the new settlement.
+    await expect(burnAccepted()).to.be.rejectedWith('SettlementNotApproved');
+
+    await tokenMetadataProgramPromoOwner.createSettlementApproval(mint, acceptingGroupSeed, acceptingLamports, null);
+    await expect(
+      tokenMetadataProgramGroupMember1.burnAcceptedPromoToken(
+        mint,
+        tokenOwner,
+        platform.publicKey,
+        acceptingGroupSeed,
+        groupSeed,
+        null,
+      ),
+    ).to.be.rejectedWith('InsufficientFunds');
+
+    const [tokenAccount] = await tokenMetadataProgram.findAssociatedTokenAccountAddress(mint, tokenOwner);
+    const tokenAccountAccount = await tokenMetadataProgram.getTokenAccount(tokenAccount);
+    expect(Number(tokenAccountAccount.amount)).to.equal(1, 'Token account amount incorrect.');
+  });
+
+  it('Rewards the referrer when a referred promo token is burned', async () => {
+    const metadataData: DataV2 = {
+      name: 'Test Referral Promo',
+      symbol: 'BTRP',
+      uri: 'https://arweave.net/frDiuZYzSVwYTwSUMR1YbggVkZqZfA7S9xsI3drPWBo',
+      sellerFeeBasisPoints: 0,
+      creators: null,
+      collection: null,
+      uses: null,
+    };
+    const referredMint = await tokenMetadataProgramPromoOwner.createPromo(
+      metadataData,
+      true,
+      groupSeed,
+      10,
+      5,
+      adminSettingsAccount.platform,
+      null,
+    );
+    const rewardMint = await tokenMetadataProgramPromoOwner.createPromo(
+      { ...metadataData, name: 'Test Reward Promo', symbol: 'BTRR' },
+      true,
+      groupSeed,
+      10,
+      5,
+      adminSettingsAccount.platform,
+      null,
+    );
+    await tokenMetadataProgramPromoOwner.createReferralReward(referredMint, rewardMint, groupSeed, null);
+
+    const referrer = Keypair.generate().publicKey;
+    await tokenMetadataProgram.mintReferredPromoToken(referredMint, referrer, groupMember1, groupSeed, null);
+    await tokenMetadataProgram.delegatePromoToken(referredMint, groupMember1.publicKey, groupSeed, null);
+    const referrerTokenAccount = await tokenMetadataProgramGroupMember1.burnReferredPromoToken(
+      referredMint,
+      tokenOwner,
+      referrer,
+      rewardMint,
+      platform.publicKey,
+      groupSeed,
+      null,
+    );
+
+    const referrerTokenAccountAccount = await tokenMetadataProgram.getTokenAccount(referrerTokenAccount);
+    expect(Number(referrerTokenAccountAccount.amount)).to.equal(1, 'Referrer token amount incorrect.');
+
+    const [referral] = await tokenMetadataProgram.findReferralAddress(referredMint, tokenOwner);
+    const referralAccount = await tokenMetadataProgram.program.account.referral.fetch(referral);
+    expect(referralAccount.referrer.toString()).to.equal(referrer.toString(), 'Referrer incorrect.');
+    expect(referralAccount.converted).to.equal(true, 'Referral not converted.');
+
+    // A referral is only rewarded once.
+    await tokenMetadataProgram.mintReferredPromoToken(referredMint, referrer, groupMember1, groupSeed, null);
+    await tokenMetadataProgram.delegatePromoToken(referredMint, groupMember1.publicKey, groupSeed, null);
+    await expect(
+      tokenMetadataProgramGroupMember1.burnReferredPromoToken(
+        referredMint,
+        tokenOwner,
+        referrer,
+        rewardMint,
+        platform.publicKey,
+        groupSeed,
+        null,
+      ),
+    ).to.be.rejectedWith('ReferralAlreadyConverted');
+  });
+
 });
@@ -14,4 +14,22 @@ pub enum ProgramError {
     InsufficientFunds,
     #[msg("Constraint not met")]
     ConstraintNotMet,
+    #[msg("Reward level not found")]
+    RewardLevelNotFound,
+    #[msg("Insufficient points")]
+    InsufficientPoints,
+    #[msg("Arithmetic overflow")]
+    Overflow,
+    #[msg("Referral already converted")]
+    ReferralAlreadyConverted,
+    #[msg("Metadata too long")]
+    MetadataTooLong,
+    #[msg("Permanent delegate required")]
+    PermanentDelegateRequired,
+    #[msg("Loyalty points can only be earned")]
+    LoyaltyPointsEarnedOnly,
+    #[msg("Settlement not approved by the redeeming group")]
+    SettlementNotApproved,
+    #[msg("Sponsorship allowance exceeded")]
+    SponsorshipAllowanceExceeded,
 }
@@ -2,6 +2,8 @@ pub mod error;
 /// Processors for each program instruction.
 pub mod processor;
 pub mod state;
+/// Token-2022 instructions and account layouts.
+pub mod token_2022;
 pub mod utils;
 
 use anchor_lang::prelude::*;
@@ -11,8 +13,15 @@ use anchor_spl::{
     token::{Mint, Token, TokenAccount},
 };
 use borsh::BorshDeserialize;
-use state::{AdminSettings, DataV2, Promo, PromoGroup};
-use utils::{ADMIN_PREFIX, AUTHORITY_PREFIX, MEMBERS_CAPACITY, PROMO_PREFIX};
+use state::{
+    AdminSettings, DataV2, Loyalty, MintExtensions, Promo, PromoAcceptance, PromoGroup, PromoMint,
+    Referral, ReferralReward, SettlementApproval, SponsorshipAllowance,
+};
+use utils::{
+    ACCEPTANCE_CAPACITY, ACCEPTANCE_PREFIX, ADMIN_PREFIX, AUTHORITY_PREFIX, LOYALTY_LEVELS_CAPACITY,
+    LOYALTY_PREFIX, MEMBERS_CAPACITY, PROMO_MINT_PREFIX, PROMO_PREFIX, REFERRAL_PREFIX,
+    REFERRAL_REWARD_PREFIX, SETTLEMENT_APPROVAL_PREFIX, SPONSORSHIP_ALLOWANCE_PREFIX,
+};
 
 declare_id!("CjSoZrc2DBZTv1UdoMx8fTcCpqEMXCyfm2EuTwy8yiGi");
 
@@ -96,6 +105,147 @@ pub mod bpl_token_metadata {
         ctx.accounts.process(memo, authority_seeds)
     }
 
+    /// Creates or updates PromoAcceptance account naming additional groups whose members
+    /// can redeem tokens of a promo.
+    pub fn create_promo_acceptance(
+        ctx: Context<CreatePromoAcceptance>,
+        data: PromoAcceptance,
+        memo: Option<String>,
+    ) -> Result<()> {
+        ctx.accounts.process(data, memo)
+    }
+
+    /// Creates or updates SettlementApproval account capping what a group accepting a promo
+    /// pays the issuing group for each token its members burn.
+    pub fn create_settlement_approval(
+        ctx: Context<CreateSettlementApproval>,
+        data: SettlementApproval,
+        memo: Option<String>,
+    ) -> Result<()> {
+        ctx.accounts.process(data, memo)
+    }
+
+    /// Delegates a promo token to a member of a group accepting the promo.
+    pub fn delegate_accepted_promo_token<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, DelegateAcceptedPromoToken<'info>>,
+        memo: Option<String>,
+    ) -> Result<()> {
+        ctx.accounts.process(memo)
+    }
+
+    /// Burns a promo token delegated to a member of a group accepting the promo.
+    pub fn burn_accepted_promo_token<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, BurnAcceptedPromoToken<'info>>,
+        memo: Option<String>,
+    ) -> Result<()> {
+        ctx.accounts.process(memo)
+    }
+
+    /// Creates Loyalty account making the mint of a promo a loyalty points mint.
+    pub fn create_loyalty(
+        ctx: Context<CreateLoyalty>,
+        data: Loyalty,
+        memo: Option<String>,
+    ) -> Result<()> {
+        ctx.accounts.process(data, memo)
+    }
+
+    /// Mints loyalty points proportional to an order amount in 0.01 currency.
+    pub fn earn_points<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, EarnPoints<'info>>,
+        order_amount: u64,
+        memo: Option<String>,
+    ) -> Result<()> {
+        let authority_seeds = [AUTHORITY_PREFIX.as_bytes(), &[ctx.bumps[AUTHORITY_PREFIX]]];
+        ctx.accounts.process(order_amount, memo, authority_seeds)
+    }
+
+    /// Burns loyalty points in exchange for the reward at a level.
+    pub fn redeem_points<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, RedeemPoints<'info>>,
+        level: u8,
+        memo: Option<String>,
+    ) -> Result<()> {
+        ctx.accounts.process(level, memo)
+    }
+
+    /// Creates or updates the ReferralReward account for a promo.
+    pub fn create_referral_reward(
+        ctx: Context<CreateReferralReward>,
+        memo: Option<String>,
+    ) -> Result<()> {
+        ctx.accounts.process(memo)
+    }
+
+    /// Mints a promo token recording the referrer of the token owner.
+    pub fn mint_referred_promo_token<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, MintReferredPromoToken<'info>>,
+        referrer: Pubkey,
+        memo: Option<String>,
+    ) -> Result<()> {
+        let authority_seeds = [AUTHORITY_PREFIX.as_bytes(), &[ctx.bumps[AUTHORITY_PREFIX]]];
+        ctx.accounts.process(referrer, memo, authority_seeds)
+    }
+
+    /// Burns a delegated promo token minting a reward promo token to the referrer.
+    pub fn burn_referred_promo_token<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, BurnReferredPromoToken<'info>>,
+        memo: Option<String>,
+    ) -> Result<()> {
+        let authority_seeds = [AUTHORITY_PREFIX.as_bytes(), &[ctx.bumps[AUTHORITY_PREFIX]]];
+        ctx.accounts.process(memo, authority_seeds)
+    }
+
+    /// Creates Promo and PromoMint accounts and a Token-2022 mint with the specified extensions.
+    pub fn create_promo_2022(
+        ctx: Context<CreatePromo2022>,
+        promo_data: Promo,
+        metadata_data: DataV2,
+        extensions: MintExtensions,
+        memo: Option<String>,
+    ) -> Result<()> {
+        ctx.accounts
+            .process(promo_data, metadata_data, extensions, memo)
+    }
+
+    /// Mints a promo token of a Token-2022 mint.
+    pub fn mint_promo_token_2022<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, MintPromoToken2022<'info>>,
+        memo: Option<String>,
+    ) -> Result<()> {
+        let authority_seeds = [AUTHORITY_PREFIX.as_bytes(), &[ctx.bumps[AUTHORITY_PREFIX]]];
+        ctx.accounts.process(memo, authority_seeds)
+    }
+
+    /// Burns a promo token of a Token-2022 mint as its permanent delegate.
+    pub fn burn_promo_token_2022<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, BurnPromoToken2022<'info>>,
+        memo: Option<String>,
+    ) -> Result<()> {
+        let authority_seeds = [AUTHORITY_PREFIX.as_bytes(), &[ctx.bumps[AUTHORITY_PREFIX]]];
+        ctx.accounts.process(memo, authority_seeds)
+    }
+
+    /// Creates or updates SponsorshipAllowance account capping the lamports that can be
+    /// settled from a group to the platform.
+    pub fn create_sponsorship_allowance(
+        ctx: Context<CreateSponsorshipAllowance>,
+        max_lamports: u64,
+        memo: Option<String>,
+    ) -> Result<()> {
+        ctx.accounts.process(max_lamports, memo)
+    }
+
+    /// Transfers lamports from a group to the platform, reimbursing the network fees and rent
+    /// paid by the platform signer for transactions it sponsored for the group.
+    pub fn settle_sponsorship(
+        ctx: Context<SettleSponsorship>,
+        lamports: u64,
+        memo: Option<String>,
+    ) -> Result<()> {
+        ctx.accounts.process(lamports, memo)
+    }
+
     /// Creates a non-fungible token. Will be used in the future with additional promo token form
     /// factors and to facilitate grouping promo tokens in collections.
     pub fn create_non_fungible(
@@ -247,6 +397,9 @@ pub struct TransferCpi<'info> {
 /// by token owners. Future versions may retain authority with the program to facilitate execution
 /// to enforce transfer restrictions).
 ///
+/// Can't be used for promos with a [Loyalty] account, whose tokens are points minted by
+/// [EarnPoints].
+///
 /// No platform fees result from minting a token.
 #[derive(Accounts, Clone)]
 pub struct MintPromoToken<'info> {
@@ -266,6 +419,12 @@ pub struct MintPromoToken<'info> {
     pub authority: UncheckedAccount<'info>,
     #[account(mut, seeds = [PROMO_PREFIX.as_bytes(), mint.key().as_ref()], bump)]
     pub promo: Account<'info, Promo>,
+    /// CHECK: pubkey checked via seeds, must not be initialized
+    #[account(
+        seeds = [LOYALTY_PREFIX.as_bytes(), mint.key().as_ref()], bump,
+        constraint = loyalty.data_is_empty() @ error::ProgramError::LoyaltyPointsEarnedOnly,
+    )]
+    pub loyalty: UncheckedAccount<'info>,
     #[account(init_if_needed, payer = payer, associated_token::mint = mint, associated_token::authority = token_owner)]
     pub token_account: Account<'info, TokenAccount>,
     pub token_program: Program<'info, Token>,
@@ -357,6 +516,456 @@ pub struct BurnDelegatedPromoToken<'info> {
     pub system_program: Program<'info, System>,
 }
 
+/// Accounts related to creating or updating a [PromoAcceptance].
+///
+/// A [PromoAcceptance] names groups other than the one that owns a [Promo] whose members can
+/// act as delegates and burners of its tokens, so that a mall or business association can
+/// issue one coupon redeemable at many member stores. Only the owner of the group that owns
+/// the promo can create or update it. The issuing group can't name itself.
+///
+/// Each time a token is burned by a member of an accepting group, `settlement_lamports` are
+/// transferred between the issuing group and the redeeming group in the direction specified by
+/// `settlement`. Redeeming groups only pay the issuing group up to the amount their owner
+/// approved in a [SettlementApproval].
+///
+/// Program derived address allows only one acceptance account to exist per mint.
+#[derive(Accounts)]
+#[instruction(data: PromoAcceptance)]
+pub struct CreatePromoAcceptance<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        constraint = payer.key() == group.owner,
+        constraint = group.key() == promo.owner,
+    )]
+    pub group: Account<'info, PromoGroup>,
+    pub mint: Account<'info, Mint>,
+    #[account(seeds = [PROMO_PREFIX.as_bytes(), mint.key().as_ref()], bump)]
+    pub promo: Account<'info, Promo>,
+    #[account(
+        init_if_needed,
+        constraint = data.groups.len() <= ACCEPTANCE_CAPACITY as usize,
+        constraint = !data.groups.contains(&group.key()),
+        constraint = data.promo == promo.key(),
+        constraint = data.owner == group.key(),
+        seeds = [ACCEPTANCE_PREFIX.as_bytes(), mint.key().as_ref()], bump,
+        payer = payer,
+        space = PromoAcceptance::LEN
+    )]
+    pub promo_acceptance: Account<'info, PromoAcceptance>,
+    pub memo_program: Program<'info, SplMemo>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts related to creating or updating a [SettlementApproval].
+///
+/// Only the owner of the accepting group can approve what the group pays the issuing group
+/// when its members burn tokens of the promo. Setting `max_settlement_lamports` to zero
+/// withdraws the approval.
+///
+/// Program derived address allows only one approval to exist per mint and group.
+#[derive(Accounts)]
+#[instruction(data: SettlementApproval)]
+pub struct CreateSettlementApproval<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(constraint = payer.key() == group.owner)]
+    pub group: Account<'info, PromoGroup>,
+    pub mint: Account<'info, Mint>,
+    #[account(seeds = [PROMO_PREFIX.as_bytes(), mint.key().as_ref()], bump)]
+    pub promo: Account<'info, Promo>,
+    #[account(
+        init_if_needed,
+        constraint = data.promo == promo.key(),
+        constraint = data.group == group.key(),
+        seeds = [SETTLEMENT_APPROVAL_PREFIX.as_bytes(), mint.key().as_ref(), group.key().as_ref()], bump,
+        payer = payer,
+        space = SettlementApproval::LEN
+    )]
+    pub settlement_approval: Account<'info, SettlementApproval>,
+    pub memo_program: Program<'info, SplMemo>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts related to the delegation of a promo token to a member of an accepting group.
+///
+/// Same as [DelegatePromoToken] except that the delegate is checked to be a member of one of
+/// the groups in the [PromoAcceptance] for the promo instead of the group that owns it.
+///
+/// No platform fees result from delegating a token.
+#[derive(Accounts, Clone)]
+pub struct DelegateAcceptedPromoToken<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: checked via group constraints
+    pub delegate: UncheckedAccount<'info>,
+    #[account(
+        constraint = group.members.contains(&delegate.key()),
+        constraint = promo_acceptance.groups.contains(&group.key()),
+    )]
+    pub group: Account<'info, PromoGroup>,
+    #[account(mut)]
+    pub token_owner: Signer<'info>,
+    pub mint: Account<'info, Mint>,
+    #[account(seeds = [PROMO_PREFIX.as_bytes(), mint.key().as_ref()], bump)]
+    pub promo: Account<'info, Promo>,
+    #[account(
+        seeds = [ACCEPTANCE_PREFIX.as_bytes(), mint.key().as_ref()], bump,
+        constraint = promo_acceptance.promo == promo.key(),
+    )]
+    pub promo_acceptance: Account<'info, PromoAcceptance>,
+    #[account(mut,
+        constraint = token_owner.key() == token_account.owner,
+        constraint = mint.key() == token_account.mint
+    )]
+    pub token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, SplMemo>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts related to the burning of a promo token delegated to a member of an accepting group.
+///
+/// Checks to make sure signer is a member of one of the groups in the [PromoAcceptance] for the
+/// promo and that `issuing_group` is the group specified in the owner property of the promo.
+///
+/// The fee specified in the `burn_promo_token_lamports` property of the [AdminSettings] account
+/// is paid the same way as in [BurnDelegatedPromoToken]. In addition, `settlement_lamports` are
+/// transferred between the issuing group and the redeeming group as specified by the
+/// `settlement` property of the [PromoAcceptance]. When the redeeming group pays, the amount
+/// can't exceed the `max_settlement_lamports` of its [SettlementApproval] for the promo.
+#[derive(Accounts, Clone)]
+pub struct BurnAcceptedPromoToken<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut,
+        constraint = group.members.contains(&payer.key()),
+        constraint = promo_acceptance.groups.contains(&group.key()),
+    )]
+    pub group: Account<'info, PromoGroup>,
+    #[account(mut, constraint = issuing_group.key() == promo.owner)]
+    pub issuing_group: Account<'info, PromoGroup>,
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+    #[account(mut, seeds = [PROMO_PREFIX.as_bytes(), mint.key().as_ref()], bump)]
+    pub promo: Account<'info, Promo>,
+    #[account(
+        seeds = [ACCEPTANCE_PREFIX.as_bytes(), mint.key().as_ref()], bump,
+        constraint = promo_acceptance.promo == promo.key(),
+    )]
+    pub promo_acceptance: Account<'info, PromoAcceptance>,
+    /// CHECK: pubkey checked via seeds, only read when the redeeming group pays
+    #[account(
+        seeds = [SETTLEMENT_APPROVAL_PREFIX.as_bytes(), mint.key().as_ref(), group.key().as_ref()],
+        bump,
+    )]
+    pub settlement_approval: UncheckedAccount<'info>,
+    /// CHECK: pubkey checked via constraint
+    #[account(mut, constraint = platform.key() == admin_settings.platform)]
+    pub platform: UncheckedAccount<'info>,
+    #[account(seeds = [ADMIN_PREFIX.as_bytes()], bump)]
+    pub admin_settings: Box<Account<'info, AdminSettings>>,
+    #[account(mut,
+        constraint = token_account.mint == mint.key(),
+        constraint = token_account.delegate.unwrap() == payer.key(),
+        constraint = token_account.delegated_amount > 0,
+    )]
+    pub token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, SplMemo>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts related to creating a [Loyalty].
+///
+/// Turns the mint of an existing [Promo] into a loyalty points mint. Only the owner of the
+/// group that owns the promo can create it. Checks to make sure there is at least one reward
+/// level and no more than `LOYALTY_LEVELS_CAPACITY`.
+///
+/// Program derived address allows only one loyalty account to exist per mint.
+#[derive(Accounts)]
+#[instruction(data: Loyalty)]
+pub struct CreateLoyalty<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        constraint = payer.key() == group.owner,
+        constraint = group.key() == promo.owner,
+    )]
+    pub group: Account<'info, PromoGroup>,
+    pub mint: Account<'info, Mint>,
+    #[account(seeds = [PROMO_PREFIX.as_bytes(), mint.key().as_ref()], bump)]
+    pub promo: Account<'info, Promo>,
+    #[account(
+        init,
+        constraint = !data.levels.is_empty(),
+        constraint = data.levels.len() <= LOYALTY_LEVELS_CAPACITY as usize,
+        constraint = data.promo == promo.key(),
+        constraint = data.mint == mint.key(),
+        seeds = [LOYALTY_PREFIX.as_bytes(), mint.key().as_ref()], bump,
+        payer = payer,
+        space = Loyalty::LEN
+    )]
+    pub loyalty: Account<'info, Loyalty>,
+    pub memo_program: Program<'info, SplMemo>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts related to earning loyalty points.
+///
+/// Requires a signature from a member of the group specified in the owner field of the
+/// promo, who attests to the order amount. Points are minted at the `earn_rate` of the
+/// [Loyalty] to the associated token account of the token owner, which is created if it
+/// doesn't already exist. Points earned in total can't exceed the promo's `max_mint`.
+///
+/// No platform fees result from earning points.
+#[derive(Accounts, Clone)]
+pub struct EarnPoints<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        constraint = group.members.contains(&payer.key()),
+        constraint = group.key() == promo.owner,
+    )]
+    pub group: Account<'info, PromoGroup>,
+    /// CHECK: any wallet can earn points
+    pub token_owner: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+    /// CHECK: pubkey checked via seeds
+    #[account(seeds = [AUTHORITY_PREFIX.as_bytes()], bump)]
+    pub authority: UncheckedAccount<'info>,
+    #[account(seeds = [PROMO_PREFIX.as_bytes(), mint.key().as_ref()], bump)]
+    pub promo: Account<'info, Promo>,
+    #[account(mut, seeds = [LOYALTY_PREFIX.as_bytes(), mint.key().as_ref()], bump)]
+    pub loyalty: Account<'info, Loyalty>,
+    #[account(init_if_needed, payer = payer, associated_token::mint = mint, associated_token::authority = token_owner)]
+    pub token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, SplMemo>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts related to redeeming loyalty points.
+///
+/// Requires a signature from a member of the group specified in the owner field of the
+/// promo as well as from the token owner as the authority of the token account. Burns the
+/// number of points required by the reward level; the reward itself is applied as a discount
+/// by the merchant.
+///
+/// No platform fees result from redeeming points.
+#[derive(Accounts, Clone)]
+pub struct RedeemPoints<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        constraint = group.members.contains(&payer.key()),
+        constraint = group.key() == promo.owner,
+    )]
+    pub group: Account<'info, PromoGroup>,
+    pub token_owner: Signer<'info>,
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+    #[account(seeds = [PROMO_PREFIX.as_bytes(), mint.key().as_ref()], bump)]
+    pub promo: Account<'info, Promo>,
+    #[account(mut, seeds = [LOYALTY_PREFIX.as_bytes(), mint.key().as_ref()], bump)]
+    pub loyalty: Account<'info, Loyalty>,
+    #[account(mut,
+        constraint = token_owner.key() == token_account.owner,
+        constraint = mint.key() == token_account.mint
+    )]
+    pub token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, SplMemo>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts related to creating or updating a [ReferralReward].
+///
+/// Only the owner of the group that owns the promo can set the reward, which must be a promo
+/// owned by the same group so that its tokens can be minted by the program authority.
+#[derive(Accounts)]
+pub struct CreateReferralReward<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        constraint = payer.key() == group.owner,
+        constraint = group.key() == promo.owner,
+        constraint = group.key() == reward_promo.owner,
+    )]
+    pub group: Account<'info, PromoGroup>,
+    pub mint: Account<'info, Mint>,
+    #[account(seeds = [PROMO_PREFIX.as_bytes(), mint.key().as_ref()], bump)]
+    pub promo: Account<'info, Promo>,
+    pub reward_mint: Account<'info, Mint>,
+    #[account(seeds = [PROMO_PREFIX.as_bytes(), reward_mint.key().as_ref()], bump)]
+    pub reward_promo: Account<'info, Promo>,
+    #[account(
+        init_if_needed,
+        seeds = [REFERRAL_REWARD_PREFIX.as_bytes(), mint.key().as_ref()], bump,
+        payer = payer,
+        space = ReferralReward::LEN
+    )]
+    pub referral_reward: Account<'info, ReferralReward>,
+    pub memo_program: Program<'info, SplMemo>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts related to minting a promo token to a referred customer.
+///
+/// Same as [MintPromoToken] except that a [Referral] is recorded for the token owner if one
+/// does not already exist. Can only be used for promos with a [ReferralReward]. Token owners
+/// can't refer themselves.
+#[derive(Accounts, Clone)]
+#[instruction(referrer: Pubkey)]
+pub struct MintReferredPromoToken<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut,
+        constraint = group.members.contains(&payer.key()),
+        constraint = group.key() == promo.owner,
+    )]
+    pub group: Account<'info, PromoGroup>,
+    #[account(mut, constraint = token_owner.key() != referrer)]
+    pub token_owner: Signer<'info>,
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+    /// CHECK: pubkey checked via seeds
+    #[account(seeds = [AUTHORITY_PREFIX.as_bytes()], bump)]
+    pub authority: UncheckedAccount<'info>,
+    #[account(mut, seeds = [PROMO_PREFIX.as_bytes(), mint.key().as_ref()], bump)]
+    pub promo: Account<'info, Promo>,
+    /// CHECK: pubkey checked via seeds, must not be initialized
+    #[account(
+        seeds = [LOYALTY_PREFIX.as_bytes(), mint.key().as_ref()], bump,
+        constraint = loyalty.data_is_empty() @ error::ProgramError::LoyaltyPointsEarnedOnly,
+    )]
+    pub loyalty: UncheckedAccount<'info>,
+    #[account(seeds = [REFERRAL_REWARD_PREFIX.as_bytes(), mint.key().as_ref()], bump)]
+    pub referral_reward: Box<Account<'info, ReferralReward>>,
+    #[account(
+        init_if_needed,
+        seeds = [REFERRAL_PREFIX.as_bytes(), mint.key().as_ref(), token_owner.key().as_ref()], bump,
+        payer = payer,
+        space = Referral::LEN
+    )]
+    pub referral: Box<Account<'info, Referral>>,
+    #[account(init_if_needed, payer = payer, associated_token::mint = mint, associated_token::authority = token_owner)]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub memo_program: Program<'info, SplMemo>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts related to burning a delegated promo token minted to a referred customer.
+///
+/// Same as [BurnDelegatedPromoToken] and additionally mints a token of the reward promo set in
+/// the [ReferralReward] to the referrer recorded in the [Referral] of the token owner, creating
+/// the referrer's token account if needed. Each referral converts only once.
+#[derive(Accounts, Clone)]
+pub struct BurnReferredPromoToken<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut,
+        constraint = group.members.contains(&payer.key()),
+        constraint = group.key() == promo.owner,
+        constraint = group.key() == reward_promo.owner,
+    )]
+    pub group: Account<'info, PromoGroup>,
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+    /// CHECK: pubkey checked via seeds
+    #[account(seeds = [AUTHORITY_PREFIX.as_bytes()], bump)]
+    pub authority: UncheckedAccount<'info>,
+    #[account(mut, seeds = [PROMO_PREFIX.as_bytes(), mint.key().as_ref()], bump)]
+    pub promo: Box<Account<'info, Promo>>,
+    /// CHECK: pubkey checked via constraint
+    #[account(mut, constraint = platform.key() == admin_settings.platform)]
+    pub platform: UncheckedAccount<'info>,
+    #[account(seeds = [ADMIN_PREFIX.as_bytes()], bump)]
+    pub admin_settings: Box<Account<'info, AdminSettings>>,
+    #[account(mut,
+        constraint = token_account.mint == mint.key(),
+        constraint = token_account.delegate.unwrap() == payer.key(),
+        constraint = token_account.delegated_amount > 0,
+    )]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [REFERRAL_PREFIX.as_bytes(), mint.key().as_ref(), token_account.owner.as_ref()], bump,
+    )]
+    pub referral: Box<Account<'info, Referral>>,
+    #[account(seeds = [REFERRAL_REWARD_PREFIX.as_bytes(), mint.key().as_ref()], bump)]
+    pub referral_reward: Box<Account<'info, ReferralReward>>,
+    /// CHECK: pubkey checked via constraint
+    #[account(constraint = referrer.key() == referral.referrer)]
+    pub referrer: UncheckedAccount<'info>,
+    #[account(mut, constraint = reward_mint.key() == referral_reward.reward_mint)]
+    pub reward_mint: Box<Account<'info, Mint>>,
+    #[account(mut, seeds = [PROMO_PREFIX.as_bytes(), reward_mint.key().as_ref()], bump)]
+    pub reward_promo: Box<Account<'info, Promo>>,
+    #[account(init_if_needed, payer = payer, associated_token::mint = reward_mint, associated_token::authority = referrer)]
+    pub referrer_token_account: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub memo_program: Program<'info, SplMemo>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts related to creating or updating a [SponsorshipAllowance].
+///
+/// Only the owner of the group can allow lamports to be settled from the group to the
+/// platform. Setting `max_lamports` to the lamports already settled stops further settlement.
+///
+/// Program derived address allows only one allowance to exist per group.
+#[derive(Accounts)]
+pub struct CreateSponsorshipAllowance<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(constraint = payer.key() == group.owner)]
+    pub group: Account<'info, PromoGroup>,
+    #[account(
+        init_if_needed,
+        seeds = [SPONSORSHIP_ALLOWANCE_PREFIX.as_bytes(), group.key().as_ref()], bump,
+        payer = payer,
+        space = SponsorshipAllowance::LEN
+    )]
+    pub sponsorship_allowance: Account<'info, SponsorshipAllowance>,
+    pub memo_program: Program<'info, SplMemo>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts related to settling sponsored transactions.
+///
+/// Requires a signature from a member of the group, the platform signer for groups that have
+/// added it as a member to pay the network fees of their transactions. Lamports can only be
+/// transferred to the platform account set in [AdminSettings], up to what is left of the
+/// group's [SponsorshipAllowance], and the group keeps enough to stay rent exempt.
+#[derive(Accounts, Clone)]
+pub struct SettleSponsorship<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, constraint = group.members.contains(&payer.key()))]
+    pub group: Account<'info, PromoGroup>,
+    #[account(
+        mut,
+        seeds = [SPONSORSHIP_ALLOWANCE_PREFIX.as_bytes(), group.key().as_ref()], bump,
+        constraint = sponsorship_allowance.group == group.key(),
+    )]
+    pub sponsorship_allowance: Account<'info, SponsorshipAllowance>,
+    /// CHECK: checked in contraints
+    #[account(mut, constraint = platform.key() == admin_settings.platform)]
+    pub platform: UncheckedAccount<'info>,
+    #[account(seeds = [ADMIN_PREFIX.as_bytes()], bump)]
+    pub admin_settings: Box<Account<'info, AdminSettings>>,
+    pub memo_program: Program<'info, SplMemo>,
+}
+
 /// Account related to creation of non-fungibles - not yet implemented.
 #[derive(Accounts, Clone)]
 pub struct CreateNonFungible<'info> {
@@ -431,6 +1040,132 @@ impl<'info> From<CreateNonFungible<'info>> for CreateMetaData<'info> {
     }
 }
 
+/// Accounts related to creating a promo with a Token-2022 mint.
+///
+/// Same as [CreatePromo] except that the mint is created under the Token-2022 program with the
+/// extensions specified in [MintExtensions]. The mint's key is generated by the client, which
+/// signs for it.
+///
+/// Token-2022 mints can't have Metaplex metadata, so name, symbol and uri are stored in a
+/// [PromoMint] account instead, which is also set as the `metadata` of the [Promo]. If the
+/// metadata pointer extension is enabled the mint points to the [PromoMint] account.
+///
+/// The permanent delegate extension is required and set to the program authority so that group
+/// members can burn tokens when they are redeemed without the token owner first delegating them.
+/// Tokens of mints with the non-transferable extension can't be transferred by their owners.
+#[derive(Accounts, Clone)]
+#[instruction(promo_data: Promo, metadata_data: DataV2, extensions: MintExtensions)]
+pub struct CreatePromo2022<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut,
+        constraint = payer.key() == group.owner,
+        constraint = group.key() == promo_data.owner,
+    )]
+    pub group: Account<'info, PromoGroup>,
+    #[account(mut)]
+    pub mint: Signer<'info>,
+    #[account(init, payer = payer,
+        seeds = [PROMO_MINT_PREFIX.as_bytes(), mint.key().as_ref()], bump,
+        space = PromoMint::LEN)]
+    pub promo_mint: Box<Account<'info, PromoMint>>,
+    /// CHECK: pubkey checked via seeds
+    #[account(seeds = [AUTHORITY_PREFIX.as_bytes()], bump)]
+    pub authority: UncheckedAccount<'info>,
+    #[account(init, payer = payer,
+        seeds = [PROMO_PREFIX.as_bytes(), mint.key().as_ref()], bump,
+        constraint = promo_data.owner == group.key(),
+        space = Promo::LEN)]
+    pub promo: Account<'info, Promo>,
+    /// CHECK: pubkey checked via constraint
+    #[account(mut,
+        constraint = platform.key() == admin_settings.platform
+    )]
+    pub platform: UncheckedAccount<'info>,
+    #[account(seeds = [ADMIN_PREFIX.as_bytes()], bump)]
+    pub admin_settings: Box<Account<'info, AdminSettings>>,
+    pub token_program: Program<'info, Token2022>,
+    pub memo_program: Program<'info, SplMemo>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts related to minting a promo token of a Token-2022 mint.
+///
+/// Same as [MintPromoToken] except that the token account is the recipient's Token-2022
+/// associated token account, created if it does not already exist.
+#[derive(Accounts, Clone)]
+pub struct MintPromoToken2022<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut,
+        constraint = group.members.contains(&payer.key()),
+        constraint = group.key() == promo.owner,
+    )]
+    pub group: Account<'info, PromoGroup>,
+    #[account(mut)]
+    pub token_owner: Signer<'info>,
+    /// CHECK: owner checked via constraint, mint checked via token program instruction
+    #[account(mut, owner = token_2022::ID)]
+    pub mint: UncheckedAccount<'info>,
+    /// CHECK: pubkey checked via seeds
+    #[account(seeds = [AUTHORITY_PREFIX.as_bytes()], bump)]
+    pub authority: UncheckedAccount<'info>,
+    #[account(mut, seeds = [PROMO_PREFIX.as_bytes(), mint.key().as_ref()], bump)]
+    pub promo: Account<'info, Promo>,
+    /// CHECK: pubkey checked via constraint, created via cpi if needed
+    #[account(mut,
+        address = token_2022::find_associated_token_address(&token_owner.key(), &mint.key())
+    )]
+    pub token_account: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token2022>,
+    pub memo_program: Program<'info, SplMemo>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts related to burning a promo token of a Token-2022 mint.
+///
+/// Same as [BurnDelegatedPromoToken] except that the token is burned by the program authority
+/// as the mint's permanent delegate, so the token owner does not need to delegate the token
+/// to the group member redeeming it first.
+///
+/// The fee specified in the `burn_promo_token_lamports` property of the [AdminSettings] account
+/// is transferred from the payer to the account specified in the `platform` property of the
+/// [AdminSettings] account.
+#[derive(Accounts, Clone)]
+pub struct BurnPromoToken2022<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut,
+        constraint = group.members.contains(&payer.key()),
+        constraint = group.key() == promo.owner,
+    )]
+    pub group: Account<'info, PromoGroup>,
+    /// CHECK: owner checked via constraint, mint checked via token program instruction
+    #[account(mut, owner = token_2022::ID)]
+    pub mint: UncheckedAccount<'info>,
+    /// CHECK: pubkey checked via seeds
+    #[account(seeds = [AUTHORITY_PREFIX.as_bytes()], bump)]
+    pub authority: UncheckedAccount<'info>,
+    #[account(mut, seeds = [PROMO_PREFIX.as_bytes(), mint.key().as_ref()], bump)]
+    pub promo: Account<'info, Promo>,
+    /// CHECK: pubkey checked via constraint
+    #[account(mut, constraint = platform.key() == admin_settings.platform)]
+    pub platform: UncheckedAccount<'info>,
+    #[account(seeds = [ADMIN_PREFIX.as_bytes()], bump)]
+    pub admin_settings: Account<'info, AdminSettings>,
+    /// CHECK: owner checked via constraint, mint and amount checked in processor
+    #[account(mut, owner = token_2022::ID)]
+    pub token_account: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub memo_program: Program<'info, SplMemo>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts, Clone)]
 pub struct TransferSol<'info> {
     /// CHECK: unchecked
@@ -459,6 +1194,15 @@ impl anchor_lang::Id for SplMemo {
     }
 }
 
+#[derive(Clone)]
+pub struct Token2022;
+
+impl anchor_lang::Id for Token2022 {
+    fn id() -> Pubkey {
+        token_2022::ID
+    }
+}
+
 #[derive(AnchorDeserialize, Clone, Debug)]
 pub struct Metadata(mpl_token_metadata::state::Metadata);
 
@@ -0,0 +1,92 @@
+use crate::utils::{create_memo, transfer_sol};
+use crate::{
+    error::ProgramError,
+    state::{Settlement, SettlementApproval},
+    BurnAcceptedPromoToken, TransferSol,
+};
+use anchor_lang::prelude::*;
+
+impl<'info> BurnAcceptedPromoToken<'info> {
+    pub fn process(&mut self, memo: Option<String>) -> Result<()> {
+        msg!("Burn accepted promo token");
+
+        // Check to see if burn_count is still below max_burn.
+        if let Some(max_burn) = self.promo.max_burn {
+            if self.promo.burn_count >= max_burn {
+                return Err(ProgramError::MaxBurnExceeded.into());
+            }
+        }
+
+        if self.admin_settings.burn_promo_token_lamports > 0 {
+            transfer_sol(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    TransferSol {
+                        payer: self.payer.to_account_info(),
+                        to: self.platform.to_account_info(),
+                    },
+                ),
+                self.admin_settings.burn_promo_token_lamports,
+            )?;
+        }
+
+        // Both groups are owned by the program so lamports can be moved between them directly.
+        let amount = self.promo_acceptance.settlement_lamports;
+        if amount > 0 {
+            let (from, to) = match self.promo_acceptance.settlement {
+                Settlement::IssuerPaysRedeemer => (
+                    self.issuing_group.to_account_info(),
+                    self.group.to_account_info(),
+                ),
+                Settlement::RedeemerPaysIssuer => {
+                    // The redeeming group only pays up to what its owner approved.
+                    let approval =
+                        Account::<SettlementApproval>::try_from(&self.settlement_approval)
+                            .map_err(|_| ProgramError::SettlementNotApproved)?;
+                    if approval.promo != self.promo.key()
+                        || amount > approval.max_settlement_lamports
+                    {
+                        return Err(ProgramError::SettlementNotApproved.into());
+                    }
+                    (
+                        self.group.to_account_info(),
+                        self.issuing_group.to_account_info(),
+                    )
+                }
+            };
+
+            // The paying group needs to stay rent exempt.
+            let rent_exempt_lamports = Rent::get()?.minimum_balance(from.data_len());
+            if from.lamports().saturating_sub(rent_exempt_lamports) < amount {
+                return Err(ProgramError::InsufficientFunds.into());
+            }
+
+            let to_lamports = to
+                .lamports()
+                .checked_add(amount)
+                .ok_or(ProgramError::Overflow)?;
+            **from.try_borrow_mut_lamports()? -= amount;
+            **to.try_borrow_mut_lamports()? = to_lamports;
+        }
+
+        let burn_ctx = anchor_spl::token::Burn {
+            mint: self.mint.to_account_info(),
+            from: self.token_account.to_account_info(),
+            authority: self.payer.to_account_info(),
+        };
+
+        anchor_spl::token::burn(
+            CpiContext::new(self.token_program.to_account_info(), burn_ctx),
+            1,
+        )?;
+
+        if let Some(memo) = memo {
+            let account_infos = vec![self.payer.to_account_info()];
+            create_memo(memo.to_string(), account_infos)?;
+        }
+
+        self.promo.burn_count += 1;
+
+        Ok(())
+    }
+}
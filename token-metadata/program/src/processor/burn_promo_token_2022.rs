@@ -0,0 +1,62 @@
+use crate::utils::{create_memo, transfer_sol};
+use crate::{error::ProgramError, token_2022, BurnPromoToken2022, TransferSol};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke_signed;
+
+impl<'info> BurnPromoToken2022<'info> {
+    pub fn process(&mut self, memo: Option<String>, authority_seeds: [&[u8]; 2]) -> Result<()> {
+        msg!("Burn promo token 2022");
+
+        // Check to see if burn_count is still below max_burn.
+        if let Some(max_burn) = self.promo.max_burn {
+            if self.promo.burn_count >= max_burn {
+                return Err(ProgramError::MaxBurnExceeded.into());
+            }
+        }
+
+        let token_account =
+            token_2022::unpack_token_account(&self.token_account.try_borrow_data()?)?;
+        if token_account.mint != self.mint.key() || token_account.amount == 0 {
+            return Err(ProgramError::ConstraintNotMet.into());
+        }
+
+        if self.admin_settings.burn_promo_token_lamports > 0 {
+            transfer_sol(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    TransferSol {
+                        payer: self.payer.to_account_info(),
+                        to: self.platform.to_account_info(),
+                    },
+                ),
+                self.admin_settings.burn_promo_token_lamports,
+            )?;
+        }
+
+        // Program authority is the permanent delegate of the mint.
+        invoke_signed(
+            &token_2022::burn(
+                &self.token_account.key(),
+                &self.mint.key(),
+                &self.authority.key(),
+                1,
+            ),
+            &[
+                self.token_account.to_account_info(),
+                self.mint.to_account_info(),
+                self.authority.to_account_info(),
+                self.token_program.to_account_info(),
+            ],
+            &[&authority_seeds],
+        )?;
+
+        if let Some(memo) = memo {
+            let account_infos = vec![self.payer.to_account_info()];
+            create_memo(memo.to_string(), account_infos)?;
+        }
+
+        self.promo.burn_count += 1;
+
+        Ok(())
+    }
+}
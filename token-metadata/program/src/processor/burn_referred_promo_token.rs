@@ -0,0 +1,82 @@
+use crate::utils::{create_memo, transfer_sol};
+use crate::{error::ProgramError, BurnReferredPromoToken, TransferSol};
+use anchor_lang::prelude::*;
+
+impl<'info> BurnReferredPromoToken<'info> {
+    pub fn process(&mut self, memo: Option<String>, authority_seeds: [&[u8]; 2]) -> Result<()> {
+        msg!("Burn referred promo token");
+
+        // Check to see if burn_count is still below max_burn.
+        if let Some(max_burn) = self.promo.max_burn {
+            if self.promo.burn_count >= max_burn {
+                return Err(ProgramError::MaxBurnExceeded.into());
+            }
+        }
+
+        if self.referral.converted {
+            return Err(ProgramError::ReferralAlreadyConverted.into());
+        }
+
+        if self.admin_settings.burn_promo_token_lamports > 0 {
+            transfer_sol(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    TransferSol {
+                        payer: self.payer.to_account_info(),
+                        to: self.platform.to_account_info(),
+                    },
+                ),
+                self.admin_settings.burn_promo_token_lamports,
+            )?;
+        }
+
+        let burn_ctx = anchor_spl::token::Burn {
+            mint: self.mint.to_account_info(),
+            from: self.token_account.to_account_info(),
+            authority: self.payer.to_account_info(),
+        };
+
+        anchor_spl::token::burn(
+            CpiContext::new(self.token_program.to_account_info(), burn_ctx),
+            1,
+        )?;
+
+        // The customer's redemption shouldn't fail because the reward promo has run out, so
+        // the referral converts without a reward in that case.
+        let reward_available = match self.reward_promo.max_mint {
+            Some(max_mint) => self.reward_promo.mint_count < max_mint,
+            None => true,
+        };
+
+        if reward_available {
+            let mint_to_ctx = anchor_spl::token::MintTo {
+                mint: self.reward_mint.to_account_info(),
+                to: self.referrer_token_account.to_account_info(),
+                authority: self.authority.to_account_info(),
+            };
+
+            anchor_spl::token::mint_to(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    mint_to_ctx,
+                    &[&authority_seeds],
+                ),
+                1,
+            )?;
+
+            self.reward_promo.mint_count += 1;
+        } else {
+            msg!("Referral reward max mints exceeded");
+        }
+
+        if let Some(memo) = memo {
+            let account_infos = vec![self.payer.to_account_info()];
+            create_memo(memo.to_string(), account_infos)?;
+        }
+
+        self.referral.converted = true;
+        self.promo.burn_count += 1;
+
+        Ok(())
+    }
+}
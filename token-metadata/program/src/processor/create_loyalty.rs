@@ -0,0 +1,21 @@
+use crate::{state::Loyalty, utils::create_memo, CreateLoyalty};
+use anchor_lang::prelude::*;
+
+impl<'info> CreateLoyalty<'info> {
+    pub fn process(&mut self, data: Loyalty, memo: Option<String>) -> Result<()> {
+        msg!("Create loyalty");
+
+        *self.loyalty = Loyalty {
+            points_earned: 0,
+            points_redeemed: 0,
+            ..data
+        };
+
+        if let Some(memo) = memo {
+            let account_infos = vec![self.payer.to_account_info()];
+            create_memo(memo, account_infos)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{
+    error::ProgramError,
+    state::{DataV2, MintExtensions, Promo, PromoMint},
+    token_2022,
+    utils::create_memo,
+    CreatePromo2022,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{program::invoke, system_instruction};
+use mpl_token_metadata::state::{MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH};
+
+impl<'info> CreatePromo2022<'info> {
+    pub fn process(
+        &mut self,
+        mut promo_data: Promo,
+        metadata_data: DataV2,
+        extensions: MintExtensions,
+        memo: Option<String>,
+    ) -> Result<()> {
+        msg!("Create promo 2022");
+
+        // Tokens are burned by the program authority as permanent delegate when redeemed.
+        if !extensions.permanent_delegate {
+            return Err(ProgramError::PermanentDelegateRequired.into());
+        }
+
+        if metadata_data.name.len() > MAX_NAME_LENGTH
+            || metadata_data.symbol.len() > MAX_SYMBOL_LENGTH
+            || metadata_data.uri.len() > MAX_URI_LENGTH
+        {
+            return Err(ProgramError::MetadataTooLong.into());
+        }
+
+        // Error if not enough lamports
+        if self.group.to_account_info().lamports.borrow().clone()
+            < self.admin_settings.create_promo_lamports
+        {
+            return Err(ProgramError::InsufficientFunds.into());
+        }
+
+        if self.admin_settings.create_promo_lamports > 0 {
+            let group = self.group.to_account_info();
+            let platform = self.platform.to_account_info();
+            let amount = self.admin_settings.create_promo_lamports;
+
+            **group.try_borrow_mut_lamports()? = group.lamports().checked_sub(amount).unwrap();
+            **platform.try_borrow_mut_lamports()? =
+                platform.lamports().checked_add(amount).unwrap();
+        }
+
+        let mint = self.mint.key();
+        let authority = self.authority.key();
+        let space = token_2022::get_mint_len(&extensions);
+
+        invoke(
+            &system_instruction::create_account(
+                &self.payer.key(),
+                &mint,
+                self.rent.minimum_balance(space),
+                space as u64,
+                &token_2022::ID,
+            ),
+            &[
+                self.payer.to_account_info(),
+                self.mint.to_account_info(),
+                self.system_program.to_account_info(),
+            ],
+        )?;
+
+        // Extensions have to be initialized before the mint.
+        let mut instructions = Vec::new();
+        if extensions.non_transferable {
+            instructions.push(token_2022::initialize_non_transferable_mint(&mint));
+        }
+        instructions.push(token_2022::initialize_permanent_delegate(&mint, &authority));
+        if extensions.metadata_pointer {
+            instructions.push(token_2022::initialize_metadata_pointer(
+                &mint,
+                &authority,
+                &self.promo_mint.key(),
+            ));
+        }
+        instructions.push(token_2022::initialize_mint2(
+            &mint,
+            &authority,
+            Some(&authority),
+            0,
+        ));
+
+        let account_infos = [
+            self.mint.to_account_info(),
+            self.token_program.to_account_info(),
+        ];
+        for ix in instructions.iter() {
+            invoke(ix, &account_infos)?;
+        }
+
+        if let Some(memo) = memo {
+            let account_infos = vec![self.payer.to_account_info()];
+            create_memo(memo, account_infos)?;
+        }
+
+        **self.promo_mint = PromoMint {
+            promo: self.promo.key(),
+            mint,
+            extensions,
+            name: metadata_data.name,
+            symbol: metadata_data.symbol,
+            uri: metadata_data.uri,
+        };
+
+        promo_data.metadata = self.promo_mint.key();
+        *self.promo = promo_data;
+        Ok(())
+    }
+}
@@ -0,0 +1,17 @@
+use crate::{state::PromoAcceptance, utils::create_memo, CreatePromoAcceptance};
+use anchor_lang::prelude::*;
+
+impl<'info> CreatePromoAcceptance<'info> {
+    pub fn process(&mut self, data: PromoAcceptance, memo: Option<String>) -> Result<()> {
+        msg!("Create promo acceptance");
+
+        *self.promo_acceptance = data;
+
+        if let Some(memo) = memo {
+            let account_infos = vec![self.payer.to_account_info()];
+            create_memo(memo, account_infos)?;
+        }
+
+        Ok(())
+    }
+}
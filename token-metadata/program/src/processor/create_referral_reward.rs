@@ -0,0 +1,18 @@
+use crate::{utils::create_memo, CreateReferralReward};
+use anchor_lang::prelude::*;
+
+impl<'info> CreateReferralReward<'info> {
+    pub fn process(&mut self, memo: Option<String>) -> Result<()> {
+        msg!("Create referral reward");
+
+        self.referral_reward.promo = self.promo.key();
+        self.referral_reward.reward_mint = self.reward_mint.key();
+
+        if let Some(memo) = memo {
+            let account_infos = vec![self.payer.to_account_info()];
+            create_memo(memo, account_infos)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,17 @@
+use crate::{state::SettlementApproval, utils::create_memo, CreateSettlementApproval};
+use anchor_lang::prelude::*;
+
+impl<'info> CreateSettlementApproval<'info> {
+    pub fn process(&mut self, data: SettlementApproval, memo: Option<String>) -> Result<()> {
+        msg!("Create settlement approval");
+
+        *self.settlement_approval = data;
+
+        if let Some(memo) = memo {
+            let account_infos = vec![self.payer.to_account_info()];
+            create_memo(memo, account_infos)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,19 @@
+use crate::{utils::create_memo, CreateSponsorshipAllowance};
+use anchor_lang::prelude::*;
+
+impl<'info> CreateSponsorshipAllowance<'info> {
+    pub fn process(&mut self, max_lamports: u64, memo: Option<String>) -> Result<()> {
+        msg!("Create sponsorship allowance");
+
+        // Lamports already settled keep counting against the new maximum.
+        self.sponsorship_allowance.group = self.group.key();
+        self.sponsorship_allowance.max_lamports = max_lamports;
+
+        if let Some(memo) = memo {
+            let account_infos = vec![self.payer.to_account_info()];
+            create_memo(memo, account_infos)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+use crate::utils::create_memo;
+use crate::DelegateAcceptedPromoToken;
+use anchor_lang::prelude::*;
+
+impl<'info> DelegateAcceptedPromoToken<'info> {
+    pub fn process(&mut self, memo: Option<String>) -> Result<()> {
+        msg!("Delegate accepted promo token");
+
+        let delegate_ctx = anchor_spl::token::Approve {
+            to: self.token_account.to_account_info(),
+            delegate: self.delegate.to_account_info(),
+            authority: self.token_owner.to_account_info(),
+        };
+
+        anchor_spl::token::approve(
+            CpiContext::new(self.token_program.to_account_info(), delegate_ctx),
+            1,
+        )?;
+
+        if let Some(memo) = memo {
+            let account_infos = vec![
+                self.payer.to_account_info(),
+                self.token_owner.to_account_info(),
+            ];
+            create_memo(memo.to_string(), account_infos)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use crate::utils::create_memo;
+use crate::{error::ProgramError, EarnPoints};
+use anchor_lang::prelude::*;
+
+impl<'info> EarnPoints<'info> {
+    pub fn process(
+        &mut self,
+        order_amount: u64,
+        memo: Option<String>,
+        authority_seeds: [&[u8]; 2],
+    ) -> Result<()> {
+        msg!("Earn points");
+
+        let points = order_amount
+            .checked_mul(self.loyalty.earn_rate as u64)
+            .ok_or(ProgramError::Overflow)?;
+        let points_earned = self
+            .loyalty
+            .points_earned
+            .checked_add(points)
+            .ok_or(ProgramError::Overflow)?;
+
+        // Points count towards the promo's max_mint.
+        if let Some(max_mint) = self.promo.max_mint {
+            if points_earned > max_mint as u64 {
+                return Err(ProgramError::MaxMintExceeded.into());
+            }
+        }
+
+        if points > 0 {
+            let mint_to_ctx = anchor_spl::token::MintTo {
+                mint: self.mint.to_account_info(),
+                to: self.token_account.to_account_info(),
+                authority: self.authority.to_account_info(),
+            };
+
+            anchor_spl::token::mint_to(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    mint_to_ctx,
+                    &[&authority_seeds],
+                ),
+                points,
+            )?;
+        }
+
+        if let Some(memo) = memo {
+            let account_infos = vec![self.payer.to_account_info()];
+            create_memo(memo.to_string(), account_infos)?;
+        }
+
+        self.loyalty.points_earned = points_earned;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use crate::utils::create_memo;
+use crate::{error::ProgramError, token_2022, MintPromoToken2022};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+
+impl<'info> MintPromoToken2022<'info> {
+    pub fn process(&mut self, memo: Option<String>, authority_seeds: [&[u8]; 2]) -> Result<()> {
+        msg!("Mint promo token 2022");
+
+        // Check to see if mint_count is still below max_mint.
+        if let Some(max_mint) = self.promo.max_mint {
+            if self.promo.mint_count >= max_mint {
+                return Err(ProgramError::MaxMintExceeded.into());
+            }
+        }
+
+        invoke(
+            &token_2022::create_associated_token_account_idempotent(
+                &self.payer.key(),
+                &self.token_owner.key(),
+                &self.mint.key(),
+            ),
+            &[
+                self.payer.to_account_info(),
+                self.token_account.to_account_info(),
+                self.token_owner.to_account_info(),
+                self.mint.to_account_info(),
+                self.system_program.to_account_info(),
+                self.token_program.to_account_info(),
+                self.associated_token_program.to_account_info(),
+            ],
+        )?;
+
+        invoke_signed(
+            &token_2022::mint_to(
+                &self.mint.key(),
+                &self.token_account.key(),
+                &self.authority.key(),
+                1,
+            ),
+            &[
+                self.mint.to_account_info(),
+                self.token_account.to_account_info(),
+                self.authority.to_account_info(),
+                self.token_program.to_account_info(),
+            ],
+            &[&authority_seeds],
+        )?;
+
+        if let Some(memo) = memo {
+            let account_infos = vec![self.payer.to_account_info()];
+            create_memo(memo.to_string(), account_infos)?;
+        }
+
+        self.promo.mint_count += 1;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use crate::utils::create_memo;
+use crate::{error::ProgramError, MintReferredPromoToken};
+use anchor_lang::prelude::*;
+
+impl<'info> MintReferredPromoToken<'info> {
+    pub fn process(
+        &mut self,
+        referrer: Pubkey,
+        memo: Option<String>,
+        authority_seeds: [&[u8]; 2],
+    ) -> Result<()> {
+        msg!("Mint referred promo token");
+
+        // Check to see if mint_count is still below max_mint.
+        if let Some(max_mint) = self.promo.max_mint {
+            if self.promo.mint_count >= max_mint {
+                return Err(ProgramError::MaxMintExceeded.into());
+            }
+        }
+
+        // Keep the first referrer if the token owner has already been referred.
+        if self.referral.promo == Pubkey::default() {
+            self.referral.promo = self.promo.key();
+            self.referral.mint = self.mint.key();
+            self.referral.token_owner = self.token_owner.key();
+            self.referral.referrer = referrer;
+            self.referral.converted = false;
+        }
+
+        let mint_to_ctx = anchor_spl::token::MintTo {
+            mint: self.mint.to_account_info(),
+            to: self.token_account.to_account_info(),
+            authority: self.authority.to_account_info(),
+        };
+
+        anchor_spl::token::mint_to(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                mint_to_ctx,
+                &[&authority_seeds],
+            ),
+            1,
+        )?;
+
+        if let Some(memo) = memo {
+            let account_infos = vec![self.payer.to_account_info()];
+            create_memo(memo.to_string(), account_infos)?;
+        }
+
+        self.promo.mint_count += 1;
+
+        Ok(())
+    }
+}
@@ -1,8 +1,23 @@
+pub mod burn_accepted_promo_token;
 pub mod burn_delegated_promo_token;
+pub mod burn_promo_token_2022;
+pub mod burn_referred_promo_token;
 pub mod create_admin_settings;
+pub mod create_loyalty;
 pub mod create_non_fungible;
 pub mod create_promo;
+pub mod create_promo_2022;
+pub mod create_promo_acceptance;
 pub mod create_promo_group;
+pub mod create_referral_reward;
+pub mod create_settlement_approval;
+pub mod create_sponsorship_allowance;
+pub mod delegate_accepted_promo_token;
 pub mod delegate_promo_token;
+pub mod earn_points;
 pub mod mint_promo_token;
+pub mod mint_promo_token_2022;
+pub mod mint_referred_promo_token;
+pub mod redeem_points;
+pub mod settle_sponsorship;
 pub mod transfer_cpi;
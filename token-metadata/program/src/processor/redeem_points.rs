@@ -0,0 +1,46 @@
+use crate::utils::create_memo;
+use crate::{error::ProgramError, RedeemPoints};
+use anchor_lang::prelude::*;
+
+impl<'info> RedeemPoints<'info> {
+    pub fn process(&mut self, level: u8, memo: Option<String>) -> Result<()> {
+        msg!("Redeem points");
+
+        let reward_level = *self
+            .loyalty
+            .levels
+            .get(level as usize)
+            .ok_or(ProgramError::RewardLevelNotFound)?;
+
+        if self.token_account.amount < reward_level.points {
+            return Err(ProgramError::InsufficientPoints.into());
+        }
+
+        let burn_ctx = anchor_spl::token::Burn {
+            mint: self.mint.to_account_info(),
+            from: self.token_account.to_account_info(),
+            authority: self.token_owner.to_account_info(),
+        };
+
+        anchor_spl::token::burn(
+            CpiContext::new(self.token_program.to_account_info(), burn_ctx),
+            reward_level.points,
+        )?;
+
+        if let Some(memo) = memo {
+            let account_infos = vec![
+                self.payer.to_account_info(),
+                self.token_owner.to_account_info(),
+            ];
+            create_memo(memo.to_string(), account_infos)?;
+        }
+
+        self.loyalty.points_redeemed = self
+            .loyalty
+            .points_redeemed
+            .checked_add(reward_level.points)
+            .ok_or(ProgramError::Overflow)?;
+
+        Ok(())
+    }
+}
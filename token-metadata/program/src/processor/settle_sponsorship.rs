@@ -0,0 +1,43 @@
+use crate::utils::create_memo;
+use crate::{error::ProgramError, SettleSponsorship};
+use anchor_lang::prelude::*;
+
+impl<'info> SettleSponsorship<'info> {
+    pub fn process(&mut self, lamports: u64, memo: Option<String>) -> Result<()> {
+        msg!("Settle sponsorship");
+
+        // Only what the group owner allowed can be settled.
+        let settled_lamports = self
+            .sponsorship_allowance
+            .settled_lamports
+            .checked_add(lamports)
+            .ok_or(ProgramError::Overflow)?;
+        if settled_lamports > self.sponsorship_allowance.max_lamports {
+            return Err(ProgramError::SponsorshipAllowanceExceeded.into());
+        }
+
+        // The group is owned by the program so lamports are moved directly rather than with
+        // a system program transfer, leaving enough for the group to stay rent exempt.
+        let group = self.group.to_account_info();
+        let rent_exempt_lamports = Rent::get()?.minimum_balance(group.data_len());
+        if group.lamports().saturating_sub(rent_exempt_lamports) < lamports {
+            return Err(ProgramError::InsufficientFunds.into());
+        }
+
+        let platform = self.platform.to_account_info();
+        let platform_lamports = platform
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(ProgramError::Overflow)?;
+        **group.try_borrow_mut_lamports()? -= lamports;
+        **platform.try_borrow_mut_lamports()? = platform_lamports;
+        self.sponsorship_allowance.settled_lamports = settled_lamports;
+
+        if let Some(memo) = memo {
+            let account_infos = vec![self.payer.to_account_info()];
+            create_memo(memo, account_infos)?;
+        }
+
+        Ok(())
+    }
+}
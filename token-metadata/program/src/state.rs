@@ -1,10 +1,11 @@
 use anchor_lang::prelude::*;
 use mpl_token_metadata::state::{
     Collection as CollectionMpl, Creator as CreatorMpl, DataV2 as DataV2Mpl,
-    UseMethod as UseMethodMpl, Uses as UsesMpl,
+    UseMethod as UseMethodMpl, Uses as UsesMpl, MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH,
+    MAX_URI_LENGTH,
 };
 
-use crate::utils::MEMBERS_CAPACITY;
+use crate::utils::{ACCEPTANCE_CAPACITY, LOYALTY_LEVELS_CAPACITY, MEMBERS_CAPACITY};
 
 //==============================
 // AdminSettings
@@ -43,6 +44,28 @@ impl PromoGroup {
     pub const LEN: usize = 8 + 32 + 32 + 1 + 32 * MEMBERS_CAPACITY as usize;
 }
 
+//==============================
+// SponsorshipAllowance
+//==============================
+
+// Lamports the owner of a group allows to be settled to the platform from the group's account
+// for sponsored transactions. `settled_lamports` counts up with each settlement, which can't
+// take it past `max_lamports`.
+#[account]
+#[derive(PartialEq, Debug)]
+pub struct SponsorshipAllowance {
+    pub group: Pubkey,
+    pub max_lamports: u64,
+    pub settled_lamports: u64,
+}
+
+impl SponsorshipAllowance {
+    pub const LEN: usize = 8
+    + 32        // group
+    + 8         // max_lamports
+    + 8; // settled_lamports
+}
+
 //==============================
 // Promo
 //==============================
@@ -72,6 +95,184 @@ impl Promo {
     + 1 + 4; // max_redeem
 }
 
+//==============================
+// PromoAcceptance
+//==============================
+
+// Lets members of groups other than the one that owns a promo act as delegates and
+// burners of its tokens, e.g. a mall issuing one coupon redeemable at member stores.
+#[account]
+#[derive(PartialEq, Debug)]
+pub struct PromoAcceptance {
+    pub promo: Pubkey,
+    pub owner: Pubkey,
+    pub groups: Vec<Pubkey>,
+    pub settlement: Settlement,
+    pub settlement_lamports: u64,
+}
+
+impl PromoAcceptance {
+    pub const LEN: usize = 8
+    + 32                                        // promo
+    + 32                                        // owner
+    + 4 + 32 * ACCEPTANCE_CAPACITY as usize     // groups
+    + 1                                         // settlement
+    + 8; // settlement_lamports
+}
+
+/// Direction of the lamports settled between the issuing group and the redeeming group each
+/// time a token is burned by a member of an accepting group.
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Debug, Clone, Copy)]
+pub enum Settlement {
+    /// Issuing group reimburses the redeeming group for honoring its promo.
+    IssuerPaysRedeemer,
+    /// Redeeming group pays the issuing group for customers it brought in.
+    RedeemerPaysIssuer,
+}
+
+//==============================
+// SettlementApproval
+//==============================
+
+// Agreement of a group accepting another group's promo to pay the issuing group up to
+// `max_settlement_lamports` each time one of its members burns a token of the promo. The
+// issuing group sets the settlement, so what the redeeming group pays is capped by this.
+#[account]
+#[derive(PartialEq, Debug)]
+pub struct SettlementApproval {
+    pub promo: Pubkey,
+    pub group: Pubkey,
+    pub max_settlement_lamports: u64,
+}
+
+impl SettlementApproval {
+    pub const LEN: usize = 8
+    + 32        // promo
+    + 32        // group
+    + 8; // max_settlement_lamports
+}
+
+//==============================
+// Loyalty
+//==============================
+
+// Loyalty promos use the promo mint for points. Points are minted at `earn_rate` per 0.01
+// currency spent and burned in exchange for the reward of one of the `levels`.
+#[account]
+#[derive(PartialEq, Debug)]
+pub struct Loyalty {
+    pub promo: Pubkey,
+    pub mint: Pubkey,
+    pub earn_rate: u32,
+    pub levels: Vec<RewardLevel>,
+    pub points_earned: u64,
+    pub points_redeemed: u64,
+}
+
+impl Loyalty {
+    pub const LEN: usize = 8
+    + 32                                                // promo
+    + 32                                                // mint
+    + 4                                                 // earn_rate
+    + 4 + RewardLevel::LEN * LOYALTY_LEVELS_CAPACITY as usize   // levels
+    + 8                                                 // points_earned
+    + 8; // points_redeemed
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Debug, Clone, Copy)]
+pub struct RewardLevel {
+    /// Number of points required to be eligible for the reward
+    pub points: u64,
+    /// Discount in 0.01 currency available when the points are redeemed
+    pub reward: u64,
+}
+
+impl RewardLevel {
+    pub const LEN: usize = 8 + 8;
+}
+
+//==============================
+// Referral
+//==============================
+
+// Set by the group that owns a promo to reward customers who refer others to it with a
+// token of another promo owned by the same group.
+#[account]
+#[derive(PartialEq, Debug)]
+pub struct ReferralReward {
+    pub promo: Pubkey,
+    pub reward_mint: Pubkey,
+}
+
+impl ReferralReward {
+    pub const LEN: usize = 8
+    + 32    // promo
+    + 32; // reward_mint
+}
+
+// Recorded when a referred customer mints a promo token, one per token owner and mint. The
+// first referrer is kept and is rewarded once, the first time the customer's token is burned.
+#[account]
+#[derive(PartialEq, Debug)]
+pub struct Referral {
+    pub promo: Pubkey,
+    pub mint: Pubkey,
+    pub token_owner: Pubkey,
+    pub referrer: Pubkey,
+    pub converted: bool,
+}
+
+impl Referral {
+    pub const LEN: usize = 8
+    + 32    // promo
+    + 32    // mint
+    + 32    // token_owner
+    + 32    // referrer
+    + 1; // converted
+}
+
+//==============================
+// PromoMint
+//==============================
+
+// Token-2022 mints can't have Metaplex metadata, so the name, symbol and uri of promos with
+// Token-2022 mints are kept here instead and the mint's metadata pointer points to this account.
+#[account]
+#[derive(PartialEq, Debug)]
+pub struct PromoMint {
+    pub promo: Pubkey,
+    pub mint: Pubkey,
+    pub extensions: MintExtensions,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+impl PromoMint {
+    pub const LEN: usize = 8
+    + 32                        // promo
+    + 32                        // mint
+    + MintExtensions::LEN       // extensions
+    + 4 + MAX_NAME_LENGTH       // name
+    + 4 + MAX_SYMBOL_LENGTH     // symbol
+    + 4 + MAX_URI_LENGTH; // uri
+}
+
+/// Token-2022 extensions initialized on a promo mint.
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Debug, Clone, Copy, Default)]
+pub struct MintExtensions {
+    /// Tokens can't be transferred by their owners
+    pub non_transferable: bool,
+    /// Program authority can burn tokens without them first being delegated
+    pub permanent_delegate: bool,
+    /// Mint points to the [PromoMint] account for its metadata
+    pub metadata_pointer: bool,
+}
+
+impl MintExtensions {
+    pub const LEN: usize = 1 + 1 + 1;
+}
+
 //==============================
 // Metadata
 //==============================
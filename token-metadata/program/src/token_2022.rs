@@ -0,0 +1,227 @@
+//! Token-2022 instructions and account layouts used by promos with Token-2022 mints.
+//!
+//! Built by hand since the versions of `spl-token-2022` that include these extensions require
+//! newer solana crates than the rest of the program. Instruction data and account layouts
+//! follow the Token-2022 program's own packing.
+
+use crate::state::MintExtensions;
+use anchor_lang::{
+    error::ErrorCode,
+    prelude::*,
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program_pack::Pack,
+    },
+};
+use anchor_spl::token::spl_token::state::{Account, Mint};
+
+declare_id!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Length of accounts with extensions before the first extension: the base account, padding
+/// mints to the same length, and the account type.
+pub const BASE_LEN: usize = Account::LEN + 1;
+
+const TLV_HEADER_LEN: usize = 4;
+const PERMANENT_DELEGATE_LEN: usize = 32;
+const METADATA_POINTER_LEN: usize = 64;
+
+// Token instruction tags.
+const MINT_TO: u8 = 7;
+const BURN: u8 = 8;
+const INITIALIZE_MINT_2: u8 = 20;
+const INITIALIZE_NON_TRANSFERABLE_MINT: u8 = 32;
+const INITIALIZE_PERMANENT_DELEGATE: u8 = 35;
+const METADATA_POINTER_EXTENSION: u8 = 39;
+
+// Associated token account instruction tag.
+const CREATE_IDEMPOTENT: u8 = 1;
+
+/// Names of extension types by their position in the Token-2022 `ExtensionType` enum.
+const EXTENSION_NAMES: [&str; 20] = [
+    "uninitialized",
+    "transferFeeConfig",
+    "transferFeeAmount",
+    "mintCloseAuthority",
+    "confidentialTransferMint",
+    "confidentialTransferAccount",
+    "defaultAccountState",
+    "immutableOwner",
+    "memoTransfer",
+    "nonTransferable",
+    "interestBearingConfig",
+    "cpiGuard",
+    "permanentDelegate",
+    "nonTransferableAccount",
+    "transferHook",
+    "transferHookAccount",
+    "confidentialTransferFeeConfig",
+    "confidentialTransferFeeAmount",
+    "metadataPointer",
+    "tokenMetadata",
+];
+
+/// Returns the space required for a mint with the given extensions.
+pub fn get_mint_len(extensions: &MintExtensions) -> usize {
+    let mut len = BASE_LEN;
+    if extensions.non_transferable {
+        len += TLV_HEADER_LEN;
+    }
+    if extensions.permanent_delegate {
+        len += TLV_HEADER_LEN + PERMANENT_DELEGATE_LEN;
+    }
+    if extensions.metadata_pointer {
+        len += TLV_HEADER_LEN + METADATA_POINTER_LEN;
+    }
+    if len == BASE_LEN {
+        Mint::LEN
+    } else {
+        len
+    }
+}
+
+/// Unpacks the base mint, ignoring any extensions.
+pub fn unpack_mint(data: &[u8]) -> Result<Mint> {
+    if data.len() < Mint::LEN {
+        return Err(error!(ErrorCode::AccountDidNotDeserialize));
+    }
+    Mint::unpack_from_slice(&data[..Mint::LEN]).map_err(Into::into)
+}
+
+/// Unpacks the base token account, ignoring any extensions.
+pub fn unpack_token_account(data: &[u8]) -> Result<Account> {
+    if data.len() < Account::LEN {
+        return Err(error!(ErrorCode::AccountDidNotDeserialize));
+    }
+    Account::unpack_from_slice(&data[..Account::LEN]).map_err(Into::into)
+}
+
+/// Returns the names of the extensions in a mint or token account.
+pub fn get_extension_names(data: &[u8]) -> Vec<&'static str> {
+    let mut names = Vec::new();
+    let mut offset = BASE_LEN;
+    while offset + TLV_HEADER_LEN <= data.len() {
+        let extension_type = u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let length = u16::from_le_bytes([data[offset + 2], data[offset + 3]]) as usize;
+        if extension_type == 0 {
+            break;
+        }
+        names.push(
+            EXTENSION_NAMES
+                .get(extension_type as usize)
+                .copied()
+                .unwrap_or("unknown"),
+        );
+        offset += TLV_HEADER_LEN + length;
+    }
+    names
+}
+
+pub fn find_associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), ID.as_ref(), mint.as_ref()],
+        &anchor_spl::associated_token::ID,
+    )
+    .0
+}
+
+pub fn initialize_mint2(
+    mint: &Pubkey,
+    mint_authority: &Pubkey,
+    freeze_authority: Option<&Pubkey>,
+    decimals: u8,
+) -> Instruction {
+    let mut data = vec![INITIALIZE_MINT_2, decimals];
+    data.extend_from_slice(mint_authority.as_ref());
+    match freeze_authority {
+        Some(freeze_authority) => {
+            data.push(1);
+            data.extend_from_slice(freeze_authority.as_ref());
+        }
+        None => data.push(0),
+    }
+    Instruction {
+        program_id: ID,
+        accounts: vec![AccountMeta::new(*mint, false)],
+        data,
+    }
+}
+
+pub fn initialize_non_transferable_mint(mint: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: vec![AccountMeta::new(*mint, false)],
+        data: vec![INITIALIZE_NON_TRANSFERABLE_MINT],
+    }
+}
+
+pub fn initialize_permanent_delegate(mint: &Pubkey, delegate: &Pubkey) -> Instruction {
+    let mut data = vec![INITIALIZE_PERMANENT_DELEGATE];
+    data.extend_from_slice(delegate.as_ref());
+    Instruction {
+        program_id: ID,
+        accounts: vec![AccountMeta::new(*mint, false)],
+        data,
+    }
+}
+
+pub fn initialize_metadata_pointer(
+    mint: &Pubkey,
+    authority: &Pubkey,
+    metadata_address: &Pubkey,
+) -> Instruction {
+    let mut data = vec![METADATA_POINTER_EXTENSION, 0];
+    data.extend_from_slice(authority.as_ref());
+    data.extend_from_slice(metadata_address.as_ref());
+    Instruction {
+        program_id: ID,
+        accounts: vec![AccountMeta::new(*mint, false)],
+        data,
+    }
+}
+
+pub fn mint_to(mint: &Pubkey, account: &Pubkey, authority: &Pubkey, amount: u64) -> Instruction {
+    let mut data = vec![MINT_TO];
+    data.extend_from_slice(&amount.to_le_bytes());
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(*account, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data,
+    }
+}
+
+pub fn burn(account: &Pubkey, mint: &Pubkey, authority: &Pubkey, amount: u64) -> Instruction {
+    let mut data = vec![BURN];
+    data.extend_from_slice(&amount.to_le_bytes());
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data,
+    }
+}
+
+pub fn create_associated_token_account_idempotent(
+    payer: &Pubkey,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: anchor_spl::associated_token::ID,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(find_associated_token_address(wallet, mint), false),
+            AccountMeta::new_readonly(*wallet, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(anchor_lang::system_program::ID, false),
+            AccountMeta::new_readonly(ID, false),
+        ],
+        data: vec![CREATE_IDEMPOTENT],
+    }
+}
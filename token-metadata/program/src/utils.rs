@@ -9,6 +9,15 @@ pub const ADMIN_PREFIX: &str = "admin";
 pub const AUTHORITY_PREFIX: &str = "authority";
 pub const PROMO_PREFIX: &str = "promo";
 pub const MEMBERS_CAPACITY: u8 = 10;
+pub const ACCEPTANCE_PREFIX: &str = "acceptance";
+pub const ACCEPTANCE_CAPACITY: u8 = 10;
+pub const LOYALTY_PREFIX: &str = "loyalty";
+pub const LOYALTY_LEVELS_CAPACITY: u8 = 5;
+pub const REFERRAL_PREFIX: &str = "referral";
+pub const REFERRAL_REWARD_PREFIX: &str = "referral_reward";
+pub const PROMO_MINT_PREFIX: &str = "promo_mint";
+pub const SETTLEMENT_APPROVAL_PREFIX: &str = "settlement_approval";
+pub const SPONSORSHIP_ALLOWANCE_PREFIX: &str = "sponsorship_allowance";
 
 pub fn transfer_sol<'a, 'b, 'c, 'info>(
     ctx: CpiContext<'a, 'b, 'c, 'info, TransferSol<'info>>,
@@ -125,10 +134,54 @@ pub fn find_promo_address(mint: &Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(&[PROMO_PREFIX.as_bytes(), mint.as_ref()], &crate::id())
 }
 
+pub fn find_promo_acceptance_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ACCEPTANCE_PREFIX.as_bytes(), mint.as_ref()], &crate::id())
+}
+
+pub fn find_settlement_approval_address(mint: &Pubkey, group: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            SETTLEMENT_APPROVAL_PREFIX.as_bytes(),
+            mint.as_ref(),
+            group.as_ref(),
+        ],
+        &crate::id(),
+    )
+}
+
+pub fn find_loyalty_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LOYALTY_PREFIX.as_bytes(), mint.as_ref()], &crate::id())
+}
+
+pub fn find_referral_reward_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[REFERRAL_REWARD_PREFIX.as_bytes(), mint.as_ref()],
+        &crate::id(),
+    )
+}
+
+pub fn find_referral_address(mint: &Pubkey, token_owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[REFERRAL_PREFIX.as_bytes(), mint.as_ref(), token_owner.as_ref()],
+        &crate::id(),
+    )
+}
+
+pub fn find_promo_mint_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROMO_MINT_PREFIX.as_bytes(), mint.as_ref()], &crate::id())
+}
+
 pub fn find_group_address(seed: &Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(&[seed.as_ref()], &crate::id())
 }
 
+pub fn find_sponsorship_allowance_address(group: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SPONSORSHIP_ALLOWANCE_PREFIX.as_bytes(), group.as_ref()],
+        &crate::id(),
+    )
+}
+
 pub fn find_metadata_address(mint: &Pubkey) -> (Pubkey, u8) {
     find_metadata_account(mint)
 }
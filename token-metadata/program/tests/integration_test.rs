@@ -1,27 +1,48 @@
 use anchor_client::{
     solana_sdk::{
         commitment_config::CommitmentConfig,
-        signature::{read_keypair_file, Keypair},
+        pubkey::Pubkey,
+        signature::{read_keypair_file, Keypair, Signature},
         signer::Signer,
-        system_program,
+        system_program, sysvar,
     },
-    Client, Cluster,
+    Client, ClientError, Cluster, Program,
 };
-use bpl_token_metadata::{instruction, state::AdminSettings, utils};
-use std::{path::PathBuf, rc::Rc, str::FromStr};
+use anchor_spl::token::TokenAccount;
+use bpl_token_metadata::{
+    accounts,
+    error::ProgramError,
+    instruction,
+    state::{
+        AdminSettings, DataV2, Loyalty, MintExtensions, Promo, PromoAcceptance, PromoGroup,
+        PromoMint, Referral, RewardLevel, Settlement, SettlementApproval, SponsorshipAllowance,
+    },
+    token_2022, utils,
+};
+use std::{rc::Rc, str::FromStr};
 use tracing_subscriber::prelude::*;
 
-#[tokio::test(flavor = "multi_thread")]
-async fn anchor_tests() {
+/// Client for the program on the cluster in Anchor.toml, paid for by the program authority,
+/// which owns and is the only member of the groups created by the tests.
+struct TestContext {
+    program: Program,
+    payer: Rc<Keypair>,
+    platform: Pubkey,
+    admin_settings: AdminSettings,
+}
+
+/// Returns the test context, funding the test accounts and creating admin settings if the
+/// cluster doesn't have them yet.
+fn setup() -> TestContext {
     tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::new(
             std::env::var("RUST_LOG").unwrap_or_else(|_| format!("{}=trace", module_path!())),
         ))
         .with(tracing_subscriber::fmt::layer())
-        .init();
+        .try_init()
+        .ok();
 
-    let anchor_toml = tokio::fs::read_to_string("../../Anchor.toml")
-        .await
+    let anchor_toml = std::fs::read_to_string("../../Anchor.toml")
         .unwrap()
         .parse::<toml::Value>()
         .unwrap();
@@ -47,12 +68,10 @@ async fn anchor_tests() {
         .expect("problem reading keypair file");
     let group_member_keypair = read_keypair_file("../../target/deploy/group_member_1-keypair.json")
         .expect("problem reading keypair file");
-    let group_seed = read_keypair_file("../../target/deploy/group_seed-keypair.json")
-        .expect("problem reading keypair file");
 
     let program_authority = program_authority_keypair.pubkey();
-    let rc_payer_keypair = Rc::new(program_authority_keypair);
-    let client = Client::new_with_options(cluster, rc_payer_keypair, CommitmentConfig::confirmed());
+    let payer = Rc::new(program_authority_keypair);
+    let client = Client::new_with_options(cluster, payer.clone(), CommitmentConfig::confirmed());
 
     let program = client.program(bpl_token_metadata::id());
 
@@ -64,9 +83,992 @@ async fn anchor_tests() {
     ]
     .iter()
     .for_each(|pubkey| {
-        program
+        let signature = program
             .rpc()
             .request_airdrop(pubkey, 1_000_000_000)
             .unwrap();
+        program.rpc().poll_for_signature(&signature).unwrap();
     });
+
+    let (admin_settings, _) = utils::find_admin_address();
+    if program.account::<AdminSettings>(admin_settings).is_err() {
+        program
+            .request()
+            .accounts(accounts::CreateAdminSettings {
+                payer: program_authority,
+                admin_settings,
+                system_program: system_program::ID,
+            })
+            .args(instruction::CreateAdminSettings {
+                data: AdminSettings {
+                    platform: platform_keypair.pubkey(),
+                    create_promo_lamports: 10_000_000,
+                    burn_promo_token_lamports: 1_000_000,
+                },
+            })
+            .send()
+            .unwrap();
+    }
+    let admin_settings = program.account::<AdminSettings>(admin_settings).unwrap();
+
+    TestContext {
+        program,
+        payer,
+        platform: admin_settings.platform,
+        admin_settings,
+    }
+}
+
+impl TestContext {
+    fn lamports(&self, address: &Pubkey) -> u64 {
+        self.program.rpc().get_balance(address).unwrap()
+    }
+
+    /// Returns the data of a Token-2022 account, which `TokenAccount` and `Mint` can't
+    /// deserialize when it has extensions.
+    fn account_data(&self, address: &Pubkey) -> Vec<u8> {
+        self.program.rpc().get_account_data(address).unwrap()
+    }
+
+    fn token_amount(&self, token_account: &Pubkey) -> u64 {
+        self.program
+            .account::<TokenAccount>(*token_account)
+            .unwrap()
+            .amount
+    }
+
+    /// Creates a group with `lamports` on top of its rent.
+    fn create_group(&self, lamports: u64) -> Pubkey {
+        let seed = Keypair::new().pubkey();
+        let (group, nonce) = utils::find_group_address(&seed);
+        self.program
+            .request()
+            .accounts(accounts::CreatePromoGroup {
+                payer: self.payer.pubkey(),
+                seed,
+                promo_group: group,
+                memo_program: spl_memo::id(),
+                system_program: system_program::ID,
+            })
+            .args(instruction::CreatePromoGroup {
+                data: PromoGroup {
+                    owner: self.payer.pubkey(),
+                    seed,
+                    nonce,
+                    members: vec![self.payer.pubkey()],
+                },
+                lamports,
+                memo: None,
+            })
+            .send()
+            .unwrap();
+        group
+    }
+
+    /// Creates a promo of `group`, returning its mint.
+    fn create_promo(&self, group: &Pubkey, max_mint: Option<u32>) -> Pubkey {
+        let mint = Keypair::new();
+        let (metadata, _) = utils::find_metadata_address(&mint.pubkey());
+        self.program
+            .request()
+            .accounts(accounts::CreatePromo {
+                payer: self.payer.pubkey(),
+                group: *group,
+                mint: mint.pubkey(),
+                metadata,
+                authority: utils::find_authority_address().0,
+                promo: utils::find_promo_address(&mint.pubkey()).0,
+                platform: self.platform,
+                admin_settings: utils::find_admin_address().0,
+                metadata_program: mpl_token_metadata::id(),
+                token_program: anchor_spl::token::ID,
+                memo_program: spl_memo::id(),
+                rent: sysvar::rent::ID,
+                system_program: system_program::ID,
+            })
+            .args(instruction::CreatePromo {
+                promo_data: Promo {
+                    owner: *group,
+                    mint: mint.pubkey(),
+                    metadata,
+                    mint_count: 0,
+                    burn_count: 0,
+                    max_mint,
+                    max_burn: None,
+                },
+                metadata_data: DataV2 {
+                    name: "Test Promo".to_string(),
+                    symbol: "BTP".to_string(),
+                    uri: "https://arweave.net/frDiuZYzSVwYTwSUMR1YbggVkZqZfA7S9xsI3drPWBo"
+                        .to_string(),
+                    seller_fee_basis_points: 0,
+                    creators: None,
+                    collection: None,
+                    uses: None,
+                },
+                is_mutable: true,
+                memo: None,
+            })
+            .signer(&mint)
+            .send()
+            .unwrap();
+        mint.pubkey()
+    }
+
+    fn mint_promo_token(
+        &self,
+        group: &Pubkey,
+        mint: &Pubkey,
+        token_owner: &Keypair,
+    ) -> Result<Signature, ClientError> {
+        self.program
+            .request()
+            .accounts(accounts::MintPromoToken {
+                payer: self.payer.pubkey(),
+                group: *group,
+                token_owner: token_owner.pubkey(),
+                mint: *mint,
+                authority: utils::find_authority_address().0,
+                promo: utils::find_promo_address(mint).0,
+                loyalty: utils::find_loyalty_address(mint).0,
+                token_account: utils::find_associated_token_address(&token_owner.pubkey(), mint),
+                token_program: anchor_spl::token::ID,
+                memo_program: spl_memo::id(),
+                associated_token_program: anchor_spl::associated_token::ID,
+                rent: sysvar::rent::ID,
+                system_program: system_program::ID,
+            })
+            .args(instruction::MintPromoToken { memo: None })
+            .signer(token_owner)
+            .send()
+    }
+
+    /// Delegates the token of `token_owner` to the payer as a member of `group`.
+    fn delegate_promo_token(&self, group: &Pubkey, mint: &Pubkey, token_owner: &Keypair) {
+        self.program
+            .request()
+            .accounts(accounts::DelegatePromoToken {
+                payer: self.payer.pubkey(),
+                delegate: self.payer.pubkey(),
+                group: *group,
+                token_owner: token_owner.pubkey(),
+                mint: *mint,
+                promo: utils::find_promo_address(mint).0,
+                token_account: utils::find_associated_token_address(&token_owner.pubkey(), mint),
+                token_program: anchor_spl::token::ID,
+                memo_program: spl_memo::id(),
+                system_program: system_program::ID,
+            })
+            .args(instruction::DelegatePromoToken { memo: None })
+            .signer(token_owner)
+            .send()
+            .unwrap();
+    }
+
+    /// Rewards referrers of `mint` with tokens of `reward_mint`.
+    fn create_referral_reward(&self, group: &Pubkey, mint: &Pubkey, reward_mint: &Pubkey) {
+        self.program
+            .request()
+            .accounts(accounts::CreateReferralReward {
+                payer: self.payer.pubkey(),
+                group: *group,
+                mint: *mint,
+                promo: utils::find_promo_address(mint).0,
+                reward_mint: *reward_mint,
+                reward_promo: utils::find_promo_address(reward_mint).0,
+                referral_reward: utils::find_referral_reward_address(mint).0,
+                memo_program: spl_memo::id(),
+                system_program: system_program::ID,
+            })
+            .args(instruction::CreateReferralReward { memo: None })
+            .send()
+            .unwrap();
+    }
+
+    fn mint_referred_promo_token(
+        &self,
+        group: &Pubkey,
+        mint: &Pubkey,
+        token_owner: &Keypair,
+        referrer: &Pubkey,
+    ) -> Result<Signature, ClientError> {
+        self.program
+            .request()
+            .accounts(accounts::MintReferredPromoToken {
+                payer: self.payer.pubkey(),
+                group: *group,
+                token_owner: token_owner.pubkey(),
+                mint: *mint,
+                authority: utils::find_authority_address().0,
+                promo: utils::find_promo_address(mint).0,
+                loyalty: utils::find_loyalty_address(mint).0,
+                referral_reward: utils::find_referral_reward_address(mint).0,
+                referral: utils::find_referral_address(mint, &token_owner.pubkey()).0,
+                token_account: utils::find_associated_token_address(&token_owner.pubkey(), mint),
+                token_program: anchor_spl::token::ID,
+                memo_program: spl_memo::id(),
+                associated_token_program: anchor_spl::associated_token::ID,
+                rent: sysvar::rent::ID,
+                system_program: system_program::ID,
+            })
+            .args(instruction::MintReferredPromoToken {
+                referrer: *referrer,
+                memo: None,
+            })
+            .signer(token_owner)
+            .send()
+    }
+
+    /// Burns the delegated token of `token_owner`, rewarding `referrer` with a token of
+    /// `reward_mint`.
+    fn burn_referred_promo_token(
+        &self,
+        group: &Pubkey,
+        mint: &Pubkey,
+        token_owner: &Pubkey,
+        referrer: &Pubkey,
+        reward_mint: &Pubkey,
+    ) -> Result<Signature, ClientError> {
+        self.program
+            .request()
+            .accounts(accounts::BurnReferredPromoToken {
+                payer: self.payer.pubkey(),
+                group: *group,
+                mint: *mint,
+                authority: utils::find_authority_address().0,
+                promo: utils::find_promo_address(mint).0,
+                platform: self.platform,
+                admin_settings: utils::find_admin_address().0,
+                token_account: utils::find_associated_token_address(token_owner, mint),
+                referral: utils::find_referral_address(mint, token_owner).0,
+                referral_reward: utils::find_referral_reward_address(mint).0,
+                referrer: *referrer,
+                reward_mint: *reward_mint,
+                reward_promo: utils::find_promo_address(reward_mint).0,
+                referrer_token_account: utils::find_associated_token_address(referrer, reward_mint),
+                token_program: anchor_spl::token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+                memo_program: spl_memo::id(),
+                rent: sysvar::rent::ID,
+                system_program: system_program::ID,
+            })
+            .args(instruction::BurnReferredPromoToken { memo: None })
+            .send()
+    }
+
+    /// Makes `mint` a loyalty promo earning one point per 0.01 currency.
+    fn create_loyalty(&self, group: &Pubkey, mint: &Pubkey) {
+        let promo = utils::find_promo_address(mint).0;
+        self.program
+            .request()
+            .accounts(accounts::CreateLoyalty {
+                payer: self.payer.pubkey(),
+                group: *group,
+                mint: *mint,
+                promo,
+                loyalty: utils::find_loyalty_address(mint).0,
+                memo_program: spl_memo::id(),
+                system_program: system_program::ID,
+            })
+            .args(instruction::CreateLoyalty {
+                data: Loyalty {
+                    promo,
+                    mint: *mint,
+                    earn_rate: 1,
+                    levels: vec![RewardLevel {
+                        points: 1_000,
+                        reward: 500,
+                    }],
+                    points_earned: 0,
+                    points_redeemed: 0,
+                },
+                memo: None,
+            })
+            .send()
+            .unwrap();
+    }
+
+    fn earn_points(
+        &self,
+        group: &Pubkey,
+        mint: &Pubkey,
+        token_owner: &Pubkey,
+        order_amount: u64,
+    ) -> Result<Signature, ClientError> {
+        self.program
+            .request()
+            .accounts(accounts::EarnPoints {
+                payer: self.payer.pubkey(),
+                group: *group,
+                token_owner: *token_owner,
+                mint: *mint,
+                authority: utils::find_authority_address().0,
+                promo: utils::find_promo_address(mint).0,
+                loyalty: utils::find_loyalty_address(mint).0,
+                token_account: utils::find_associated_token_address(token_owner, mint),
+                token_program: anchor_spl::token::ID,
+                memo_program: spl_memo::id(),
+                associated_token_program: anchor_spl::associated_token::ID,
+                rent: sysvar::rent::ID,
+                system_program: system_program::ID,
+            })
+            .args(instruction::EarnPoints {
+                order_amount,
+                memo: None,
+            })
+            .send()
+    }
+
+    /// Creates a promo of `group` with a Token-2022 mint, returning the mint.
+    fn create_promo_2022(
+        &self,
+        group: &Pubkey,
+        extensions: MintExtensions,
+    ) -> Result<Pubkey, ClientError> {
+        let mint = Keypair::new();
+        let (promo_mint, _) = utils::find_promo_mint_address(&mint.pubkey());
+        self.program
+            .request()
+            .accounts(accounts::CreatePromo2022 {
+                payer: self.payer.pubkey(),
+                group: *group,
+                mint: mint.pubkey(),
+                promo_mint,
+                authority: utils::find_authority_address().0,
+                promo: utils::find_promo_address(&mint.pubkey()).0,
+                platform: self.platform,
+                admin_settings: utils::find_admin_address().0,
+                token_program: token_2022::ID,
+                memo_program: spl_memo::id(),
+                rent: sysvar::rent::ID,
+                system_program: system_program::ID,
+            })
+            .args(instruction::CreatePromo2022 {
+                promo_data: Promo {
+                    owner: *group,
+                    mint: mint.pubkey(),
+                    metadata: promo_mint,
+                    mint_count: 0,
+                    burn_count: 0,
+                    max_mint: None,
+                    max_burn: None,
+                },
+                metadata_data: DataV2 {
+                    name: "Test Promo 2022".to_string(),
+                    symbol: "BTP22".to_string(),
+                    uri: "https://arweave.net/frDiuZYzSVwYTwSUMR1YbggVkZqZfA7S9xsI3drPWBo"
+                        .to_string(),
+                    seller_fee_basis_points: 0,
+                    creators: None,
+                    collection: None,
+                    uses: None,
+                },
+                extensions,
+                memo: None,
+            })
+            .signer(&mint)
+            .send()?;
+        Ok(mint.pubkey())
+    }
+
+    fn mint_promo_token_2022(&self, group: &Pubkey, mint: &Pubkey, token_owner: &Keypair) {
+        self.program
+            .request()
+            .accounts(accounts::MintPromoToken2022 {
+                payer: self.payer.pubkey(),
+                group: *group,
+                token_owner: token_owner.pubkey(),
+                mint: *mint,
+                authority: utils::find_authority_address().0,
+                promo: utils::find_promo_address(mint).0,
+                token_account: token_2022::find_associated_token_address(
+                    &token_owner.pubkey(),
+                    mint,
+                ),
+                token_program: token_2022::ID,
+                memo_program: spl_memo::id(),
+                associated_token_program: anchor_spl::associated_token::ID,
+                rent: sysvar::rent::ID,
+                system_program: system_program::ID,
+            })
+            .args(instruction::MintPromoToken2022 { memo: None })
+            .signer(token_owner)
+            .send()
+            .unwrap();
+    }
+
+    /// Burns the token in `token_account` as the mint's permanent delegate.
+    fn burn_promo_token_2022(&self, group: &Pubkey, mint: &Pubkey, token_account: &Pubkey) {
+        self.program
+            .request()
+            .accounts(accounts::BurnPromoToken2022 {
+                payer: self.payer.pubkey(),
+                group: *group,
+                mint: *mint,
+                authority: utils::find_authority_address().0,
+                promo: utils::find_promo_address(mint).0,
+                platform: self.platform,
+                admin_settings: utils::find_admin_address().0,
+                token_account: *token_account,
+                token_program: token_2022::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+                memo_program: spl_memo::id(),
+                rent: sysvar::rent::ID,
+                system_program: system_program::ID,
+            })
+            .args(instruction::BurnPromoToken2022 { memo: None })
+            .send()
+            .unwrap();
+    }
+
+    fn create_promo_acceptance(
+        &self,
+        issuing_group: &Pubkey,
+        mint: &Pubkey,
+        groups: Vec<Pubkey>,
+        settlement: Settlement,
+        settlement_lamports: u64,
+    ) {
+        let promo = utils::find_promo_address(mint).0;
+        self.program
+            .request()
+            .accounts(accounts::CreatePromoAcceptance {
+                payer: self.payer.pubkey(),
+                group: *issuing_group,
+                mint: *mint,
+                promo,
+                promo_acceptance: utils::find_promo_acceptance_address(mint).0,
+                memo_program: spl_memo::id(),
+                system_program: system_program::ID,
+            })
+            .args(instruction::CreatePromoAcceptance {
+                data: PromoAcceptance {
+                    promo,
+                    owner: *issuing_group,
+                    groups,
+                    settlement,
+                    settlement_lamports,
+                },
+                memo: None,
+            })
+            .send()
+            .unwrap();
+    }
+
+    /// Approves `group` paying up to `max_settlement_lamports` for each token of `mint` burned.
+    fn create_settlement_approval(
+        &self,
+        group: &Pubkey,
+        mint: &Pubkey,
+        max_settlement_lamports: u64,
+    ) {
+        self.program
+            .request()
+            .accounts(accounts::CreateSettlementApproval {
+                payer: self.payer.pubkey(),
+                group: *group,
+                mint: *mint,
+                promo: utils::find_promo_address(mint).0,
+                settlement_approval: utils::find_settlement_approval_address(mint, group).0,
+                memo_program: spl_memo::id(),
+                system_program: system_program::ID,
+            })
+            .args(instruction::CreateSettlementApproval {
+                data: SettlementApproval {
+                    promo: utils::find_promo_address(mint).0,
+                    group: *group,
+                    max_settlement_lamports,
+                },
+                memo: None,
+            })
+            .send()
+            .unwrap();
+    }
+
+    /// Allows up to `max_lamports` in total to be settled from `group` to the platform.
+    fn create_sponsorship_allowance(&self, group: &Pubkey, max_lamports: u64) {
+        self.program
+            .request()
+            .accounts(accounts::CreateSponsorshipAllowance {
+                payer: self.payer.pubkey(),
+                group: *group,
+                sponsorship_allowance: utils::find_sponsorship_allowance_address(group).0,
+                memo_program: spl_memo::id(),
+                system_program: system_program::ID,
+            })
+            .args(instruction::CreateSponsorshipAllowance {
+                max_lamports,
+                memo: None,
+            })
+            .send()
+            .unwrap();
+    }
+
+    fn settle_sponsorship(&self, group: &Pubkey, lamports: u64) -> Result<Signature, ClientError> {
+        self.program
+            .request()
+            .accounts(accounts::SettleSponsorship {
+                payer: self.payer.pubkey(),
+                group: *group,
+                sponsorship_allowance: utils::find_sponsorship_allowance_address(group).0,
+                platform: self.platform,
+                admin_settings: utils::find_admin_address().0,
+                memo_program: spl_memo::id(),
+            })
+            .args(instruction::SettleSponsorship {
+                lamports,
+                memo: None,
+            })
+            .send()
+    }
+
+    /// Mints a token of `mint` and delegates it to the payer as a member of `group`.
+    fn mint_and_delegate_accepted(
+        &self,
+        issuing_group: &Pubkey,
+        group: &Pubkey,
+        mint: &Pubkey,
+        token_owner: &Keypair,
+    ) -> Pubkey {
+        self.mint_promo_token(issuing_group, mint, token_owner)
+            .unwrap();
+        let token_account = utils::find_associated_token_address(&token_owner.pubkey(), mint);
+        self.program
+            .request()
+            .accounts(accounts::DelegateAcceptedPromoToken {
+                payer: self.payer.pubkey(),
+                delegate: self.payer.pubkey(),
+                group: *group,
+                token_owner: token_owner.pubkey(),
+                mint: *mint,
+                promo: utils::find_promo_address(mint).0,
+                promo_acceptance: utils::find_promo_acceptance_address(mint).0,
+                token_account,
+                token_program: anchor_spl::token::ID,
+                memo_program: spl_memo::id(),
+                system_program: system_program::ID,
+            })
+            .args(instruction::DelegateAcceptedPromoToken { memo: None })
+            .signer(token_owner)
+            .send()
+            .unwrap();
+        token_account
+    }
+
+    fn burn_accepted_promo_token(
+        &self,
+        issuing_group: &Pubkey,
+        group: &Pubkey,
+        mint: &Pubkey,
+        token_account: &Pubkey,
+    ) -> Result<Signature, ClientError> {
+        self.program
+            .request()
+            .accounts(accounts::BurnAcceptedPromoToken {
+                payer: self.payer.pubkey(),
+                group: *group,
+                issuing_group: *issuing_group,
+                mint: *mint,
+                promo: utils::find_promo_address(mint).0,
+                promo_acceptance: utils::find_promo_acceptance_address(mint).0,
+                settlement_approval: utils::find_settlement_approval_address(mint, group).0,
+                platform: self.platform,
+                admin_settings: utils::find_admin_address().0,
+                token_account: *token_account,
+                token_program: anchor_spl::token::ID,
+                memo_program: spl_memo::id(),
+                system_program: system_program::ID,
+            })
+            .args(instruction::BurnAcceptedPromoToken { memo: None })
+            .send()
+    }
+}
+
+/// Asserts that the transaction failed with `error`.
+fn assert_program_error<T: std::fmt::Display>(result: Result<T, ClientError>, error: ProgramError) {
+    let code = format!("custom program error: {:#x}", u32::from(error));
+    match result {
+        Ok(value) => panic!("expected {}, got {}", code, value),
+        Err(err) => assert!(err.to_string().contains(&code), "{} not in {}", code, err),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn anchor_tests() {
+    let context = setup();
+    assert_eq!(context.program.id(), bpl_token_metadata::id());
+    assert_ne!(context.platform, Pubkey::default());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_promo_acceptance() {
+    let context = setup();
+    let issuing_group = context.create_group(100_000_000);
+    let redeeming_group = context.create_group(100_000_000);
+    let mint = context.create_promo(&issuing_group, None);
+
+    // The issuing group pays the redeeming group for each token burned.
+    context.create_promo_acceptance(
+        &issuing_group,
+        &mint,
+        vec![redeeming_group],
+        Settlement::IssuerPaysRedeemer,
+        1_000_000,
+    );
+    let token_owner = Keypair::new();
+    let token_account =
+        context.mint_and_delegate_accepted(&issuing_group, &redeeming_group, &mint, &token_owner);
+    let issuing_lamports = context.lamports(&issuing_group);
+    let redeeming_lamports = context.lamports(&redeeming_group);
+    let platform_lamports = context.lamports(&context.platform);
+    context
+        .burn_accepted_promo_token(&issuing_group, &redeeming_group, &mint, &token_account)
+        .unwrap();
+    assert_eq!(context.token_amount(&token_account), 0);
+    assert_eq!(
+        context.lamports(&issuing_group),
+        issuing_lamports - 1_000_000
+    );
+    assert_eq!(
+        context.lamports(&redeeming_group),
+        redeeming_lamports + 1_000_000
+    );
+    assert_eq!(
+        context.lamports(&context.platform),
+        platform_lamports + context.admin_settings.burn_promo_token_lamports
+    );
+    let promo = context
+        .program
+        .account::<Promo>(utils::find_promo_address(&mint).0)
+        .unwrap();
+    assert_eq!(promo.burn_count, 1);
+
+    // The issuing group can change the direction of the settlement.
+    context.create_promo_acceptance(
+        &issuing_group,
+        &mint,
+        vec![redeeming_group],
+        Settlement::RedeemerPaysIssuer,
+        2_000_000,
+    );
+    let token_owner = Keypair::new();
+    let token_account =
+        context.mint_and_delegate_accepted(&issuing_group, &redeeming_group, &mint, &token_owner);
+
+    // The redeeming group only pays what its owner approved.
+    assert_program_error(
+        context.burn_accepted_promo_token(&issuing_group, &redeeming_group, &mint, &token_account),
+        ProgramError::SettlementNotApproved,
+    );
+    context.create_settlement_approval(&redeeming_group, &mint, 1_000_000);
+    assert_program_error(
+        context.burn_accepted_promo_token(&issuing_group, &redeeming_group, &mint, &token_account),
+        ProgramError::SettlementNotApproved,
+    );
+    assert_eq!(context.token_amount(&token_account), 1);
+
+    context.create_settlement_approval(&redeeming_group, &mint, 2_000_000);
+    let issuing_lamports = context.lamports(&issuing_group);
+    let redeeming_lamports = context.lamports(&redeeming_group);
+    context
+        .burn_accepted_promo_token(&issuing_group, &redeeming_group, &mint, &token_account)
+        .unwrap();
+    assert_eq!(
+        context.lamports(&issuing_group),
+        issuing_lamports + 2_000_000
+    );
+    assert_eq!(
+        context.lamports(&redeeming_group),
+        redeeming_lamports - 2_000_000
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_promo_acceptance_insufficient_funds() {
+    let context = setup();
+    let issuing_group = context.create_group(100_000_000);
+    let redeeming_group = context.create_group(1_000_000);
+    let mint = context.create_promo(&issuing_group, None);
+
+    // Settling would leave the redeeming group one lamport short of being rent exempt.
+    let rent_exempt_lamports = context
+        .program
+        .rpc()
+        .get_minimum_balance_for_rent_exemption(PromoGroup::LEN)
+        .unwrap();
+    let settlement_lamports = context.lamports(&redeeming_group) - rent_exempt_lamports + 1;
+    context.create_promo_acceptance(
+        &issuing_group,
+        &mint,
+        vec![redeeming_group],
+        Settlement::RedeemerPaysIssuer,
+        settlement_lamports,
+    );
+    context.create_settlement_approval(&redeeming_group, &mint, settlement_lamports);
+    let token_owner = Keypair::new();
+    let token_account =
+        context.mint_and_delegate_accepted(&issuing_group, &redeeming_group, &mint, &token_owner);
+    let redeeming_lamports = context.lamports(&redeeming_group);
+
+    assert_program_error(
+        context.burn_accepted_promo_token(&issuing_group, &redeeming_group, &mint, &token_account),
+        ProgramError::InsufficientFunds,
+    );
+    assert_eq!(context.token_amount(&token_account), 1);
+    assert_eq!(context.lamports(&redeeming_group), redeeming_lamports);
+
+    // Groups can't redeem promos that don't name them.
+    let other_group = context.create_group(1_000_000);
+    let result = context
+        .program
+        .request()
+        .accounts(accounts::BurnAcceptedPromoToken {
+            payer: context.payer.pubkey(),
+            group: other_group,
+            issuing_group,
+            mint,
+            promo: utils::find_promo_address(&mint).0,
+            promo_acceptance: utils::find_promo_acceptance_address(&mint).0,
+            settlement_approval: utils::find_settlement_approval_address(&mint, &other_group).0,
+            platform: context.platform,
+            admin_settings: utils::find_admin_address().0,
+            token_account,
+            token_program: anchor_spl::token::ID,
+            memo_program: spl_memo::id(),
+            system_program: system_program::ID,
+        })
+        .args(instruction::BurnAcceptedPromoToken { memo: None })
+        .send();
+    assert!(result.is_err());
+    assert_eq!(context.token_amount(&token_account), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_settle_sponsorship() {
+    let context = setup();
+    let group = context.create_group(10_000_000);
+    let (sponsorship_allowance, _) = utils::find_sponsorship_allowance_address(&group);
+
+    // Members can't settle from groups whose owner hasn't allowed it.
+    assert!(context.settle_sponsorship(&group, 1_000_000).is_err());
+
+    context.create_sponsorship_allowance(&group, 2_000_000);
+    let group_lamports = context.lamports(&group);
+    context.settle_sponsorship(&group, 1_500_000).unwrap();
+    assert_eq!(context.lamports(&group), group_lamports - 1_500_000);
+
+    assert_program_error(
+        context.settle_sponsorship(&group, 1_000_000),
+        ProgramError::SponsorshipAllowanceExceeded,
+    );
+    assert_eq!(context.lamports(&group), group_lamports - 1_500_000);
+    context.settle_sponsorship(&group, 500_000).unwrap();
+
+    // Raising the allowance keeps counting what was already settled.
+    context.create_sponsorship_allowance(&group, 3_000_000);
+    let allowance = context
+        .program
+        .account::<SponsorshipAllowance>(sponsorship_allowance)
+        .unwrap();
+    assert_eq!(allowance.settled_lamports, 2_000_000);
+    assert_program_error(
+        context.settle_sponsorship(&group, 1_000_001),
+        ProgramError::SponsorshipAllowanceExceeded,
+    );
+    context.settle_sponsorship(&group, 1_000_000).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_loyalty() {
+    let context = setup();
+    let group = context.create_group(100_000_000);
+    let mint = context.create_promo(&group, Some(2_500));
+    let token_owner = Keypair::new();
+
+    // Loyalty promo tokens are only minted as points.
+    context.create_loyalty(&group, &mint);
+    assert_program_error(
+        context.mint_promo_token(&group, &mint, &token_owner),
+        ProgramError::LoyaltyPointsEarnedOnly,
+    );
+
+    let token_account = utils::find_associated_token_address(&token_owner.pubkey(), &mint);
+    context
+        .earn_points(&group, &mint, &token_owner.pubkey(), 1_500)
+        .unwrap();
+    assert_eq!(context.token_amount(&token_account), 1_500);
+
+    // Points earned can't exceed the promo's max mint.
+    assert_program_error(
+        context.earn_points(&group, &mint, &token_owner.pubkey(), 1_001),
+        ProgramError::MaxMintExceeded,
+    );
+    context
+        .earn_points(&group, &mint, &token_owner.pubkey(), 1_000)
+        .unwrap();
+    assert_eq!(context.token_amount(&token_account), 2_500);
+    let loyalty = context
+        .program
+        .account::<Loyalty>(utils::find_loyalty_address(&mint).0)
+        .unwrap();
+    assert_eq!(loyalty.points_earned, 2_500);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_referral() {
+    let context = setup();
+    let group = context.create_group(100_000_000);
+    let mint = context.create_promo(&group, None);
+    let reward_mint = context.create_promo(&group, Some(1));
+    context.create_referral_reward(&group, &mint, &reward_mint);
+    let referrer = Keypair::new().pubkey();
+    let referrer_token_account = utils::find_associated_token_address(&referrer, &reward_mint);
+
+    // Token owners can't refer themselves.
+    let token_owner = Keypair::new();
+    assert!(context
+        .mint_referred_promo_token(&group, &mint, &token_owner, &token_owner.pubkey())
+        .is_err());
+
+    // The referrer is rewarded when the referred token is burned.
+    context
+        .mint_referred_promo_token(&group, &mint, &token_owner, &referrer)
+        .unwrap();
+    context.delegate_promo_token(&group, &mint, &token_owner);
+    context
+        .burn_referred_promo_token(
+            &group,
+            &mint,
+            &token_owner.pubkey(),
+            &referrer,
+            &reward_mint,
+        )
+        .unwrap();
+    let token_account = utils::find_associated_token_address(&token_owner.pubkey(), &mint);
+    assert_eq!(context.token_amount(&token_account), 0);
+    assert_eq!(context.token_amount(&referrer_token_account), 1);
+    let referral = context
+        .program
+        .account::<Referral>(utils::find_referral_address(&mint, &token_owner.pubkey()).0)
+        .unwrap();
+    assert_eq!(referral.referrer, referrer);
+    assert!(referral.converted);
+
+    // Referrals convert once and keep the first referrer.
+    context
+        .mint_referred_promo_token(&group, &mint, &token_owner, &Keypair::new().pubkey())
+        .unwrap();
+    context.delegate_promo_token(&group, &mint, &token_owner);
+    assert_program_error(
+        context.burn_referred_promo_token(
+            &group,
+            &mint,
+            &token_owner.pubkey(),
+            &referrer,
+            &reward_mint,
+        ),
+        ProgramError::ReferralAlreadyConverted,
+    );
+    assert_eq!(context.token_amount(&token_account), 1);
+
+    // Referrals still convert once the reward promo has run out, without a reward.
+    let token_owner = Keypair::new();
+    context
+        .mint_referred_promo_token(&group, &mint, &token_owner, &referrer)
+        .unwrap();
+    context.delegate_promo_token(&group, &mint, &token_owner);
+    context
+        .burn_referred_promo_token(
+            &group,
+            &mint,
+            &token_owner.pubkey(),
+            &referrer,
+            &reward_mint,
+        )
+        .unwrap();
+    let token_account = utils::find_associated_token_address(&token_owner.pubkey(), &mint);
+    assert_eq!(context.token_amount(&token_account), 0);
+    assert_eq!(context.token_amount(&referrer_token_account), 1);
+    let reward_promo = context
+        .program
+        .account::<Promo>(utils::find_promo_address(&reward_mint).0)
+        .unwrap();
+    assert_eq!(reward_promo.mint_count, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_promo_2022() {
+    let context = setup();
+    let group = context.create_group(100_000_000);
+
+    let result = context.create_promo_2022(
+        &group,
+        MintExtensions {
+            non_transferable: true,
+            permanent_delegate: false,
+            metadata_pointer: true,
+        },
+    );
+    assert_program_error(result, ProgramError::PermanentDelegateRequired);
+
+    let extensions = MintExtensions {
+        non_transferable: true,
+        permanent_delegate: true,
+        metadata_pointer: true,
+    };
+    let mint = context.create_promo_2022(&group, extensions).unwrap();
+
+    let promo_mint = context
+        .program
+        .account::<PromoMint>(utils::find_promo_mint_address(&mint).0)
+        .unwrap();
+    assert_eq!(promo_mint.mint, mint);
+    assert_eq!(promo_mint.extensions, extensions);
+    assert_eq!(promo_mint.name, "Test Promo 2022");
+
+    let mint_data = context.account_data(&mint);
+    assert_eq!(
+        token_2022::get_extension_names(&mint_data),
+        vec!["nonTransferable", "permanentDelegate", "metadataPointer"]
+    );
+    let mint_account = token_2022::unpack_mint(&mint_data).unwrap();
+    assert_eq!(
+        mint_account.mint_authority,
+        Some(utils::find_authority_address().0).into()
+    );
+
+    let token_owner = Keypair::new();
+    context.mint_promo_token_2022(&group, &mint, &token_owner);
+    let token_account = token_2022::find_associated_token_address(&token_owner.pubkey(), &mint);
+    let token_account_data = context.account_data(&token_account);
+    assert_eq!(
+        token_2022::unpack_token_account(&token_account_data)
+            .unwrap()
+            .amount,
+        1
+    );
+    assert!(
+        token_2022::get_extension_names(&token_account_data).contains(&"nonTransferableAccount")
+    );
+
+    // Burned by the program authority without the token owner delegating the token.
+    let platform_start = context.lamports(&context.platform);
+    context.burn_promo_token_2022(&group, &mint, &token_account);
+    assert_eq!(
+        token_2022::unpack_token_account(&context.account_data(&token_account))
+            .unwrap()
+            .amount,
+        0
+    );
+    assert_eq!(
+        context.lamports(&context.platform) - platform_start,
+        context.admin_settings.burn_promo_token_lamports
+    );
+
+    let promo = context
+        .program
+        .account::<Promo>(utils::find_promo_address(&mint).0)
+        .unwrap();
+    assert_eq!(promo.mint_count, 1);
+    assert_eq!(promo.burn_count, 1);
 }